@@ -20,7 +20,8 @@ fn main() {
 						}
 					}
 				}
-				5_usize
+				// characters without a glyph are drawn as the missing glyph, unless they're meant to be blank
+				if char::from_u32(idx as u32).is_some_and(char::is_whitespace) { 5_usize } else { 0_usize }
 			} as u8);
 		}
 		// SAFETY: all values are written to
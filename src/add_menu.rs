@@ -0,0 +1,60 @@
+use crate::assets::HEADER_SIZE;
+use crate::elements::chunk::{NbtChunk, NbtRegion};
+use crate::elements::compound::NbtCompound;
+use crate::elements::element::{id_to_string_name, NbtByte, NbtByteArray, NbtDouble, NbtElement, NbtFloat, NbtInt, NbtIntArray, NbtLong, NbtLongArray, NbtShort};
+use crate::elements::list::NbtList;
+use crate::elements::string::NbtString;
+use crate::vertex_buffer_builder::VertexBufferBuilder;
+
+/// The types a compound or an empty list can be given, in the order of the number keys which create them.
+const TYPES: [u8; 12] = [NbtByte::ID, NbtShort::ID, NbtInt::ID, NbtLong::ID, NbtFloat::ID, NbtDouble::ID, NbtByteArray::ID, NbtIntArray::ID, NbtLongArray::ID, NbtString::ID, NbtList::ID, NbtCompound::ID];
+
+/// The types which can be added to a container with Ctrl + I, regions are never offered since nothing can hold one.
+pub struct AddMenu {
+	/// The indices of the container the element is added to.
+	pub parent: Box<[usize]>,
+	/// Where inside of the container the element is added.
+	pub idx: usize,
+	ids: Vec<u8>,
+	selected: usize,
+}
+
+impl AddMenu {
+	/// The menu for adding an element to `container` at `idx`, `None` if nothing more fits inside of it.
+	#[must_use]
+	pub fn new(parent: Box<[usize]>, idx: usize, container: &NbtElement) -> Option<Self> {
+		let ids = match container.id() {
+			NbtCompound::ID | NbtChunk::ID => TYPES.to_vec(),
+			NbtList::ID => {
+				let list = container.as_list()?;
+				if list.is_empty() { TYPES.to_vec() } else { vec![list.element] }
+			}
+			NbtByteArray::ID => vec![NbtByte::ID],
+			NbtIntArray::ID => vec![NbtInt::ID],
+			NbtLongArray::ID => vec![NbtLong::ID],
+			NbtRegion::ID if container.len().is_some_and(|len| len < 32 * 32) => vec![NbtChunk::ID],
+			_ => return None,
+		};
+		Some(Self { parent, idx, ids, selected: 0 })
+	}
+
+	pub fn select_previous(&mut self) { self.selected = self.selected.checked_sub(1).unwrap_or(self.ids.len() - 1); }
+
+	pub fn select_next(&mut self) { self.selected = if self.selected + 1 >= self.ids.len() { 0 } else { self.selected + 1 }; }
+
+	/// If the highlighted type can still be added to `container`, which might have changed since the menu was opened for it.
+	#[must_use]
+	pub fn still_fits(&self, container: &NbtElement) -> bool { Self::new(self.parent.clone(), self.idx, container).is_some_and(|menu| menu.ids.contains(&self.selected())) }
+
+	/// The type of the highlighted entry.
+	#[must_use]
+	pub fn selected(&self) -> u8 { self.ids[self.selected] }
+
+	pub fn render(&self, builder: &mut VertexBufferBuilder) {
+		let mut lines = vec!["Add element ([Up] / [Down] to select, [Enter] to add, [Esc] to close)".to_owned()];
+		for (idx, &id) in self.ids.iter().enumerate() {
+			lines.push(format!("{marker} {name}", marker = if idx == self.selected { '>' } else { ' ' }, name = id_to_string_name(id).0));
+		}
+		builder.draw_tooltip(&lines.iter().map(String::as_str).collect::<Vec<_>>(), (16, HEADER_SIZE), true);
+	}
+}
@@ -2,6 +2,7 @@ use crate::assets::{ALERT_TEXT_Z, ALERT_UV, ALERT_Z, HEADER_SIZE};
 use crate::color::TextColor;
 use crate::vertex_buffer_builder::{Vec2u, VertexBufferBuilder};
 use crate::{since_epoch, smoothstep64, StrExt};
+use std::fmt::{Display, Formatter};
 use std::time::Duration;
 
 pub struct Alert {
@@ -70,6 +71,16 @@ impl Alert {
 		let _ = write!(builder, "{}", self.message);
 	}
 
+	/// How long until the alert next moves, zero while it slides in or out.
+	#[must_use]
+	pub fn next_change(&self) -> Duration {
+		let Some(timestamp) = self.timestamp else { return Duration::ZERO };
+		let display_time = Duration::from_millis(((self.message.len() + self.title.len()) * 60 + 3000) as u64);
+		let shown = since_epoch().saturating_sub(timestamp);
+		let slid_in = Duration::from_millis(250);
+		if shown < slid_in { Duration::ZERO } else { (slid_in + display_time).saturating_sub(shown) }
+	}
+
 	pub fn is_invisible(&mut self) -> bool {
 		let ms = since_epoch().saturating_sub(*self.timestamp.get_or_insert(since_epoch())).as_millis() as usize;
 		let display_time = (self.message.len() + self.title.len()) * 60 + 3000;
@@ -89,3 +100,7 @@ impl Alert {
 		}
 	}
 }
+
+impl Display for Alert {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{} {}", self.title, self.message) }
+}
@@ -0,0 +1,260 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use compact_str::CompactString;
+use zune_inflate::{DeflateDecoder, DeflateOptions};
+
+use crate::assets::HEADER_SIZE;
+use crate::elements::element::NbtElement;
+use crate::file::{self, FileFormat};
+use crate::vertex_buffer_builder::VertexBufferBuilder;
+use crate::SortAlgorithm;
+
+/// Extensions of archive entries which are treated as NBT without sniffing them.
+pub const NBT_EXTENSIONS: &[&str] = &["nbt", "dat", "dat_old", "schem", "schematic", "litematic", "mcstructure"];
+/// Extensions of archive entries which are never NBT, skipped without inflating them, most of a jar are its classes.
+pub const SKIPPED_EXTENSIONS: &[&str] = &["class", "png", "ogg", "json", "mcmeta", "txt", "md", "mf", "toml", "properties", "lang", "js"];
+/// How many bytes of an entry without an NBT extension are inflated to sniff it before the rest of it is.
+const SNIFF_LEN: usize = 4;
+
+#[must_use]
+pub fn is_zip(buf: &[u8]) -> bool { buf.starts_with(b"PK\x03\x04") || buf.starts_with(b"PK\x05\x06") }
+
+/// The name and decompressed bytes of every file inside of a zip archive (`.zip`, `.jar`) which has an NBT extension or sniffs as (optionally gzip or zlib compressed) NBT.
+///
+/// Entries are told apart by their extension first, see [`NBT_EXTENSIONS`] and [`SKIPPED_EXTENSIONS`], only the first bytes of any other entry are inflated to sniff it.
+///
+/// Only the stored and deflated compression methods are supported, encrypted and zip64 entries are skipped.
+pub fn nbt_entries(buf: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+	fn u16_at(buf: &[u8], idx: usize) -> Option<usize> { buf.get(idx..idx + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize) }
+	fn u32_at(buf: &[u8], idx: usize) -> Option<usize> { buf.get(idx..idx + 4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize) }
+	fn sniffs_as_nbt(bytes: &[u8]) -> bool {
+		matches!(bytes.first_chunk::<2>().copied().map(u16::from_be_bytes), Some(0x1F8B | 0x7801 | 0x789C | 0x78DA)) || bytes.first() == Some(&0x0A)
+	}
+
+	// the end of central directory record is 22 bytes, followed by a comment of up to 65535 bytes
+	let last = buf.len().checked_sub(22).context("Archive is too short")?;
+	let eocd = (last.saturating_sub(u16::MAX as usize)..=last).rev().find(|&idx| buf[idx..].starts_with(b"PK\x05\x06")).context("Could not find the end of the archive's central directory")?;
+	let count = u16_at(buf, eocd + 10).context("Truncated end of central directory")?;
+	let mut pos = u32_at(buf, eocd + 16).context("Truncated end of central directory")?;
+
+	let mut entries = Vec::new();
+	for _ in 0..count {
+		if !buf.get(pos..).is_some_and(|header| header.starts_with(b"PK\x01\x02")) { return Err(anyhow!("Invalid central directory header at offset {pos}")) }
+		let flags = u16_at(buf, pos + 8).context("Truncated central directory header")?;
+		let method = u16_at(buf, pos + 10).context("Truncated central directory header")?;
+		let compressed_len = u32_at(buf, pos + 20).context("Truncated central directory header")?;
+		let name_len = u16_at(buf, pos + 28).context("Truncated central directory header")?;
+		let extra_len = u16_at(buf, pos + 30).context("Truncated central directory header")?;
+		let comment_len = u16_at(buf, pos + 32).context("Truncated central directory header")?;
+		let local_header = u32_at(buf, pos + 42).context("Truncated central directory header")?;
+		let name = buf.get(pos + 46..pos + 46 + name_len).map(String::from_utf8_lossy).context("Truncated central directory header")?.into_owned();
+		pos += 46 + name_len + extra_len + comment_len;
+
+		if name.ends_with('/') || flags & 1 > 0 || compressed_len == u32::MAX as usize || local_header == u32::MAX as usize || !matches!(method, 0 | 8) { continue }
+		let extension = name.rsplit_once('.').map(|(_, extension)| extension);
+		let has_extension_of = |extensions: &[&str]| extension.is_some_and(|extension| extensions.iter().any(|x| x.eq_ignore_ascii_case(extension)));
+		if has_extension_of(SKIPPED_EXTENSIONS) { continue }
+		if !buf.get(local_header..).is_some_and(|header| header.starts_with(b"PK\x03\x04")) { continue }
+		let Some(local_name_len) = u16_at(buf, local_header + 26) else { continue };
+		let Some(local_extra_len) = u16_at(buf, local_header + 28) else { continue };
+		let start = local_header + 30 + local_name_len + local_extra_len;
+		let Some(data) = buf.get(start..start + compressed_len) else { continue };
+
+		if !has_extension_of(NBT_EXTENSIONS) {
+			let sniffed = if method == 0 {
+				sniffs_as_nbt(data)
+			} else {
+				// stops as soon as more than the first bytes are inflated, which are then kept by the error
+				match DeflateDecoder::new_with_options(data, DeflateOptions::default().set_limit(SNIFF_LEN).set_size_hint(SNIFF_LEN)).decode_deflate() {
+					Ok(bytes) => sniffs_as_nbt(&bytes),
+					Err(e) => sniffs_as_nbt(&e.data),
+				}
+			};
+			if !sniffed { continue }
+		}
+		let bytes = if method == 0 {
+			data.to_vec()
+		} else if let Ok(bytes) = DeflateDecoder::new(data).decode_deflate() {
+			bytes
+		} else {
+			continue
+		};
+		entries.push((name, bytes));
+	}
+
+	Ok(entries)
+}
+
+/// The NBT entries of an archive being opened, one of which, or all of them at once, is picked to open as a tab.
+pub struct EntryPicker {
+	/// The file name of the archive, entries are opened as relative paths inside of it so saving them asks for a new file instead of writing over the archive.
+	archive: PathBuf,
+	entries: Vec<(String, Vec<u8>)>,
+	/// `0` opens every entry at once, see [`file::from_archive`].
+	selected: usize,
+}
+
+impl EntryPicker {
+	#[must_use]
+	pub fn new(archive: &Path, entries: Vec<(String, Vec<u8>)>) -> Self {
+		Self { archive: archive.to_path_buf(), entries, selected: 0 }
+	}
+
+	pub fn select_previous(&mut self) { self.selected = self.selected.checked_sub(1).unwrap_or(self.entries.len()); }
+
+	pub fn select_next(&mut self) { self.selected = if self.selected >= self.entries.len() { 0 } else { self.selected + 1 }; }
+
+	/// Parses the highlighted entry, along with its format, root name and the path it's opened as.
+	///
+	/// # Errors
+	///
+	/// * The entry doesn't parse, or when opening every entry, none of them do
+	pub fn open(self, sort_algorithm: SortAlgorithm) -> Result<(NbtElement, FileFormat, CompactString, PathBuf)> {
+		let Self { archive, mut entries, selected } = self;
+		if selected == 0 || selected > entries.len() {
+			return Ok((file::from_archive_entries(entries, sort_algorithm)?, FileFormat::Nbt, CompactString::new_inline(""), archive))
+		}
+		let (name, bytes) = entries.swap_remove(selected - 1);
+		let path = archive.join(&name);
+		let (nbt, format, root_name) = file::from_file(&path, bytes, sort_algorithm).with_context(|| format!("Failed to open {name}"))?;
+		Ok((nbt, format, root_name, path))
+	}
+
+	pub fn render(&self, builder: &mut VertexBufferBuilder) {
+		let marker = |idx: usize| if idx == self.selected { '>' } else { ' ' };
+		let mut lines = vec![format!("Open from {} ([Up] / [Down] to select, [Enter] to open, [Esc] to close)", self.archive.display()), format!("{} All {} entries", marker(0), self.entries.len())];
+		for (idx, (name, _)) in self.entries.iter().enumerate() {
+			lines.push(format!("{} {name}", marker(idx + 1)));
+		}
+		builder.draw_tooltip(&lines.iter().map(String::as_str).collect::<Vec<_>>(), (16, HEADER_SIZE), true);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Write;
+
+	use flate2::write::{DeflateEncoder, GzEncoder};
+	use flate2::Compression;
+
+	use std::path::Path;
+
+	use crate::elements::element::NbtElement;
+	use crate::file::{from_archive, FileFormat};
+	use crate::SortAlgorithm;
+
+	use super::{is_zip, nbt_entries, EntryPicker};
+
+	/// A zip archive of `(name, deflated, data)` entries, written the way zip tools lay them out.
+	fn zip(entries: &[(&str, bool, &[u8])]) -> Vec<u8> {
+		let mut buf = vec![];
+		let mut central_directory = vec![];
+		for &(name, deflated, data) in entries {
+			let data = if deflated {
+				let mut encoder = DeflateEncoder::new(vec![], Compression::best());
+				encoder.write_all(data).expect("writing to a vec can't fail");
+				encoder.finish().expect("writing to a vec can't fail")
+			} else {
+				data.to_vec()
+			};
+			let method: u16 = if deflated { 8 } else { 0 };
+			let offset = buf.len() as u32;
+			buf.extend_from_slice(b"PK\x03\x04\x14\x00\x00\x00");
+			buf.extend_from_slice(&method.to_le_bytes());
+			buf.extend_from_slice(&[0; 8]); // time, date and crc
+			buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+			buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+			buf.extend_from_slice(&(name.len() as u16).to_le_bytes());
+			buf.extend_from_slice(&0_u16.to_le_bytes());
+			buf.extend_from_slice(name.as_bytes());
+			buf.extend_from_slice(&data);
+
+			central_directory.extend_from_slice(b"PK\x01\x02\x14\x00\x14\x00\x00\x00");
+			central_directory.extend_from_slice(&method.to_le_bytes());
+			central_directory.extend_from_slice(&[0; 8]); // time, date and crc
+			central_directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+			central_directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+			central_directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+			central_directory.extend_from_slice(&[0; 12]); // extra and comment lengths, disk, attributes
+			central_directory.extend_from_slice(&offset.to_le_bytes());
+			central_directory.extend_from_slice(name.as_bytes());
+		}
+		let central_directory_offset = buf.len() as u32;
+		buf.extend_from_slice(&central_directory);
+		buf.extend_from_slice(b"PK\x05\x06\x00\x00\x00\x00");
+		buf.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+		buf.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+		buf.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+		buf.extend_from_slice(&central_directory_offset.to_le_bytes());
+		buf.extend_from_slice(&0_u16.to_le_bytes());
+		buf
+	}
+
+	#[test]
+	fn nbt_entries_of_a_zip_are_opened() {
+		let structure = NbtElement::from_snbt("{size: [3, 4, 5], blocks: [{pos: [0, 0, 0], state: 0}]}").expect("the fixture is valid SNBT");
+		let level = NbtElement::from_snbt("{Data: {LevelName: \"world\", version: 19133}}").expect("the fixture is valid SNBT");
+		let mut gzip = GzEncoder::new(vec![], Compression::default());
+		gzip.write_all(&level.to_be_file()).expect("writing to a vec can't fail");
+		let level_bytes = gzip.finish().expect("writing to a vec can't fail");
+		let archive = zip(&[
+			("META-INF/MANIFEST.MF", true, b"Manifest-Version: 1.0\r\n"),
+			("data/mod/structures/house.nbt", true, &structure.to_be_file()),
+			("level.dat", false, &level_bytes),
+			("blob", false, &structure.to_be_file()),
+			("assets/", false, b""),
+			// sniffs as NBT, but classes are skipped before looking inside of them
+			("Mod.class", true, &structure.to_be_file()),
+			("deflated", true, &level_bytes),
+			("readme", true, "not nbt at all ".repeat(100).as_bytes()),
+		]);
+		assert!(is_zip(&archive));
+
+		let entries = nbt_entries(&archive).expect("the archive is valid");
+		assert_eq!(entries.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), ["data/mod/structures/house.nbt", "level.dat", "blob", "deflated"]);
+		assert_eq!(entries[3].1, level_bytes);
+
+		let nbt = from_archive(&archive, SortAlgorithm::None).expect("the archive holds NBT");
+		let compound = nbt.as_compound().expect("archives open as a compound");
+		let entry = |name: &str| compound.entries.idx_of(name).and_then(|idx| compound.get(idx)).map(|(_, value)| value);
+		assert!(entry("data/mod/structures/house.nbt").is_some_and(|value| value.deep_eq(&structure)));
+		assert!(entry("level.dat").is_some_and(|value| value.deep_eq(&level)));
+		assert!(entry("blob").is_some_and(|value| value.deep_eq(&structure)));
+	}
+
+	#[test]
+	fn entries_are_picked_to_open() {
+		let structure = NbtElement::from_snbt("{size: [1, 2, 3]}").expect("the fixture is valid SNBT");
+		let archive = zip(&[("a.nbt", true, &structure.to_be_file()), ("b.nbt", false, b"\x0A\xFF\xFF")]);
+		let entries = || nbt_entries(&archive).expect("the archive is valid");
+
+		let mut picker = EntryPicker::new(Path::new("mod.jar"), entries());
+		picker.select_next();
+		let (nbt, format, _, path) = picker.open(SortAlgorithm::None).expect("the entry is valid NBT");
+		assert!(nbt.deep_eq(&structure));
+		assert_eq!(format, FileFormat::Nbt);
+		assert_eq!(path, Path::new("mod.jar/a.nbt"));
+
+		// the first choice opens every entry at once
+		let mut picker = EntryPicker::new(Path::new("mod.jar"), entries());
+		picker.select_next();
+		picker.select_next();
+		picker.select_next();
+		let (nbt, _, _, path) = picker.open(SortAlgorithm::None).expect("one of the entries is valid NBT");
+		assert_eq!(nbt.as_compound().map(|compound| compound.len()), Some(1));
+		assert_eq!(path, Path::new("mod.jar"));
+
+		let mut picker = EntryPicker::new(Path::new("mod.jar"), entries());
+		picker.select_previous();
+		assert!(picker.open(SortAlgorithm::None).is_err_and(|e| e.to_string().contains("b.nbt")));
+	}
+
+	#[test]
+	fn archives_without_nbt_are_rejected() {
+		let archive = zip(&[("readme.txt", false, b"hello")]);
+		assert!(nbt_entries(&archive).is_ok_and(|entries| entries.is_empty()));
+		assert!(from_archive(&archive, SortAlgorithm::None).is_err());
+		assert!(nbt_entries(&archive[..archive.len() - 30]).is_err());
+	}
+}
@@ -2,11 +2,12 @@ use std::cell::LazyCell;
 use std::mem::ManuallyDrop;
 use std::ops::Deref;
 use zune_png::zune_core::options::DecoderOptions;
-use crate::since_epoch;
 
 use crate::vertex_buffer_builder::Vec2u;
 
 pub const HEADER_SIZE: usize = 48;
+/// The height of the status bar below the tree.
+pub const STATUS_BAR_SIZE: usize = 16;
 
 pub const ATLAS_ENCODED: &[u8] = include_bytes!("assets/atlas.png");
 pub const ATLAS_WIDTH: usize = 256;
@@ -171,6 +172,8 @@ pub enum ZOffset {
     BOOKMARK_Z = 140,
     SELECTED_TEXT_Z = 170,
 	SELECTED_TEXT_SELECTION_Z = 171,
+	STATUS_BAR_Z = 180,
+	STATUS_BAR_TEXT_Z = 181,
     ACTION_WHEEL_Z = 190,
     SCROLLBAR_BOOKMARK_Z = 199,
     SCROLLBAR_Z = 200,
@@ -181,6 +184,7 @@ pub enum ZOffset {
     TOOLTIP_Z = 255,
 }
 
+#[cfg_attr(not(feature = "gui"), allow(unused_imports))]
 pub use ZOffset::*;
 
 static mut ATLAS_CELL: LazyCell<Vec<u8>> = LazyCell::new(|| zune_png::PngDecoder::new_with_options(ATLAS_ENCODED, DecoderOptions::new_fast().png_set_confirm_crc(false)).decode_raw().unwrap());
@@ -189,11 +193,12 @@ pub fn atlas() -> &'static [u8] {
 	unsafe { ATLAS_CELL.deref().as_slice() }
 }
 
+/// The music disc icons in the order taken by [`icon`], as they're named in the config.
+pub const ICON_NAMES: [&str; 8] = ["otherside", "pigstep", "mellohi", "5", "ward", "11", "relic", "stal"];
+
 #[allow(clippy::cast_ptr_alignment)]
-pub fn icon() -> Vec<u8> {
-	// error!("Hello, world!");
-	let original = match (since_epoch().as_millis() & 7) as u8 {
-		// it's a good random only because its used once
+pub fn icon(idx: usize) -> Vec<u8> {
+	let original = match idx & 7 {
 		0 => OTHERSIDE_MUSIC_DISC_ICON,
 		1 => PIGSTEP_MUSIC_DISC_ICON,
 		2 => MELLOHI_MUSIC_DISC_ICON,
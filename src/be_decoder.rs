@@ -3,13 +3,18 @@ use std::marker::PhantomData;
 
 use compact_str::CompactString;
 use crate::elements::compound::CompoundMap;
-use crate::SortAlgorithm;
+use crate::elements::list::EndListError;
+use crate::{mutf8, SortAlgorithm};
 
 pub struct BigEndianDecoder<'a> {
 	pub data: *const u8,
 	end: *const u8,
 	sort: SortAlgorithm,
 	_marker: PhantomData<&'a ()>,
+	/// The amount of lists of `TAG_End` with a length read so far, they fail to parse unless `lenient_lists` reads them as empty lists.
+	pub end_lists: usize,
+	/// The list of `TAG_End` with a length which failed the parse, see [`crate::elements::list::NbtList::end_list_len`].
+	pub end_list_error: Option<EndListError>,
 }
 
 #[allow(improper_ctypes_definitions)]
@@ -22,6 +27,8 @@ impl<'a> BigEndianDecoder<'a> {
 			data: data.as_ptr(),
 			sort,
 			_marker: PhantomData,
+			end_lists: 0,
+			end_list_error: None,
 		}
 	}
 
@@ -33,14 +40,17 @@ impl<'a> BigEndianDecoder<'a> {
 	#[optimize(speed)]
 	#[must_use]
 	pub fn assert_len(&self, remaining_len: usize) -> Option<()> {
-		// <= end because it will read *until* that byte
-		if unsafe { likely((self.data.add(remaining_len) as usize) <= self.end as usize) } {
+		// compared as lengths, since a pointer offset by a crafted length can overflow
+		if likely(remaining_len <= self.remaining_len()) {
 			Some(())
 		} else {
 			None
 		}
 	}
 
+	#[must_use]
+	pub fn remaining_len(&self) -> usize { self.end as usize - self.data as usize }
+
 	#[optimize(speed)]
 	pub unsafe fn read_bytes<const N: usize>(&mut self) -> [u8; N] {
 		let array = self.data.cast::<[u8; N]>().read();
@@ -86,8 +96,36 @@ impl<'a> BigEndianDecoder<'a> {
 		let len = self.u16() as usize;
 		self.assert_len(len)?;
 
-		let out = CompactString::from_utf8_lossy(core::slice::from_raw_parts(self.data, len));
+		let out = mutf8::decode(core::slice::from_raw_parts(self.data, len));
 		self.data = self.data.add(len);
 		Some(out)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::elements::element::{NbtElement, NbtInt, NbtLongArray};
+	use crate::elements::list::NbtList;
+	use crate::SortAlgorithm;
+
+	use super::BigEndianDecoder;
+
+	#[test]
+	fn lengths_past_the_end_of_the_input_are_rejected() {
+		let claimed = (1_u32 << 31).to_be_bytes();
+		let array = [&claimed[..], &[0; 6]].concat();
+		assert_eq!(array.len(), 10);
+		assert!(NbtElement::from_be_bytes(NbtLongArray::ID, &mut BigEndianDecoder::new(&array, SortAlgorithm::None)).is_none());
+		let list = [&[NbtInt::ID][..], &claimed, &[0; 5]].concat();
+		assert_eq!(list.len(), 10);
+		assert!(NbtElement::from_be_bytes(NbtList::ID, &mut BigEndianDecoder::new(&list, SortAlgorithm::None)).is_none());
+	}
+
+	#[test]
+	fn truncated_strings_are_rejected() {
+		let mut decoder = BigEndianDecoder::new(&[0x00, 0x05, b'a', b'b'], SortAlgorithm::None);
+		assert!(unsafe { decoder.string() }.is_none());
+		let mut decoder = BigEndianDecoder::new(&[0x00, 0x02, b'a', b'b'], SortAlgorithm::None);
+		assert_eq!(unsafe { decoder.string() }.as_deref(), Some("ab"));
+	}
+}
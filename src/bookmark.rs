@@ -97,6 +97,10 @@ pub struct Bookmarks {
     inner: Vec<Bookmark>,
 }
 
+impl Default for Bookmarks {
+    fn default() -> Self { Self::new() }
+}
+
 impl Bookmarks {
     #[inline]
     pub const fn new() -> Self {
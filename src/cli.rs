@@ -1,27 +1,34 @@
-use std::fmt::Formatter;
+use std::fmt::Write;
 use std::fs::{File, read};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use compact_str::CompactString;
 
 use glob::glob;
-
-use crate::{create_regex, error, log, SortAlgorithm, WindowProperties};
-use crate::elements::element::NbtElement;
+use zune_png::PngEncoder;
+use zune_png::zune_core::bit_depth::BitDepth;
+use zune_png::zune_core::colorspace::ColorSpace;
+use zune_png::zune_core::options::EncoderOptions;
+
+use crate::{archive, create_regex, error, log, SortAlgorithm};
+use crate::elements::chunk::{ExternalChunks, NbtRegion};
+use crate::elements::element::{NbtElement, PathSegment};
+use crate::encoder::UncheckedBufWriter;
 use crate::search_box::{SearchBox, SearchPredicate, SearchPredicateInner};
-use crate::tab::FileFormat;
-use crate::workbench::Workbench;
+use crate::file::{self, FileFormat};
+use crate::json;
 
 struct SearchResult {
     path: PathBuf,
-    lines: Vec<usize>,
+    /// The true line number of every match, along with its path and value.
+    lines: Vec<(usize, String, CompactString)>,
 }
 
 impl std::fmt::Display for SearchResult {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Found {n} matches in file {path:?} at line numbers:", n = self.lines.len(), path = self.path)?;
-        for &line in &self.lines {
-            writeln!(f, "{line}")?;
+        for (line, path, value) in &self.lines {
+            writeln!(f, "{line} {path} = {value}")?;
         }
         Ok(())
     }
@@ -108,6 +115,15 @@ fn increment_progress_bar(completed: &AtomicU64, size: u64, total: u64, action:
     let _ = std::io::Write::flush(&mut std::io::stdout());
 }
 
+/// Reads the file at `path` like opening it does, archives included.
+fn open(path: &Path, bytes: Vec<u8>) -> anyhow::Result<(NbtElement, FileFormat)> {
+    if archive::is_zip(&bytes) {
+        Ok((file::from_archive(&bytes, SortAlgorithm::Type)?, FileFormat::Nbt))
+    } else {
+        file::from_file(path, bytes, SortAlgorithm::Type).map(|(value, format, _)| (value, format))
+    }
+}
+
 fn get_argument(key: &str, args: &mut Vec<String>) -> Option<String> {
     Some(args.remove(args.iter().position(|x| x.strip_prefix(key).is_some_and(|x| x.starts_with("=")))?).split_off(key.len() + 1))
 }
@@ -132,9 +148,6 @@ pub fn find() -> ! {
             let mut path = root.clone();
             path.push(p);
             results.push(s.spawn(|| 'a: {
-                let mut workbench = Workbench::new(&mut WindowProperties::Fake);
-                workbench.tabs.clear();
-
                 let bytes = match read(&path) {
                     Ok(bytes) => bytes,
                     Err(e) => {
@@ -146,20 +159,28 @@ pub fn find() -> ! {
 
                 let len = bytes.len() as u64;
 
-                if let Err(e) = workbench.on_open_file(&path, bytes, &mut WindowProperties::Fake) {
-                    error!("File parse error: {e}");
-                    increment_progress_bar(&completed, len, total_size, "Searching");
-                    break 'a None;
-                }
+                let value = match open(&path, bytes) {
+                    Ok((value, _)) => value,
+                    Err(e) => {
+                        error!("File parse error: {e}");
+                        increment_progress_bar(&completed, len, total_size, "Searching");
+                        break 'a None;
+                    }
+                };
 
-                let tab = workbench.tabs.remove(0);
-                let bookmarks = SearchBox::search0(&tab.value, &predicate);
-                std::thread::Builder::new().stack_size(50_331_648 /*48MiB*/).spawn(move || drop(tab)).expect("Failed to spawn thread");
+                let bookmarks = SearchBox::search0(&value, &predicate);
+                let lines = bookmarks.iter().map(|bookmark| {
+                    let line = bookmark.true_line_number();
+                    let path = value.path_to(line).unwrap_or_default();
+                    let value = value.get_by_path(&path).map(|element| element.value().0).unwrap_or_default();
+                    (line, PathSegment::join(&path), value)
+                }).collect::<Vec<_>>();
+                std::thread::Builder::new().stack_size(50_331_648 /*48MiB*/).spawn(move || drop(value)).expect("Failed to spawn thread");
                 increment_progress_bar(&completed, len, total_size, "Searching");
-                if !bookmarks.is_empty() {
+                if !lines.is_empty() {
                     Some(SearchResult {
                         path,
-                        lines: bookmarks.into_iter().map(|x| x.true_line_number()).collect(),
+                        lines,
                     })
                 } else {
                     None
@@ -230,9 +251,6 @@ pub fn reformat() -> ! {
             s.spawn(|| 'a: {
                 let p = p;
                 let path = pa;
-                let mut workbench = Workbench::new(&mut WindowProperties::Fake);
-                workbench.tabs.clear();
-
                 let bytes = match read(&path) {
                     Ok(bytes) => bytes,
                     Err(e) => {
@@ -244,19 +262,21 @@ pub fn reformat() -> ! {
 
                 let len = bytes.len() as u64;
 
-                if let Err(e) = workbench.on_open_file(&path, bytes, &mut WindowProperties::Fake) {
-                    error!("File parse error: {e}");
-                    increment_progress_bar(&completed, len, total_size, "Reformatting");
-                    break 'a;
-                }
+                let (value, old_format) = match open(&path, bytes) {
+                    Ok(opened) => opened,
+                    Err(e) => {
+                        error!("File parse error: {e}");
+                        increment_progress_bar(&completed, len, total_size, "Reformatting");
+                        break 'a;
+                    }
+                };
 
-                let tab = workbench.tabs.remove(0);
-                if let FileFormat::Nbt | FileFormat::Snbt | FileFormat::Gzip | FileFormat::Zlib = tab.format {} else {
-                    error!("Tab had invalid file format {}", tab.format.to_string());
+                if let FileFormat::Nbt | FileFormat::Snbt | FileFormat::Gzip | FileFormat::Zlib = old_format {} else {
+                    error!("File had invalid format {old_format}");
                 }
 
-                let out = format.encode(&tab.value);
-                std::thread::Builder::new().stack_size(50_331_648 /*48MiB*/).spawn(move || drop(tab)).expect("Failed to spawn thread");
+                let out = format.encode(&value);
+                std::thread::Builder::new().stack_size(50_331_648 /*48MiB*/).spawn(move || drop(value)).expect("Failed to spawn thread");
 
                 let name = path.file_stem().expect("File must have stem").to_string_lossy().into_owned() + "." + &extension;
 
@@ -284,3 +304,612 @@ pub fn reformat() -> ! {
 
     std::process::exit(0);
 }
+
+#[inline]
+pub fn check_region() -> ! {
+    let mut args = std::env::args().collect::<Vec<_>>();
+    args.drain(..2);
+
+    let rename = if let Some(idx) = args.iter().position(|x| x == "--rename" || x == "-r") {
+        args.remove(idx);
+        true
+    } else {
+        false
+    };
+
+    let (root, paths) = get_paths(args);
+
+    let mut checked = 0_usize;
+    let mut mismatched = 0_usize;
+    for p in paths {
+        let mut path = root.clone();
+        path.push(p);
+
+        let check = match check_region_file(&path) {
+            Ok(check) => check,
+            Err(e) => {
+                error!("{e}");
+                continue;
+            }
+        };
+        checked += 1;
+
+        for &(a, b) in &check.overlapping {
+            error!("File {path:?} has chunks {a} and {b} sharing sectors");
+        }
+        for &chunk in &check.out_of_bounds {
+            error!("File {path:?} has chunk {chunk} allocated over its header or past its end");
+        }
+        if let Some((used, free, gaps)) = check.fragmented {
+            log!("File {path:?} is fragmented, {used} sectors are used by chunks and {free} are free across {gaps} gaps");
+        }
+
+        let Some((actual_x, actual_z)) = check.actual else { continue };
+        if check.strays > 0 {
+            log!("File {path:?} has {n} chunks that do not belong to region ({actual_x}, {actual_z})", n = check.strays);
+        }
+
+        if let Some(name) = check.suggested_name {
+            mismatched += 1;
+            let (x, z) = check.named;
+            log!("File {path:?} contains chunks of region ({actual_x}, {actual_z}) but is named as region ({x}, {z}), suggested name: {name}");
+            if rename {
+                let new_path = path.with_file_name(&name);
+                if new_path.exists() {
+                    error!("Could not rename {path:?}, {new_path:?} already exists");
+                } else if let Err(e) = std::fs::rename(&path, &new_path) {
+                    error!("File rename error: {e}");
+                } else {
+                    log!("Renamed {path:?} to {new_path:?}");
+                }
+            }
+        }
+    }
+
+    log!("Checked {checked} region files, {mismatched} did not match their file name.");
+
+    std::process::exit(if mismatched > 0 { 1 } else { 0 });
+}
+
+/// What `check-region` found in a region file, see [`check_region_file`].
+struct RegionCheck {
+    /// The region the file is named after.
+    named: (i32, i32),
+    /// The region most of its chunks belong to by their `xPos` and `zPos`, `None` when no chunk has them.
+    actual: Option<(i32, i32)>,
+    /// The amount of chunks which belong to another region than `actual`.
+    strays: usize,
+    /// The name of `actual`, when it isn't the region the file is named after.
+    suggested_name: Option<String>,
+    /// See [`crate::elements::chunk::SectorMap::overlapping`].
+    overlapping: Vec<(u16, u16)>,
+    /// See [`crate::elements::chunk::SectorMap::out_of_bounds`].
+    out_of_bounds: Vec<u16>,
+    /// The sectors used by chunks, the free ones and the gaps they are spread across, when a free sector is followed by a used one.
+    fragmented: Option<(usize, usize, usize)>,
+}
+
+/// Reads the region file at `path` and compares the coordinates of its chunks with its name.
+///
+/// # Errors
+///
+/// * `path` isn't named like a region file, can't be read or doesn't parse as one
+fn check_region_file(path: &Path) -> Result<RegionCheck, String> {
+    let Some((x, z, extension)) = NbtRegion::coordinates_of(path) else {
+        return Err(format!("File {path:?} is not named like a region file (r.<x>.<z>.mca)"));
+    };
+
+    let bytes = read(path).map_err(|e| format!("File read error: {e}"))?;
+    let region = NbtRegion::from_be_bytes(&bytes, SortAlgorithm::None, ExternalChunks::Files(path)).map_err(|e| format!("File parse error: {path:?} is not a valid region file, {e}"))?;
+
+    let (overlapping, out_of_bounds, fragmented) = match NbtRegion::sector_map(&bytes) {
+        Some(map) => {
+            let gaps = map.gaps();
+            let fragmented = gaps.iter().any(|gap| gap.end < map.sectors().len()).then(|| (map.used(), map.free(), gaps.len()));
+            (map.overlapping().to_vec(), map.out_of_bounds().to_vec(), fragmented)
+        }
+        None => (vec![], vec![], None),
+    };
+
+    let mut regions: Vec<((i32, i32), usize)> = vec![];
+    for chunk in region.children().filter_map(NbtElement::as_chunk) {
+        let Some((chunk_x, chunk_z)) = chunk.world_coordinates() else { continue };
+        let coordinates = (chunk_x >> 5, chunk_z >> 5);
+        if let Some((_, count)) = regions.iter_mut().find(|(other, _)| *other == coordinates) {
+            *count += 1;
+        } else {
+            regions.push((coordinates, 1));
+        }
+    }
+
+    let most = regions.iter().max_by_key(|(_, count)| *count).copied();
+    let total = regions.iter().map(|(_, count)| count).sum::<usize>();
+    Ok(RegionCheck {
+        named: (x, z),
+        actual: most.map(|(actual, _)| actual),
+        strays: most.map_or(0, |(_, count)| total - count),
+        suggested_name: most.filter(|&(actual, _)| actual != (x, z)).map(|((actual_x, actual_z), _)| format!("r.{actual_x}.{actual_z}.{extension}")),
+        overlapping,
+        out_of_bounds,
+        fragmented,
+    })
+}
+
+/// A check `validate` runs on top of parsing, selected with `--checks`.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Check {
+    /// Keys found more than once in a compound.
+    Duplicates,
+    /// Lists of `TAG_End` with a length, only problems with `--strict` since they can only be read at all with `lenient_lists`.
+    Lists,
+    /// Overlapping or out-of-bounds chunk sectors of region files, and with `--strict`, fragmented ones.
+    Sectors,
+}
+
+impl Check {
+    const ALL: [Self; 3] = [Self::Duplicates, Self::Lists, Self::Sectors];
+
+    fn from_str_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "duplicates" => Self::Duplicates,
+            "lists" => Self::Lists,
+            "sectors" => Self::Sectors,
+            _ => return None,
+        })
+    }
+}
+
+struct Problem {
+    path: PathBuf,
+    check: &'static str,
+    message: String,
+}
+
+#[inline]
+pub fn validate() -> ! {
+    let mut args = std::env::args().collect::<Vec<_>>();
+    args.drain(..2);
+
+    let strict = if let Some(idx) = args.iter().position(|x| x == "--strict" || x == "-s") {
+        args.remove(idx);
+        true
+    } else {
+        false
+    };
+    let checks = match get_argument("--checks", &mut args).or_else(|| get_argument("-c", &mut args)) {
+        Some(checks) => checks.split(',').map(|check| Check::from_str_name(check.trim()).unwrap_or_else(|| {
+            error!("Unknown check `{check}`, expected `duplicates`, `lists` or `sectors`.");
+            std::process::exit(1);
+        })).collect::<Vec<_>>(),
+        None => Check::ALL.to_vec(),
+    };
+
+    let (root, paths) = get_paths(args);
+    let report = validate_files(&root, paths, &checks, strict);
+
+    for problem in &report.problems {
+        error!("{path:?}: [{check}] {message}", path = problem.path, check = problem.check, message = problem.message);
+    }
+    if report.problems.is_empty() {
+        error!("Validated {checked} files, no problems found.", checked = report.checked);
+    } else {
+        // the report above goes to stderr, so stdout only holds the summary
+        log!("{json}", json = report.to_json());
+    }
+
+    std::process::exit(report.exit_code());
+}
+
+/// What `validate` found in the files it checked, see [`validate_files`].
+struct Report {
+    checked: usize,
+    problems: Vec<Problem>,
+}
+
+impl Report {
+    /// `0` when no problems were found, `1` otherwise.
+    fn exit_code(&self) -> i32 { i32::from(!self.problems.is_empty()) }
+
+    /// The machine-readable summary, `{"checked":<files>,"problems":[{"path":...,"check":...,"message":...}]}`.
+    fn to_json(&self) -> String {
+        let mut json = format!("{{\"checked\":{checked},\"problems\":[", checked = self.checked);
+        for (idx, problem) in self.problems.iter().enumerate() {
+            if idx > 0 { json.push(',') }
+            json.push_str("{\"path\":");
+            json::write_string(&problem.path.to_string_lossy(), &mut json);
+            json.push_str(",\"check\":");
+            json::write_string(problem.check, &mut json);
+            json.push_str(",\"message\":");
+            json::write_string(&problem.message, &mut json);
+            json.push('}');
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+/// Parses every file of `paths` inside of `root`, running `checks` on the ones which parse.
+fn validate_files(root: &Path, paths: Vec<PathBuf>, checks: &[Check], strict: bool) -> Report {
+    let mut checked = 0_usize;
+    let mut problems = vec![];
+    for p in paths {
+        let mut path = root.to_path_buf();
+        path.push(p);
+        let mut problem = |check: &'static str, message: String| problems.push(Problem { path: path.clone(), check, message });
+
+        let bytes = match read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                problem("parse", format!("File read error: {e}"));
+                continue;
+            }
+        };
+        let format = match file::from_file(&path, bytes.clone(), SortAlgorithm::None) {
+            Ok((_, format, _)) => format,
+            Err(e) => {
+                problem("parse", format!("{e:#}"));
+                continue;
+            }
+        };
+        checked += 1;
+
+        // detecting the format doesn't count what it read past, so it is parsed once more with the detected format
+        let Some((value, counts)) = format.decode_counted(&bytes, SortAlgorithm::None, ExternalChunks::Files(&path)) else {
+            problem("parse", format!("Failed to parse file as {}", format.into_str()));
+            continue;
+        };
+        for (x, z) in value.as_region().into_iter().flat_map(NbtRegion::unreadable) {
+            problem("parse", format!("Chunk {x}|{z} failed to parse"));
+        }
+
+        let duplicates = counts.duplicate_keys;
+        if checks.contains(&Check::Duplicates) && duplicates > 0 {
+            problem("duplicates", format!("{duplicates} keys are present more than once in their compound, every one of their entries was kept"));
+        }
+        let lists = counts.end_lists;
+        if checks.contains(&Check::Lists) && strict && lists > 0 {
+            problem("lists", format!("{lists} lists of TAG_End have a length, they were read as empty lists"));
+        }
+        if checks.contains(&Check::Sectors) && format == FileFormat::Mca && let Some(map) = NbtRegion::sector_map(&bytes) {
+            for &(a, b) in map.overlapping() {
+                problem("sectors", format!("Chunks {a} and {b} share sectors"));
+            }
+            for &chunk in map.out_of_bounds() {
+                problem("sectors", format!("Chunk {chunk} is allocated over the header or past the end of the file"));
+            }
+            let gaps = map.gaps();
+            if strict && gaps.iter().any(|gap| gap.end < map.sectors().len()) {
+                problem("sectors", format!("Fragmented, {free} sectors are free across {n} gaps", free = map.free(), n = gaps.len()));
+            }
+        }
+    }
+
+    Report { checked, problems }
+}
+
+#[inline]
+pub fn convert() -> ! {
+    let mut args = std::env::args().collect::<Vec<_>>();
+    args.drain(..2);
+
+    let format_arg = get_argument("--format", &mut args).or_else(|| get_argument("-f", &mut args));
+    let [input, output] = <[String; 2]>::try_from(args).unwrap_or_else(|_| {
+        error!("Expected an input and an output path.");
+        std::process::exit(1);
+    });
+    let (input, output) = (PathBuf::from(input), PathBuf::from(output));
+
+    let name = format_arg.as_deref().or_else(|| output.extension().and_then(|extension| extension.to_str()));
+    let format = match name {
+        Some("nbt") => FileFormat::Nbt,
+        Some("dat" | "dat_old" | "gzip") => FileFormat::Gzip,
+        Some("zlib") => FileFormat::Zlib,
+        Some("lz4") => FileFormat::Lz4,
+        Some("snbt") => FileFormat::Snbt,
+        Some("mca" | "mcr") => FileFormat::Mca,
+        None => {
+            error!("Could not infer the output format from {output:?}, specify it with `--format`.");
+            std::process::exit(1);
+        }
+        Some(x) => {
+            error!("Invalid format '{x}'");
+            std::process::exit(1);
+        }
+    };
+
+    let bytes = match read(&input) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("File read error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let (value, root_name) = if format == FileFormat::Mca && input.extension().is_some_and(|extension| extension == "snbt") {
+        let Some(region) = core::str::from_utf8(&bytes).ok().and_then(region_from_snbt) else {
+            error!("File parse error: Failed to parse SNBT chunks");
+            std::process::exit(1);
+        };
+        (NbtElement::Region(region), CompactString::new_inline(""))
+    } else {
+        match file::from_file(&input, bytes, SortAlgorithm::None) {
+            Ok((value, _, root_name)) => (value, root_name),
+            Err(e) => {
+                error!("File parse error: {e:#}");
+                std::process::exit(1);
+            }
+        }
+    };
+    if format == FileFormat::Mca && value.id() != NbtRegion::ID {
+        error!("Only region files (or SNBT chunks) can be converted to the region format.");
+        std::process::exit(1);
+    }
+
+    let (out, external) = if format == FileFormat::Snbt && let Some(region) = value.as_region() {
+        (region_to_snbt(region).into_bytes(), vec![])
+    } else {
+        format.encode_external(&value, &root_name)
+    };
+    if !external.is_empty() {
+        let Some((x, z, _)) = NbtRegion::coordinates_of(&output) else {
+            error!("{n} chunks are too big for the region file and need to be saved next to it, which requires the file to be named like a region file (r.<x>.<z>.mca)", n = external.len());
+            std::process::exit(1);
+        };
+        for (pos, bytes) in &external {
+            if let Err(e) = std::fs::write(output.with_file_name(ExternalChunks::file_name((x, z), *pos)), bytes) {
+                error!("File write error: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Err(e) = std::fs::write(&output, out) {
+        error!("File write error: {e}");
+        std::process::exit(1);
+    }
+
+    log!("Converted {input:?} to {output:?} as {}", format.into_str());
+    std::process::exit(0);
+}
+
+/// Regions have no SNBT of their own, so `convert` writes one chunk per line in the `x|z{...}` form chunks are read back from.
+fn region_to_snbt(region: &NbtRegion) -> String {
+    let mut str = String::new();
+    for chunk in region.children() {
+        let _ = writeln!(str, "{chunk}");
+    }
+    str
+}
+
+/// The reverse of [`region_to_snbt`], blank lines are skipped.
+fn region_from_snbt(str: &str) -> Option<NbtRegion> {
+    let mut region = NbtRegion::new();
+    for line in str.lines().filter(|line| !line.trim().is_empty()) {
+        let (_, chunk) = NbtElement::from_str(line, SortAlgorithm::None)?;
+        region.insert(region.len(), chunk).ok()?;
+    }
+    Some(region)
+}
+
+#[inline]
+pub fn optimize() -> ! {
+    let args = std::env::args().skip(2).collect::<Vec<_>>();
+    let (root, paths) = get_paths(args);
+
+    let mut optimized = 0_usize;
+    let (mut old_total, mut new_total) = (0_u64, 0_u64);
+    for p in paths {
+        let mut path = root.clone();
+        path.push(p);
+
+        let bytes = match read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                error!("File read error: {e}");
+                continue;
+            }
+        };
+        let region = match NbtRegion::from_be_bytes(&bytes, SortAlgorithm::None, ExternalChunks::Files(&path)) {
+            Ok(region) => region,
+            Err(e) => {
+                error!("File parse error: {path:?} is not a valid region file, {e}");
+                continue;
+            }
+        };
+
+        let mut writer = UncheckedBufWriter::new();
+        let external = region.to_be_bytes_external(&mut writer);
+        let out = writer.finish();
+        if !NbtRegion::from_be_bytes(&out, SortAlgorithm::None, ExternalChunks::Encoded(&external)).is_ok_and(|written| written.matches(&region)) {
+            error!("File {path:?} did not read back the same after repacking, it was left as is");
+            continue;
+        }
+        if !external.is_empty() {
+            let Some((x, z, _)) = NbtRegion::coordinates_of(&path) else {
+                error!("File {path:?} has {n} chunks too big for the region file, which requires it to be named like a region file (r.<x>.<z>.mca)", n = external.len());
+                continue;
+            };
+            if let Err(e) = external.iter().try_for_each(|(pos, bytes)| std::fs::write(path.with_file_name(ExternalChunks::file_name((x, z), *pos)), bytes)) {
+                error!("File write error: {e}");
+                continue;
+            }
+        }
+
+        let (old, new) = (bytes.len() as u64, out.len() as u64);
+        if out != bytes {
+            if let Err(e) = std::fs::write(&path, &out) {
+                error!("File write error: {e}");
+                continue;
+            }
+        }
+        optimized += 1;
+        old_total += old;
+        new_total += new;
+        log!("{path:?}: {old} -> {new} bytes ({saved} sectors freed)", saved = (old.saturating_sub(new)) / 4096);
+    }
+
+    log!("Optimized {optimized} region files, {old_total} -> {new_total} bytes ({saved} bytes saved).", saved = old_total.saturating_sub(new_total));
+
+    std::process::exit(0);
+}
+
+#[inline]
+pub fn map() -> ! {
+    let mut args = std::env::args().collect::<Vec<_>>();
+    args.drain(..2);
+
+    let by_last_modified = if let Some(idx) = args.iter().position(|x| x == "--timestamps" || x == "-t") {
+        args.remove(idx);
+        true
+    } else {
+        false
+    };
+    let [input, output] = <[String; 2]>::try_from(args).unwrap_or_else(|_| {
+        error!("Expected a region file and an output path.");
+        std::process::exit(1);
+    });
+    let (input, output) = (PathBuf::from(input), PathBuf::from(output));
+
+    let bytes = match read(&input) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("File read error: {e}");
+            std::process::exit(1);
+        }
+    };
+    let region = match NbtRegion::from_be_bytes(&bytes, SortAlgorithm::None, ExternalChunks::Files(&input)) {
+        Ok(region) => region,
+        Err(e) => {
+            error!("File parse error: {input:?} is not a valid region file, {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let image = region.presence_map(by_last_modified);
+    let png = PngEncoder::new(&image, EncoderOptions::new(32, 32, ColorSpace::RGBA, BitDepth::Eight)).encode();
+    if let Err(e) = std::fs::write(&output, png) {
+        error!("File write error: {e}");
+        std::process::exit(1);
+    }
+    log!("Mapped the {n} chunks of {input:?} to {output:?}", n = region.len());
+
+    std::process::exit(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::elements::chunk::{NbtChunk, NbtRegion};
+    use crate::elements::element::NbtElement;
+    use crate::file::FileFormat;
+    use crate::json::JsonValue;
+
+    use super::{check_region_file, validate_files, Check, Report};
+
+    fn nbt(snbt: &str) -> Vec<u8> { NbtElement::from_snbt(snbt).expect("the fixture is valid SNBT").to_be_file() }
+
+    /// A region with chunks at 0|0 and 0|1, with `overlapping` the second one pointing at the sectors of the first.
+    fn region(overlapping: bool) -> Vec<u8> {
+        let mut region = NbtRegion::new();
+        for z in 0..2 {
+            let compound = NbtElement::from_snbt("{DataVersion: 3465}").and_then(NbtElement::into_compound).expect("the fixture is valid SNBT");
+            assert!(region.insert(region.len(), NbtElement::Chunk(NbtChunk::from_compound(compound, (0, z), FileFormat::Zlib, 0))).is_ok());
+        }
+        let mut bytes = NbtElement::Region(region).to_be_file();
+        if overlapping {
+            bytes.copy_within(0..4, 4);
+        }
+        bytes
+    }
+
+    /// Writes `files` into a fresh temp dir and validates them.
+    fn validate(files: &[(&str, Vec<u8>)], checks: &[Check], strict: bool) -> (Report, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("nbtworkbench-validate-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("the temp dir is writable");
+        for (name, bytes) in files {
+            std::fs::write(dir.join(name), bytes).expect("the temp dir is writable");
+        }
+        let report = validate_files(&dir, files.iter().map(|(name, _)| PathBuf::from(name)).collect(), checks, strict);
+        (report, dir)
+    }
+
+    /// The `path` (by file name), `check` and `message` of every problem of the JSON summary.
+    fn problems_of(json: &str) -> (usize, Vec<(String, String, String)>) {
+        let JsonValue::Object(entries) = JsonValue::parse(json).expect("the summary is valid JSON") else { panic!("the summary is an object") };
+        let [(checked_key, JsonValue::Number(checked)), (problems_key, JsonValue::Array(problems))] = &entries[..] else { panic!("the summary has a count and problems") };
+        assert_eq!((checked_key.as_str(), problems_key.as_str()), ("checked", "problems"));
+        let problems = problems.iter().map(|problem| {
+            let JsonValue::Object(fields) = problem else { panic!("problems are objects") };
+            let [(_, JsonValue::String(path)), (_, JsonValue::String(check)), (_, JsonValue::String(message))] = &fields[..] else { panic!("problems have a path, check and message") };
+            let name = Path::new(path.as_str()).file_name().expect("problems are of files").to_string_lossy().into_owned();
+            (name, check.to_string(), message.to_string())
+        }).collect();
+        (checked.parse().expect("the count is an integer"), problems)
+    }
+
+    #[test]
+    fn clean_files_exit_with_zero() {
+        let (report, dir) = validate(&[("level.dat", nbt("{Data: {Time: 1L}}")), ("r.0.0.mca", region(false))], &Check::ALL, true);
+        assert_eq!(report.exit_code(), 0);
+        assert_eq!(report.checked, 2);
+        assert_eq!(problems_of(&report.to_json()), (2, vec![]));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn problems_exit_with_one_and_are_reported() {
+        let files = [
+            ("clean.nbt", nbt("{a: 1}")),
+            ("duplicates.nbt", nbt("{a: 1, a: 2, b: {c: 3, c: 4, c: 5}}")),
+            ("garbage.nbt", b"\x0Anot nbt".to_vec()),
+            ("r.0.0.mca", region(true)),
+        ];
+        let (report, dir) = validate(&files, &Check::ALL, false);
+        assert_eq!(report.exit_code(), 1);
+        let (checked, problems) = problems_of(&report.to_json());
+        assert_eq!(checked, 3);
+        let summary = problems.iter().map(|(name, check, _)| (name.as_str(), check.as_str())).collect::<Vec<_>>();
+        assert_eq!(summary, [("duplicates.nbt", "duplicates"), ("garbage.nbt", "parse"), ("r.0.0.mca", "sectors")]);
+        assert!(problems[0].2.starts_with("3 keys"), "{}", problems[0].2);
+        assert_eq!(problems[2].2, "Chunks 0 and 1 share sectors");
+
+        // only the selected checks run, parsing always does
+        let (report, _) = validate(&files, &[Check::Lists], false);
+        assert_eq!(report.exit_code(), 1);
+        let (_, problems) = problems_of(&report.to_json());
+        assert_eq!(problems.iter().map(|(name, check, _)| (name.as_str(), check.as_str())).collect::<Vec<_>>(), [("garbage.nbt", "parse")]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn regions_named_after_another_region_are_reported() {
+        // two chunks of region 1|-1 and one of 0|0, saved as region 0|0
+        let mut region = NbtRegion::new();
+        for (idx, (x, z)) in [(32, -32), (33, -32), (0, 0)].into_iter().enumerate() {
+            let compound = NbtElement::from_snbt(&format!("{{DataVersion: 3465, xPos: {x}, zPos: {z}}}")).and_then(NbtElement::into_compound).expect("the fixture is valid SNBT");
+            assert!(region.insert(region.len(), NbtElement::Chunk(NbtChunk::from_compound(compound, (0, idx as u8), FileFormat::Zlib, 0))).is_ok());
+        }
+        let dir = std::env::temp_dir().join(format!("nbtworkbench-check-region-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("the temp dir is writable");
+        let path = dir.join("r.0.0.mca");
+        std::fs::write(&path, NbtElement::Region(region).to_be_file()).expect("the temp dir is writable");
+
+        let check = check_region_file(&path).expect("the region parses");
+        assert_eq!(check.named, (0, 0));
+        assert_eq!(check.actual, Some((1, -1)));
+        assert_eq!(check.strays, 1);
+        assert_eq!(check.suggested_name.as_deref(), Some("r.1.-1.mca"));
+        assert!(check.overlapping.is_empty());
+        assert!(check.out_of_bounds.is_empty());
+        assert_eq!(check.fragmented, None);
+
+        // under its suggested name, only the stray is left
+        let renamed = dir.join("r.1.-1.mca");
+        std::fs::rename(&path, &renamed).expect("the temp dir is writable");
+        let check = check_region_file(&renamed).expect("the region parses");
+        assert_eq!((check.named, check.actual, check.strays), ((1, -1), Some((1, -1)), 1));
+        assert_eq!(check.suggested_name, None);
+
+        assert!(check_region_file(&dir.join("level.dat")).is_err_and(|e| e.contains("is not named like a region file")));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
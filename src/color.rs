@@ -1,3 +1,53 @@
+use crate::config;
+
+/// The colors of the interface which aren't part of the textures, set by `theme.<name>=<RRGGBB>` entries of the config.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+	/// Behind everything, only seen where the window isn't covered by the tree's stripes.
+	pub background: u32,
+	/// Text without a color of its own, which is what [`TextColor::White`] draws as.
+	pub text: u32,
+	pub string: u32,
+	pub key: u32,
+	pub primitive: u32,
+}
+
+impl Theme {
+	pub const DEFAULT: Self = Self {
+		background: 0x1E1E1E,
+		text: 0xFFFFFF,
+		string: 0xA4F2C6,
+		key: 0x8BC3F3,
+		primitive: 0xF1B073,
+	};
+
+	/// Sets the color called `name`, returning if it exists.
+	#[cfg(feature = "gui")]
+	pub fn set(&mut self, name: &str, color: u32) -> bool {
+		*match name {
+			"background" => &mut self.background,
+			"text" => &mut self.text,
+			"string" => &mut self.string,
+			"key" => &mut self.key,
+			"primitive" => &mut self.primitive,
+			_ => return false,
+		} = color & 0xFFFFFF;
+		true
+	}
+
+	/// The background as a color of the render pass.
+	#[must_use]
+	#[cfg(feature = "gui")]
+	pub fn background(&self) -> wgpu::Color {
+		wgpu::Color {
+			r: f64::from((self.background >> 16) as u8) / 255.0,
+			g: f64::from((self.background >> 8) as u8) / 255.0,
+			b: f64::from(self.background as u8) / 255.0,
+			a: 1.0,
+		}
+	}
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum TextColor {
 	Black,
@@ -40,10 +90,10 @@ impl TextColor {
 			Self::Red => 0xFF5555,
 			Self::LightPurple => 0xFF55FF,
 			Self::Yellow => 0xFFFF55,
-			Self::White => 0xFFFFFF,
-			Self::TreeString => 0xA4F2C6,
-			Self::TreeKey => 0x8BC3F3,
-			Self::TreePrimitive => 0xF1B073,
+			Self::White => config::get_theme().text,
+			Self::TreeString => config::get_theme().string,
+			Self::TreeKey => config::get_theme().key,
+			Self::TreePrimitive => config::get_theme().primitive,
 			Self::Custom(value) => value & 0xFFFFFF,
 		}
 	}
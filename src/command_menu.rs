@@ -0,0 +1,46 @@
+use crate::assets::HEADER_SIZE;
+use crate::elements::compound::NbtCompound;
+use crate::vertex_buffer_builder::VertexBufferBuilder;
+
+/// The longest a command is shown inside of the menu, the whole of it is copied either way.
+const PREVIEW_WIDTH: usize = 96;
+
+/// The commands a compound can be copied as with Ctrl + Alt + C, written with the compact SNBT of [`NbtCompound`]'s `Display`.
+pub struct CommandMenu {
+	commands: Vec<(&'static str, String)>,
+	selected: usize,
+}
+
+impl CommandMenu {
+	#[must_use]
+	pub fn new(compound: &NbtCompound) -> Self {
+		let mut commands = vec![
+			("Merge into entity", format!("/data merge entity @s {compound}")),
+			("Merge into block", format!("/data merge block ~ ~ ~ {compound}")),
+		];
+		// the type of the entity is given by the command rather than its nbt
+		if let Some(idx) = compound.entries.idx_of("id") && let Some((_, id)) = compound.get(idx) && let Some(id) = id.as_string() {
+			let mut nbt = compound.clone();
+			let _ = nbt.remove_idx(idx);
+			commands.push(("Summon", format!("/summon {id} ~ ~ ~ {nbt}", id = id.str.as_str())));
+		}
+		Self { commands, selected: 0 }
+	}
+
+	pub fn select_previous(&mut self) { self.selected = self.selected.checked_sub(1).unwrap_or(self.commands.len() - 1); }
+
+	pub fn select_next(&mut self) { self.selected = if self.selected + 1 >= self.commands.len() { 0 } else { self.selected + 1 }; }
+
+	/// The highlighted command.
+	#[must_use]
+	pub fn selected(&self) -> &str { &self.commands[self.selected].1 }
+
+	pub fn render(&self, builder: &mut VertexBufferBuilder) {
+		let mut lines = vec!["Copy as command ([Up] / [Down] to select, [Enter] to copy, [Esc] to close)".to_owned()];
+		for (idx, (name, command)) in self.commands.iter().enumerate() {
+			let preview = if command.chars().count() > PREVIEW_WIDTH { format!("{}...", command.chars().take(PREVIEW_WIDTH).collect::<String>()) } else { command.clone() };
+			lines.push(format!("{marker} {name}: {preview}", marker = if idx == self.selected { '>' } else { ' ' }));
+		}
+		builder.draw_tooltip(&lines.iter().map(String::as_str).collect::<Vec<_>>(), (16, HEADER_SIZE), true);
+	}
+}
@@ -0,0 +1,368 @@
+#[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+use std::path::PathBuf;
+#[cfg(feature = "gui")]
+use std::time::Duration;
+
+use compact_str::CompactString;
+#[cfg(feature = "gui")]
+use wgpu::PresentMode;
+
+#[cfg(feature = "gui")]
+use crate::keybinds::Keybinds;
+
+#[cfg(feature = "gui")]
+use crate::assets::ICON_NAMES;
+use crate::color::Theme;
+#[cfg(feature = "gui")]
+use crate::elements::element::{id_to_string_name, NbtByte, NbtDouble, NbtElement, NbtFloat, NbtInt, NbtLong, NbtShort};
+#[cfg(feature = "gui")]
+use crate::elements::string::NbtString;
+
+/// The default amount of elements an array will lay out when opened, more are loaded in chunks of this size when scrolled to.
+pub const DEFAULT_RENDER_LIMIT: usize = 16_384;
+
+/// The default amount of levels of the tree opened when a file is opened.
+#[cfg(feature = "gui")]
+pub const DEFAULT_EXPAND_DEPTH: usize = 1;
+
+/// The default amount of lines scrolled by a notch of the mouse wheel, macOS already accelerates the deltas it reports.
+#[cfg(all(feature = "gui", target_os = "macos"))]
+pub const DEFAULT_SCROLL_LINES: f32 = 0.25;
+/// The default amount of lines scrolled by a notch of the mouse wheel.
+#[cfg(all(feature = "gui", not(target_os = "macos")))]
+pub const DEFAULT_SCROLL_LINES: f32 = 3.0;
+
+/// The types which can be given a default value, the ones with a value which can be typed in.
+#[cfg(feature = "gui")]
+const DEFAULTABLE_IDS: [u8; 7] = [NbtByte::ID, NbtShort::ID, NbtInt::ID, NbtLong::ID, NbtFloat::ID, NbtDouble::ID, NbtString::ID];
+
+/// The value new elements of type `id` start with, set by a `default.<type>=<value>` entry, or `default.<type>.<key>=<value>` for the ones given the key `key`.
+struct DefaultValue {
+	id: u8,
+	key: Option<Box<str>>,
+	value: CompactString,
+}
+
+/// User settings, stored as `key=value` lines inside `nbtworkbench/config.txt` of the platform's config directory.
+pub struct Config {
+	render_limit: usize,
+	#[cfg(feature = "gui")]
+	expand_depth: usize,
+	paranoid_save: bool,
+	#[cfg(feature = "gui")]
+	icon: Option<usize>,
+	// tests read theirs from `TEST_LENIENT_LISTS`
+	#[cfg_attr(test, allow(dead_code))]
+	lenient_lists: bool,
+	#[cfg(feature = "gui")]
+	present_mode: PresentMode,
+	#[cfg(feature = "gui")]
+	scroll_lines: f32,
+	#[cfg(feature = "gui")]
+	invert_scroll: bool,
+	#[cfg(feature = "gui")]
+	indent_guides: bool,
+	indent_limit: Option<usize>,
+	#[cfg(feature = "gui")]
+	backup_interval: Option<Duration>,
+	theme: Theme,
+	// tests read theirs from `TEST_DEFAULTS`, and only the editor's tests set any
+	#[cfg_attr(all(test, not(feature = "gui")), allow(dead_code))]
+	defaults: Vec<DefaultValue>,
+	#[cfg(feature = "gui")]
+	keybinds: Keybinds,
+}
+
+static mut CONFIG: Config = Config::new();
+
+#[cfg(test)]
+thread_local! {
+	/// The defaults read by [`get_default`] in tests, set by [`tests::set_defaults`], since they run in parallel and can't share [`CONFIG`].
+	static TEST_DEFAULTS: std::cell::RefCell<Vec<DefaultValue>> = const { std::cell::RefCell::new(vec![]) };
+	/// [`get_lenient_lists`] in tests, set with [`set_test_lenient_lists`].
+	static TEST_LENIENT_LISTS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+impl Config {
+	#[must_use]
+	const fn new() -> Self {
+		Self {
+			render_limit: DEFAULT_RENDER_LIMIT,
+			#[cfg(feature = "gui")]
+			expand_depth: DEFAULT_EXPAND_DEPTH,
+			paranoid_save: false,
+			#[cfg(feature = "gui")]
+			icon: None,
+			lenient_lists: false,
+			#[cfg(feature = "gui")]
+			present_mode: PresentMode::Fifo,
+			#[cfg(feature = "gui")]
+			scroll_lines: DEFAULT_SCROLL_LINES,
+			#[cfg(feature = "gui")]
+			invert_scroll: false,
+			#[cfg(feature = "gui")]
+			indent_guides: true,
+			indent_limit: None,
+			#[cfg(feature = "gui")]
+			backup_interval: None,
+			theme: Theme::DEFAULT,
+			defaults: vec![],
+			#[cfg(feature = "gui")]
+			keybinds: Keybinds::new(),
+		}
+	}
+
+	#[cfg(feature = "gui")]
+	fn set(&mut self, key: &str, value: &str) -> bool {
+		match key {
+			"render_limit" => if let Ok(limit) = value.parse::<usize>() && limit > 0 { self.render_limit = limit } else { return false },
+			"expand_depth" => if let Ok(depth) = value.parse::<usize>() { self.expand_depth = depth } else { return false },
+			"paranoid_save" => if let Ok(paranoid) = value.parse::<bool>() { self.paranoid_save = paranoid } else { return false },
+			"icon" => if value == "random" { self.icon = None } else if let Some(idx) = ICON_NAMES.iter().position(|&name| name == value) { self.icon = Some(idx) } else { return false },
+			"lenient_lists" => if let Ok(lenient) = value.parse::<bool>() { self.lenient_lists = lenient } else { return false },
+			#[cfg(feature = "gui")]
+			"present_mode" => if let Some(mode) = present_mode_from_str(value) { self.present_mode = mode } else { return false },
+			"scroll_lines" => if let Ok(lines) = value.parse::<f32>() && lines.is_finite() && lines > 0.0 { self.scroll_lines = lines } else { return false },
+			"invert_scroll" => if let Ok(invert) = value.parse::<bool>() { self.invert_scroll = invert } else { return false },
+			"indent_guides" => if let Ok(guides) = value.parse::<bool>() { self.indent_guides = guides } else { return false },
+			"indent_limit" => if value == "none" { self.indent_limit = None } else if let Ok(limit) = value.parse::<usize>() && limit > 0 { self.indent_limit = Some(limit) } else { return false },
+			"backup_interval" => if value == "none" { self.backup_interval = None } else if let Ok(secs) = value.parse::<u64>() && secs > 0 { self.backup_interval = Some(Duration::from_secs(secs)) } else { return false },
+			#[cfg(feature = "gui")]
+			key if key.starts_with("keybind.") => return self.keybinds.set(&key["keybind.".len()..], value),
+			_ => if let Some(name) = key.strip_prefix("theme.") {
+				let Ok(color) = u32::from_str_radix(value.strip_prefix('#').unwrap_or(value), 16) else { return false };
+				return self.theme.set(name, color)
+			} else if let Some(rest) = key.strip_prefix("default.") {
+				let (name, key) = rest.split_once('.').map_or((rest, None), |(name, key)| (name, Some(key)));
+				let Some(id) = DEFAULTABLE_IDS.into_iter().find(|&id| id_to_string_name(id).0 == name) else { return false };
+				let value = CompactString::from(value);
+				if !NbtElement::from_id(id).set_value(value.clone()).is_some_and(|(_, success)| success) { return false }
+				self.defaults.retain(|default| default.id != id || default.key.as_deref() != key);
+				self.defaults.push(DefaultValue { id, key: key.map(Into::into), value });
+			} else {
+				return false
+			},
+		}
+		true
+	}
+}
+
+/// The name of a present mode used by the `present_mode` entry.
+#[must_use]
+#[cfg(feature = "gui")]
+pub fn present_mode_into_str(mode: PresentMode) -> &'static str {
+	match mode {
+		PresentMode::Immediate => "immediate",
+		PresentMode::Mailbox => "mailbox",
+		_ => "fifo",
+	}
+}
+
+#[must_use]
+#[cfg(feature = "gui")]
+fn present_mode_from_str(str: &str) -> Option<PresentMode> {
+	match str {
+		"immediate" => Some(PresentMode::Immediate),
+		"fifo" | "vsync" => Some(PresentMode::Fifo),
+		"mailbox" => Some(PresentMode::Mailbox),
+		_ => None,
+	}
+}
+
+/// The `nbtworkbench` folder of the platform's config directory.
+#[must_use]
+#[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+pub fn dir() -> Option<PathBuf> {
+	#[cfg(target_os = "windows")]
+	let base = std::env::var_os("APPDATA").map(PathBuf::from);
+	#[cfg(target_os = "macos")]
+	let base = std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library").join("Application Support"));
+	#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+	let base = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+	base.map(|base| base.join("nbtworkbench"))
+}
+
+/// Reads the config file, any missing or invalid entries keep their default values.
+#[cfg(all(feature = "gui", not(target_arch = "wasm32")))]
+pub fn read() -> bool {
+	let Some(path) = dir().map(|dir| dir.join("config.txt")) else {
+		log::warn!("Could not find the config directory");
+		return false
+	};
+	let Ok(str) = std::fs::read_to_string(&path) else {
+		log::debug!("No config file at {}, using defaults", path.display());
+		return false
+	};
+	let config = unsafe { &mut *core::ptr::addr_of_mut!(CONFIG) };
+	for line in str.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+		if let Some((key, value)) = line.split_once('=') && config.set(key.trim(), value.trim()) {
+			continue;
+		}
+		log::warn!("Invalid config entry in {}: {line}", path.display());
+	}
+	log::debug!("Read config from {}", path.display());
+	true
+}
+
+#[cfg(all(feature = "gui", target_arch = "wasm32"))]
+pub fn read() -> bool { false }
+
+/// The amount of elements an array lays out at once, see [`DEFAULT_RENDER_LIMIT`].
+#[must_use]
+pub fn get_render_limit() -> usize { unsafe { (*core::ptr::addr_of!(CONFIG)).render_limit } }
+
+/// The amount of levels of the tree opened when a file is opened, see [`DEFAULT_EXPAND_DEPTH`].
+#[must_use]
+#[cfg(feature = "gui")]
+pub fn get_expand_depth() -> usize { unsafe { (*core::ptr::addr_of!(CONFIG)).expand_depth } }
+
+/// If saving re-parses what was written and refuses to write it to disk when it doesn't match the tree, off by default since it roughly doubles the cost of saving.
+#[must_use]
+pub fn get_paranoid_save() -> bool { unsafe { (*core::ptr::addr_of!(CONFIG)).paranoid_save } }
+
+/// The music disc used as the window icon, see [`ICON_NAMES`], `None` picks a random one each launch.
+///
+/// The `NBTWB_ICON` environment variable takes precedence over the config file when set to a disc name (or `random`), for consistent screenshots.
+#[must_use]
+#[cfg(feature = "gui")]
+pub fn get_icon() -> Option<usize> {
+	if let Ok(name) = std::env::var("NBTWB_ICON") {
+		if name == "random" { return None }
+		if let Some(idx) = ICON_NAMES.iter().position(|&icon| icon == name) { return Some(idx) }
+		log::warn!("Invalid NBTWB_ICON: {name}");
+	}
+	unsafe { (*core::ptr::addr_of!(CONFIG)).icon }
+}
+
+/// If lists of `TAG_End` with a non-zero length are read as empty lists instead of failing to parse, off by default.
+#[must_use]
+pub fn get_lenient_lists() -> bool {
+	#[cfg(test)]
+	return TEST_LENIENT_LISTS.get();
+	#[cfg(not(test))]
+	unsafe { (*core::ptr::addr_of!(CONFIG)).lenient_lists }
+}
+
+/// Sets [`get_lenient_lists`] for the tests running on this thread.
+#[cfg(test)]
+pub fn set_test_lenient_lists(lenient: bool) { TEST_LENIENT_LISTS.set(lenient) }
+
+/// How frames are presented, `fifo` (vsync) by default, modes the adapter doesn't support fall back to it.
+#[must_use]
+#[cfg(feature = "gui")]
+pub fn get_present_mode() -> PresentMode { unsafe { (*core::ptr::addr_of!(CONFIG)).present_mode } }
+
+/// The keys of the actions which can be rebound by `keybind.<action>=<binding>` entries, see [`Keybinds::set`].
+#[must_use]
+#[cfg(feature = "gui")]
+pub fn get_keybinds() -> &'static Keybinds { unsafe { &(*core::ptr::addr_of!(CONFIG)).keybinds } }
+
+/// The amount of lines scrolled by a notch of the mouse wheel, see [`DEFAULT_SCROLL_LINES`], trackpads scroll by the pixels they report instead.
+#[must_use]
+#[cfg(feature = "gui")]
+pub fn get_scroll_lines() -> f32 { unsafe { (*core::ptr::addr_of!(CONFIG)).scroll_lines } }
+
+/// If scrolling moves the view the other way (natural scrolling), off by default.
+#[must_use]
+#[cfg(feature = "gui")]
+pub fn get_invert_scroll() -> bool { unsafe { (*core::ptr::addr_of!(CONFIG)).invert_scroll } }
+
+/// If the lines connecting the elements of the tree to their parents are drawn, on by default.
+#[must_use]
+#[cfg(feature = "gui")]
+pub fn get_indent_guides() -> bool { unsafe { (*core::ptr::addr_of!(CONFIG)).indent_guides } }
+
+/// The level past which elements aren't indented any further, their depth is written beside them instead, `None` (unlimited) by default.
+#[must_use]
+pub fn get_indent_limit() -> Option<usize> { unsafe { (*core::ptr::addr_of!(CONFIG)).indent_limit } }
+
+/// How often tabs with unsaved changes are written to a `.bak` next to their file, see [`crate::tab::Tab::backup`], `None` (never) by default.
+#[must_use]
+#[cfg(feature = "gui")]
+pub fn get_backup_interval() -> Option<Duration> { unsafe { (*core::ptr::addr_of!(CONFIG)).backup_interval } }
+
+/// The colors of the interface, [`Theme::DEFAULT`] for the ones which aren't configured.
+#[must_use]
+pub fn get_theme() -> Theme { unsafe { (*core::ptr::addr_of!(CONFIG)).theme } }
+
+/// The value new elements of type `id` start with, the one for `key` when given and configured, `None` keeps the zero or empty value of [`NbtElement::from_id`].
+#[must_use]
+pub fn get_default(id: u8, key: Option<&str>) -> Option<CompactString> {
+	fn find(defaults: &[DefaultValue], id: u8, key: Option<&str>) -> Option<CompactString> { defaults.iter().find(|default| default.id == id && default.key.as_deref() == key).map(|default| default.value.clone()) }
+
+	#[cfg(test)]
+	return TEST_DEFAULTS.with_borrow(|defaults| find(defaults, id, key));
+	#[cfg(not(test))]
+	find(unsafe { &(*core::ptr::addr_of!(CONFIG)).defaults }, id, key)
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+	use super::Config;
+
+	/// Gives the defaults of `config` to [`super::get_default`] on this test's thread.
+	fn set_defaults(config: Config) { super::TEST_DEFAULTS.set(config.defaults) }
+
+	#[test]
+	fn invalid_defaults_are_rejected() {
+		let mut config = Config::new();
+		assert!(!config.set("default.byte", "256"));
+		assert!(!config.set("default.compound", "{}"));
+		assert!(!config.set("default.unknown", "1"));
+		assert!(config.set("default.byte", "1"));
+		assert!(config.set("default.byte", "2"));
+		assert_eq!(config.defaults.len(), 1);
+	}
+
+	#[test]
+	fn inserting_uses_the_configured_default() {
+		use std::path::Path;
+
+		use compact_str::CompactString;
+
+		use crate::color::TextColor;
+		use crate::elements::element::{NbtElement, NbtShort};
+		use crate::elements::string::NbtString;
+		use crate::file::FileFormat;
+		use crate::selected_text::{SelectedText, SelectedTextAdditional};
+		use crate::tab::Tab;
+		use crate::text::Text;
+		use crate::workbench_action::WorkbenchAction;
+		use crate::WindowProperties;
+
+		use super::get_default;
+
+		let mut config = Config::new();
+		assert!(config.set("default.short", "7"));
+		assert!(config.set("default.string.id", "minecraft:stone"));
+		set_defaults(config);
+		assert_eq!(get_default(NbtShort::ID, None).as_deref(), Some("7"));
+		assert_eq!(NbtElement::from_id_configured(NbtShort::ID).as_short().map(|short| short.value), Some(7));
+		assert_eq!(NbtElement::from_id_configured(NbtString::ID).as_string().map(|string| string.str.as_str()), Some(""));
+
+		// added the way the add menu does it, still keyed `_`, then renamed
+		let nbt = NbtElement::from_snbt("{}").expect("the fixture is valid SNBT");
+		let mut tab = Tab::new(nbt, Path::new("test.nbt"), FileFormat::Nbt, 420, 620).expect("compounds can be opened");
+		let action = WorkbenchAction::Remove { element: (Some(CompactString::new_inline("_")), NbtElement::from_id_configured(NbtString::ID)), indices: Box::new([0]) }.undo(&mut tab.value, &mut tab.bookmarks, &mut None, &mut tab.path, &mut tab.name, &mut tab.root_name);
+		tab.append_to_history(action);
+		tab.selected_text = Some(SelectedText(Text::new("id".to_owned(), 2, true, SelectedTextAdditional {
+			y: 0,
+			indices: Box::new([0]),
+			value_color: TextColor::TreeKey,
+			keyfix: None,
+			prefix: (String::new(), TextColor::White),
+			suffix: (": ".to_owned(), TextColor::TreeKey),
+			valuefix: Some(("\"\"".to_owned(), TextColor::TreeString)),
+		})));
+		assert!(tab.close_selected_text(false, &mut WindowProperties::Fake));
+		let (key, value) = tab.value.as_compound().and_then(|compound| compound.get(0)).expect("the string was added");
+		assert_eq!(key, "id");
+		assert_eq!(value.as_string().map(|string| string.str.as_str()), Some("minecraft:stone"));
+
+		// the default replaced the value as part of the rename, so one undo brings back both
+		assert!(tab.step_history(false, &mut None));
+		let (key, value) = tab.value.as_compound().and_then(|compound| compound.get(0)).expect("the string is still there");
+		assert_eq!(key, "_");
+		assert_eq!(value.as_string().map(|string| string.str.as_str()), Some(""));
+	}
+}
@@ -0,0 +1,162 @@
+use crate::elements::element::{NbtElement, PathSegment};
+use crate::elements::chunk::NbtRegion;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum DiffKind {
+	Added,
+	Removed,
+	Changed,
+}
+
+/// A difference between two trees, see [`diff`].
+pub struct DiffEntry {
+	pub kind: DiffKind,
+	/// The indices of the element in the second tree, or for removed elements, the ones of the parent they were removed from since they don't exist in it.
+	pub indices: Box<[usize]>,
+	/// The path of the element (see [`NbtElement::path_of`]), in the first tree for removed elements and in the second one otherwise.
+	pub path: Box<[PathSegment]>,
+}
+
+/// The structural differences which turn `a` into `b`, compound entries are matched by their key, the chunks of regions by their coordinates and everything else by its index.
+///
+/// Elements which changed type are reported as changed rather than compared any deeper.
+#[must_use]
+pub fn diff(a: &NbtElement, b: &NbtElement) -> Vec<DiffEntry> {
+	let mut entries = vec![];
+	diff0(a, b, (a, b), &mut (vec![], vec![]), &mut entries);
+	entries
+}
+
+/// `roots` are the trees `a` and `b` belong to, and `indices` the ones leading to them from each root.
+fn diff0(a: &NbtElement, b: &NbtElement, roots: (&NbtElement, &NbtElement), indices: &mut (Vec<usize>, Vec<usize>), entries: &mut Vec<DiffEntry>) {
+	let (Some(a_len), Some(b_len)) = (a.len(), b.len()) else {
+		if !a.deep_eq(b) { entries.push(DiffEntry { kind: DiffKind::Changed, indices: indices.1.as_slice().into(), path: roots.1.path_of(&indices.1).unwrap_or_default().into_boxed_slice() }) }
+		return
+	};
+	if a.id() != b.id() {
+		entries.push(DiffEntry { kind: DiffKind::Changed, indices: indices.1.as_slice().into(), path: roots.1.path_of(&indices.1).unwrap_or_default().into_boxed_slice() });
+		return
+	}
+
+	// the index of each child of `a` in `b`, if it's there, and the children of `b` which weren't matched
+	let (matches, matched) = match (a.children(), b.children()) {
+		(Some(Err(a_children)), Some(Err(b_children))) => {
+			let b_keys = b_children.map(|(key, _)| key).collect::<Vec<_>>();
+			let mut matched = vec![false; b_len];
+			let matches = a_children.map(|(key, _)| {
+				let b_idx = b_keys.iter().position(|&k| k == key);
+				if let Some(b_idx) = b_idx { matched[b_idx] = true }
+				b_idx
+			}).collect::<Vec<_>>();
+			(matches, matched)
+		}
+		_ if a.id() == NbtRegion::ID => {
+			let pos_of = |element: &NbtElement| element.as_chunk().map(|chunk| (chunk.x, chunk.z));
+			let b_positions = (0..b_len).map(|idx| b.get(idx).and_then(pos_of)).collect::<Vec<_>>();
+			let mut matched = vec![false; b_len];
+			let matches = (0..a_len).map(|a_idx| {
+				let pos = a.get(a_idx).and_then(pos_of);
+				let b_idx = b_positions.iter().position(|&p| p.is_some() && p == pos);
+				if let Some(b_idx) = b_idx { matched[b_idx] = true }
+				b_idx
+			}).collect::<Vec<_>>();
+			(matches, matched)
+		}
+		_ => ((0..a_len).map(|idx| (idx < b_len).then_some(idx)).collect(), (0..b_len).map(|idx| idx < a_len).collect()),
+	};
+
+	for (a_idx, b_idx) in matches.into_iter().enumerate() {
+		indices.0.push(a_idx);
+		if let Some(b_idx) = b_idx {
+			indices.1.push(b_idx);
+			if let (Some(a_child), Some(b_child)) = (a.get(a_idx), b.get(b_idx)) {
+				diff0(a_child, b_child, roots, indices, entries);
+			}
+			indices.1.pop();
+		} else {
+			entries.push(DiffEntry { kind: DiffKind::Removed, indices: indices.1.as_slice().into(), path: roots.0.path_of(&indices.0).unwrap_or_default().into_boxed_slice() });
+		}
+		indices.0.pop();
+	}
+	for b_idx in matched.into_iter().enumerate().filter(|&(_, matched)| !matched).map(|(b_idx, _)| b_idx) {
+		indices.1.push(b_idx);
+		entries.push(DiffEntry { kind: DiffKind::Added, indices: indices.1.as_slice().into(), path: roots.1.path_of(&indices.1).unwrap_or_default().into_boxed_slice() });
+		indices.1.pop();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::elements::chunk::{NbtChunk, NbtRegion};
+	use crate::elements::element::{NbtElement, PathSegment};
+	use crate::file::FileFormat;
+
+	use super::{diff, DiffKind};
+
+	fn parse(snbt: &str) -> NbtElement { NbtElement::from_snbt(snbt).expect("fixtures are valid SNBT") }
+
+	/// The kind, joined path and indices of every difference between `a` and `b`.
+	fn differences(a: &NbtElement, b: &NbtElement) -> Vec<(char, String, Vec<usize>)> {
+		diff(a, b).into_iter().map(|entry| (match entry.kind {
+			DiffKind::Added => '+',
+			DiffKind::Removed => '-',
+			DiffKind::Changed => '~',
+		}, PathSegment::join(&entry.path), entry.indices.into_vec())).collect()
+	}
+
+	#[test]
+	fn identical_trees_have_no_differences() {
+		let nbt = parse(r#"{a: 1b, b: [1, 2], c: {d: "e"}, f: [I; 1, 2]}"#);
+		assert!(diff(&nbt, &nbt.clone()).is_empty());
+	}
+
+	#[test]
+	fn compound_entries_are_matched_by_key() {
+		let a = parse(r#"{kept: 1b, removed: 2b, changed: "old", nested: {x: 1, y: 2}}"#);
+		let b = parse(r#"{added: 0b, nested: {y: 3, x: 1}, changed: "new", kept: 1b}"#);
+		assert_eq!(differences(&a, &b), [
+			('-', "removed".to_owned(), vec![]),
+			('~', "changed".to_owned(), vec![2]),
+			('~', "nested.y".to_owned(), vec![1, 0]),
+			('+', "added".to_owned(), vec![0]),
+		]);
+	}
+
+	#[test]
+	fn lists_and_arrays_are_matched_by_index() {
+		let a = parse("{list: [1s, 2s, 3s], array: [B; 1b, 2b]}");
+		let b = parse("{list: [1s, 5s], array: [B; 1b, 2b, 3b]}");
+		assert_eq!(differences(&a, &b), [
+			('~', "list[1]".to_owned(), vec![0, 1]),
+			('-', "list[2]".to_owned(), vec![0]),
+			('+', "array[2]".to_owned(), vec![1, 2]),
+		]);
+	}
+
+	#[test]
+	fn elements_of_another_type_are_changed_rather_than_compared() {
+		let a = parse("{a: [1b], b: {c: 1b}, d: 1b}");
+		let b = parse("{a: [B; 1b], b: [{c: 1b}], d: 1s}");
+		assert_eq!(differences(&a, &b), [
+			('~', "a".to_owned(), vec![0]),
+			('~', "b".to_owned(), vec![1]),
+			('~', "d".to_owned(), vec![2]),
+		]);
+	}
+
+	#[test]
+	fn chunks_are_matched_by_their_coordinates() {
+		let region = |chunks: &[((u8, u8), &str)]| {
+			let mut region = NbtRegion::new();
+			for (idx, &(pos, snbt)) in chunks.iter().enumerate() {
+				let compound = parse(snbt).into_compound().expect("the fixture is a compound");
+				assert!(region.insert(idx, NbtElement::Chunk(NbtChunk::from_compound(compound, pos, FileFormat::Zlib, 0))).is_ok());
+			}
+			NbtElement::Region(region)
+		};
+		let a = region(&[((0, 0), "{a: 1b}"), ((1, 0), "{a: 2b}"), ((2, 0), "{a: 3b}")]);
+		let b = region(&[((1, 0), "{a: 2b}"), ((0, 0), "{a: 4b}"), ((0, 1), "{a: 5b}")]);
+		let differences = differences(&a, &b);
+		assert_eq!(differences.iter().map(|(kind, _, indices)| (*kind, indices.as_slice())).collect::<Vec<_>>(), [('~', &[1, 0][..]), ('-', &[]), ('+', &[2])]);
+	}
+}
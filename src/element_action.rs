@@ -1,4 +1,3 @@
-use std::cmp::Ordering;
 #[cfg(not(target_arch = "wasm32"))]
 use std::{fs::OpenOptions, process::Command};
 
@@ -7,15 +6,12 @@ use compact_str::CompactString;
 use notify::{EventKind, PollWatcher, RecursiveMode, Watcher};
 use uuid::Uuid;
 
-use crate::{panic_unchecked, set_clipboard, FileUpdateSubscription};
+use crate::{panic_unchecked, set_clipboard, FileUpdateSubscription, SortAlgorithm};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::{FileUpdateSubscriptionType, assets::{OPEN_ARRAY_IN_HEX_UV, OPEN_IN_TXT}, since_epoch, StrExt};
 use crate::assets::{ACTION_WHEEL_Z, COPY_FORMATTED_UV, COPY_RAW_UV, SORT_COMPOUND_BY_NAME, SORT_COMPOUND_BY_TYPE};
 use crate::elements::chunk::NbtChunk;
-use crate::elements::compound::NbtCompound;
-use crate::elements::element::{NbtByte, NbtByteArray, NbtDouble, NbtElement, NbtFloat, NbtInt, NbtIntArray, NbtLong, NbtLongArray, NbtShort};
-use crate::elements::list::NbtList;
-use crate::elements::string::NbtString;
+use crate::elements::element::{NbtByte, NbtElement, NbtInt, NbtLong, NbtShort};
 use crate::vertex_buffer_builder::VertexBufferBuilder;
 use crate::workbench_action::WorkbenchAction;
 use crate::bookmark::Bookmarks;
@@ -77,37 +73,6 @@ impl ElementAction {
 		}
 	}
 
-	#[must_use]
-	pub fn by_name(a: (&str, &NbtElement), b: (&str, &NbtElement)) -> Ordering {
-		let (a_str, _) = a;
-		let (b_str, _) = b;
-		a_str.cmp(b_str)
-	}
-
-	#[must_use]
-	pub fn by_type(a: (&str, &NbtElement), b: (&str, &NbtElement)) -> Ordering {
-		const ORDERING: [usize; 256] = {
-			let mut array = [usize::MAX; 256];
-			array[NbtChunk::ID as usize] = 0;
-			array[NbtCompound::ID as usize] = 1;
-			array[NbtList::ID as usize] = 2;
-			array[NbtLongArray::ID as usize] = 3;
-			array[NbtIntArray::ID as usize] = 4;
-			array[NbtByteArray::ID as usize] = 5;
-			array[NbtString::ID as usize] = 6;
-			array[NbtDouble::ID as usize] = 7;
-			array[NbtFloat::ID as usize] = 8;
-			array[NbtLong::ID as usize] = 9;
-			array[NbtInt::ID as usize] = 10;
-			array[NbtShort::ID as usize] = 11;
-			array[NbtByte::ID as usize] = 12;
-			array
-		};
-
-		let (a_str, a_nbt) = a;
-		let (b_str, b_nbt) = b;
-		ORDERING[a_nbt.id() as usize].cmp(&ORDERING[b_nbt.id() as usize]).then_with(|| a_str.cmp(b_str))
-	}
 
 	#[allow(clippy::too_many_lines)]
 	pub fn apply(self, key: Option<CompactString>, indices: Box<[usize]>, _tab_uuid: Uuid, true_line_number: usize, line_number: usize, element: &mut NbtElement, bookmarks: &mut Bookmarks, _subscription: &mut Option<FileUpdateSubscription>) -> Option<WorkbenchAction> {
@@ -307,9 +272,9 @@ impl ElementAction {
 					let true_height = element.true_height();
 					let bookmark_slice = &mut bookmarks[true_line_number..true_line_number + element.true_height()];
 					let reordering_indices = if let Some(compound) = element.as_compound_mut() {
-						compound.entries.sort_by(Self::by_name, line_number, true_line_number, true_height, open, bookmark_slice)
+						compound.entries.sort_by(SortAlgorithm::by_name, line_number, true_line_number, true_height, open, bookmark_slice)
 					} else if let Some(chunk) = element.as_chunk_mut() {
-						chunk.entries.sort_by(Self::by_name, line_number, true_line_number, true_height, open, bookmark_slice)
+						chunk.entries.sort_by(SortAlgorithm::by_name, line_number, true_line_number, true_height, open, bookmark_slice)
 					} else {
 						unsafe { panic_unchecked("Unknown element kind for compound sorting") }
 					};
@@ -321,9 +286,9 @@ impl ElementAction {
 					let true_height = element.true_height();
 					let bookmark_slice = &mut bookmarks[true_line_number..true_line_number + element.true_height()];
 					let reordering_indices = if let Some(compound) = element.as_compound_mut() {
-						compound.entries.sort_by(Self::by_type, line_number, true_line_number, true_height, open, bookmark_slice)
+						compound.entries.sort_by(SortAlgorithm::by_type, line_number, true_line_number, true_height, open, bookmark_slice)
 					} else if let Some(chunk) = element.as_chunk_mut() {
-						chunk.entries.sort_by(Self::by_type, line_number, true_line_number, true_height, open, bookmark_slice)
+						chunk.entries.sort_by(SortAlgorithm::by_type, line_number, true_line_number, true_height, open, bookmark_slice)
 					} else {
 						unsafe { panic_unchecked("Unknown element kind for compound sorting") }
 					};
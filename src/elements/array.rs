@@ -1,10 +1,67 @@
+/// The smallest, largest and sum of the values of an array, written beside arrays too long to be laid out at once.
+#[derive(Clone)]
+pub struct ArraySummary {
+	pub min: i64,
+	pub max: i64,
+	pub sum: i128,
+}
+
+/// How the values of an array are written, switched per array with Alt + X and Alt + U, the values themselves are left untouched.
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[cfg(feature = "gui")]
+pub enum ArrayView {
+	Decimal,
+	Hex,
+	Binary,
+	/// Every value split into the values of this many bits packed inside of it from its lowest bits up, the way chunk sections store their block states and heightmaps.
+	Packed(u8),
+}
+
+#[cfg(feature = "gui")]
+impl ArrayView {
+	/// The view after this one for Alt + X, unpacked arrays go back to decimal.
+	#[must_use]
+	pub const fn next(self) -> Self {
+		match self {
+			Self::Decimal => Self::Hex,
+			Self::Hex => Self::Binary,
+			Self::Binary | Self::Packed(_) => Self::Decimal,
+		}
+	}
+
+	/// Writes the `value` of an array of `bits` bit values, negative values are written as their two's complement in every view but decimal.
+	#[must_use]
+	pub fn format(self, value: i64, bits: u32) -> compact_str::CompactString {
+		const fn mask(bits: u32) -> u64 { if bits >= 64 { u64::MAX } else { (1 << bits) - 1 } }
+
+		let unsigned = value as u64 & mask(bits);
+		match self {
+			Self::Decimal => compact_str::format_compact!("{value}"),
+			Self::Hex => compact_str::format_compact!("0x{unsigned:0width$X}", width = bits as usize / 4),
+			Self::Binary => compact_str::format_compact!("0b{unsigned:0width$b}", width = bits as usize),
+			Self::Packed(packed) => {
+				let packed = u32::from(packed);
+				let mut str = compact_str::CompactString::new_inline("");
+				for idx in 0..bits / packed {
+					if idx > 0 { str.push_str(", ") }
+					str.push_str(&compact_str::format_compact!("{}", (unsigned >> (idx * packed)) & mask(packed)));
+				}
+				str
+			}
+		}
+	}
+}
+
 #[macro_export]
 macro_rules! array {
 	($element_field:ident, $name:ident, $t:ty, $my_id:literal, $id:literal, $char:literal, $uv:ident, $element_uv:ident) => {
 		#[repr(C)]
 		pub struct $name {
 			values: Box<Vec<NbtElement>>,
-			max_depth: u32,
+			/// Only kept for arrays with more values than [`crate::config::get_render_limit`], cleared when a value is edited until the array is recached.
+			summary: Option<Box<$crate::elements::array::ArraySummary>>,
+			shown: u32,
+			max_depth: u16,
 			open: bool,
 		}
 
@@ -36,24 +93,48 @@ macro_rules! array {
 					boxx.write(Vec::from_raw_parts(ptr, len, len));
 					Self {
 						values: Box::from_raw(boxx),
+						summary: self.summary.clone(),
 						max_depth: self.max_depth,
+						shown: self.shown,
 						open: self.open,
 					}
 				}
 			}
 		}
 
+		impl Default for $name {
+			fn default() -> Self { Self::new() }
+		}
+
 		impl $name {
 			#[inline]
 			#[must_use]
 			pub fn new() -> Self {
 				Self {
 					values: Box::<Vec<NbtElement>>::default(),
+					summary: None,
 					open: false,
 					max_depth: 0,
+					shown: 0,
 				}
 			}
 
+			/// `None` if one of `values` isn't an element of the array.
+			#[inline]
+			#[must_use]
+			pub fn from_values(values: Vec<NbtElement>) -> Option<Self> {
+				if values.iter().any(|value| value.id() != $id) { return None }
+				let mut array = Self {
+					values: Box::new(values),
+					summary: None,
+					open: false,
+					max_depth: 0,
+					shown: 0,
+				};
+				array.resummarize();
+				Some(array)
+			}
+
 			pub const ID: u8 = $my_id;
 
 			#[inline]
@@ -75,6 +156,7 @@ macro_rules! array {
 					}
 				}
 				array.values.shrink_to_fit();
+				array.resummarize();
 				Some((s.strip_prefix(']')?, array))
 			}
 
@@ -83,7 +165,7 @@ macro_rules! array {
 				unsafe {
 					decoder.assert_len(4)?;
 					let len = decoder.u32() as usize;
-					decoder.assert_len(len * core::mem::size_of::<$t>())?;
+					decoder.assert_len(len.checked_mul(core::mem::size_of::<$t>())?)?;
 					let vec = alloc(Layout::array::<NbtElement>(len).unwrap_unchecked()).cast::<NbtElement>();
 					for idx in 0..len {
 						let mut element = NbtElement {
@@ -101,11 +183,15 @@ macro_rules! array {
 					decoder.data = decoder.data.add(len * core::mem::size_of::<$t>());
 					let boxx = alloc(Layout::new::<Vec<NbtElement>>()).cast::<Vec<NbtElement>>();
 					boxx.write(Vec::from_raw_parts(vec, len, len));
-					Some(Self {
+					let mut array = Self {
 						values: Box::from_raw(boxx),
+						summary: None,
 						open: false,
 						max_depth: 0,
-					})
+						shown: 0,
+					};
+					array.resummarize();
+					Some(array)
 				}
 			}
 
@@ -122,7 +208,7 @@ macro_rules! array {
 				unsafe {
 					decoder.assert_len(4)?;
 					let len = decoder.u32() as usize;
-					decoder.assert_len(len * core::mem::size_of::<$t>())?;
+					decoder.assert_len(len.checked_mul(core::mem::size_of::<$t>())?)?;
 					let vec = alloc(Layout::array::<NbtElement>(len).unwrap_unchecked()).cast::<NbtElement>();
 					for idx in 0..len {
 						let mut element = NbtElement {
@@ -140,11 +226,15 @@ macro_rules! array {
 					decoder.data = decoder.data.add(len * core::mem::size_of::<$t>());
 					let boxx = alloc(Layout::new::<Vec<NbtElement>>()).cast::<Vec<NbtElement>>();
 					boxx.write(Vec::from_raw_parts(vec, len, len));
-					Some(Self {
+					let mut array = Self {
 						values: Box::from_raw(boxx),
+						summary: None,
 						open: false,
 						max_depth: 0,
-					})
+						shown: 0,
+					};
+					array.resummarize();
+					Some(array)
 				}
 			}
 
@@ -171,7 +261,7 @@ macro_rules! array {
 			#[must_use]
 			pub fn height(&self) -> usize {
 				if self.open {
-					self.len() + 1
+					self.shown() + 1
 				} else {
 					1
 				}
@@ -183,10 +273,38 @@ macro_rules! array {
 
 			#[inline]
 			pub fn toggle(&mut self) -> Option<()> {
-				self.open = !self.open && !self.is_empty();
+				if self.open {
+					self.open = false;
+				} else {
+					self.reveal();
+				}
 				Some(())
 			}
 
+			/// Opens the array, laying out at least the first [`crate::config::get_render_limit`] elements.
+			#[inline]
+			fn reveal(&mut self) {
+				self.open = !self.is_empty();
+				self.shown = self.shown.max(usize::min(self.len(), $crate::config::get_render_limit()) as u32);
+			}
+
+			/// The amount of elements currently laid out while open, the rest are skipped until scrolled to.
+			#[inline]
+			#[must_use]
+			pub const fn shown(&self) -> usize { self.shown as usize }
+
+			/// Lays out the next chunk of elements if `idx` is the last shown one, returns the amount of rows added.
+			#[inline]
+			pub fn show_more(&mut self, idx: usize) -> usize {
+				if !self.open || idx + 1 != self.shown() || self.shown() >= self.len() {
+					return 0;
+				}
+
+				let before = self.shown();
+				self.shown = usize::min(self.len(), before + $crate::config::get_render_limit()) as u32;
+				self.shown() - before
+			}
+
 			#[inline]
 			#[must_use]
 			pub const fn open(&self) -> bool { self.open }
@@ -210,6 +328,10 @@ macro_rules! array {
 						self.values.try_reserve_exact(1).unwrap_unchecked();
 					}
 					self.values.insert(idx, value);
+					self.summary = None;
+					if idx <= self.shown() {
+						self.shown += 1;
+					}
 					self.increment(1, 1);
 					Ok(())
 				} else {
@@ -220,12 +342,18 @@ macro_rules! array {
 			#[inline]
 			pub fn remove(&mut self, idx: usize) -> NbtElement {
 				let removed = self.values.remove(idx);
+				self.summary = None;
+				if idx < self.shown() {
+					self.shown -= 1;
+				}
 				self.values.shrink_to_fit();
 				removed
 			}
 
+			#[cfg(feature = "gui")]
 			#[inline]
 			pub fn render(&self, builder: &mut VertexBufferBuilder, key: Option<&str>, remaining_scroll: &mut usize, tail: bool, ctx: &mut RenderContext) {
+				let view = ctx.array_view();
 				'head: {
 					if *remaining_scroll > 0 {
 						*remaining_scroll -= 1;
@@ -248,6 +376,29 @@ macro_rules! array {
 
 						builder.color = TextColor::TreeKey.to_raw();
 						let _ = write!(builder, "{}", self.value());
+						if let Some(summary) = self.summary() {
+							builder.color = TextColor::Gray.to_raw();
+							let _ = write!(builder, " (min {}, max {}, sum {})", summary.min, summary.max, summary.sum);
+						}
+						if self.open && self.shown() < self.len() {
+							builder.color = TextColor::Gray.to_raw();
+							let _ = write!(builder, " ({} not shown)", self.len() - self.shown());
+						}
+						match view {
+							$crate::elements::array::ArrayView::Decimal => {}
+							$crate::elements::array::ArrayView::Hex => {
+								builder.color = TextColor::Gray.to_raw();
+								let _ = write!(builder, " (hex)");
+							}
+							$crate::elements::array::ArrayView::Binary => {
+								builder.color = TextColor::Gray.to_raw();
+								let _ = write!(builder, " (binary)");
+							}
+							$crate::elements::array::ArrayView::Packed(bits) => {
+								builder.color = TextColor::Gray.to_raw();
+								let _ = write!(builder, " (unpacked as {bits} bit values)");
+							}
+						}
 					}
 
 					let pos = ctx.pos();
@@ -257,9 +408,9 @@ macro_rules! array {
 				}
 
 				if self.open {
-					ctx.x_offset += 16;
+					ctx.indent();
 
-					for (idx, element) in self.children().enumerate() {
+					for (idx, element) in self.children().take(self.shown()).enumerate() {
 						if ctx.y_offset > builder.window_height() {
 							break;
 						}
@@ -273,23 +424,23 @@ macro_rules! array {
 						let pos = ctx.pos();
 						ctx.draw_held_entry_bar(ctx.pos(), builder, |x, y| pos == (x, y), |id| id == $id);
 
-						builder.draw_texture(
+						ctx.draw_connection(
 							ctx.pos() - (16, 0),
-							CONNECTION_UV,
 							(
 								16,
 								(idx != self.len() - 1) as usize * 7 + 9,
 							),
+							builder,
 						);
 						if !tail {
-							builder.draw_texture(ctx.pos() - (32, 0), CONNECTION_UV, (8, 16));
+							ctx.draw_connection(ctx.pos() - (32, 0), (8, 16), builder);
 						}
 
 						ctx.line_number();
 						Self::render_element_icon(ctx.pos(), builder);
 						ctx.check_for_invalid_value(|value| value.parse::<$t>().is_err());
 						ctx.render_errors(ctx.pos(), builder);
-						let str = Self::transmute(element).to_compact_string();
+						let str = view.format(Self::transmute(element) as i64, <$t>::BITS);
 						if ctx.forbid(ctx.pos()) {
 							builder.settings(ctx.pos() + (20, 0), false, JUST_OVERLAPPING_BASE_TEXT_Z);
 							builder.color = TextColor::TreePrimitive.to_raw();
@@ -301,8 +452,9 @@ macro_rules! array {
 						let pos = ctx.pos();
 						ctx.draw_held_entry_bar(ctx.pos(), builder, |x, y| pos == (x, y + 8), |id| id == $id);
 					}
+					ctx.skip_line_numbers(self.len() - self.shown());
 
-					ctx.x_offset -= 16;
+					ctx.dedent();
 				} else {
 					ctx.skip_line_numbers(self.len());
 				}
@@ -314,7 +466,28 @@ macro_rules! array {
 
 			#[inline]
 			#[must_use]
-			pub fn get_mut(&mut self, idx: usize) -> Option<&mut NbtElement> { self.values.get_mut(idx) }
+			pub fn get_mut(&mut self, idx: usize) -> Option<&mut NbtElement> {
+				self.summary = None;
+				self.values.get_mut(idx)
+			}
+
+			#[inline]
+			#[must_use]
+			pub fn summary(&self) -> Option<&$crate::elements::array::ArraySummary> { self.summary.as_deref() }
+
+			/// Recomputes [`Self::summary`] for arrays with more values than [`crate::config::get_render_limit`].
+			fn resummarize(&mut self) {
+				self.summary = None;
+				if self.len() <= $crate::config::get_render_limit() { return }
+				let mut summary = $crate::elements::array::ArraySummary { min: i64::MAX, max: i64::MIN, sum: 0 };
+				for value in self.values.iter() {
+					let value = Self::transmute(value) as i64;
+					summary.min = summary.min.min(value);
+					summary.max = summary.max.max(value);
+					summary.sum += i128::from(value);
+				}
+				self.summary = Some(Box::new(summary));
+			}
 
 			#[inline]
 			#[must_use]
@@ -331,21 +504,27 @@ macro_rules! array {
 			pub fn children(&self) -> ValueIterator { ValueIterator::Generic(self.values.iter()) }
 
 			#[inline] // ret type is #[must_use]
-			pub fn children_mut(&mut self) -> ValueMutIterator { ValueMutIterator::Generic(self.values.iter_mut()) }
+			pub fn children_mut(&mut self) -> ValueMutIterator {
+				self.summary = None;
+				ValueMutIterator::Generic(self.values.iter_mut())
+			}
 
 			pub fn drop(&mut self, key: Option<CompactString>, element: NbtElement, y: &mut usize, depth: usize, target_depth: usize, line_number: usize, indices: &mut Vec<usize>) -> DropFn {
 				if 8 <= *y && *y < 16 && depth == target_depth {
+					let before = self.height();
 					indices.push(0);
 					if let Err(element) = self.insert(0, element) { return DropFn::InvalidType(key, element) }
-					self.open = true;
-					return DropFn::Dropped(1, 1, None, line_number + 1);
+					self.reveal();
+					return DropFn::Dropped(self.height() - before, 1, None, line_number + 1);
 				}
 
 				if self.height() * 16 <= *y && *y < self.height() * 16 + 8 && depth == target_depth {
-					indices.push(self.len());
-					if let Err(element) = self.insert(self.len(), element) { return DropFn::InvalidType(key, element) }
-					self.open = true;
-					return DropFn::Dropped(1, 1, None, line_number + self.len());
+					let before = self.height();
+					let idx = if self.open { self.shown() } else { self.len() };
+					indices.push(idx);
+					if let Err(element) = self.insert(idx, element) { return DropFn::InvalidType(key, element) }
+					self.reveal();
+					return DropFn::Dropped(self.height() - before, 1, None, line_number + idx + 1);
 				}
 
 				if *y < 16 {
@@ -358,7 +537,7 @@ macro_rules! array {
 					if depth == target_depth {
 						indices.push(0);
 						let ptr = unsafe { &mut *indices.as_mut_ptr().add(indices.len() - 1) };
-						for idx in 0..self.values.len() {
+						for idx in 0..self.shown() {
 							*ptr = idx;
 							if *y < 8 && depth == target_depth {
 								if let Err(element) = self.insert(idx, element) { return DropFn::InvalidType(key, element) }
@@ -373,7 +552,7 @@ macro_rules! array {
 						}
 						indices.pop();
 					} else {
-						*y = y.saturating_sub((self.len() + 1) * 16);
+						*y = y.saturating_sub((self.shown() + 1) * 16);
 					}
 				}
 				DropFn::Missed(key, element)
@@ -383,26 +562,32 @@ macro_rules! array {
 			pub fn shut(&mut self) { self.open = false; }
 
 			#[inline]
-			pub fn expand(&mut self) { self.open = !self.is_empty(); }
+			pub fn expand(&mut self) {
+				self.open = !self.is_empty();
+				self.shown = self.len() as u32;
+			}
 
 			#[inline]
 			pub fn recache_depth(&mut self) {
 				let mut max_depth = 0;
 				if self.open() {
-					for child in self.children() {
+					for child in self.children().take(self.shown()) {
 						max_depth = usize::max(max_depth, 16 + 4 + child.value().0.width());
 					}
 				}
-				self.max_depth = max_depth as u32;
+				self.max_depth = max_depth.min(u16::MAX as usize) as u16;
+				self.resummarize();
 			}
 
 			#[inline]
 			#[must_use]
 			pub const fn max_depth(&self) -> usize { self.max_depth as usize }
 
+			#[cfg(feature = "gui")]
 			#[inline]
 			pub fn render_icon(pos: impl Into<(usize, usize)>, z: ZOffset, builder: &mut VertexBufferBuilder) { builder.draw_texture_z(pos, z, $uv, (16, 16)); }
 
+			#[cfg(feature = "gui")]
 			#[inline]
 			pub fn render_element_icon(pos: impl Into<(usize, usize)>, builder: &mut VertexBufferBuilder) { builder.draw_texture(pos, $element_uv, (16, 16)); }
 		}
@@ -421,27 +606,41 @@ macro_rules! array {
 		}
 
 		impl $name {
-			pub fn pretty_fmt(&self, f: &mut PrettyFormatter) {
-				if self.is_empty() {
-					f.write_str(concat!("[", $char, ";]"))
-				} else {
-					let len = self.len();
-					f.write_str(concat!("[", $char, ";\n"));
-					f.increase();
-					for (idx, element) in self.children().enumerate() {
-						f.indent();
-						element.pretty_fmt(f);
-						if idx + 1 < len {
-							f.write_str(",\n");
-						} else {
-							f.write_str("\n");
-						}
-					}
-					f.decrease();
-					f.indent();
-					f.write_str("]");
-				}
-			}
+			/// Arrays stay on one line, a line per number would only make them harder to read.
+			pub fn pretty_fmt(&self, f: &mut PrettyFormatter) { f.write_str(&self.to_string()) }
 		}
 	};
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::config::get_render_limit;
+	use crate::elements::element::{NbtElement, NbtInt, NbtIntArray};
+
+	#[test]
+	fn huge_arrays_are_laid_out_in_chunks() {
+		let len = 100_000;
+		let limit = get_render_limit();
+		let values = (0..len as i32).map(|value| NbtElement::Int(NbtInt { value })).collect();
+		let mut array = NbtElement::IntArray(NbtIntArray::from_values(values).expect("every value is an int"));
+		assert_eq!(array.true_height(), len + 1);
+
+		let summary = array.as_int_array().and_then(NbtIntArray::summary).expect("arrays past the limit are summarized");
+		assert_eq!((summary.min, summary.max, summary.sum), (0, len as i64 - 1, (0..len as i128).sum()));
+
+		array.toggle().expect("arrays can be opened");
+		assert_eq!(array.height(), limit + 1);
+		// only the last shown element loads more
+		assert_eq!(array.show_more(0), 0);
+		assert_eq!(array.show_more(limit - 1), limit);
+		assert_eq!(array.height(), 2 * limit + 1);
+
+		let mut shown = 2 * limit;
+		while shown < len {
+			shown += array.show_more(shown - 1);
+		}
+		assert_eq!(shown, len);
+		assert_eq!(array.height(), array.true_height());
+		assert_eq!(array.show_more(len - 1), 0);
+	}
+}
@@ -1,28 +1,115 @@
 use std::alloc::{alloc, Layout};
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 use std::intrinsics::likely;
-use std::mem::{ManuallyDrop, MaybeUninit};
+use std::mem::ManuallyDrop;
+#[cfg(not(target_arch = "wasm32"))]
+use std::num::NonZeroUsize;
 use std::ops::{Deref, DerefMut};
 #[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
 use std::thread::Scope;
 
+use anyhow::{anyhow, Context, Result};
 use compact_str::{format_compact, CompactString, ToCompactString};
 use zune_inflate::{DeflateDecoder, DeflateOptions};
 
-use crate::assets::{JUST_OVERLAPPING_BASE_TEXT_Z, BASE_Z, CHUNK_UV, CONNECTION_UV, HEADER_SIZE, LINE_NUMBER_CONNECTOR_Z, LINE_NUMBER_SEPARATOR_UV, REGION_UV, ZOffset};
+#[cfg(feature = "gui")]
+use crate::assets::{JUST_OVERLAPPING_BASE_TEXT_Z, BASE_Z, CHUNK_UV, HEADER_SIZE, LINE_NUMBER_CONNECTOR_Z, LINE_NUMBER_SEPARATOR_UV, REGION_UV, UNKNOWN_NBT_UV, ZOffset};
 use crate::elements::compound::NbtCompound;
 use crate::elements::element::NbtElement;
 use crate::elements::list::{ValueIterator, ValueMutIterator};
+use crate::elements::string::NbtString;
 use crate::encoder::UncheckedBufWriter;
-use crate::tab::FileFormat;
+use crate::file::FileFormat;
+#[cfg(feature = "gui")]
 use crate::vertex_buffer_builder::VertexBufferBuilder;
-use crate::{DropFn, RenderContext, SortAlgorithm, StrExt};
+use crate::{lz4, DropFn, SortAlgorithm, StrExt};
+#[cfg(feature = "gui")]
+use crate::RenderContext;
+#[cfg(feature = "gui")]
 use crate::color::TextColor;
 use crate::formatter::PrettyFormatter;
 
+/// The most sectors the offset table of a region file can give a single chunk.
+pub const MAX_CHUNK_SECTORS: usize = 255;
+/// Set on the compression byte of chunks which are too big for their region file, their compressed bytes are then inside a `c.<x>.<z>.mcc` file next to it instead.
+pub const EXTERNAL_CHUNK_FLAG: u8 = 0x80;
+/// The compression byte some tools write for chunks which are only stored inside their `c.<x>.<z>.mcc` file, leaving a stub in the region file; the compression of those bytes is then detected from them, see [`NbtChunk::detect_compression`].
+pub const EXTERNAL_ONLY_COMPRESSION: u8 = 127;
+
+/// The compressed chunks which are too big for their region file, by their position inside it.
+pub type EncodedExternalChunks = Vec<(u16, Vec<u8>)>;
+
+/// Where the chunks which are too big for their region file are read from.
+#[derive(Copy, Clone)]
+pub enum ExternalChunks<'a> {
+	/// The `c.<x>.<z>.mcc` files next to the region file at this path.
+	#[cfg(not(target_arch = "wasm32"))]
+	Files(&'a Path),
+	/// The compressed chunks by their position, as returned by [`NbtRegion::to_be_bytes_external`].
+	Encoded(&'a [(u16, Vec<u8>)]),
+	/// External chunks fail to read.
+	None,
+}
+
+impl<'a> ExternalChunks<'a> {
+	/// The name of the external file of the chunk at `pos` in the region at `region`, `c.<x>.<z>.mcc` with world chunk coordinates.
+	#[must_use]
+	pub fn file_name(region: (i32, i32), pos: u16) -> String { format!("c.{x}.{z}.mcc", x = region.0 * 32 + (pos & 31) as i32, z = region.1 * 32 + (pos >> 5) as i32) }
+
+	/// The coordinates of the region the external file named `name` (see [`Self::file_name`]) belongs to, `None` if it isn't named like one.
+	#[must_use]
+	pub fn region_of(name: &str) -> Option<(i32, i32)> {
+		let (x, z) = name.strip_prefix("c.")?.strip_suffix(".mcc")?.split_once('.')?;
+		Some((x.parse::<i32>().ok()?.div_euclid(32), z.parse::<i32>().ok()?.div_euclid(32)))
+	}
+
+	#[must_use]
+	fn read(self, pos: u16) -> Option<Cow<'a, [u8]>> {
+		match self {
+			#[cfg(not(target_arch = "wasm32"))]
+			Self::Files(path) => {
+				let (x, z, _) = NbtRegion::coordinates_of(path)?;
+				let path = path.with_file_name(Self::file_name((x, z), pos));
+				match std::fs::read(&path) {
+					Ok(bytes) => Some(Cow::Owned(bytes)),
+					Err(e) => {
+						log::warn!("Failed to read external chunk {}: {e}", path.display());
+						None
+					}
+				}
+			}
+			Self::Encoded(chunks) => chunks.iter().find(|(idx, _)| *idx == pos).map(|(_, bytes)| Cow::Borrowed(bytes.as_slice())),
+			Self::None => None,
+		}
+	}
+}
+
+/// The compressed bytes of a chunk as they were read from its region file, written back instead of compressing the chunk again while it's unchanged so that untouched chunks stay byte for byte the same, see [`NbtRegion::mark_dirty`].
+///
+/// Chunks which fail to parse are kept as one of these without a chunk, so that saving writes them back as they were instead of losing them.
+#[derive(Clone)]
+pub struct RawChunk {
+	/// The compression byte the chunk was stored with, without [`EXTERNAL_CHUNK_FLAG`].
+	compression: u8,
+	/// The timestamp the chunk was stored with, which parsed chunks keep in [`NbtChunk`] instead.
+	timestamp: u32,
+	/// Empty for chunks which failed to parse before their bytes could be read.
+	bytes: Box<[u8]>,
+}
+
+/// The positions of the chunks of an [`NbtRegion`] in display order, every chunk by its position, and for regions read from a file, the [`RawChunk`] each position was read with.
+///
+/// Only positions without a chunk keep the bytes of one which failed to parse, the others lose theirs once their chunk is edited or removed.
+pub type RegionChunks = Box<(Vec<u16>, [NbtElement; 32 * 32], Vec<Option<RawChunk>>)>;
+
 #[repr(C)]
 pub struct NbtRegion {
-	pub chunks: Box<(Vec<u16>, [NbtElement; 32 * 32])>,
+	pub chunks: RegionChunks,
 	height: u32,
 	true_height: u32,
 	max_depth: u32,
@@ -44,30 +131,16 @@ impl NbtRegion {
 }
 
 impl Clone for NbtRegion {
-	#[allow(clippy::cast_ptr_alignment)]
 	#[inline]
 	fn clone(&self) -> Self {
-		unsafe {
-			let (map, chunks) = &*self.chunks;
-			let box_ptr = alloc(Layout::new::<(Vec<u16>, [NbtElement; 32 * 32])>()).cast::<(Vec<u16>, [NbtElement; 32 * 32])>();
-			let map_ptr = alloc(Layout::array::<u16>(map.len()).unwrap_unchecked()).cast::<u16>();
-			let chunks_ptr = alloc(Layout::array::<NbtElement>(32 * 32).unwrap_unchecked()).cast::<NbtElement>();
-			map.as_ptr().copy_to_nonoverlapping(map_ptr, map.len());
-			for n in 0..1024 {
-				chunks_ptr.add(n).write(chunks.get_unchecked(n).clone());
-			}
-			box_ptr.write((
-				Vec::from_raw_parts(map_ptr, map.len(), map.len()),
-				chunks_ptr.cast::<[NbtElement; 32 * 32]>().read(),
-			));
-
-			Self {
-				chunks: Box::from_raw(box_ptr),
-				height: self.height,
-				true_height: self.true_height,
-				max_depth: self.max_depth,
-				open: self.open,
-			}
+		let (map, chunks, raw) = &*self.chunks;
+		Self {
+			// empty slots are null elements, which clone as null elements
+			chunks: Box::new((map.clone(), core::array::from_fn(|n| chunks[n].clone()), raw.clone())),
+			height: self.height,
+			true_height: self.true_height,
+			max_depth: self.max_depth,
+			open: self.open,
 		}
 	}
 }
@@ -75,7 +148,7 @@ impl Clone for NbtRegion {
 impl Default for NbtRegion {
 	fn default() -> Self {
 		Self {
-			chunks: Box::new((Vec::new(), unsafe { core::mem::zeroed() })),
+			chunks: Box::new((Vec::new(), [NbtElement::NULL; 32 * 32], Vec::new())),
 			height: 1,
 			true_height: 1,
 			open: false,
@@ -91,169 +164,279 @@ impl NbtRegion {
 	#[must_use]
 	pub fn new() -> Self { Self::default() }
 
+	/// Parses the region coordinates out of a `r.<x>.<z>.mca` (or `.mcr`) file name.
 	#[must_use]
-	pub fn from_be_bytes(bytes: &[u8], sort: SortAlgorithm) -> Option<Self> {
-		fn parse(raw: u32, bytes: &[u8], sort: SortAlgorithm) -> Option<(FileFormat, Option<NbtCompound>)> {
-			if raw < 512 { return Some((FileFormat::Zlib, None)) }
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn coordinates_of(path: &Path) -> Option<(i32, i32, &str)> {
+		let name = path.file_name()?.to_str()?;
+		let (rest, extension) = name.strip_prefix("r.")?.rsplit_once('.')?;
+		if extension != "mca" && extension != "mcr" { return None }
+		let (x, z) = rest.split_once('.')?;
+		Some((x.parse().ok()?, z.parse().ok()?, extension))
+	}
+
+	/// Parses a region file, reading the chunks marked with [`EXTERNAL_CHUNK_FLAG`] from `external`.
+	///
+	/// # Errors
+	///
+	/// * The file is too short for its header
+	///
+	/// Chunks allocated sectors which start past the end of the file are read as unreadable (see [`Self::truncation`]), the last sector of the file may be cut short.
+	pub fn from_be_bytes(bytes: &[u8], sort: SortAlgorithm, external: ExternalChunks) -> Result<Self> { Self::from_be_bytes_counted(bytes, sort, external).map(|(region, _)| region) }
 
+	/// [`Self::from_be_bytes`], along with the amount of lists of `TAG_End` with a length read as empty lists across every chunk, see `lenient_lists`.
+	///
+	/// # Errors
+	///
+	/// * See [`Self::from_be_bytes`]
+	pub fn from_be_bytes_counted(bytes: &[u8], sort: SortAlgorithm, external: ExternalChunks) -> Result<(Self, usize)> {
+		/// `None` for empty slots, and the bytes of the chunk (if they could be read at all) when it fails to parse.
+		fn parse(raw: u32, bytes: &[u8], idx: usize, timestamp: u32, sort: SortAlgorithm, external: ExternalChunks) -> Option<Result<(NbtChunk, RawChunk, usize), RawChunk>> {
+			if raw < 512 { return None }
+
+			let unreadable = |compression: u8, bytes: Box<[u8]>| RawChunk { compression, timestamp, bytes };
+			let pos = ((idx >> 5) as u8 & 31, idx as u8 & 31);
 			let len = (raw as usize & 0xFF) * 4096;
 			let offset = ((raw >> 8) - 2) as usize * 4096;
-			if bytes.len() < offset + len { return None }
-			let data = &bytes[offset..(offset + len)];
-
-			if let &[a, b, c, d, compression, ref data @ ..] = data {
-				let chunk_len = (u32::from_be_bytes([a, b, c, d]) as usize).checked_sub(1)?;
-				if data.len() < chunk_len { return None }
-				let data = &data[..chunk_len];
-				let (compression, element) = match compression {
-					1 => (
-						FileFormat::Gzip,
-						NbtElement::from_be_file(
-							&DeflateDecoder::new_with_options(data, DeflateOptions::default().set_confirm_checksum(false))
-								.decode_gzip()
-								.ok()?,
-							sort,
-						)?,
-					),
-					2 => (
-						FileFormat::Zlib,
-						NbtElement::from_be_file(
-							&DeflateDecoder::new_with_options(data, DeflateOptions::default().set_confirm_checksum(false))
-								.decode_zlib()
-								.ok()?,
-							sort,
-						)?,
-					),
-					3 => (FileFormat::Nbt, NbtElement::from_be_file(data, sort)?),
-					4 => (FileFormat::Lz4, NbtElement::from_be_file(&lz4_flex::decompress(data, data.len()).ok()?, sort)?),
-					_ => return None,
-				};
-				if let Some(element) = element.into_compound() {
-					return Some((compression, Some(element)));
-				}
+			// cut off at the end of the file, the last sector isn't always padded and the file may be truncated, see `NbtRegion::truncation`
+			let data = &bytes[offset.min(bytes.len())..(offset + len).min(bytes.len())];
+
+			let &[a, b, c, d, compression, ref data @ ..] = data else { return Some(Err(unreadable(0, Box::new([])))) };
+			let Some(chunk_len) = (u32::from_be_bytes([a, b, c, d]) as usize).checked_sub(1) else { return Some(Err(unreadable(0, Box::new([])))) };
+			if data.len() < chunk_len {
+				log::warn!("Chunk {pos:?} is {chunk_len} bytes long but only {} bytes are allocated to it", data.len());
+				return Some(Err(unreadable(compression & !EXTERNAL_CHUNK_FLAG, data.into())))
 			}
-			None
+			// the sentinel says there's nothing inline, but the stub is still read if it has bytes after all
+			let external_only = compression & !EXTERNAL_CHUNK_FLAG == EXTERNAL_ONLY_COMPRESSION && chunk_len == 0;
+			let (data, compression) = if compression & EXTERNAL_CHUNK_FLAG > 0 || external_only {
+				let Some(data) = external.read(idx as u16) else {
+					log::warn!("Failed to find the external data of chunk {pos:?}");
+					return Some(Err(unreadable(compression & !EXTERNAL_CHUNK_FLAG, Box::new([]))))
+				};
+				(data, compression & !EXTERNAL_CHUNK_FLAG)
+			} else {
+				(Cow::Borrowed(&data[..chunk_len]), compression)
+			};
+			let compression = if compression == EXTERNAL_ONLY_COMPRESSION { NbtChunk::detect_compression(&data) } else { compression };
+			let Some((chunk, end_lists)) = NbtChunk::from_raw_counted(&data, compression, pos, timestamp, sort) else {
+				log::warn!("Failed to parse chunk {pos:?} with compression {compression}");
+				return Some(Err(unreadable(compression, data.into_owned().into_boxed_slice())))
+			};
+			Some(Ok((chunk, unreadable(compression, data.into_owned().into_boxed_slice()), end_lists)))
 		}
 
-		if bytes.len() < 8192 { return None }
+		if bytes.is_empty() { return Err(anyhow!("The region file is empty")) }
+		let Some((&offsets, bytes)) = bytes.split_first_chunk::<4096>() else { return Err(anyhow!("The region file is truncated, it's {len} bytes long but its header alone is 8192 bytes", len = bytes.len())) };
+		let Some((&timestamps, bytes)) = bytes.split_first_chunk::<4096>() else { return Err(anyhow!("The region file is truncated, it's {len} bytes long but its header alone is 8192 bytes", len = bytes.len() + 4096)) };
+		let slots = offsets
+			.array_chunks::<4>()
+			.zip(timestamps.array_chunks::<4>())
+			.map(|(&offset, &timestamp)| (u32::from_be_bytes(offset), u32::from_be_bytes(timestamp)))
+			.collect::<Vec<_>>();
 
+		// a worker per core taking the next slot until they run out, instead of a thread per slot
 		#[cfg(not(target_arch = "wasm32"))]
-		return std::thread::scope(|s| {
-			let mut region = Self::new();
-
-			let (&offsets, bytes) = bytes.split_first_chunk::<4096>()?;
-			let (&timestamps, bytes) = bytes.split_first_chunk::<4096>()?;
-			let mut threads = Vec::with_capacity(1024);
-
-
-			for (&offset, &timestamp) in offsets
-				.array_chunks::<4>()
-				.zip(timestamps.array_chunks::<4>())
-			{
-				let timestamp = u32::from_be_bytes(timestamp);
-				let offset = u32::from_be_bytes(offset);
-				threads.push((timestamp, s.spawn(move || parse(offset, bytes, sort))));
-			}
-
-			let mut pos = 0;
-			for (timestamp, thread) in threads {
-				let (format, element) = thread.join().ok()??;
-				if let Some(element) = element {
+		let chunks = {
+			let next = AtomicUsize::new(0);
+			let (next, slots) = (&next, &slots);
+			let workers = std::thread::available_parallelism().map_or(1, NonZeroUsize::get).min(slots.len());
+			let mut chunks = (0..slots.len()).map(|_| None).collect::<Vec<_>>();
+			std::thread::scope(|s| {
+				let threads = (0..workers).map(|_| s.spawn(move || {
+					let mut parsed = vec![];
+					loop {
+						let pos = next.fetch_add(1, Ordering::Relaxed);
+						let Some(&(offset, timestamp)) = slots.get(pos) else { break parsed };
+						parsed.push((pos, parse(offset, bytes, pos, timestamp, sort, external)));
+					}
+				})).collect::<Vec<_>>();
+				for thread in threads {
+					for (pos, chunk) in thread.join().ok()? {
+						chunks[pos] = chunk;
+					}
+				}
+				Some(())
+			}).context("A thread parsing the chunks of the region file panicked")?;
+			chunks
+		};
+		#[cfg(target_arch = "wasm32")]
+		let chunks = slots.iter().enumerate().map(|(pos, &(offset, timestamp))| parse(offset, bytes, pos, timestamp, sort, external)).collect::<Vec<_>>();
+
+		let mut region = Self::new();
+		let mut raw_chunks = vec![None; 32 * 32];
+		let mut end_lists = 0;
+		for (pos, chunk) in chunks.into_iter().enumerate() {
+			match chunk {
+				Some(Ok((chunk, raw, chunk_end_lists))) => {
 					unsafe {
-						region.insert_unchecked(
-							pos,
-							region.len(),
-							NbtElement::Chunk(NbtChunk::from_compound(element, ((pos >> 5) as u8 & 31, pos as u8 & 31), format, timestamp, )),
-						);
+						region.insert_unchecked(pos, region.len(), NbtElement::Chunk(chunk));
 					}
+					raw_chunks[pos] = Some(raw);
+					end_lists += chunk_end_lists;
 				}
-				pos += 1;
+				Some(Err(raw)) => raw_chunks[pos] = Some(raw),
+				None => {}
 			}
+		}
+		region.chunks.2 = raw_chunks;
 
-			Some(region)
-		});
+		Ok((region, end_lists))
+	}
 
-		#[cfg(target_arch = "wasm32")]
-		return {
-			let mut region = Self::new();
+	/// Why the region file `bytes` is truncated, if it has chunks allocated sectors past its end, which [`Self::from_be_bytes`] reads as unreadable.
+	#[must_use]
+	pub fn truncation(bytes: &[u8]) -> Option<String> {
+		let (offsets, _) = bytes.split_first_chunk::<4096>()?;
+		let len = bytes.len().checked_sub(8192)?;
+		let truncated = offsets
+			.array_chunks::<4>()
+			.map(|&offset| u32::from_be_bytes(offset))
+			.enumerate()
+			// every sector of the chunk has to at least start inside the file, slots pointing into the header are read as empty
+			.filter(|&(_, raw)| raw >= 512 && raw & 0xFF > 0 && ((raw >> 8) as usize - 2 + (raw as usize & 0xFF) - 1) * 4096 >= len)
+			.map(|(idx, _)| format!("{x}|{z}", x = (idx >> 5) & 31, z = idx & 31))
+			.collect::<Vec<_>>();
+		if truncated.is_empty() { return None }
+		let (s, are) = if truncated.len() == 1 { ("", "is") } else { ("s", "are") };
+		Some(format!("The region file is truncated, it's {sectors} sectors long but {n} chunk{s} {are} allocated sectors past its end and could only be read in part, if at all: {chunks}", sectors = len.div_ceil(4096) + 2, n = truncated.len(), chunks = truncated.join(", ")))
+	}
 
-			let (&offsets, bytes) = bytes.split_first_chunk::<4096>()?;
-			let (&timestamps, bytes) = bytes.split_first_chunk::<4096>()?;
-			let mut threads = Vec::with_capacity(1024);
+	/// A 32×32 RGBA image with a pixel for every slot, `x` going right and `z` going down, slots with a chunk are white, or with `by_last_modified`, blue for the least recently modified ones through to yellow for the most recently modified ones, chunks which failed to parse are red and empty slots are transparent.
+	#[must_use]
+	pub fn presence_map(&self, by_last_modified: bool) -> Vec<u8> {
+		const OLDEST: [u8; 3] = [0x3C, 0x5A, 0xC3];
+		const NEWEST: [u8; 3] = [0xF1, 0xD0, 0x43];
+
+		let (_, chunks, _) = &*self.chunks;
+		let (min, max) = chunks.iter().filter_map(NbtElement::as_chunk).fold((u32::MAX, 0), |(min, max), chunk| (min.min(chunk.last_modified), max.max(chunk.last_modified)));
+		let mut image = vec![0; 32 * 32 * 4];
+		for chunk in chunks.iter().filter_map(NbtElement::as_chunk) {
+			let color = if by_last_modified {
+				let t = if max > min { (chunk.last_modified - min) as f32 / (max - min) as f32 } else { 1.0 };
+				core::array::from_fn(|idx| (f32::from(OLDEST[idx]) + (f32::from(NEWEST[idx]) - f32::from(OLDEST[idx])) * t) as u8)
+			} else {
+				[0xFF; 3]
+			};
+			let pixel = (chunk.z as usize * 32 + chunk.x as usize) * 4;
+			image[pixel..pixel + 4].copy_from_slice(&[color[0], color[1], color[2], 0xFF]);
+		}
+		for (x, z) in self.unreadable() {
+			let pixel = (z as usize * 32 + x as usize) * 4;
+			image[pixel..pixel + 4].copy_from_slice(&[0xC3, 0x3C, 0x3C, 0xFF]);
+		}
+		image
+	}
 
+	/// Reads the sector allocation of the region file `bytes` from its offset table, `None` if it is too short to have one.
+	#[must_use]
+	pub fn sector_map(bytes: &[u8]) -> Option<SectorMap> {
+		let (offsets, _) = bytes.split_first_chunk::<4096>()?;
+		if bytes.len() < 8192 { return None }
 
-			for (&offset, &timestamp) in offsets
-				.array_chunks::<4>()
-				.zip(timestamps.array_chunks::<4>())
-			{
-				let timestamp = u32::from_be_bytes(timestamp);
-				let offset = u32::from_be_bytes(offset);
-				threads.push((timestamp, parse(offset, bytes, sort)));
+		let mut map = SectorMap {
+			sectors: vec![Sector::Free; bytes.len().div_ceil(4096)],
+			overlapping: vec![],
+			out_of_bounds: vec![],
+		};
+		map.sectors[..2].fill(Sector::Header);
+		for (idx, &offset) in offsets.array_chunks::<4>().enumerate() {
+			let idx = idx as u16;
+			let raw = u32::from_be_bytes(offset);
+			if raw == 0 { continue }
+			let start = (raw >> 8) as usize;
+			let end = start + (raw & 0xFF) as usize;
+			if end > map.sectors.len() {
+				map.out_of_bounds.push(idx);
+				map.sectors.resize(end, Sector::Free);
 			}
-
-
-			for (pos, (timestamp, thread)) in threads.into_iter().enumerate() {
-				let (format, element) = thread?;
-				if let Some(element) = element {
-					unsafe {
-						region.insert_unchecked(
-							pos,
-							region.len(),
-							NbtElement::Chunk(NbtChunk::from_compound(element, ((pos >> 5) as u8 & 31, pos as u8 & 31), format, timestamp, )),
-						);
+			for sector in &mut map.sectors[start..end] {
+				*sector = match *sector {
+					Sector::Free => Sector::Chunk(idx),
+					Sector::Header => {
+						if !map.out_of_bounds.contains(&idx) { map.out_of_bounds.push(idx) }
+						Sector::Header
 					}
-				}
+					Sector::Chunk(other) | Sector::Overlapping(other) => {
+						if !map.overlapping.contains(&(other, idx)) { map.overlapping.push((other, idx)) }
+						Sector::Overlapping(other)
+					}
+				};
 			}
-
-			Some(region)
-		};
+		}
+		Some(map)
 	}
+
 	pub fn to_be_bytes(&self, writer: &mut UncheckedBufWriter) {
+		let external = self.to_be_bytes_external(writer);
+		if !external.is_empty() {
+			log::error!("{n} chunks are too big for the region file and were written without their data", n = external.len());
+		}
+	}
+
+	/// [`Self::to_be_bytes`], returning the compressed chunks which are too big for the region file by their position, which belong in the `c.<x>.<z>.mcc` files of [`ExternalChunks::Files`].
+	#[must_use]
+	pub fn to_be_bytes_external(&self, writer: &mut UncheckedBufWriter) -> EncodedExternalChunks {
 		unsafe {
 			std::thread::scope(move |s| {
 				let mut chunks = Vec::with_capacity(1024);
-				for chunk in &self.chunks.as_ref().1 {
+				let (_, region, raw_chunks) = self.chunks.as_ref();
+				for (pos, chunk) in region.iter().enumerate() {
+					let raw = raw_chunks.get(pos).and_then(Option::as_ref);
 					chunks.push(s.spawn(move || {
 						if chunk.is_null() {
-							(vec![], 0)
+							// chunks which failed to parse are written back as they were read
+							if let Some(raw) = raw && !raw.bytes.is_empty() {
+								let mut writer = UncheckedBufWriter::new();
+								let external = NbtChunk::write_encoded(&mut writer, Cow::Borrowed(&raw.bytes), raw.compression);
+								(writer.finish(), raw.timestamp, external)
+							} else {
+								(vec![], 0, None)
+							}
 						} else {
 							let chunk = &(chunk as *const NbtElement)
 								.cast::<ManuallyDrop<NbtChunk>>()
 								.read();
 							let mut writer = UncheckedBufWriter::new();
-							chunk.to_be_bytes(&mut writer);
-							(writer.finish(), chunk.last_modified)
+							let external = chunk.to_be_bytes_preserving(&mut writer, raw);
+							(writer.finish(), chunk.last_modified, external)
 						}
 					}));
 				}
 				let mut o = 2_u32;
-				let mut offsets = MaybeUninit::<u32>::uninit_array::<1024>();
-				let mut timestamps = MaybeUninit::<u32>::uninit_array::<1024>();
+				let mut offsets = [[0_u8; 4]; 1024];
+				let mut timestamps = [[0_u8; 4]; 1024];
 				let mut new_chunks = Vec::with_capacity(chunks.len());
-				for (chunk, (offset, timestamp)) in chunks
+				let mut external_chunks = vec![];
+				for (pos, (chunk, (offset, timestamp))) in chunks
 					.into_iter()
 					.zip(offsets.iter_mut().zip(timestamps.iter_mut()))
+					.enumerate()
 				{
-					let Ok((chunk, last_modified)) = chunk.join() else {
-						return;
+					let Ok((chunk, last_modified, external)) = chunk.join() else {
+						return vec![];
 					};
-					let sectors = (chunk.len() / 4096) as u32;
+					if let Some(external) = external {
+						external_chunks.push((pos as u16, external));
+					}
+					// chunks of more than 255 sectors are written externally, leaving a single sector behind
+					let sectors = (chunk.len() / 4096) as u8;
 					if sectors > 0 {
-						offset.write((o.to_be() >> 8) | (sectors << 24));
-						o += sectors;
-						timestamp.write(last_modified);
+						// a big endian 3 byte sector offset followed by the sector count
+						let [_, a, b, c] = o.to_be_bytes();
+						*offset = [a, b, c, sectors];
+						o += u32::from(sectors);
+						*timestamp = last_modified.to_be_bytes();
 						new_chunks.push(chunk);
-					} else {
-						offset.write(0);
-						timestamp.write(0);
 					}
 				}
-				writer.write(&core::mem::transmute::<_, [u8; 4096]>(offsets));
-				writer.write(&core::mem::transmute::<_, [u8; 4096]>(timestamps));
+				writer.write(offsets.as_flattened());
+				writer.write(timestamps.as_flattened());
 				for chunk in new_chunks {
 					writer.write(&chunk);
 				}
-			});
+				external_chunks
+			})
 		}
 	}
 
@@ -304,25 +487,69 @@ impl NbtRegion {
 	#[must_use]
 	pub fn is_empty(&self) -> bool { (*self.chunks).0.is_empty() }
 
+	/// Shows the chunks which failed to parse as hex dumps of their decompressed bytes, see [`NbtChunk::from_hex_dump`], returning their indices inside of the region and how many couldn't even be decompressed.
+	///
+	/// The bytes they were read with stay the [`RawChunk`] of the hex dump, so it's written back byte for byte unless it's edited, and removing it removes the chunk.
+	pub fn view_unreadable(&mut self) -> (Vec<usize>, usize) {
+		let mut viewed = vec![];
+		let mut failed = 0;
+		let (_, chunks, raw_chunks) = &mut *self.chunks;
+		for (pos, slot) in raw_chunks.iter_mut().enumerate() {
+			let Some(raw) = slot else { continue };
+			if !chunks[pos].is_null() || raw.bytes.is_empty() { continue }
+			let Some((compression, data)) = NbtChunk::decompress(&raw.bytes, raw.compression) else {
+				failed += 1;
+				continue
+			};
+			viewed.push((pos, NbtChunk::from_hex_dump(&data, ((pos >> 5) as u8, pos as u8 & 31), compression, raw.timestamp), slot.take()));
+		}
+		let mut indices = Vec::with_capacity(viewed.len());
+		for (pos, chunk, raw) in viewed {
+			let idx = self.len();
+			unsafe {
+				self.insert_unchecked(pos, idx, NbtElement::Chunk(chunk));
+			}
+			self.chunks.2[pos] = raw;
+			indices.push(idx);
+		}
+		(indices, failed)
+	}
+
+	/// The positions of the chunks which failed to parse when the region was read, see [`RawChunk`].
+	pub fn unreadable(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+		let (_, chunks, raw_chunks) = &*self.chunks;
+		raw_chunks.iter().enumerate().filter(|&(pos, raw)| raw.is_some() && chunks[pos].is_null()).map(|(pos, _)| ((pos >> 5) as u8, pos as u8 & 31))
+	}
+
+	/// The heap memory held by the region, including the compressed chunks it keeps to write back unchanged ones as they were, see [`NbtElement::memory_size`].
+	#[must_use]
+	pub fn memory_size(&self) -> usize {
+		let (map, chunks, raw_chunks) = &*self.chunks;
+		let raw = raw_chunks.capacity() * core::mem::size_of::<Option<RawChunk>>() + raw_chunks.iter().flatten().map(|raw| raw.bytes.len()).sum::<usize>();
+		let chunks = chunks.iter().filter(|chunk| !chunk.is_null()).map(NbtElement::memory_size).sum::<usize>();
+		core::mem::size_of_val(&*self.chunks) + map.capacity() * core::mem::size_of::<u16>() + raw + chunks
+	}
+
 	/// # Errors
 	///
 	/// * `NbtElement` is not of `NbtChunk`
 	///
 	/// * Index is outside the range of `NbtRegion`
+	///
+	/// * The position of the chunk's coordinates is already taken, chunks are never moved to other coordinates to make room
 	#[inline]
-	pub fn insert(&mut self, idx: usize, mut value: NbtElement) -> Result<(), NbtElement> {
-		if let Some(chunk) = value.as_chunk_mut() {
-			let mut pos = ((chunk.x as u16) << 5) | (chunk.z as u16);
-			let (map, chunks) = &mut *self.chunks;
-			while pos < chunks.len() as u16 && !chunks[pos as usize].is_null() {
-				pos += 1;
-			}
-			chunk.x = (pos >> 5) as u8 & 31;
-			chunk.z = pos as u8 & 31;
-			if pos < chunks.len() as u16 && idx <= map.len() && chunks[pos as usize].is_null() {
+	pub fn insert(&mut self, idx: usize, value: NbtElement) -> Result<(), NbtElement> {
+		if let Some(chunk) = value.as_chunk() {
+			let pos = ((chunk.x as usize & 31) << 5) | (chunk.z as usize & 31);
+			let (map, chunks, raw_chunks) = &mut *self.chunks;
+			if idx <= map.len() && chunks.get(pos).is_some_and(NbtElement::is_null) {
 				let (height, true_height) = (value.height(), value.true_height());
-				map.insert(idx, pos);
-				chunks[map[idx] as usize] = value;
+				// the chunk takes the place of the one which failed to parse there
+				if let Some(raw) = raw_chunks.get_mut(pos) {
+					*raw = None;
+				}
+				map.insert(idx, pos as u16);
+				chunks[pos] = value;
 				self.increment(height, true_height);
 				return Ok(());
 			}
@@ -331,6 +558,26 @@ impl NbtRegion {
 		Err(value)
 	}
 
+	/// Moves the chunk at `idx` to the coordinates `x`, `z` of the region, keeping its place in the display order, returns its previous coordinates.
+	///
+	/// `None` if `idx` is out of range, the coordinates are outside the region or another chunk is already there.
+	#[inline]
+	pub fn relocate_chunk(&mut self, idx: usize, x: u8, z: u8) -> Option<(u8, u8)> {
+		if x > 31 || z > 31 { return None }
+		let (map, chunks, raw_chunks) = &mut *self.chunks;
+		let from = *map.get(idx)? as usize;
+		let to = ((x as usize) << 5) | (z as usize);
+		if from != to && !chunks[to].is_null() { return None }
+		// the chunk takes the place of the one which failed to parse there, and its bytes go along with it
+		if from != to && raw_chunks.len() > from.max(to) {
+			raw_chunks[to] = raw_chunks[from].take();
+		}
+		chunks.swap(from, to);
+		map[idx] = to as u16;
+		let chunk = unsafe { chunks[to].as_chunk_unchecked_mut() };
+		Some((core::mem::replace(&mut chunk.x, x), core::mem::replace(&mut chunk.z, z)))
+	}
+
 	/// # Safety
 	///
 	/// * `value` must be variant `NbtElement::Chunk`
@@ -341,7 +588,10 @@ impl NbtRegion {
 	#[inline]
 	pub unsafe fn insert_unchecked(&mut self, pos: usize, idx: usize, value: NbtElement) {
 		self.increment(value.height(), value.true_height());
-		let (map, chunks) = &mut *self.chunks;
+		let (map, chunks, raw_chunks) = &mut *self.chunks;
+		if let Some(raw) = raw_chunks.get_mut(pos) {
+			*raw = None;
+		}
 		map.insert(idx, pos as u16);
 		unsafe { chunks.as_mut_ptr().cast::<NbtElement>().add(pos).write(value); }
 	}
@@ -349,26 +599,37 @@ impl NbtRegion {
 	#[inline]
 	#[must_use]
 	pub fn remove(&mut self, idx: usize) -> NbtElement {
-		let (map, chunks) = &mut *self.chunks;
-		unsafe {
-			core::ptr::replace(
-				core::ptr::addr_of_mut!(chunks[map.remove(idx) as usize]),
-				core::mem::zeroed(),
-			)
+		let (map, chunks, raw_chunks) = &mut *self.chunks;
+		let pos = map.remove(idx) as usize;
+		// the bytes aren't put back with the chunk, since another one may have taken its place by then
+		if let Some(raw) = raw_chunks.get_mut(pos) {
+			*raw = None;
+		}
+		core::mem::replace(&mut chunks[pos], NbtElement::NULL)
+	}
+
+	/// Marks the chunk at `idx` as changed since it was read so it's compressed again when saved, dropping the bytes it was read with unless it's a hex dump, which falls back to them while one of its lines isn't hex.
+	pub fn mark_dirty(&mut self, idx: usize) {
+		let (map, chunks, raw_chunks) = &mut *self.chunks;
+		let Some(&pos) = map.get(idx) else { return };
+		let Some(chunk) = chunks[pos as usize].as_chunk_mut() else { return };
+		chunk.dirty = true;
+		if !chunk.raw && let Some(raw) = raw_chunks.get_mut(pos as usize) {
+			*raw = None;
 		}
 	}
 
 	#[inline]
 	#[must_use]
 	pub fn get(&self, idx: usize) -> Option<&NbtElement> {
-		let (map, chunks) = &*self.chunks;
+		let (map, chunks, _) = &*self.chunks;
 		map.get(idx).and_then(|&x| chunks.get(x as usize))
 	}
 
 	#[inline]
 	#[must_use]
 	pub fn get_mut(&mut self, idx: usize) -> Option<&mut NbtElement> {
-		let (map, chunks) = &mut *self.chunks;
+		let (map, chunks, _) = &mut *self.chunks;
 		map.get(idx).and_then(|&x| chunks.get_mut(x as usize))
 	}
 
@@ -382,6 +643,7 @@ impl NbtRegion {
 		)
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	#[allow(clippy::too_many_lines)]
 	pub fn render_root(&self, builder: &mut VertexBufferBuilder, str: &str, ctx: &mut RenderContext) {
@@ -404,7 +666,7 @@ impl NbtRegion {
 				(2, 2),
 			);
 			Self::render_icon(ctx.pos(), BASE_Z, builder);
-			builder.draw_texture(ctx.pos() - (16, 0), CONNECTION_UV, (16, 9));
+			ctx.draw_connection(ctx.pos() - (16, 0), (16, 9), builder);
 			if !self.is_empty() {
 				ctx.draw_toggle(ctx.pos() - (16, 0), self.open, builder);
 			}
@@ -423,7 +685,7 @@ impl NbtRegion {
 			ctx.y_offset += 16;
 		}
 
-		ctx.x_offset += 16;
+		ctx.indent();
 
 		if self.open {
 			let shadowing_other = {
@@ -485,13 +747,13 @@ impl NbtRegion {
 				ctx.draw_held_entry_bar(ctx.pos(), builder, |x, y| pos == (x, y), |id| id == NbtChunk::ID);
 
 				if remaining_scroll == 0 {
-					builder.draw_texture(
+					ctx.draw_connection(
 						ctx.pos() - (16, 0),
-						CONNECTION_UV,
 						(
 							16,
 							(idx != self.len() - 1) as usize * 7 + 9,
 						),
+						builder,
 					);
 				}
 				let forbidden_y = ctx.selected_y;
@@ -513,18 +775,19 @@ impl NbtRegion {
 		}
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render_icon(pos: impl Into<(usize, usize)>, z: ZOffset, builder: &mut VertexBufferBuilder) { builder.draw_texture_z(pos, z, REGION_UV, (16, 16)); }
 
 	#[inline]
 	pub fn children(&self) -> ValueIterator {
-		let (map, chunks) = &*self.chunks;
+		let (map, chunks, _) = &*self.chunks;
 		ValueIterator::Region(chunks, map.iter())
 	}
 
 	#[inline]
 	pub fn children_mut(&mut self) -> ValueMutIterator {
-		let (map, chunks) = &mut *self.chunks;
+		let (map, chunks, _) = &mut *self.chunks;
 		ValueMutIterator::Region(chunks, map.iter())
 	}
 
@@ -720,6 +983,65 @@ impl NbtRegion {
 	}
 }
 
+/// The owner of a 4 KiB sector of a region file, see [`SectorMap`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Sector {
+	/// The offset and timestamp tables, always the first 2 sectors.
+	Header,
+	Free,
+	/// Allocated to the chunk with this index (`(x << 5) | z`, like [`NbtRegion::insert`]) in the header.
+	Chunk(u16),
+	/// Allocated to more than one chunk, the lowest index is kept.
+	Overlapping(u16),
+}
+
+/// Which 4 KiB sectors of a region file are allocated to which chunk, according to its offset table, see [`NbtRegion::sector_map`].
+pub struct SectorMap {
+	/// Covers every sector of the file, extended with free sectors if a chunk is allocated past the end of it.
+	sectors: Vec<Sector>,
+	overlapping: Vec<(u16, u16)>,
+	/// The indices of chunks allocated over the header or past the end of the file.
+	out_of_bounds: Vec<u16>,
+}
+
+impl SectorMap {
+	#[must_use]
+	pub fn sectors(&self) -> &[Sector] { &self.sectors }
+
+	/// The amount of sectors allocated to chunks, excluding the header.
+	#[must_use]
+	pub fn used(&self) -> usize { self.sectors.iter().filter(|sector| matches!(sector, Sector::Chunk(_) | Sector::Overlapping(_))).count() }
+
+	/// The amount of sectors after the header which belong to no chunk.
+	#[must_use]
+	pub fn free(&self) -> usize { self.sectors.iter().filter(|&&sector| sector == Sector::Free).count() }
+
+	/// The ranges of consecutive free sectors (the trailing free sectors of a file included).
+	#[must_use]
+	pub fn gaps(&self) -> Vec<core::ops::Range<usize>> {
+		let mut gaps = vec![];
+		let mut start = None;
+		for (idx, &sector) in self.sectors.iter().chain(core::iter::once(&Sector::Header)).enumerate() {
+			match (sector == Sector::Free, start) {
+				(true, None) => start = Some(idx),
+				(false, Some(from)) => {
+					gaps.push(from..idx);
+					start = None;
+				}
+				_ => {}
+			}
+		}
+		gaps
+	}
+
+	/// Pairs of chunk indices which share at least one sector, the lower index first.
+	#[must_use]
+	pub fn overlapping(&self) -> &[(u16, u16)] { &self.overlapping }
+
+	#[must_use]
+	pub fn out_of_bounds(&self) -> &[u16] { &self.out_of_bounds }
+}
+
 #[repr(C)]
 #[allow(clippy::module_name_repetitions)]
 pub struct NbtChunk {
@@ -729,6 +1051,10 @@ pub struct NbtChunk {
 	compression: FileFormat,
 	pub x: u8,
 	pub z: u8,
+	/// If the chunk is a hex dump of one which failed to parse, see [`Self::from_hex_dump`].
+	raw: bool,
+	/// If an element of the chunk was changed since it was read, see [`NbtRegion::mark_dirty`].
+	dirty: bool,
 }
 
 impl NbtChunk {
@@ -750,6 +1076,8 @@ impl Clone for NbtChunk {
 				compression: self.compression,
 				x: self.x,
 				z: self.z,
+				raw: self.raw,
+				dirty: self.dirty,
 			}
 		}
 	}
@@ -768,33 +1096,206 @@ impl NbtChunk {
 			inner: Box::new(compound),
 			compression,
 			last_modified,
+			raw: false,
+			dirty: false,
 		}
 	}
+
+	/// A chunk holding the decompressed `bytes` of one which failed to parse, as a string of 16 hex bytes for every line keyed by its offset.
+	///
+	/// It's written as whatever bytes its strings hold, so the chunk can be fixed by hand.
+	#[must_use]
+	pub fn from_hex_dump(bytes: &[u8], pos: (u8, u8), compression: FileFormat, last_modified: u32) -> Self {
+		use std::fmt::Write;
+
+		let mut compound = NbtCompound::new();
+		for (idx, line) in bytes.chunks(16).enumerate() {
+			let mut hex = CompactString::with_capacity(line.len() * 3);
+			for (n, byte) in line.iter().enumerate() {
+				let _ = write!(hex, "{space}{byte:02x}", space = if n == 0 { "" } else { " " });
+			}
+			compound.push(format_compact!("{:08x}", idx * 16), NbtElement::String(NbtString::new(hex)));
+		}
+		Self {
+			raw: true,
+			..Self::from_compound(compound, pos, compression, last_modified)
+		}
+	}
+
+	/// If the chunk is a hex dump of one which failed to parse, see [`Self::from_hex_dump`].
+	#[inline]
+	#[must_use]
+	pub const fn is_raw(&self) -> bool { self.raw }
+
+	/// The timestamp of the chunk in its region file, in seconds since the epoch.
+	#[inline]
+	#[must_use]
+	pub const fn last_modified(&self) -> u32 { self.last_modified }
+
+	/// The bytes the strings of a hex dump hold, `None` if one of them isn't hex, see [`Self::from_hex_dump`].
+	#[must_use]
+	fn hex_bytes(&self) -> Option<Vec<u8>> {
+		let mut bytes = Vec::with_capacity(self.len() * 16);
+		for (_, value) in self.children() {
+			for byte in value.as_string()?.str.as_str().split_ascii_whitespace() {
+				if byte.len() > 2 { return None }
+				bytes.push(u8::from_str_radix(byte, 16).ok()?);
+			}
+		}
+		Some(bytes)
+	}
+
+	/// The compression byte of chunk bytes stored without one (see [`EXTERNAL_ONLY_COMPRESSION`]), from the magic of gzip, zlib and LZ4 and otherwise uncompressed.
+	#[must_use]
+	pub fn detect_compression(bytes: &[u8]) -> u8 {
+		match bytes {
+			[0x1F, 0x8B, ..] => 1,
+			[0x78, ..] => 2,
+			bytes if lz4::is_lz4(bytes) => 4,
+			_ => 3,
+		}
+	}
+
+	/// Decompresses a chunk as it's stored inside a region file, see [`Self::from_raw`].
+	#[must_use]
+	fn decompress(bytes: &[u8], compression: u8) -> Option<(FileFormat, Cow<[u8]>)> {
+		Some(match compression {
+			1 => (FileFormat::Gzip, Cow::Owned(DeflateDecoder::new_with_options(bytes, DeflateOptions::default().set_confirm_checksum(false)).decode_gzip().ok()?)),
+			2 => (FileFormat::Zlib, Cow::Owned(DeflateDecoder::new_with_options(bytes, DeflateOptions::default().set_confirm_checksum(false)).decode_zlib().ok()?)),
+			3 => (FileFormat::Nbt, Cow::Borrowed(bytes)),
+			4 => (FileFormat::Lz4, Cow::Owned(lz4::decompress(bytes)?)),
+			_ => return None,
+		})
+	}
+
+	/// Decompresses and parses a chunk as it's stored inside a region file, `compression` being the region's compression byte (1 = GZip, 2 = Zlib, 3 = Uncompressed, 4 = LZ4).
+	#[must_use]
+	pub fn from_raw(bytes: &[u8], compression: u8, pos: (u8, u8), timestamp: u32, sort: SortAlgorithm) -> Option<Self> { Self::from_raw_counted(bytes, compression, pos, timestamp, sort).map(|(chunk, _)| chunk) }
+
+	/// [`Self::from_raw`], along with the amount of lists of `TAG_End` with a length read as empty lists, see `lenient_lists`.
+	#[must_use]
+	pub fn from_raw_counted(bytes: &[u8], compression: u8, pos: (u8, u8), timestamp: u32, sort: SortAlgorithm) -> Option<(Self, usize)> {
+		let (compression, bytes) = Self::decompress(bytes, compression)?;
+		let (element, _, end_lists) = NbtElement::from_be_file_counted(&bytes, sort).ok()?;
+		Some((Self::from_compound(element.into_compound()?, pos, compression, timestamp), end_lists))
+	}
+
+	/// The world chunk coordinates stored inside the chunk, `xPos` and `zPos` (inside `Level` before 1.18).
+	#[must_use]
+	pub fn world_coordinates(&self) -> Option<(i32, i32)> {
+		fn get<'a>(compound: &'a NbtCompound, key: &str) -> Option<&'a NbtElement> {
+			compound.entries.idx_of(key).and_then(|idx| compound.entries.get_idx(idx)).map(|(_, value)| value)
+		}
+
+		let compound = get(&self.inner, "Level").and_then(NbtElement::as_compound).unwrap_or(&self.inner);
+		Some((get(compound, "xPos")?.as_int()?.value, get(compound, "zPos")?.as_int()?.value))
+	}
+
+	/// The `DataVersion` the chunk was last saved with, see [`crate::data_version::data_version_name`].
+	#[must_use]
+	pub fn data_version(&self) -> Option<i32> { self.inner.entries.idx_of("DataVersion").and_then(|idx| self.inner.get(idx)).and_then(|(_, value)| value.as_int()).map(|int| int.value) }
+
+	/// The generation statuses a chunk's `Status` can be for its `DataVersion`, `None` for versions before `Status` existed.
+	#[must_use]
+	pub fn known_statuses(data_version: i32) -> Option<&'static [&'static str]> {
+		Some(match data_version {
+			// 1.13
+			1519..=1900 => &["empty", "base", "carved", "liquid_carved", "decorated", "lighted", "mobs_spawned", "finalized", "fullchunk", "postprocessed"],
+			// 1.14 - 1.17
+			1901..=2859 => &["empty", "structure_starts", "structure_references", "biomes", "noise", "surface", "carvers", "liquid_carvers", "features", "light", "spawn", "heightmaps", "full"],
+			// 1.18 - 1.19
+			2860..=3462 => &["minecraft:empty", "minecraft:structure_starts", "minecraft:structure_references", "minecraft:biomes", "minecraft:noise", "minecraft:surface", "minecraft:carvers", "minecraft:liquid_carvers", "minecraft:features", "minecraft:light", "minecraft:spawn", "minecraft:heightmaps", "minecraft:full"],
+			// 1.20+
+			3463.. => &["minecraft:empty", "minecraft:structure_starts", "minecraft:structure_references", "minecraft:biomes", "minecraft:noise", "minecraft:surface", "minecraft:carvers", "minecraft:liquid_carvers", "minecraft:features", "minecraft:initialize_light", "minecraft:light", "minecraft:spawn", "minecraft:heightmaps", "minecraft:full"],
+			_ => return None,
+		})
+	}
+
+	/// The known values of the `Status` string at `indices` (relative to this chunk, inside `Level` before 1.18), `None` if `indices` points elsewhere or the chunk's `DataVersion` is unknown.
+	#[must_use]
+	pub fn status_options(&self, indices: &[usize]) -> Option<&'static [&'static str]> {
+		let (key, value) = match *indices {
+			[idx] => self.inner.get(idx)?,
+			[level, idx] => match self.inner.get(level)? {
+				("Level", level) => level.as_compound()?.get(idx)?,
+				_ => return None,
+			},
+			_ => return None,
+		};
+		if key != "Status" || value.id() != NbtString::ID { return None }
+		Self::known_statuses(self.data_version()?)
+	}
+
 	pub fn to_be_bytes(&self, writer: &mut UncheckedBufWriter) {
-		// todo, mcc
+		if self.to_be_bytes_preserving(writer, None).is_some() {
+			log::error!("Chunk {x}, {z} is too big for its region file and was written without its data", x = self.x, z = self.z);
+		}
+	}
+
+	/// The uncompressed NBT of the chunk, written from the compound itself since the id of one which was never inside an [`NbtElement`] (like the ones parsed from SNBT) isn't set.
+	#[must_use]
+	fn to_be_file(&self) -> Vec<u8> {
+		let mut writer = UncheckedBufWriter::new();
+		writer.write(&[NbtCompound::ID, 0x00, 0x00]);
+		self.inner.to_be_bytes(&mut writer);
+		writer.finish()
+	}
+
+	/// [`Self::to_be_bytes`], but writing the bytes of `raw` as they are if the chunk is unchanged since it was read with them, see [`NbtRegion::mark_dirty`].
+	///
+	/// Chunks which don't fit in the [`MAX_CHUNK_SECTORS`] of a region file are written as a stub flagged as external instead, the returned compressed chunk then belongs in its `c.<x>.<z>.mcc` file.
+	#[must_use]
+	pub fn to_be_bytes_preserving(&self, writer: &mut UncheckedBufWriter, raw: Option<&RawChunk>) -> Option<Vec<u8>> {
+		if let Some(raw) = raw && !self.dirty {
+			return Self::write_encoded(writer, Cow::Borrowed(&raw.bytes), raw.compression)
+		}
+		let nbt = match (self.raw.then(|| self.hex_bytes()).flatten(), raw) {
+			(Some(bytes), _) => bytes,
+			(None, Some(raw)) if self.raw => {
+				log::warn!("Chunk {x}, {z} has a line which isn't hex and was written as it was read", x = self.x, z = self.z);
+				return Self::write_encoded(writer, Cow::Borrowed(&raw.bytes), raw.compression)
+			}
+			_ => self.to_be_file(),
+		};
+		let encoded = self.compression.compress(&nbt);
+		drop(nbt);
+		Self::write_encoded(writer, Cow::Owned(encoded), self.compression_byte())
+	}
+
+	/// The compression byte of the chunk inside a region file.
+	#[must_use]
+	fn compression_byte(&self) -> u8 {
+		match self.compression {
+			FileFormat::Gzip => 1_u8,
+			FileFormat::Zlib => 2_u8,
+			FileFormat::Nbt => 3_u8,
+			FileFormat::Lz4 => 4_u8,
+			_ => unsafe { core::hint::unreachable_unchecked() },
+		}
+	}
+
+	/// Writes already compressed chunk bytes into the sectors of a region file, or a stub when they don't fit, see [`Self::to_be_bytes_preserving`].
+	#[must_use]
+	fn write_encoded(writer: &mut UncheckedBufWriter, encoded: Cow<[u8]>, compression: u8) -> Option<Vec<u8>> {
 		unsafe {
-			let encoded = self
-				.compression
-				.encode(&*(self.inner.as_ref() as *const NbtCompound).cast::<NbtElement>());
+			// plus four for the len field, and one for the compression
+			if (encoded.len() + 5).div_ceil(4096) > MAX_CHUNK_SECTORS {
+				writer.write(&1_u32.to_be_bytes());
+				writer.write(&[compression | EXTERNAL_CHUNK_FLAG]);
+				writer.write(&[0; 4096 - 5]);
+				return Some(encoded.into_owned())
+			}
 			let len = encoded.len() + 1;
 			// plus four for the len field writing, and + 1 for the compression
 			let pad_len = (4096 - (len + 4) % 4096) % 4096;
 			writer.write(&(len as u32).to_be_bytes());
-			writer.write(
-				&match self.compression {
-					FileFormat::Gzip => 1_u8,
-					FileFormat::Zlib => 2_u8,
-					FileFormat::Nbt => 3_u8,
-					FileFormat::Lz4 => 4_u8,
-					_ => core::hint::unreachable_unchecked(),
-				}
-				.to_be_bytes(),
-			);
+			writer.write(&[compression]);
 			writer.write(&encoded);
 			drop(encoded);
 			let mut pad = Box::<[u8]>::new_uninit_slice(pad_len);
 			pad.as_mut_ptr().write_bytes(0, pad_len);
 			writer.write(&pad.assume_init());
+			None
 		}
 	}
 
@@ -802,6 +1303,11 @@ impl NbtChunk {
 	#[must_use]
 	pub fn value(&self) -> String { format!("{}, {}", self.x, self.z) }
 
+	/// The heap memory held by the chunk's compound, see [`NbtElement::memory_size`].
+	#[must_use]
+	pub fn memory_size(&self) -> usize { core::mem::size_of::<NbtCompound>() + self.inner.memory_size() }
+
+	#[cfg(feature = "gui")]
 	#[inline]
 	#[allow(clippy::too_many_lines)]
 	pub fn render(&self, builder: &mut VertexBufferBuilder, remaining_scroll: &mut usize, tail: bool, ctx: &mut RenderContext) {
@@ -817,7 +1323,11 @@ impl NbtChunk {
 			}
 
 			ctx.line_number();
-			Self::render_icon(ctx.pos(), BASE_Z, builder);
+			if self.raw {
+				builder.draw_texture_z(ctx.pos(), BASE_Z, UNKNOWN_NBT_UV, (16, 16));
+			} else {
+				Self::render_icon(ctx.pos(), BASE_Z, builder);
+			}
 			if !self.is_empty() {
 				ctx.draw_toggle(ctx.pos() - (16, 0), self.open(), builder);
 			}
@@ -830,6 +1340,13 @@ impl NbtChunk {
 				let _ = write!(builder, ", ");
 				builder.color = TextColor::TreePrimitive.to_raw();
 				let _ = write!(builder, "{}", self.z);
+				if let Some(data_version) = self.data_version() {
+					builder.color = TextColor::Gray.to_raw();
+					match crate::data_version::data_version_name(data_version) {
+						Some(name) => { let _ = write!(builder, " ({name})"); }
+						None => { let _ = write!(builder, " (DataVersion {data_version})"); }
+					}
+				}
 			}
 
 			ctx.y_offset += 16;
@@ -839,7 +1356,7 @@ impl NbtChunk {
 		let x_before = ctx.x_offset - 16;
 
 		if self.open() {
-			ctx.x_offset += 16;
+			ctx.indent();
 
 			{
 				let children_contains_forbidden = 'f: {
@@ -883,13 +1400,13 @@ impl NbtChunk {
 				}
 
 				if *remaining_scroll == 0 {
-					builder.draw_texture(
+					ctx.draw_connection(
 						ctx.pos() - (16, 0),
-						CONNECTION_UV,
 						(
 							16,
 							(idx != self.len() - 1) as usize * 7 + 9,
 						),
+						builder,
 					);
 				}
 				ctx.check_for_key_duplicate(|text, _| self.inner.entries.has(text) && key != text, false);
@@ -908,16 +1425,17 @@ impl NbtChunk {
 			if !tail {
 				let len = (ctx.y_offset - y_before) / 16;
 				for i in 0..len {
-					builder.draw_texture((x_before, y_before + i * 16), CONNECTION_UV, (8, 16));
+					ctx.draw_connection((x_before, y_before + i * 16), (8, 16), builder);
 				}
 			}
 
-			ctx.x_offset -= 16;
+			ctx.dedent();
 		} else {
 			ctx.skip_line_numbers(self.true_height() - 1);
 		}
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render_icon(pos: impl Into<(usize, usize)>, z: ZOffset, builder: &mut VertexBufferBuilder) { builder.draw_texture_z(pos, z, CHUNK_UV, (16, 16)); }
 }
@@ -957,3 +1475,157 @@ impl NbtChunk {
 		self.inner.pretty_fmt(f)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::io::Write;
+
+	use flate2::write::{GzEncoder, ZlibEncoder};
+	use flate2::Compression;
+
+	use crate::elements::compound::NbtCompound;
+	use crate::elements::element::NbtElement;
+	use crate::file::FileFormat;
+	use crate::{lz4, SortAlgorithm};
+
+	use super::{ExternalChunks, NbtChunk, NbtRegion, Sector};
+
+	fn fixture() -> Vec<u8> { NbtElement::from_snbt("{DataVersion: 3465, xPos: 3, zPos: -2, Status: \"minecraft:full\", Heightmaps: {}}").expect("the fixture is valid SNBT").to_be_file() }
+
+	#[test]
+	fn chunks_are_parsed_from_every_compression() {
+		let nbt = fixture();
+		let mut gzip = GzEncoder::new(vec![], Compression::default());
+		gzip.write_all(&nbt).expect("writes into a vec always succeed");
+		let mut zlib = ZlibEncoder::new(vec![], Compression::default());
+		zlib.write_all(&nbt).expect("writes into a vec always succeed");
+		let compressed = [
+			(1, gzip.finish().expect("writes into a vec always succeed"), FileFormat::Gzip),
+			(2, zlib.finish().expect("writes into a vec always succeed"), FileFormat::Zlib),
+			(3, nbt.clone(), FileFormat::Nbt),
+			(4, lz4::compress(&nbt), FileFormat::Lz4),
+		];
+		for (compression, bytes, format) in compressed {
+			let chunk = NbtChunk::from_raw(&bytes, compression, (4, 5), 1234, SortAlgorithm::None).expect("the chunk is valid");
+			assert_eq!(chunk.compression, format);
+			assert_eq!((chunk.x, chunk.z, chunk.last_modified), (4, 5, 1234));
+			assert_eq!(chunk.world_coordinates(), Some((3, -2)));
+			assert_eq!(NbtElement::Compound((*chunk.inner).clone()).to_be_file(), nbt, "compression {compression}");
+		}
+	}
+
+	#[test]
+	fn lz4_chunks_of_the_game_are_parsed() {
+		assert_eq!(NbtChunk::detect_compression(lz4::RAW_CHUNK), 4);
+		let chunk = NbtChunk::from_raw(lz4::RAW_CHUNK, 4, (4, 5), 1234, SortAlgorithm::None).expect("the chunk is valid");
+		assert_eq!(chunk.compression, FileFormat::Lz4);
+		assert_eq!(NbtElement::Compound((*chunk.inner).clone()).to_be_file(), fixture());
+	}
+
+	/// A region file of `sectors` sectors, with the chunks of `allocations` at their `(index, first sector, sector count)`.
+	fn region_file(sectors: usize, allocations: &[(usize, u32, u32)]) -> Vec<u8> {
+		let mut bytes = vec![0; sectors * 4096];
+		for &(idx, start, len) in allocations {
+			bytes[idx * 4..idx * 4 + 4].copy_from_slice(&(start << 8 | len).to_be_bytes());
+		}
+		bytes
+	}
+
+	#[test]
+	fn fragmented_sectors_are_accounted_for() {
+		let bytes = region_file(12, &[(0, 2, 2), (1, 6, 1), (33, 9, 2)]);
+		let map = NbtRegion::sector_map(&bytes).expect("the header is complete");
+		assert_eq!(map.sectors(), [
+			Sector::Header, Sector::Header,
+			Sector::Chunk(0), Sector::Chunk(0),
+			Sector::Free, Sector::Free,
+			Sector::Chunk(1),
+			Sector::Free, Sector::Free,
+			Sector::Chunk(33), Sector::Chunk(33),
+			Sector::Free,
+		]);
+		assert_eq!(map.used(), 5);
+		assert_eq!(map.free(), 5);
+		assert_eq!(map.used() + map.free() + 2, bytes.len() / 4096);
+		assert_eq!(map.gaps(), [4..6, 7..9, 11..12]);
+		assert!(map.overlapping().is_empty());
+		assert!(map.out_of_bounds().is_empty());
+	}
+
+	#[test]
+	fn misallocated_sectors_are_reported() {
+		let bytes = region_file(6, &[(0, 2, 2), (5, 3, 2), (7, 1, 2), (8, 0, 1), (9, 5, 3)]);
+		// chunks starting inside of the header are out of bounds, along with anything else they overlap
+		let map = NbtRegion::sector_map(&bytes).expect("the header is complete");
+		assert_eq!(map.sectors(), [
+			Sector::Header, Sector::Header,
+			Sector::Overlapping(0),
+			Sector::Overlapping(0),
+			Sector::Chunk(5),
+			Sector::Chunk(9), Sector::Chunk(9), Sector::Chunk(9),
+		]);
+		assert_eq!(map.overlapping(), [(0, 5), (0, 7)]);
+		assert_eq!(map.out_of_bounds(), [7, 8, 9]);
+		assert_eq!(map.free(), 0);
+		assert!(map.gaps().is_empty());
+		assert!(NbtRegion::sector_map(&bytes[..8191]).is_none());
+	}
+
+	#[test]
+	fn broken_chunks_are_rejected() {
+		let nbt = fixture();
+		assert!(NbtChunk::from_raw(&nbt, 5, (0, 0), 0, SortAlgorithm::None).is_none());
+		assert!(NbtChunk::from_raw(&nbt, 2, (0, 0), 0, SortAlgorithm::None).is_none());
+		assert!(NbtChunk::from_raw(&nbt[..nbt.len() / 2], 3, (0, 0), 0, SortAlgorithm::None).is_none());
+	}
+
+	#[test]
+	fn files_shorter_than_their_header_are_refused() {
+		let error = |bytes: &[u8]| NbtRegion::from_be_bytes(bytes, SortAlgorithm::None, ExternalChunks::None).err().map(|e| e.to_string());
+		assert_eq!(error(&[]).as_deref(), Some("The region file is empty"));
+		assert_eq!(error(&[0; 100]).as_deref(), Some("The region file is truncated, it's 100 bytes long but its header alone is 8192 bytes"));
+		assert_eq!(error(&[0; 5000]).as_deref(), Some("The region file is truncated, it's 5000 bytes long but its header alone is 8192 bytes"));
+		assert!(NbtRegion::truncation(&[0; 5000]).is_none());
+		assert!(NbtRegion::from_be_bytes(&[0; 8192], SortAlgorithm::None, ExternalChunks::None).is_ok_and(|region| region.is_empty()));
+	}
+
+	#[test]
+	fn chunks_past_the_end_of_the_file_are_unreadable() {
+		let mut zlib = ZlibEncoder::new(vec![], Compression::default());
+		zlib.write_all(&fixture()).expect("writes into a vec always succeed");
+		let compressed = zlib.finish().expect("writes into a vec always succeed");
+		// a readable chunk in sector 2, one cut off after sector 3, and one wholly past the end of the 4 sector file
+		let mut bytes = region_file(4, &[(0, 2, 1), (1, 3, 2), (2, 9, 1)]);
+		bytes[8192..8192 + 4].copy_from_slice(&(compressed.len() as u32 + 1).to_be_bytes());
+		bytes[8192 + 4] = 2;
+		bytes[8192 + 5..8192 + 5 + compressed.len()].copy_from_slice(&compressed);
+		bytes[12288..12288 + 4].copy_from_slice(&6000_u32.to_be_bytes());
+		bytes[12288 + 4] = 2;
+
+		let region = NbtRegion::from_be_bytes(&bytes, SortAlgorithm::None, ExternalChunks::None).expect("truncated regions still open");
+		assert_eq!(region.len(), 1);
+		assert_eq!(region.unreadable().collect::<Vec<_>>(), [(0, 1), (0, 2)]);
+		assert_eq!(NbtRegion::truncation(&bytes).as_deref(), Some("The region file is truncated, it's 4 sectors long but 2 chunks are allocated sectors past its end and could only be read in part, if at all: 0|1, 0|2"));
+		assert!(NbtRegion::truncation(&region_file(4, &[(0, 2, 1), (1, 3, 1)])).is_none());
+	}
+
+	#[test]
+	fn inserting_into_a_full_region_is_refused() {
+		let chunk = |x: u8, z: u8| NbtElement::Chunk(NbtChunk::from_compound(NbtCompound::new(), (x, z), FileFormat::Zlib, 0));
+		let mut region = NbtRegion::new();
+		for pos in 0..32 * 32 {
+			assert!(region.insert(region.len(), chunk((pos >> 5) as u8, pos as u8 & 31)).is_ok(), "slot {pos} is free");
+		}
+		assert_eq!(region.len(), 32 * 32);
+
+		let mut compound = NbtCompound::new();
+		compound.push("refused".into(), NbtElement::from_snbt("1b").expect("the fixture is valid SNBT"));
+		let refused = region.insert(region.len(), NbtElement::Chunk(NbtChunk::from_compound(compound, (7, 9), FileFormat::Zlib, 0))).expect_err("every slot is taken");
+		let refused = refused.as_chunk().expect("the chunk is given back");
+		assert_eq!((refused.x, refused.z), (7, 9));
+		assert_eq!(refused.get(0).map(|(key, _)| key), Some("refused"));
+		assert_eq!(region.len(), 32 * 32);
+		let taken = region.get(7 * 32 + 9).and_then(NbtElement::as_chunk).expect("the slot keeps its chunk");
+		assert_eq!((taken.x, taken.z), (7, 9));
+	}
+}
@@ -1,6 +1,8 @@
 use std::alloc::{alloc, Layout};
 use std::cmp::Ordering;
-use std::fmt::{Display, Formatter, Write};
+use std::fmt::{Display, Formatter};
+#[cfg(feature = "gui")]
+use std::fmt::Write;
 use std::hash::Hasher;
 use std::intrinsics::likely;
 use std::ops::Deref;
@@ -11,17 +13,34 @@ use compact_str::{format_compact, CompactString, ToCompactString};
 use fxhash::FxHasher;
 use hashbrown::raw::RawTable;
 
-use crate::assets::{JUST_OVERLAPPING_BASE_TEXT_Z, BASE_Z, COMPOUND_ROOT_UV, COMPOUND_UV, CONNECTION_UV, HEADER_SIZE, LINE_NUMBER_CONNECTOR_Z, LINE_NUMBER_SEPARATOR_UV, ZOffset};
+#[cfg(feature = "gui")]
+use crate::assets::{JUST_OVERLAPPING_BASE_TEXT_Z, BASE_Z, COMPOUND_ROOT_UV, COMPOUND_UV, HEADER_SIZE, LINE_NUMBER_CONNECTOR_Z, LINE_NUMBER_SEPARATOR_UV, ZOffset};
 use crate::be_decoder::BigEndianDecoder;
+#[cfg(feature = "gui")]
 use crate::elements::chunk::NbtChunk;
 use crate::elements::element::NbtElement;
 use crate::encoder::UncheckedBufWriter;
-use crate::{DropFn, OptionExt, RenderContext, SortAlgorithm, StrExt, VertexBufferBuilder};
+use crate::{DropFn, OptionExt, SortAlgorithm, StrExt};
+#[cfg(feature = "gui")]
+use crate::{RenderContext, VertexBufferBuilder};
+#[cfg(feature = "gui")]
 use crate::color::TextColor;
 use crate::formatter::PrettyFormatter;
+#[cfg(feature = "gui")]
 use crate::bookmark::{Bookmark, BookmarkSlice};
 use crate::le_decoder::LittleEndianDecoder;
 
+/// How [`NbtCompound::merge`] settles the keys both compounds have.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum MergeStrategy {
+	/// The entry already present is kept.
+	KeepSelf,
+	/// The entry merged in replaces it.
+	KeepOther,
+	/// Compounds found in both are merged the same way and lists of the same type are appended to, anything else is replaced like [`Self::KeepOther`].
+	RecurseCompounds,
+}
+
 #[allow(clippy::module_name_repetitions)]
 #[repr(C)]
 pub struct NbtCompound {
@@ -65,7 +84,7 @@ impl NbtCompound {
 			let (key, s2) = s.snbt_string_read()?;
 			s = s2.trim_start().strip_prefix(':')?.trim_start();
 			let (s2, value) = NbtElement::from_str0(s, sort)?;
-			compound.insert_replacing(key, value);
+			compound.push(key, value);
 			s = s2.trim_start();
 			if let Some(s2) = s.strip_prefix(',') {
 				s = s2.trim_start();
@@ -88,7 +107,7 @@ impl NbtCompound {
 				decoder.assert_len(2)?;
 				let key = decoder.string()?;
 				let value = NbtElement::from_be_bytes(current_element, decoder)?;
-				compound.insert_replacing(key, value);
+				compound.push(key, value);
 				match decoder.assert_len(1) {
 					Some(()) => {}
 					None => break, // wow mojang, saving one byte, so cool of you
@@ -120,7 +139,7 @@ impl NbtCompound {
 				decoder.assert_len(2)?;
 				let key = decoder.string()?;
 				let value = NbtElement::from_le_bytes(current_element, decoder)?;
-				compound.insert_replacing(key, value);
+				compound.push(key, value);
 				match decoder.assert_len(1) {
 					Some(()) => {}
 					None => break, // wow mojang, saving one byte, so cool of you
@@ -175,14 +194,50 @@ impl NbtCompound {
 		}
 	}
 
-	#[inline] // has some unchecked stuff
-	pub fn insert_replacing(&mut self, str: CompactString, element: NbtElement) {
-		self.true_height += element.true_height() as u32;
-		if let Some(element) = self.entries.insert(str, element) {
-			self.true_height -= element.true_height() as u32;
-		} else {
-			self.height += 1;
+	/// A free key for a copy of the entry `key`, suffixed with `" copy"` and then numbered once that's taken too.
+	#[must_use]
+	pub fn copy_key(&self, key: &str) -> CompactString {
+		let copy = format_compact!("{key} copy");
+		if !self.entries.has(&copy) { return copy }
+		(2_usize..).map(|n| format_compact!("{copy} {n}")).find(|key| !self.entries.has(key)).unwrap_or(copy)
+	}
+
+	/// Merges the entries of `other` into this compound in their order, new keys are appended and the ones already present are settled by `strategy` against the first entry with that key.
+	///
+	/// Every entry of a key found more than once in `other` is appended if this compound didn't have it, the same way duplicate keys are kept when read (see [`Self::push`]), otherwise each of them is settled in turn.
+	pub fn merge(&mut self, mut other: NbtCompound, strategy: MergeStrategy) {
+		let len = self.len();
+		for Entry { key, value: mut other_value, .. } in core::mem::take(&mut other.entries.entries) {
+			let Some(idx) = self.entries.idx_of(&key).filter(|&idx| idx < len) else {
+				self.increment(other_value.height(), other_value.true_height());
+				self.entries.push(key, other_value);
+				continue
+			};
+			if strategy == MergeStrategy::KeepSelf { continue }
+			let Some((_, value)) = self.entries.get_idx_mut(idx) else { continue };
+			let (height, true_height) = (value.height(), value.true_height());
+			if strategy == MergeStrategy::RecurseCompounds && let Some(compound) = value.as_compound_mut() && let Some(other) = other_value.as_compound_mut() {
+				compound.merge(core::mem::take(other), strategy);
+			} else if strategy == MergeStrategy::RecurseCompounds && let Some(list) = value.as_list_mut() && let Some(other) = other_value.as_list_mut() && (list.is_empty() || other.is_empty() || list.element == other.element) {
+				for element in core::mem::take(&mut *other.elements) {
+					let _ = list.insert(list.len(), element);
+				}
+			} else {
+				*value = other_value;
+			}
+			let (new_height, new_true_height) = (value.height(), value.true_height());
+			self.decrement(height, true_height);
+			self.increment(new_height, new_true_height);
 		}
+		self.recache_depth();
+	}
+
+	/// Appends an entry as it was read, an earlier entry with the same key is kept alongside it rather than replaced so files with duplicate keys are written back the way they were read.
+	#[inline]
+	pub fn push(&mut self, str: CompactString, element: NbtElement) {
+		self.height += 1;
+		self.true_height += element.true_height() as u32;
+		self.entries.push(str, element);
 	}
 
 	#[inline]
@@ -245,6 +300,16 @@ impl NbtCompound {
 	#[must_use]
 	pub fn is_empty(&self) -> bool { self.entries.is_empty() }
 
+	/// The heap memory held by the compound's map, its keys and everything inside of it, see [`NbtElement::memory_size`].
+	#[must_use]
+	pub fn memory_size(&self) -> usize {
+		// every bucket is an index and a control byte
+		let indices = self.entries.indices.buckets() * (core::mem::size_of::<usize>() + 1);
+		let entries = self.entries.entries.capacity() * core::mem::size_of::<Entry>();
+		let children = self.entries.entries.iter().map(|entry| if entry.key.is_heap_allocated() { entry.key.capacity() } else { 0 } + entry.value.memory_size()).sum::<usize>();
+		core::mem::size_of::<CompoundMap>() + indices + entries + children
+	}
+
 	#[inline]
 	#[must_use]
 	pub fn get(&self, idx: usize) -> Option<(&str, &NbtElement)> { self.entries.get_idx(idx) }
@@ -263,6 +328,7 @@ impl NbtCompound {
 		)
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	#[allow(clippy::too_many_lines)]
 	pub fn render_root(&self, builder: &mut VertexBufferBuilder, str: &str, ctx: &mut RenderContext) {
@@ -283,7 +349,7 @@ impl NbtCompound {
 				(2, 2),
 			);
 			builder.draw_texture(ctx.pos(), COMPOUND_ROOT_UV, (16, 16));
-			builder.draw_texture(ctx.pos() - (16, 0), CONNECTION_UV, (16, 9));
+			ctx.draw_connection(ctx.pos() - (16, 0), (16, 9), builder);
 			if !self.is_empty() {
 				ctx.draw_toggle(ctx.pos() - (16, 0), self.open, builder);
 			}
@@ -302,7 +368,7 @@ impl NbtCompound {
 			ctx.y_offset += 16;
 		}
 
-		ctx.x_offset += 16;
+		ctx.indent();
 
 		if self.open {
 			{
@@ -350,13 +416,13 @@ impl NbtCompound {
 				ctx.draw_held_entry_bar(ctx.pos(), builder, |x, y| pos == (x, y), |id| id != NbtChunk::ID);
 
 				if remaining_scroll == 0 {
-					builder.draw_texture(
+					ctx.draw_connection(
 						ctx.pos() - (16, 0),
-						CONNECTION_UV,
 						(
 							16,
 							(idx != self.len() - 1) as usize * 7 + 9,
 						),
+						builder,
 					);
 				}
 				ctx.check_for_key_duplicate(|text, _| self.entries.has(text) && name != text, false);
@@ -465,6 +531,7 @@ impl NbtCompound {
 // }
 
 impl NbtCompound {
+	#[cfg(feature = "gui")]
 	#[inline]
 	#[allow(clippy::too_many_lines)]
 	pub fn render(&self, builder: &mut VertexBufferBuilder, name: Option<&str>, remaining_scroll: &mut usize, tail: bool, ctx: &mut RenderContext) {
@@ -504,7 +571,7 @@ impl NbtCompound {
 		let x_before = ctx.x_offset - 16;
 
 		if self.open {
-			ctx.x_offset += 16;
+			ctx.indent();
 
 			{
 				let children_contains_forbidden = 'f: {
@@ -551,13 +618,13 @@ impl NbtCompound {
 				ctx.draw_held_entry_bar(ctx.pos(), builder, |x, y| pos == (x, y), |id| id != NbtChunk::ID);
 
 				if *remaining_scroll == 0 {
-					builder.draw_texture(
+					ctx.draw_connection(
 						ctx.pos() - (16, 0),
-						CONNECTION_UV,
 						(
 							16,
 							(idx != self.len() - 1) as usize * 7 + 9,
 						),
+						builder,
 					);
 				}
 				ctx.check_for_key_duplicate(|text, _| self.entries.has(text) && key != text, false);
@@ -579,11 +646,11 @@ impl NbtCompound {
 			if !tail {
 				let len = (ctx.y_offset - y_before) / 16;
 				for i in 0..len {
-					builder.draw_texture((x_before, y_before + i * 16), CONNECTION_UV, (8, 16));
+					ctx.draw_connection((x_before, y_before + i * 16), (8, 16), builder);
 				}
 			}
 
-			ctx.x_offset -= 16;
+			ctx.dedent();
 		} else {
 			ctx.skip_line_numbers(self.true_height() - 1);
 		}
@@ -751,6 +818,7 @@ impl NbtCompound {
 		}
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render_icon(pos: impl Into<(usize, usize)>, z: ZOffset, builder: &mut VertexBufferBuilder) { builder.draw_texture_z(pos, z, COMPOUND_UV, (16, 16)); }
 }
@@ -779,22 +847,23 @@ impl Clone for CompoundMap {
 	fn clone(&self) -> Self {
 		pub unsafe fn clone_entries(entries: &[Entry]) -> Vec<Entry> {
 			let len = entries.len();
+			if len == 0 { return Vec::new() }
 			let ptr = alloc(Layout::array::<Entry>(len).unwrap_unchecked()).cast::<Entry>();
 			for n in 0..len {
-				ptr.write(entries.get_unchecked(n).clone());
+				ptr.add(n).write(entries.get_unchecked(n).clone());
 			}
 			Vec::from_raw_parts(ptr, len, len)
 		}
 
 		unsafe {
-			let mut table = RawTable::try_with_capacity(self.indices.len()).unwrap_unchecked();
-			for (idx, bucket) in self.indices.iter().enumerate() {
-				let hash = hash!(self.entries.get_unchecked(idx).key.as_str());
-				let _ = table.insert_in_slot(hash, core::mem::transmute(idx), *bucket.as_ref());
+			let entries = clone_entries(&self.entries);
+			let mut table = RawTable::try_with_capacity(entries.len()).unwrap_unchecked();
+			for (idx, entry) in entries.iter().enumerate() {
+				table.insert_no_grow(entry.hash, idx);
 			}
 			Self {
 				indices: table,
-				entries: clone_entries(&self.entries),
+				entries,
 			}
 		}
 	}
@@ -817,20 +886,37 @@ impl Default for CompoundMap {
 }
 
 impl CompoundMap {
+	/// The index of the first entry with this key, later ones with the same key (see [`Self::push`]) are only reachable by index.
 	#[must_use]
 	pub fn idx_of(&self, key: &str) -> Option<usize> {
-		self.indices
-			.get(hash!(key), |&idx| unsafe {
-				self.entries.get_unchecked(idx).key.as_str() == key
-			})
-			.copied()
+		unsafe {
+			self.indices
+				.iter_hash(hash!(key))
+				.map(|bucket| *bucket.as_ref())
+				.filter(|&idx| self.entries.get_unchecked(idx).key.as_str() == key)
+				.min()
+		}
 	}
 
 	#[must_use]
 	pub fn has(&self, key: &str) -> bool { self.idx_of(key.as_ref()).is_some() }
 
+	/// The amount of entries keyed like an entry before them, which [`Self::push`] keeps alongside it.
+	#[must_use]
+	pub fn duplicates(&self) -> usize { self.entries.iter().enumerate().filter(|(idx, entry)| self.idx_of(&entry.key) != Some(*idx)).count() }
+
 	pub fn insert(&mut self, key: CompactString, element: NbtElement) -> Option<NbtElement> { self.insert_full(key, element).1 }
 
+	/// Appends an entry even if the key is already there, every entry is indexed so the ones sharing a key are told apart by their index alone. Returns if the key was already there.
+	pub fn push(&mut self, key: CompactString, element: NbtElement) -> bool {
+		let duplicate = self.has(&key);
+		let hash = hash!(key.as_str());
+		let len = self.entries.len();
+		self.entries.push(Entry { key, value: element, hash });
+		self.indices.insert(hash, len, |&idx| unsafe { self.entries.get_unchecked(idx).hash });
+		duplicate
+	}
+
 	#[must_use]
 	pub fn len(&self) -> usize { self.entries.len() }
 
@@ -946,30 +1032,27 @@ impl CompoundMap {
 	///
 	/// * idx must be valid
 	pub unsafe fn update_key_idx_unchecked(&mut self, idx: usize, key: CompactString) -> CompactString {
-		let old_key = self.entries.get_unchecked(idx).key.as_str();
-		let hash = hash!(old_key);
+		let hash = self.entries.get_unchecked(idx).hash;
 		let _ = self
 			.indices
-			.remove_entry(hash, |&idx| {
-				self.entries.get_unchecked(idx).key.as_str() == old_key
-			})
+			.remove_entry(hash, |&x| x == idx)
 			.unwrap_unchecked();
-		let old_key = core::mem::replace(&mut self.entries.get_unchecked_mut(idx).key, key);
+		let entry = self.entries.get_unchecked_mut(idx);
+		let old_key = core::mem::replace(&mut entry.key, key);
+		entry.hash = hash!(entry.key.as_str());
 		self.indices.insert(
-			hash!(self.entries.get_unchecked(idx).key.as_str()),
+			entry.hash,
 			idx,
-			|&idx| hash!(self.entries.get_unchecked(idx).key.as_str()),
+			|&idx| self.entries.get_unchecked(idx).hash,
 		);
 		old_key
 	}
 
 	pub fn shift_remove_idx(&mut self, idx: usize) -> Option<(CompactString, NbtElement)> {
-		if idx > self.entries.len() { return None }
+		if idx >= self.entries.len() { return None }
 		unsafe {
-			let Entry { key, hash, .. } = &self.entries.get_unchecked(idx);
-			let _ = self.indices.remove_entry(*hash, |&idx| {
-				self.entries.get_unchecked(idx).key.as_str() == key.as_str()
-			});
+			let hash = self.entries.get_unchecked(idx).hash;
+			let _ = self.indices.remove_entry(hash, |&x| x == idx);
 			for bucket in self.indices.iter() {
 				if *bucket.as_ref() > idx {
 					*bucket.as_mut() -= 1;
@@ -982,19 +1065,17 @@ impl CompoundMap {
 	}
 
 	pub fn swap_remove_idx(&mut self, idx: usize) -> Option<(CompactString, NbtElement)> {
-		if idx > self.entries.len() { return None }
+		if idx >= self.entries.len() { return None }
 		let Entry { key, value, hash } = self.entries.swap_remove(idx);
 		unsafe {
-			let tail = self
-				.indices
-				.remove_entry(hash, |&idx| idx + 1 == self.entries.len())
-				.unwrap_unchecked();
-			*self
-				.indices
-				.get_mut(hash, |&idx| {
-					self.entries.get_unchecked(idx).key.as_str() == key.as_str()
-				})
-				.unwrap_unchecked() = tail;
+			let _ = self.indices.remove_entry(hash, |&x| x == idx).unwrap_unchecked();
+			let tail = self.entries.len();
+			if idx < tail {
+				*self
+					.indices
+					.get_mut(self.entries.get_unchecked(idx).hash, |&x| x == tail)
+					.unwrap_unchecked() = idx;
+			}
 		}
 		Some((key, value))
 	}
@@ -1030,6 +1111,7 @@ impl CompoundMap {
 		Some((entry.key.as_ref(), &mut entry.value))
 	}
 
+	#[cfg(feature = "gui")]
 	pub fn sort_by<F: FnMut((&str, &NbtElement), (&str, &NbtElement)) -> Ordering>(&mut self, mut f: F, line_number: usize, true_line_number: usize, true_height: usize, open: bool, bookmarks: &mut BookmarkSlice) -> Box<[usize]> {
 		let hashes = self.entries.iter().map(|entry| entry.hash).collect::<Vec<_>>();
 		let true_line_numbers = {
@@ -1137,3 +1219,83 @@ impl<'a> DoubleEndedIterator for CompoundMapIterMut<'a> {
 impl<'a> ExactSizeIterator for CompoundMapIterMut<'a> {
 	fn len(&self) -> usize { self.0.len() }
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::elements::element::NbtElement;
+	use crate::SortAlgorithm;
+
+	use super::MergeStrategy;
+
+	fn parse(snbt: &str) -> NbtElement { NbtElement::from_snbt(snbt).expect("fixtures are valid SNBT") }
+
+	/// Merges `other` into `this` with `strategy` and checks the result against `expected`, down to the heights kept alongside the entries.
+	fn assert_merges_into(this: &str, other: &str, strategy: MergeStrategy, expected: &str) {
+		let mut compound = parse(this).into_compound().expect("the fixture is a compound");
+		compound.merge(parse(other).into_compound().expect("the fixture is a compound"), strategy);
+		let expected = parse(expected).into_compound().expect("the fixture is a compound");
+		assert_eq!((compound.height, compound.true_height), (expected.height, expected.true_height));
+		assert_eq!(NbtElement::Compound(compound).to_be_file(), NbtElement::Compound(expected).to_be_file());
+	}
+
+	#[test]
+	fn duplicate_keys_are_written_back_as_they_were_read() {
+		let file = [
+			&[10, 0, 0][..],
+			&[1, 0, 1, b'a', 1],
+			&[8, 0, 1, b'b', 0, 2, b'h', b'i'],
+			&[1, 0, 1, b'a', 2],
+			&[0],
+		].concat();
+		let nbt = NbtElement::from_be_file(&file, SortAlgorithm::None).expect("the file is valid NBT");
+		let compound = nbt.as_compound().expect("the root is a compound");
+		assert_eq!(compound.len(), 3);
+		assert_eq!(compound.entries.duplicates(), 1);
+		assert_eq!(nbt.to_be_file(), file);
+	}
+
+	#[test]
+	fn merging_keeps_duplicates_of_new_keys() {
+		let mut compound = parse("{b: 0b}").into_compound().expect("the fixture is a compound");
+		compound.merge(parse("{a: 1b, a: 2b}").into_compound().expect("the fixture is a compound"), MergeStrategy::KeepOther);
+		assert_eq!(NbtElement::Compound(compound).to_be_file(), parse("{b: 0b, a: 1b, a: 2b}").to_be_file());
+
+		let mut compound = parse("{a: 0b}").into_compound().expect("the fixture is a compound");
+		compound.merge(parse("{a: 1b, a: 2b}").into_compound().expect("the fixture is a compound"), MergeStrategy::KeepOther);
+		assert_eq!(NbtElement::Compound(compound).to_be_file(), parse("{a: 2b}").to_be_file());
+	}
+
+	#[test]
+	fn duplicate_keys_are_settled_in_turn() {
+		assert_merges_into("{a: 0b}", "{a: 1b, a: 2b, b: 3b}", MergeStrategy::KeepSelf, "{a: 0b, b: 3b}");
+		assert_merges_into("{a: 0b}", "{a: 1b, a: 2b, b: 3b}", MergeStrategy::KeepOther, "{a: 2b, b: 3b}");
+		assert_merges_into("{a: 0b}", "{a: 1b, a: 2b, b: 3b}", MergeStrategy::RecurseCompounds, "{a: 2b, b: 3b}");
+		assert_merges_into("{a: {x: 0b}}", "{a: {y: 1b}, a: {z: 2b}}", MergeStrategy::RecurseCompounds, "{a: {x: 0b, y: 1b, z: 2b}}");
+	}
+
+	#[test]
+	fn nested_compounds_are_only_merged_when_recursing() {
+		let this = "{a: {b: {c: 0b, d: 1b}, e: 2b}, f: 3b}";
+		let other = "{a: {b: {c: 4b, g: 5b}, h: [6b]}, f: {i: 7b}}";
+		assert_merges_into(this, other, MergeStrategy::KeepSelf, this);
+		assert_merges_into(this, other, MergeStrategy::KeepOther, "{a: {b: {c: 4b, g: 5b}, h: [6b]}, f: {i: 7b}}");
+		// compounds replace anything else they meet, like `f`
+		assert_merges_into(this, other, MergeStrategy::RecurseCompounds, "{a: {b: {c: 4b, d: 1b, g: 5b}, e: 2b, h: [6b]}, f: {i: 7b}}");
+	}
+
+	#[test]
+	fn lists_are_appended_to_when_of_the_same_type() {
+		assert_merges_into("{a: [0b, 1b]}", "{a: [2b]}", MergeStrategy::RecurseCompounds, "{a: [0b, 1b, 2b]}");
+		assert_merges_into("{a: [{b: 0b}]}", "{a: [{c: 1b}, {d: 2b}]}", MergeStrategy::RecurseCompounds, "{a: [{b: 0b}, {c: 1b}, {d: 2b}]}");
+		assert_merges_into("{a: []}", "{a: [0s]}", MergeStrategy::RecurseCompounds, "{a: [0s]}");
+		assert_merges_into("{a: [0s]}", "{a: []}", MergeStrategy::RecurseCompounds, "{a: [0s]}");
+		assert_merges_into("{a: [0b, 1b]}", "{a: [2b]}", MergeStrategy::KeepOther, "{a: [2b]}");
+		assert_merges_into("{a: [0b, 1b]}", "{a: [2b]}", MergeStrategy::KeepSelf, "{a: [0b, 1b]}");
+	}
+
+	#[test]
+	fn lists_of_another_type_are_replaced() {
+		assert_merges_into("{a: [0b, 1b]}", "{a: [2s]}", MergeStrategy::RecurseCompounds, "{a: [2s]}");
+		assert_merges_into("{a: [0b, 1b]}", "{a: [B; 2b]}", MergeStrategy::RecurseCompounds, "{a: [B; 2b]}");
+	}
+}
@@ -7,23 +7,31 @@ use std::ops::{Deref, Index, IndexMut};
 use std::thread::Scope;
 use std::{fmt, fmt::Write};
 
+use anyhow::{anyhow, Context};
 use compact_str::{format_compact, CompactString, ToCompactString};
 use hashbrown::raw::RawTable;
 use polonius_the_crab::{polonius, polonius_return};
 
-use crate::assets::{BASE_Z, BYTE_ARRAY_UV, BYTE_UV, CONNECTION_UV, DOUBLE_UV, FLOAT_UV, INT_ARRAY_UV, INT_UV, LONG_ARRAY_UV, LONG_UV, SHORT_UV, ZOffset};
+#[cfg(feature = "gui")]
+use crate::assets::{BASE_Z, BYTE_ARRAY_UV, BYTE_UV, DOUBLE_UV, FLOAT_UV, INT_ARRAY_UV, INT_UV, LONG_ARRAY_UV, LONG_UV, SHORT_UV, ZOffset};
 use crate::be_decoder::BigEndianDecoder;
-use crate::elements::chunk::{NbtChunk, NbtRegion};
+use crate::elements::chunk::{ExternalChunks, NbtChunk, NbtRegion};
 use crate::elements::compound::{CompoundMap, CompoundMapIter, Entry, NbtCompound};
+#[cfg(feature = "gui")]
 use crate::element_action::ElementAction;
 use crate::elements::list::{NbtList, ValueIterator, ValueMutIterator};
 use crate::elements::string::NbtString;
 use crate::encoder::UncheckedBufWriter;
-use crate::{panic_unchecked, since_epoch, SortAlgorithm, array, primitive, DropFn, RenderContext, StrExt, VertexBufferBuilder, TextColor, assets::JUST_OVERLAPPING_BASE_TEXT_Z};
+use crate::{config, panic_unchecked, since_epoch, SortAlgorithm, array, primitive, DropFn, StrExt, TextColor};
+#[cfg(feature = "gui")]
+use crate::{RenderContext, VertexBufferBuilder, assets::JUST_OVERLAPPING_BASE_TEXT_Z};
 use crate::elements::null::NbtNull;
+use crate::elements::primitive::Operation;
 use crate::formatter::PrettyFormatter;
+use crate::json::{self, JsonValue, TypeHints};
 use crate::le_decoder::LittleEndianDecoder;
-use crate::tab::FileFormat;
+use crate::schema::{Schema, SchemaError};
+use crate::file::FileFormat;
 
 primitive!(BYTE_UV, { Some('b') }, NbtByte, i8, 1);
 primitive!(SHORT_UV, { Some('s') }, NbtShort, i16, 2);
@@ -87,6 +95,28 @@ impl NbtElement {
 			}
 		}
 	}
+
+	/// Exact equality, unlike [`Self::matches`] this requires the same entries in the same order and compares floats by their bits (so `NaN`s are equal).
+	pub fn deep_eq(&self, other: &Self) -> bool {
+		fn compound_eq(a: &NbtCompound, b: &NbtCompound) -> bool {
+			a.len() == b.len() && a.children().zip(b.children()).all(|((a_key, a), (b_key, b))| a_key == b_key && a.deep_eq(b))
+		}
+
+		if self.id() != other.id() { return false }
+
+		unsafe {
+			match self.id() {
+				NbtFloat::ID => self.float.value.to_bits() == other.float.value.to_bits(),
+				NbtDouble::ID => self.double.value.to_bits() == other.double.value.to_bits(),
+				NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID | NbtList::ID => self.list.len() == other.list.len() && self.list.children().zip(other.list.children()).all(|(a, b)| a.deep_eq(b)),
+				NbtCompound::ID => compound_eq(&self.compound, &other.compound),
+				NbtChunk::ID => compound_eq(&self.chunk, &other.chunk),
+				// chunks are compared by their slot rather than their (user-sortable) order
+				NbtRegion::ID => self.region.chunks.1.iter().zip(other.region.chunks.1.iter()).all(|(a, b)| a.deep_eq(b)),
+				_ => self.matches(other),
+			}
+		}
+	}
 }
 
 impl Clone for NbtElement {
@@ -139,7 +169,8 @@ impl Clone for NbtElement {
 
 #[allow(non_snake_case)]
 impl NbtElement {
-	pub const NULL: NbtElement = unsafe { core::mem::zeroed() };
+	/// The element of empty slots, like the ones of a region without a chunk, see [`Self::is_null`].
+	pub const NULL: NbtElement = NbtElement { id: NbtElementId { _pad: [MaybeUninit::new(0); 23], id: NbtNull::ID } };
 	pub const NULL_REF: &'static NbtElement = &Self::NULL;
 
 	#[inline]
@@ -317,6 +348,15 @@ impl NbtElement {
 		Some((prefix, element))
 	}
 
+	/// Parses Mojang's stringified NBT, like the output of `/data get`, which has to make up the whole input, compounds keep the order they were written in.
+	#[must_use]
+	pub fn from_snbt(input: &str) -> Option<Self> {
+		match Self::from_str(input, SortAlgorithm::None)? {
+			(None, element) => Some(element),
+			(Some(_), _) => None,
+		}
+	}
+
 	#[allow(clippy::too_many_lines)]
 	pub(in crate::elements) fn from_str0(mut s: &str, sort: SortAlgorithm) -> Option<(&str, Self)> {
 		if let Some(s2) = s.strip_prefix("false") { return Some((s2, Self::Byte(NbtByte { value: 0 }))) }
@@ -479,6 +519,20 @@ impl NbtElement {
 
 		NbtString::from_str0(s).map(|(s, x)| (s, Self::String(x)))
 	}
+	/// The least amount of bytes the payload of an element of type `element` takes up in either endianness, used to bound the lengths of lists.
+	#[must_use]
+	pub const fn min_payload_len(element: u8) -> usize {
+		match element {
+			NbtNull::ID => 0,
+			NbtShort::ID | NbtString::ID => 2,
+			NbtInt::ID | NbtFloat::ID | NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID => 4,
+			NbtLong::ID | NbtDouble::ID => 8,
+			NbtList::ID => 5,
+			// bytes, compounds (their `TAG_End`) and invalid types, which fail to parse anyway
+			_ => 1,
+		}
+	}
+
 	#[inline(never)]
 	pub fn from_be_bytes(element: u8, decoder: &mut BigEndianDecoder) -> Option<Self> {
 		Some(match element {
@@ -571,7 +625,7 @@ impl NbtElement {
 
 	#[inline]
 	#[must_use]
-	pub const fn is_null(&self) -> bool { self.id() == 0 }
+	pub const fn is_null(&self) -> bool { self.id() == NbtNull::ID }
 
 	#[inline]
 	#[must_use]
@@ -599,43 +653,105 @@ impl NbtElement {
 		}
 	}
 
+	/// [`Self::from_id`], but starting with the value configured for new elements of its type, see [`config::get_default`].
+	#[must_use]
+	pub fn from_id_configured(id: u8) -> Self {
+		let mut element = Self::from_id(id);
+		if let Some(value) = config::get_default(id, None) {
+			let _ = element.set_value(value);
+		}
+		element
+	}
+
 	#[inline]
 	#[must_use]
-	pub fn from_be_file(bytes: &[u8], sort: SortAlgorithm) -> Option<Self> {
+	pub fn from_be_file(bytes: &[u8], sort: SortAlgorithm) -> Option<Self> { Self::from_be_file_named(bytes, sort).map(|(nbt, _)| nbt) }
+
+	/// [`Self::from_be_file`], along with the name of the root, empty for the nameless root of the >= 1.20.2 network format.
+	#[must_use]
+	pub fn from_be_file_named(bytes: &[u8], sort: SortAlgorithm) -> Option<(Self, CompactString)> { Self::from_be_file_counted(bytes, sort).ok().map(|(nbt, name, _)| (nbt, name)) }
+
+	/// [`Self::from_be_file_named`], along with the amount of lists of `TAG_End` with a length read as empty lists, see `lenient_lists`.
+	///
+	/// # Errors
+	///
+	/// * `bytes` aren't big endian NBT, with an [`EndListError`](crate::elements::list::EndListError) when a list of `TAG_End` with a length is what stopped them from being read
+	pub fn from_be_file_counted(bytes: &[u8], sort: SortAlgorithm) -> anyhow::Result<(Self, CompactString, usize)> {
 		let mut decoder = BigEndianDecoder::new(bytes, sort);
+		let mut named = BigEndianDecoder::new(bytes.get(1..).unwrap_or_default(), sort);
+		Self::from_be_decoders(&mut decoder, &mut named).ok_or_else(|| named.end_list_error.or(decoder.end_list_error).map_or_else(|| anyhow!("Not big endian NBT"), anyhow::Error::new))
+	}
+
+	/// Reads the root of a big endian file with `decoder`, or with `named` (the same bytes, past the id of the root) when it has a name spanning the whole file.
+	fn from_be_decoders(decoder: &mut BigEndianDecoder, named: &mut BigEndianDecoder) -> Option<(Self, CompactString, usize)> {
 		decoder.assert_len(1)?;
 		unsafe {
-			if decoder.u8() != NbtCompound::ID { return None }
+			let id = decoder.u8();
+			if id == NbtList::ID {
+				// has to span the whole file too, since little endian lists would otherwise be read with garbage lengths
+				decoder.assert_len(2)?;
+				let name = decoder.string()?;
+				let list = NbtList::from_be_bytes(decoder)?;
+				return (decoder.remaining_len() == 0).then_some((Self::List(list), name, decoder.end_lists))
+			}
+			if id != NbtCompound::ID { return None }
+			// a named root has to span the whole file, otherwise it is the network format with the key of its first entry read as the name
+			if decoder.assert_len(2).is_some() && decoder.data.cast::<u16>().read_unaligned() != 0_u16.to_be() && let Some(name) = named.string() && let Some(compound) = NbtCompound::from_be_bytes(named) && named.remaining_len() == 0 {
+				return Some((Self::Compound(compound), name, named.end_lists))
+			}
 			// fix for >= 1.20.2 protocol since they removed the empty field
 			if decoder.assert_len(2).is_none() || decoder.data.cast::<u16>().read_unaligned() == 0_u16.to_be() {
 				let _ = decoder.u16();
 			}
 		}
-		let nbt = Self::Compound(NbtCompound::from_be_bytes(&mut decoder)?);
-		Some(nbt)
+		let nbt = Self::Compound(NbtCompound::from_be_bytes(decoder)?);
+		Some((nbt, CompactString::new_inline(""), decoder.end_lists))
 	}
 
 	#[inline]
 	#[must_use]
-	pub fn to_be_file(&self) -> Vec<u8> {
+	pub fn to_be_file(&self) -> Vec<u8> { self.to_be_file_named("") }
+
+	/// [`Self::to_be_file`], with `name` as the name of a root compound or list.
+	#[must_use]
+	pub fn to_be_file_named(&self, name: &str) -> Vec<u8> {
 		let mut writer = UncheckedBufWriter::new();
-		if self.id() == NbtCompound::ID {
-			writer.write(&[NbtCompound::ID, 0x00, 0x00]);
-		}
-		self.to_be_bytes(&mut writer);
+		self.write_be_file_named(name, &mut writer);
 		writer.finish()
 	}
 
+	/// [`Self::to_be_file_named`], into `writer`, which can stream it into a file as it goes, see [`UncheckedBufWriter::with_sink`].
+	pub fn write_be_file_named(&self, name: &str, writer: &mut UncheckedBufWriter) {
+		if self.id() == NbtCompound::ID || self.id() == NbtList::ID {
+			writer.write(&[self.id()]);
+			writer.write_be_str(name);
+		}
+		self.to_be_bytes(writer);
+	}
+
+	/// # Errors
+	///
+	/// * See [`NbtRegion::from_be_bytes`]
 	#[inline]
-	#[must_use]
-	pub fn from_be_mca(bytes: &[u8], sort: SortAlgorithm) -> Option<Self> {
-		NbtRegion::from_be_bytes(bytes, sort).map(Self::Region)
+	pub fn from_be_mca(bytes: &[u8], sort: SortAlgorithm, external: ExternalChunks) -> anyhow::Result<Self> {
+		NbtRegion::from_be_bytes(bytes, sort, external).map(Self::Region)
 	}
 
 	#[inline]
 	#[must_use]
-	pub fn from_le_file(bytes: &[u8], sort: SortAlgorithm) -> Option<(Self, bool)> {
+	pub fn from_le_file(bytes: &[u8], sort: SortAlgorithm) -> Option<(Self, bool)> { Self::from_le_file_counted(bytes, sort).ok().map(|(nbt, header, _)| (nbt, header)) }
+
+	/// [`Self::from_le_file`], along with the amount of lists of `TAG_End` with a length read as empty lists, see `lenient_lists`.
+	///
+	/// # Errors
+	///
+	/// * `bytes` aren't little endian NBT, with an [`EndListError`](crate::elements::list::EndListError) when a list of `TAG_End` with a length is what stopped them from being read
+	pub fn from_le_file_counted(bytes: &[u8], sort: SortAlgorithm) -> anyhow::Result<(Self, bool, usize)> {
 		let mut decoder = LittleEndianDecoder::new(bytes, sort);
+		Self::from_le_decoder(&mut decoder).ok_or_else(|| decoder.end_list_error.map_or_else(|| anyhow!("Not little endian NBT"), anyhow::Error::new))
+	}
+
+	fn from_le_decoder(decoder: &mut LittleEndianDecoder) -> Option<(Self, bool, usize)> {
 		unsafe {
 			decoder.assert_len(1)?;
 			let kind = decoder.u8();
@@ -643,14 +759,18 @@ impl NbtElement {
 				NbtCompound::ID => {
 					decoder.assert_len(2)?;
 					let skip = decoder.u16() as usize;
+					// the name isn't kept, but it still has to be inside of the file
+					decoder.assert_len(skip)?;
 					decoder.skip(skip);
-					Some((Self::Compound(NbtCompound::from_le_bytes(&mut decoder)?), decoder.header()))
+					Some((Self::Compound(NbtCompound::from_le_bytes(decoder)?), decoder.header(), decoder.end_lists))
 				},
 				NbtList::ID => {
 					decoder.assert_len(2)?;
 					let skip = decoder.u16() as usize;
+					// the name isn't kept, but it still has to be inside of the file
+					decoder.assert_len(skip)?;
 					decoder.skip(skip);
-					Some((Self::List(NbtList::from_le_bytes(&mut decoder)?), decoder.header()))
+					Some((Self::List(NbtList::from_le_bytes(decoder)?), decoder.header(), decoder.end_lists))
 				},
 				_ => return None,
 			}
@@ -675,6 +795,7 @@ impl NbtElement {
 		}
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render(&self, remaining_scroll: &mut usize, builder: &mut VertexBufferBuilder, str: Option<&str>, tail: bool, ctx: &mut RenderContext) {
 		unsafe {
@@ -719,6 +840,198 @@ impl NbtElement {
 	#[must_use]
 	pub fn is_empty(&self) -> bool { self.len().is_some_and(|x| x > 0) }
 
+	/// The heap memory held by the element and everything inside of it, in bytes, not counting the element itself.
+	#[must_use]
+	pub fn memory_size(&self) -> usize {
+		unsafe {
+			match self.id() {
+				NbtString::ID => self.string.str.heap_size(),
+				// arrays share the layout of lists
+				NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID | NbtList::ID => self.list.memory_size(),
+				NbtCompound::ID => self.compound.memory_size(),
+				NbtChunk::ID => self.chunk.memory_size(),
+				NbtRegion::ID => self.region.memory_size(),
+				_ => 0,
+			}
+		}
+	}
+
+	/// The element as JSON for consumers which don't know NBT, compounds become objects, lists and arrays become arrays and numbers stay numbers.
+	///
+	/// Longs beyond the integers JavaScript can represent exactly (±2⁵³ - 1) and floats which aren't finite become strings, chunks become an object of their `x`, `z` and `last_modified` with their compound as `data`, and regions become an array of their chunks.
+	#[must_use]
+	pub fn to_json(&self) -> String {
+		fn float(value: impl Display, finite: bool, out: &mut String) {
+			if finite {
+				let _ = write!(out, "{value}");
+			} else {
+				let _ = write!(out, "\"{value}\"");
+			}
+		}
+
+		fn array<'a>(values: impl Iterator<Item = &'a NbtElement>, out: &mut String) {
+			out.push('[');
+			for (idx, value) in values.enumerate() {
+				if idx > 0 { out.push(',') }
+				json(value, out);
+			}
+			out.push(']');
+		}
+
+		fn object<'a>(entries: impl Iterator<Item = (&'a str, &'a NbtElement)>, out: &mut String) {
+			out.push('{');
+			for (idx, (key, value)) in entries.enumerate() {
+				if idx > 0 { out.push(',') }
+				json::write_string(key, out);
+				out.push(':');
+				json(value, out);
+			}
+			out.push('}');
+		}
+
+		fn json(element: &NbtElement, out: &mut String) {
+			const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+			unsafe {
+				match element.id() {
+					NbtByte::ID => { let _ = write!(out, "{}", element.byte.value); }
+					NbtShort::ID => { let _ = write!(out, "{}", element.short.value); }
+					NbtInt::ID => { let _ = write!(out, "{}", element.int.value); }
+					NbtLong::ID if element.long.value.unsigned_abs() > MAX_SAFE_INTEGER => { let _ = write!(out, "\"{}\"", element.long.value); }
+					NbtLong::ID => { let _ = write!(out, "{}", element.long.value); }
+					NbtFloat::ID => float(element.float.value, element.float.value.is_finite(), out),
+					NbtDouble::ID => float(element.double.value, element.double.value.is_finite(), out),
+					NbtString::ID => json::write_string(element.string.str.as_str(), out),
+					// arrays share the layout of lists
+					NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID | NbtList::ID => array(element.list.children(), out),
+					NbtCompound::ID => object(element.compound.children(), out),
+					NbtChunk::ID => {
+						let chunk = &*element.chunk;
+						let _ = write!(out, "{{\"x\":{x},\"z\":{z},\"last_modified\":{last_modified},\"data\":", x = chunk.x, z = chunk.z, last_modified = chunk.last_modified());
+						object(chunk.children(), out);
+						out.push('}');
+					}
+					NbtRegion::ID => array(element.region.children(), out),
+					_ => out.push_str("null"),
+				}
+			}
+		}
+
+		let mut out = String::new();
+		json(self, &mut out);
+		out
+	}
+
+	/// The inverse of [`Self::to_json`], taking the types JSON doesn't keep from `hints`.
+	///
+	/// # Errors
+	///
+	/// * A value doesn't fit the type it's hinted as, is `null`, or the elements of an array are of different types (with integers and other numbers all becoming doubles)
+	pub fn from_json(value: &JsonValue, hints: &TypeHints) -> anyhow::Result<Self> {
+		fn at(path: &[PathSegment]) -> String {
+			if path.is_empty() { String::from("the root") } else { PathSegment::join(path) }
+		}
+
+		fn compound(entries: &[(CompactString, JsonValue)], hints: &TypeHints, path: &mut Vec<PathSegment>) -> anyhow::Result<NbtCompound> {
+			let mut compound = NbtCompound::new();
+			for (key, value) in entries {
+				path.push(PathSegment::Key(key.clone()));
+				let element = from_json0(value, hints, path, None)?;
+				path.pop();
+				compound.push(key.clone(), element);
+			}
+			Ok(compound)
+		}
+
+		fn get<'a>(entries: &'a [(CompactString, JsonValue)], name: &str) -> Option<&'a JsonValue> { entries.iter().find(|(key, _)| *key == name).map(|(_, value)| value) }
+
+		fn position(entries: &[(CompactString, JsonValue)], path: &[PathSegment]) -> anyhow::Result<(u8, u8)> {
+			let coordinate = |name: &str| match get(entries, name) {
+				Some(JsonValue::Number(text)) => text.parse::<u8>().ok().filter(|&coordinate| coordinate < 32),
+				_ => None,
+			};
+			let (Some(x), Some(z)) = (coordinate("x"), coordinate("z")) else { return Err(anyhow!("The chunk at {} needs an x and z from 0 to 31", at(path))) };
+			Ok((x, z))
+		}
+
+		/// `path` leads to the chunk itself, which for the chunks of a region ends with their coordinates.
+		fn chunk(entries: &[(CompactString, JsonValue)], hints: &TypeHints, path: &mut Vec<PathSegment>) -> anyhow::Result<NbtChunk> {
+			let get = |name: &str| get(entries, name);
+			let (x, z) = position(entries, path)?;
+			let last_modified = match get("last_modified") {
+				Some(JsonValue::Number(text)) => text.parse::<u32>().map_err(|_| anyhow!("The chunk at {} has an invalid last_modified", at(path)))?,
+				_ => since_epoch().as_secs() as u32,
+			};
+			let Some(JsonValue::Object(entries)) = get("data") else { return Err(anyhow!("The chunk at {} needs an object as its data", at(path))) };
+			Ok(NbtChunk::from_compound(compound(entries, hints, path)?, (x, z), FileFormat::Zlib, last_modified))
+		}
+
+		fn from_json0(value: &JsonValue, hints: &TypeHints, path: &mut Vec<PathSegment>, hint: Option<u8>) -> anyhow::Result<NbtElement> {
+			let hint = hint.or_else(|| hints.get(path));
+			Ok(match value {
+				JsonValue::Null => return Err(anyhow!("{} is null, which NBT has no type for", at(path))),
+				JsonValue::Bool(_) => json::number(value, hint.unwrap_or(NbtByte::ID)).with_context(|| format!("At {}", at(path)))?,
+				JsonValue::Number(text) => match hint {
+					Some(NbtString::ID) => NbtElement::String(NbtString::new(text.clone())),
+					Some(id) => json::number(value, id).with_context(|| format!("At {}", at(path)))?,
+					None if text.contains(['.', 'e', 'E']) => json::number(value, NbtDouble::ID)?,
+					None => json::number(value, NbtInt::ID).or_else(|_| json::number(value, NbtLong::ID)).or_else(|_| json::number(value, NbtDouble::ID))?,
+				},
+				JsonValue::String(str) => match hint {
+					Some(id @ (NbtByte::ID | NbtShort::ID | NbtInt::ID | NbtLong::ID | NbtFloat::ID | NbtDouble::ID)) => json::number(value, id).with_context(|| format!("At {}", at(path)))?,
+					_ => NbtElement::String(NbtString::new(str.clone())),
+				},
+				JsonValue::Array(values) => match hint {
+					Some(id @ (NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID)) => json::array(values, id).with_context(|| format!("At {}", at(path)))?,
+					Some(NbtRegion::ID) => {
+						let mut region = NbtRegion::new();
+						for value in values {
+							let JsonValue::Object(entries) = value else { return Err(anyhow!("The elements of the region at {} must be chunks", at(path))) };
+							let (x, z) = position(entries, path)?;
+							path.push(PathSegment::Chunk(x, z));
+							let chunk = NbtElement::Chunk(chunk(entries, hints, path)?);
+							path.pop();
+							if region.insert(region.len(), chunk).is_err() { return Err(anyhow!("The region at {} has two chunks at {x}, {z}", at(path))) }
+						}
+						NbtElement::Region(region)
+					}
+					_ => {
+						let mut elements = Vec::with_capacity(values.len());
+						for (idx, value) in values.iter().enumerate() {
+							path.push(PathSegment::Index(idx));
+							elements.push(from_json0(value, hints, path, None)?);
+							path.pop();
+						}
+						let id = elements.first().map_or(0, NbtElement::id);
+						if elements.iter().any(|element| element.id() != id) {
+							if elements.iter().all(|element| matches!(element.id(), NbtInt::ID | NbtLong::ID | NbtDouble::ID)) {
+								elements = values.iter().map(|value| json::number(value, NbtDouble::ID)).collect::<anyhow::Result<_>>()?;
+							} else {
+								return Err(anyhow!("The elements of the list at {} must all be of the same type", at(path)))
+							}
+						}
+						let id = match elements.first() {
+							Some(element) => element.id(),
+							None => {
+								path.push(PathSegment::Index(0));
+								let id = hints.get(path).unwrap_or(0);
+								path.pop();
+								id
+							}
+						};
+						NbtElement::List(NbtList::new(elements, id))
+					}
+				},
+				JsonValue::Object(entries) => match hint {
+					Some(NbtChunk::ID) => NbtElement::Chunk(chunk(entries, hints, path)?),
+					_ => NbtElement::Compound(compound(entries, hints, path)?),
+				},
+			})
+		}
+
+		from_json0(value, hints, &mut vec![], None)
+	}
+
 	#[inline]
 	#[must_use]
 	pub fn display_name(&self) -> &'static str {
@@ -742,6 +1055,7 @@ impl NbtElement {
 		}
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render_icon(id: u8, pos: impl Into<(usize, usize)>, z: ZOffset, builder: &mut VertexBufferBuilder) {
 		match id {
@@ -807,6 +1121,293 @@ impl NbtElement {
 		}
 	}
 
+	/// The path of the element on the true line number `target_line`, counted like the line numbers shown next to the tree with this element on line 1, `None` if it is outside of this element.
+	#[must_use]
+	pub fn path_to(&self, target_line: usize) -> Option<Vec<PathSegment>> {
+		let mut target_line = target_line.checked_sub(1)?;
+		let mut path = vec![];
+		let mut element = self;
+		while target_line > 0 {
+			target_line -= 1;
+			// every element of an array is a single line
+			if let NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID = element.id() {
+				if target_line >= element.len()? { return None }
+				path.push(PathSegment::Index(target_line));
+				break
+			}
+			let mut line = 0;
+			let (segment, child) = match element.children()? {
+				Ok(iter) => iter.enumerate().find_map(|(idx, child)| {
+					let start = line;
+					line += child.true_height();
+					(target_line < line).then(|| {
+						target_line -= start;
+						(child.as_chunk().map_or(PathSegment::Index(idx), |chunk| PathSegment::Chunk(chunk.x, chunk.z)), child)
+					})
+				})?,
+				Err(iter) => iter.into_iter().find_map(|(key, child)| {
+					let start = line;
+					line += child.true_height();
+					(target_line < line).then(|| {
+						target_line -= start;
+						(PathSegment::Key(key.to_compact_string()), child)
+					})
+				})?,
+			};
+			path.push(segment);
+			element = child;
+		}
+		Some(path)
+	}
+
+	/// The row the true line number `target_line` (see [`Self::path_to`]) is shown on with this element on row 0, the one of its closest open parent when it's inside of a closed element, lines past the end are clamped to the last one.
+	#[must_use]
+	pub fn row_of(&self, target_line: usize) -> usize {
+		let mut target_line = target_line.clamp(1, self.true_height()) - 1;
+		let mut row = 0;
+		let mut element = self;
+		while target_line > 0 && element.open() {
+			target_line -= 1;
+			row += 1;
+			// every element of an array is a single row, past the shown ones there's only the last of them
+			if let NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID = element.id() {
+				return row + target_line.min(element.height().saturating_sub(2))
+			}
+			let mut line = 0;
+			let child = match element.children() {
+				Some(Ok(iter)) => iter.into_iter().find(|child| {
+					if target_line < line + child.true_height() { return true }
+					line += child.true_height();
+					row += child.height();
+					false
+				}),
+				Some(Err(iter)) => iter.into_iter().find(|(_, child)| {
+					if target_line < line + child.true_height() { return true }
+					line += child.true_height();
+					row += child.height();
+					false
+				}).map(|(_, child)| child),
+				None => None,
+			};
+			let Some(child) = child else { break };
+			target_line -= line;
+			element = child;
+		}
+		row
+	}
+
+	/// The element at `path` (see [`Self::path_to`]), `None` if it no longer leads anywhere.
+	#[must_use]
+	pub fn get_by_path(&self, path: &[PathSegment]) -> Option<&Self> { self.indices_of(path)?.into_iter().try_fold(self, |element, idx| element.get(idx)) }
+
+	/// The path of the element at `indices`, `None` if they don't lead to an element.
+	#[must_use]
+	pub fn path_of(&self, indices: &[usize]) -> Option<Vec<PathSegment>> {
+		let mut path = Vec::with_capacity(indices.len());
+		let mut element = self;
+		for &idx in indices {
+			let child = element.get(idx)?;
+			path.push(match element.children()? {
+				Ok(_) => child.as_chunk().map_or(PathSegment::Index(idx), |chunk| PathSegment::Chunk(chunk.x, chunk.z)),
+				Err(mut iter) => PathSegment::Key(iter.nth(idx)?.0.to_compact_string()),
+			});
+			element = child;
+		}
+		Some(path)
+	}
+
+	/// The indices leading to the element at `path`, `None` if it no longer leads anywhere.
+	#[must_use]
+	pub fn indices_of(&self, path: &[PathSegment]) -> Option<Vec<usize>> {
+		let mut indices = Vec::with_capacity(path.len());
+		let mut element = self;
+		for segment in path {
+			let idx = match segment {
+				PathSegment::Key(key) => element.children()?.err()?.position(|(k, _)| k == key.as_str())?,
+				&PathSegment::Index(idx) => {
+					if element.id() == NbtRegion::ID || element.children()?.is_err() { return None }
+					idx
+				}
+				&PathSegment::Chunk(x, z) => element.as_region()?.children().position(|chunk| chunk.as_chunk().is_some_and(|chunk| chunk.x == x && chunk.z == z))?,
+			};
+			element = element.get(idx)?;
+			indices.push(idx);
+		}
+		Some(indices)
+	}
+
+	/// The path of the first key or string inside of this element which is too long for the `u16` length NBT prefixes them with, along with its length in bytes and if it's a key rather than a string.
+	///
+	/// The length is of the modified UTF-8 of big endian NBT (see [`mutf8::encode`](crate::mutf8::encode)), or of standard UTF-8 with `little_endian`.
+	#[must_use]
+	pub fn too_long_string(&self, little_endian: bool) -> Option<(Vec<PathSegment>, usize, bool)> {
+		fn too_long(str: &str, little_endian: bool) -> Option<usize> {
+			// modified UTF-8 takes at most twice the bytes, for nulls
+			let len = if little_endian || str.len() * 2 <= u16::MAX as usize { str.len() } else { crate::mutf8::encode(str).len() };
+			(len > u16::MAX as usize).then_some(len)
+		}
+
+		fn find(element: &NbtElement, path: &mut Vec<PathSegment>, little_endian: bool) -> Option<(usize, bool)> {
+			if let Some(string) = element.as_string() { return too_long(string.str.as_str(), little_endian).map(|len| (len, false)) }
+			match element.children()? {
+				Ok(children) => for (idx, child) in children.enumerate() {
+					path.push(child.as_chunk().map_or(PathSegment::Index(idx), |chunk| PathSegment::Chunk(chunk.x, chunk.z)));
+					if let Some(found) = find(child, path, little_endian) { return Some(found) }
+					path.pop();
+				},
+				Err(entries) => for (key, child) in entries {
+					path.push(PathSegment::Key(key.to_compact_string()));
+					if let Some(len) = too_long(key, little_endian) { return Some((len, true)) }
+					if let Some(found) = find(child, path, little_endian) { return Some(found) }
+					path.pop();
+				},
+			}
+			None
+		}
+
+		let mut path = vec![];
+		let (len, key) = find(self, &mut path, little_endian)?;
+		Some((path, len, key))
+	}
+
+	/// The rendered row of the element at `indices` relative to this element's row, matching the heights used to render the tree.
+	///
+	/// If the element is hidden inside a closed element (or past the shown elements of an array), the row of the closest visible ancestor is returned instead.
+	///
+	/// Returns `None` if `indices` doesn't lead to an element.
+	#[must_use]
+	pub fn row_of_path(&self, indices: &[usize]) -> Option<usize> {
+		let mut row = 0;
+		let mut element = self;
+		let mut visible = true;
+		for &idx in indices {
+			let child = element.get(idx)?;
+			if visible {
+				let offset = 1 + match element.children()? {
+					Ok(iter) => iter.take(idx).map(NbtElement::height).sum::<usize>(),
+					Err(iter) => iter.take(idx).map(|(_, value)| value.height()).sum::<usize>(),
+				};
+				if offset < element.height() {
+					row += offset;
+				} else {
+					visible = false;
+				}
+			}
+			element = child;
+		}
+		Some(row)
+	}
+
+	/// The line of the element at `indices` below this one, counting the lines of closed elements the way line numbers do, unlike [`Self::row_of_path`].
+	///
+	/// Returns `None` if `indices` doesn't lead to an element.
+	#[must_use]
+	pub fn line_of_path(&self, indices: &[usize]) -> Option<usize> {
+		let mut line = 0;
+		let mut element = self;
+		for &idx in indices {
+			let child = element.get(idx)?;
+			line += 1 + match element.children()? {
+				Ok(iter) => iter.take(idx).map(NbtElement::true_height).sum::<usize>(),
+				Err(iter) => iter.take(idx).map(|(_, value)| value.true_height()).sum::<usize>(),
+			};
+			element = child;
+		}
+		Some(line)
+	}
+
+	/// Every element of the tree which doesn't match `schema`, see [`Schema::validate`].
+	#[must_use]
+	pub fn validate(&self, schema: &Schema) -> Vec<SchemaError> { schema.validate(self) }
+
+	/// If the element at `indices` is shown, which is when every element containing it is open.
+	#[must_use]
+	pub fn is_shown(&self, indices: &[usize]) -> bool {
+		let mut element = self;
+		for &idx in indices {
+			if !element.open() { return false }
+			let Some(child) = element.get(idx) else { return false };
+			element = child;
+		}
+		true
+	}
+
+	/// Converts the element to the type `id` in place, keeping its value where there is a sensible mapping, returning what it was before, or `None` (leaving it as is) for conversions without one, like a compound to an int.
+	///
+	/// Numbers convert between each other, saturating when they don't fit, and byte, int and long arrays convert to and from lists of the same numbers.
+	pub fn convert_to(&mut self, id: u8) -> Option<Self> {
+		const NUMBERS: [u8; 6] = [NbtByte::ID, NbtShort::ID, NbtInt::ID, NbtLong::ID, NbtFloat::ID, NbtDouble::ID];
+
+		fn array_element(id: u8) -> Option<u8> {
+			match id {
+				NbtByteArray::ID => Some(NbtByte::ID),
+				NbtIntArray::ID => Some(NbtInt::ID),
+				NbtLongArray::ID => Some(NbtLong::ID),
+				_ => None,
+			}
+		}
+
+		if self.id() == id { return None }
+		let converted = unsafe {
+			if NUMBERS.contains(&self.id()) && NUMBERS.contains(&id) {
+				// `as` casts from floats saturate
+				let (int, float) = match self.id() {
+					NbtByte::ID => (i64::from(self.byte.value), f64::from(self.byte.value)),
+					NbtShort::ID => (i64::from(self.short.value), f64::from(self.short.value)),
+					NbtInt::ID => (i64::from(self.int.value), f64::from(self.int.value)),
+					NbtLong::ID => (self.long.value, self.long.value as f64),
+					NbtFloat::ID => (self.float.value as i64, f64::from(self.float.value)),
+					NbtDouble::ID => (self.double.value as i64, self.double.value),
+					_ => core::hint::unreachable_unchecked(),
+				};
+				match id {
+					NbtByte::ID => Self::Byte(NbtByte { value: int.clamp(i8::MIN.into(), i8::MAX.into()) as i8 }),
+					NbtShort::ID => Self::Short(NbtShort { value: int.clamp(i16::MIN.into(), i16::MAX.into()) as i16 }),
+					NbtInt::ID => Self::Int(NbtInt { value: int.clamp(i32::MIN.into(), i32::MAX.into()) as i32 }),
+					NbtLong::ID => Self::Long(NbtLong { value: int }),
+					NbtFloat::ID => Self::Float(NbtFloat { value: float.clamp(f32::MIN.into(), f32::MAX.into()) as f32 }),
+					NbtDouble::ID => Self::Double(NbtDouble { value: float }),
+					_ => core::hint::unreachable_unchecked(),
+				}
+			} else if let Some(element) = array_element(self.id()) && id == NbtList::ID {
+				let Some(Ok(values)) = self.children() else { core::hint::unreachable_unchecked() };
+				Self::List(NbtList::new(values.cloned().collect(), element))
+			} else if let Some(element) = array_element(id) && self.id() == NbtList::ID && (self.list.element == element || self.list.is_empty()) {
+				let mut array = Self::from_id(id);
+				for value in self.list.elements.iter() {
+					array.insert(array.len().unwrap_or(0), value.clone()).ok()?;
+				}
+				array
+			} else {
+				return None
+			}
+		};
+		Some(core::mem::replace(self, converted))
+	}
+
+	/// Applies `operation` to this number, or to every number of this list or array, see [`Arithmetic`](crate::elements::primitive::Arithmetic).
+	///
+	/// `None` for elements which aren't (or don't hold) numbers, `Some(false)` when the operation is undefined for one of them, like dividing an integer by zero, leaving the numbers before it changed.
+	pub fn map_number(&mut self, operation: Operation, operand: f64) -> Option<bool> {
+		unsafe {
+			Some(match self.id() {
+				NbtByte::ID => self.byte.map_value(operation, operand),
+				NbtShort::ID => self.short.map_value(operation, operand),
+				NbtInt::ID => self.int.map_value(operation, operand),
+				NbtLong::ID => self.long.map_value(operation, operand),
+				NbtFloat::ID => self.float.map_value(operation, operand),
+				NbtDouble::ID => self.double.map_value(operation, operand),
+				NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID | NbtList::ID => {
+					if let Some(list) = self.as_list() && !matches!(list.element, NbtByte::ID | NbtShort::ID | NbtInt::ID | NbtLong::ID | NbtFloat::ID | NbtDouble::ID) { return None }
+					let mut defined = true;
+					self.map_leaves(|_, child| if defined { defined = child.map_number(operation, operand) == Some(true) });
+					defined
+				}
+				_ => return None,
+			})
+		}
+	}
+
 	#[inline]
 	pub fn set_value(&mut self, value: CompactString) -> Option<(CompactString, bool)> {
 		unsafe {
@@ -883,6 +1484,17 @@ impl NbtElement {
 		}
 	}
 
+	/// Lays out the next chunk of a partially shown array if `idx` is its last shown element, returns the amount of rows added.
+	#[inline]
+	pub fn show_more(&mut self, idx: usize) -> usize {
+		unsafe {
+			match self.id() {
+				NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID => self.byte_array.show_more(idx),
+				_ => 0,
+			}
+		}
+	}
+
 	#[inline]
 	#[must_use]
 	pub fn open(&self) -> bool {
@@ -1040,6 +1652,97 @@ impl NbtElement {
 		}
 	}
 
+	/// Opens this element and its descendants until `depth` levels deep, the chunks of a region are always left closed.
+	pub fn expand_to_depth(&mut self, depth: usize) {
+		if depth == 0 { return }
+
+		if depth > 1 && let NbtList::ID | NbtCompound::ID | NbtChunk::ID = self.id() {
+			let mut increment = 0;
+			for idx in 0..self.len().unwrap_or(0) {
+				let Some(child) = self.get_mut(idx) else { continue };
+				let before = child.height();
+				child.expand_to_depth(depth - 1);
+				increment += child.height() - before;
+			}
+			self.increment(increment, 0);
+		}
+		if !self.open() {
+			let _ = self.toggle();
+		}
+		self.recache_depth();
+	}
+
+	/// Calls `f` on every element of the tree, parents before their children, with the indices leading to it from `self`, then recaches the widths of the transformed values.
+	///
+	/// `f` may only change values in place, changing an element's type or adding and removing children would desync the cached heights, the tree operations (and their undo actions) are there for those.
+	pub fn map_values(&mut self, mut f: impl FnMut(&[usize], &mut NbtElement)) { self.map_values0(&mut vec![], &mut f, false) }
+
+	/// [`Self::map_values`], but only calling `f` on elements which can't have children, like numbers and strings.
+	pub fn map_leaves(&mut self, mut f: impl FnMut(&[usize], &mut NbtElement)) { self.map_values0(&mut vec![], &mut f, true) }
+
+	/// The amount of entries of the compounds and chunks of the tree which are keyed like an entry before them, see [`CompoundMap::duplicates`](crate::elements::compound::CompoundMap::duplicates).
+	#[must_use]
+	pub fn duplicate_keys(&self) -> usize {
+		let own = if let Some(compound) = self.as_compound() {
+			compound.entries.duplicates()
+		} else if let Some(chunk) = self.as_chunk() {
+			chunk.entries.duplicates()
+		} else {
+			0
+		};
+		own + (0..self.len().unwrap_or(0)).filter_map(|idx| self.get(idx)).map(Self::duplicate_keys).sum::<usize>()
+	}
+
+	fn map_values0(&mut self, indices: &mut Vec<usize>, f: &mut impl FnMut(&[usize], &mut NbtElement), leaves_only: bool) {
+		let Some(len) = self.len() else {
+			let id = self.id();
+			f(indices, self);
+			debug_assert_eq!(id, self.id(), "map_values must not change the type of an element");
+			return
+		};
+
+		if !leaves_only {
+			let (id, height, true_height) = (self.id(), self.height(), self.true_height());
+			f(indices, self);
+			debug_assert_eq!((id, height, true_height), (self.id(), self.height(), self.true_height()), "map_values must not change the structure of the tree");
+		}
+		for idx in 0..len {
+			let Some(child) = self.get_mut(idx) else { continue };
+			indices.push(idx);
+			child.map_values0(indices, f, leaves_only);
+			indices.pop();
+		}
+		self.recache_depth();
+	}
+
+	#[inline]
+	pub fn recache_depth(&mut self) {
+		unsafe {
+			match self.id() {
+				NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID => self.byte_array.recache_depth(),
+				NbtList::ID => self.list.recache_depth(),
+				NbtCompound::ID => self.compound.recache_depth(),
+				NbtChunk::ID => self.chunk.recache_depth(),
+				NbtRegion::ID => self.region.recache_depth(),
+				_ => {}
+			}
+		}
+	}
+
+	/// If `value` is of a type `self` can hold, regions also refuse chunks whose position is taken when inserted.
+	#[must_use]
+	pub fn can_insert(&self, value: &Self) -> bool {
+		match self.id() {
+			NbtByteArray::ID => value.id() == NbtByte::ID,
+			NbtIntArray::ID => value.id() == NbtInt::ID,
+			NbtLongArray::ID => value.id() == NbtLong::ID,
+			NbtList::ID => unsafe { self.list.element == value.id() || self.list.is_empty() },
+			NbtCompound::ID | NbtChunk::ID => value.id() != NbtChunk::ID,
+			NbtRegion::ID => value.id() == NbtChunk::ID,
+			_ => false,
+		}
+	}
+
 	/// # Errors
 	///
 	/// * `self` cannot contain that specific variant of `Self`, i.e. `Self::NbtByte` in an `Self::NbtIntArray`
@@ -1128,6 +1831,7 @@ impl NbtElement {
 		}
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	#[must_use]
 	#[allow(clippy::match_same_arms)]
@@ -1275,6 +1979,14 @@ impl Display for NbtElement {
 }
 
 impl NbtElement {
+	/// Multi-line SNBT with every compound entry and list element on its own line, indented by `indent` per level, which reads back with [`Self::from_snbt`] like the compact [`Display`] output does.
+	#[must_use]
+	pub fn to_snbt_pretty(&self, indent: &str) -> String {
+		let mut formatter = PrettyFormatter::with_indent(indent);
+		self.pretty_fmt(&mut formatter);
+		formatter.finish()
+	}
+
 	pub fn pretty_fmt(&self, f: &mut PrettyFormatter) {
 		unsafe {
 			match self.id() {
@@ -1313,6 +2025,7 @@ impl Drop for NbtElement {
 			let id = self.id();
 			match id {
 				NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID => {
+					core::ptr::addr_of_mut!(self.byte_array.summary).drop_in_place();
 					let vec = &mut *self.byte_array.values;
 					if !vec.is_empty() {
 						dealloc(
@@ -1391,8 +2104,9 @@ impl Drop for NbtElement {
 				}
 				// no real speedup from using threads, seems to be memory-bound, or dealloc-call-bound
 				NbtRegion::ID => {
-					let (map, chunks) = *core::ptr::addr_of_mut!(self.region.chunks).read();
+					let (map, chunks, raw_chunks) = *core::ptr::addr_of_mut!(self.region.chunks).read();
 					drop(map);
+					drop(raw_chunks);
 					for mut chunk in core::mem::transmute::<_, [ManuallyDrop<Self>; 1024]>(chunks) {
 						if !chunk.is_null() {
 							let ptr = &mut **chunk.as_chunk_unchecked_mut();
@@ -1425,7 +2139,7 @@ impl Drop for NbtElement {
 						}
 					}
 				}
-				NbtNull::ID => {}
+				NbtByte::ID | NbtShort::ID | NbtInt::ID | NbtLong::ID | NbtFloat::ID | NbtDouble::ID | NbtNull::ID => {}
 				_ => core::hint::unreachable_unchecked()
 			}
 		}
@@ -1496,6 +2210,92 @@ impl IndexMut<usize> for NbtElement {
 	}
 }
 
+/// A step from an element to one of its children, unlike indices these don't depend on the order of a region's chunks.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+	/// An entry of a compound or chunk.
+	Key(CompactString),
+	/// An element of a list or array.
+	Index(usize),
+	/// A chunk of a region, by its coordinates within it.
+	Chunk(u8, u8),
+}
+
+impl PathSegment {
+	/// `path` as a string, keys (quoted if they aren't plain) joined by `.`, `[n]` for the nth element of a list or array and `[x, z]` for the chunk of a region at those coordinates, read back by [`Self::parse`].
+	#[must_use]
+	pub fn join(path: &[Self]) -> String {
+		let mut out = String::new();
+		for segment in path {
+			if matches!(segment, Self::Key(_)) && !out.is_empty() { out.push('.') }
+			let _ = write!(out, "{segment}");
+		}
+		out
+	}
+
+	/// The path of a string made by [`Self::join`], `None` if it isn't one.
+	#[must_use]
+	pub fn parse(str: &str) -> Option<Vec<Self>> {
+		let mut path = vec![];
+		let mut chars = str.chars().peekable();
+		while let Some(&char) = chars.peek() {
+			if char == '[' {
+				chars.next();
+				let mut inside = String::new();
+				loop {
+					match chars.next()? {
+						']' => break,
+						char => inside.push(char),
+					}
+				}
+				path.push(match inside.split_once(", ") {
+					Some((x, z)) => Self::Chunk(x.parse().ok()?, z.parse().ok()?),
+					None => Self::Index(inside.parse().ok()?),
+				});
+				continue
+			}
+			if !path.is_empty() && chars.next() != Some('.') { return None }
+			let mut key = String::new();
+			if chars.peek() == Some(&'"') {
+				chars.next();
+				loop {
+					match chars.next()? {
+						'"' => break,
+						'\\' => key.push(chars.next()?),
+						char => key.push(char),
+					}
+				}
+			} else {
+				while let Some(&char) = chars.peek() && char != '.' && char != '[' {
+					key.push(char);
+					chars.next();
+				}
+			}
+			path.push(Self::Key(key.into()));
+		}
+		Some(path)
+	}
+}
+
+impl Display for PathSegment {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Key(key) => if key.is_empty() || key.needs_escape() || key.contains(['.', '[']) {
+				f.write_char('"')?;
+				for char in key.chars() {
+					if matches!(char, '"' | '\\') { f.write_char('\\')? }
+					f.write_char(char)?;
+				}
+				f.write_char('"')
+			} else {
+				f.write_str(key)
+			},
+			Self::Index(idx) => write!(f, "[{idx}]"),
+			Self::Chunk(x, z) => write!(f, "[{x}, {z}]"),
+		}
+	}
+}
+
 #[inline]
 #[must_use]
 pub fn id_to_string_name(id: u8) -> (&'static str, &'static str) {
@@ -2621,3 +3421,198 @@ impl NbtElement {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::elements::chunk::{NbtChunk, NbtRegion};
+	use crate::elements::primitive::Operation;
+	use crate::file::FileFormat;
+	#[cfg(feature = "gui")]
+	use crate::tree_travel::TraverseParents;
+	use crate::SortAlgorithm;
+
+	use super::{NbtElement, PathSegment};
+
+	fn parse(snbt: &str) -> NbtElement { NbtElement::from_snbt(snbt).expect("fixtures are valid SNBT") }
+
+	/// The indices of the element the renderer draws on `row`, the way hovering finds it.
+	#[cfg(feature = "gui")]
+	fn indices_at_row(root: &mut NbtElement, row: usize) -> Vec<usize> {
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(row, root);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		indices
+	}
+
+	/// The rows taken up by `element` counted from what is open, to check the cached heights against.
+	fn shown_rows(element: &NbtElement) -> usize {
+		if !element.open() { return 1 }
+		1 + (0..element.len().unwrap_or(0)).filter_map(|idx| element.get(idx)).map(shown_rows).sum::<usize>()
+	}
+
+	#[test]
+	#[cfg(feature = "gui")]
+	fn row_of_path_follows_open_and_closed_elements() {
+		let mut root = parse("{a: 1, b: {c: 2b, d: [I; 1, 2, 3]}, e: [3s, 4s]}");
+		// everything is hidden inside of the closed root
+		assert_eq!(root.row_of_path(&[1, 1, 2]), Some(0));
+
+		root.toggle().expect("compounds can be opened");
+		assert_eq!(root.row_of_path(&[]), Some(0));
+		assert_eq!(root.row_of_path(&[1]), Some(2));
+		assert_eq!(root.row_of_path(&[2]), Some(3));
+		assert_eq!(root.row_of_path(&[1, 0]), Some(2));
+		assert_eq!(root.row_of_path(&[3]), None);
+		assert_eq!(root.row_of_path(&[1, 1, 3]), None);
+
+		root.get_mut(1).and_then(NbtElement::toggle).expect("compounds can be opened");
+		root.increment(2, 0);
+		assert_eq!(root.row_of_path(&[1, 1]), Some(4));
+		assert_eq!(root.row_of_path(&[1, 1, 2]), Some(4));
+		assert_eq!(root.row_of_path(&[2]), Some(5));
+
+		std::thread::scope(|scope| root.expand(scope));
+		let height = root.height();
+		assert_eq!(height, root.true_height());
+		assert_eq!(root.row_of_path(&[1, 1, 2]), Some(7));
+		assert_eq!(root.row_of_path(&[2, 1]), Some(10));
+		// every row is where the renderer draws it
+		for row in 1..height {
+			let indices = indices_at_row(&mut root, row);
+			assert_eq!(root.row_of_path(&indices), Some(row), "{indices:?}");
+		}
+	}
+
+	#[test]
+	fn expand_to_depth_opens_that_many_levels() {
+		let fixture = "{a: {b: {c: [I; 1, 2]}, d: [{e: 1b}]}, f: 2}";
+		for depth in 0..5 {
+			let mut root = parse(fixture);
+			root.expand_to_depth(depth);
+			let a = root.get(0).expect("a is the first key");
+			let b = a.get(0).expect("b is the first key of a");
+			let d = a.get(1).expect("d is the second key of a");
+			assert_eq!([root.open(), a.open(), b.open(), d.open(), b.get(0).is_some_and(NbtElement::open), d.get(0).is_some_and(NbtElement::open)], [depth >= 1, depth >= 2, depth >= 3, depth >= 3, depth >= 4, depth >= 4], "depth {depth}");
+			assert_eq!(root.height(), shown_rows(&root), "depth {depth}");
+		}
+	}
+
+	#[test]
+	fn expand_to_depth_leaves_chunks_closed() {
+		let mut region = NbtElement::Region(NbtRegion::new());
+		let chunk = parse("{DataVersion: 3465, Status: \"minecraft:full\"}").into_compound().expect("the fixture is a compound");
+		region.insert(0, NbtElement::Chunk(NbtChunk::from_compound(chunk, (1, 2), FileFormat::Zlib, 0))).expect("the region is empty");
+		region.expand_to_depth(3);
+		assert!(region.open());
+		assert!(!region.get(0).is_some_and(NbtElement::open));
+		assert_eq!(region.height(), 2);
+	}
+
+	#[test]
+	fn map_leaves_transforms_every_leaf_in_place() {
+		let mut root = parse(r#"{name: "steve", tags: ["a", "b"], nested: {inner: ["c"], count: 3}}"#);
+		std::thread::scope(|scope| root.expand(scope));
+		let (height, true_height) = (root.height(), root.true_height());
+
+		let mut visited = vec![];
+		root.map_leaves(|indices, leaf| {
+			visited.push(indices.to_vec());
+			if let Some(string) = leaf.as_string() {
+				let upper = string.str.as_str().to_uppercase();
+				assert!(leaf.set_value(upper.into()).is_some_and(|(_, success)| success));
+			}
+		});
+		assert_eq!(visited, [vec![0], vec![1, 0], vec![1, 1], vec![2, 0, 0], vec![2, 1]]);
+		assert_eq!(root.to_be_file(), parse(r#"{name: "STEVE", tags: ["A", "B"], nested: {inner: ["C"], count: 3}}"#).to_be_file());
+		assert_eq!((root.height(), root.true_height()), (height, true_height));
+	}
+
+	#[test]
+	fn map_values_visits_parents_before_their_children() {
+		let mut root = parse("{a: [1b, 2b], b: {c: 3}}");
+		let mut visited = vec![];
+		root.map_values(|indices, _| visited.push(indices.to_vec()));
+		assert_eq!(visited, [vec![], vec![0], vec![0, 0], vec![0, 1], vec![1], vec![1, 0]]);
+	}
+
+	#[test]
+	fn numbers_of_arrays_and_lists_are_mapped() {
+		let mut array = parse("[I; 1, 2, 3]");
+		assert_eq!(array.map_number(Operation::Multiply, 2.0), Some(true));
+		assert_eq!(array.to_be_file(), parse("[I; 2, 4, 6]").to_be_file());
+
+		let mut list = parse("[1.5d, 2.5d]");
+		assert_eq!(list.map_number(Operation::Add, 1.0), Some(true));
+		assert_eq!(list.to_be_file(), parse("[2.5d, 3.5d]").to_be_file());
+
+		assert_eq!(parse("[I; 1, 2]").map_number(Operation::Divide, 0.0), Some(false));
+		assert_eq!(parse(r#"["a"]"#).map_number(Operation::Add, 1.0), None);
+	}
+
+	/// A named tag of a little endian (Bedrock) file, written out by hand rather than by the encoder under test.
+	fn le_tag(id: u8, key: &str, payload: &[u8]) -> Vec<u8> {
+		let mut bytes = vec![id];
+		bytes.extend_from_slice(&(key.len() as u16).to_le_bytes());
+		bytes.extend_from_slice(key.as_bytes());
+		bytes.extend_from_slice(payload);
+		bytes
+	}
+
+	#[test]
+	fn bedrock_files_are_saved_as_they_were_read() {
+		let mut root = vec![];
+		root.extend(le_tag(1, "byte", &[0xFD]));
+		root.extend(le_tag(2, "short", &0x1234_i16.to_le_bytes()));
+		root.extend(le_tag(3, "int", &(-0x1234_5678_i32).to_le_bytes()));
+		root.extend(le_tag(4, "long", &((1_i64 << 40) + 5).to_le_bytes()));
+		root.extend(le_tag(5, "float", &1.5_f32.to_le_bytes()));
+		root.extend(le_tag(6, "double", &(-2.25_f64).to_le_bytes()));
+		root.extend(le_tag(7, "bytes", &[3, 0, 0, 0, 1, 2, 3]));
+		root.extend(le_tag(8, "string", &[7, 0, b'B', b'e', b'd', b'r', b'o', b'c', b'k']));
+		root.extend(le_tag(9, "list", &[3, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0]));
+		root.extend(le_tag(10, "compound", &[1, 1, 0, b'n', 1, 0]));
+		root.extend(le_tag(11, "ints", &[1, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]));
+		root.extend(le_tag(12, "longs", &[1, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0]));
+		root.push(0);
+		let raw = le_tag(10, "", &root);
+		let mut file = vec![8, 0, 0, 0];
+		file.extend_from_slice(&(raw.len() as u32).to_le_bytes());
+		file.extend_from_slice(&raw);
+
+		let (nbt, header) = NbtElement::from_le_file(&file, SortAlgorithm::None).expect("the file is valid little endian NBT");
+		assert!(header);
+		assert_eq!(nbt.as_compound().and_then(|compound| compound.get(2)).and_then(|(_, int)| int.as_int()).map(|int| int.value), Some(-0x1234_5678));
+		assert_eq!(nbt.to_le_file(true), file);
+		let (nbt, header) = NbtElement::from_le_file(&raw, SortAlgorithm::None).expect("the file is valid little endian NBT");
+		assert!(!header);
+		assert_eq!(nbt.to_le_file(false), raw);
+	}
+
+	#[test]
+	fn paths_lead_back_to_their_elements() {
+		let root = parse(r#"{a: {b: [1, 2, 3]}, "odd.key": {"": 4b}, c: [[I; 5, 6]], "d[0]": 7}"#);
+		for indices in [&[0][..], &[0, 0], &[0, 0, 2], &[1], &[1, 0], &[2, 0, 1], &[3]] {
+			let path = PathSegment::join(&root.path_of(indices).expect("the indices are valid"));
+			let parsed = PathSegment::parse(&path).expect("joined paths parse");
+			assert_eq!(root.indices_of(&parsed).as_deref(), Some(indices), "{path}");
+		}
+		assert_eq!(root.path_of(&[1, 0]).map(|path| PathSegment::join(&path)).as_deref(), Some(r#""odd.key"."""#));
+		assert_eq!(root.path_of(&[0, 0, 2]).map(|path| PathSegment::join(&path)).as_deref(), Some("a.b[2]"));
+		assert!(root.indices_of(&PathSegment::parse("a.b[3]").expect("the path is valid")).is_none());
+		assert!(PathSegment::parse("a[1").is_none());
+
+		// chunks are found by their coordinates, whatever their place in the region
+		let mut region = NbtRegion::new();
+		for (x, z) in [(3, 4), (1, 2)] {
+			assert!(region.insert(region.len(), NbtElement::Chunk(NbtChunk::from_compound(parse("{Status: \"minecraft:full\"}").into_compound().expect("the fixture is a compound"), (x, z), FileFormat::Zlib, 0))).is_ok());
+		}
+		let region = NbtElement::Region(region);
+		let path = region.path_of(&[1, 0]).expect("the indices are valid");
+		assert_eq!(path, [PathSegment::Chunk(1, 2), PathSegment::Key("Status".into())]);
+		assert_eq!(PathSegment::join(&path), "[1, 2].Status");
+		assert_eq!(region.indices_of(&path).as_deref(), Some(&[1, 0][..]));
+		assert!(region.indices_of(&[PathSegment::Index(0)]).is_none());
+	}
+}
@@ -1,24 +1,48 @@
 use compact_str::{format_compact, CompactString};
 use std::alloc::{alloc, Layout};
-use std::fmt::{Display, Formatter, Write};
+use std::fmt::{Display, Formatter};
+#[cfg(feature = "gui")]
+use std::fmt::Write;
 use std::intrinsics::likely;
 use std::slice::{Iter, IterMut};
 #[cfg(not(target_arch = "wasm32"))]
 use std::thread::Scope;
 
-use crate::assets::{JUST_OVERLAPPING_BASE_TEXT_Z, BASE_Z, CONNECTION_UV, LIST_UV, ZOffset};
+#[cfg(feature = "gui")]
+use crate::assets::{JUST_OVERLAPPING_BASE_TEXT_Z, BASE_Z, LIST_UV, ZOffset};
 use crate::be_decoder::BigEndianDecoder;
+#[cfg(feature = "gui")]
 use crate::elements::chunk::NbtChunk;
 use crate::elements::element::{id_to_string_name, NbtElement};
+use crate::elements::null::NbtNull;
 use crate::encoder::UncheckedBufWriter;
-use crate::{DropFn, OptionExt, RenderContext, SortAlgorithm, StrExt, VertexBufferBuilder};
+use crate::{config, DropFn, OptionExt, SortAlgorithm, StrExt};
+#[cfg(feature = "gui")]
+use crate::{RenderContext, VertexBufferBuilder};
+#[cfg(feature = "gui")]
 use crate::color::TextColor;
 use crate::formatter::PrettyFormatter;
 use crate::le_decoder::LittleEndianDecoder;
 
+/// A list of `TAG_End` with a length, which fails the parse unless `lenient_lists` reads it as an empty list, see [`NbtList::end_list_len`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EndListError {
+	pub len: usize,
+}
+
+impl Display for EndListError {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "Found a list of {len} TAG_End elements, set `lenient_lists=true` in the config to read it as an empty list", len = self.len)
+	}
+}
+
+impl std::error::Error for EndListError {}
+
 #[allow(clippy::module_name_repetitions)]
 #[repr(C)]
 pub struct NbtList {
+	// boxed to fit inside of `NbtElement`
+	#[allow(clippy::box_collection)]
 	pub elements: Box<Vec<NbtElement>>,
 	height: u32,
 	true_height: u32,
@@ -87,6 +111,19 @@ impl NbtList {
 			decoder.assert_len(5)?;
 			let element = decoder.u8();
 			let len = decoder.u32() as usize;
+			let len = if element == NbtNull::ID {
+				match Self::end_list_len(len, &mut decoder.end_lists) {
+					Ok(len) => len,
+					Err(e) => {
+						decoder.end_list_error = Some(e);
+						return None
+					}
+				}
+			} else {
+				len
+			};
+			// a crafted length would otherwise allocate far more than the file could hold before failing
+			decoder.assert_len(len.checked_mul(NbtElement::min_payload_len(element))?)?;
 			let ptr = alloc(Layout::array::<NbtElement>(len).unwrap_unchecked()).cast::<NbtElement>();
 			let mut true_height = 1;
 			for n in 0..len {
@@ -107,6 +144,23 @@ impl NbtList {
 		}
 	}
 
+	/// Lists of `TAG_End` can only be empty, vanilla writes every empty list with a length of `0` in all versions, but some third-party generators keep the old length when clearing a list.
+	/// `TAG_End` has no payload so nothing is stored for those elements, with `lenient_lists` these lists are read as empty, otherwise the file fails to parse, either way they're counted in `end_lists`.
+	///
+	/// # Errors
+	///
+	/// * `len` isn't `0` and `lenient_lists` is off
+	fn end_list_len(len: usize, end_lists: &mut usize) -> Result<usize, EndListError> {
+		if len == 0 { return Ok(0) }
+		*end_lists += 1;
+		if config::get_lenient_lists() {
+			log::warn!("Read a list of {len} TAG_End elements as an empty list");
+			Ok(0)
+		} else {
+			Err(EndListError { len })
+		}
+	}
+
 	#[inline]
 	pub fn to_be_bytes(&self, writer: &mut UncheckedBufWriter) {
 		writer.write(&[self.element]);
@@ -123,6 +177,19 @@ impl NbtList {
 			decoder.assert_len(5)?;
 			let element = decoder.u8();
 			let len = decoder.u32() as usize;
+			let len = if element == NbtNull::ID {
+				match Self::end_list_len(len, &mut decoder.end_lists) {
+					Ok(len) => len,
+					Err(e) => {
+						decoder.end_list_error = Some(e);
+						return None
+					}
+				}
+			} else {
+				len
+			};
+			// a crafted length would otherwise allocate far more than the file could hold before failing
+			decoder.assert_len(len.checked_mul(NbtElement::min_payload_len(element))?)?;
 			let ptr = alloc(Layout::array::<NbtElement>(len).unwrap_unchecked()).cast::<NbtElement>();
 			let mut true_height = 1;
 			for n in 0..len {
@@ -213,6 +280,12 @@ impl NbtList {
 	#[must_use]
 	pub fn len(&self) -> usize { self.elements.len() }
 
+	/// The heap memory held by the list and everything inside of it, see [`NbtElement::memory_size`].
+	#[must_use]
+	pub fn memory_size(&self) -> usize {
+		core::mem::size_of::<Vec<NbtElement>>() + self.elements.capacity() * core::mem::size_of::<NbtElement>() + self.elements.iter().map(NbtElement::memory_size).sum::<usize>()
+	}
+
 	#[inline]
 	#[must_use]
 	pub fn is_empty(&self) -> bool { self.elements.is_empty() }
@@ -301,6 +374,7 @@ impl NbtList {
 }
 
 impl NbtList {
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render_root(&self, builder: &mut VertexBufferBuilder, str: &str, ctx: &mut RenderContext) {
 		let mut remaining_scroll = builder.scroll() / 16;
@@ -331,7 +405,7 @@ impl NbtList {
 		}
 
 		if self.open {
-			ctx.x_offset += 16;
+			ctx.indent();
 
 			for (idx, element) in self.children().enumerate() {
 				if ctx.y_offset > builder.window_height() {
@@ -349,13 +423,13 @@ impl NbtList {
 				ctx.draw_held_entry_bar(ctx.pos(), builder, |x, y| pos == (x, y), |id| (id != NbtChunk::ID) && (id == self.element || self.is_empty()));
 
 				if remaining_scroll == 0 {
-					builder.draw_texture(
+					ctx.draw_connection(
 						ctx.pos() - (16, 0),
-						CONNECTION_UV,
 						(
 							16,
 							(idx != self.len() - 1) as usize * 7 + 9,
 						),
+						builder,
 					);
 				}
 				ctx.check_for_key_duplicate(|_, _| false, false);
@@ -371,12 +445,13 @@ impl NbtList {
 				ctx.draw_held_entry_bar(ctx.pos(), builder, |x, y| pos == (x, y + 8), |id| (id != NbtChunk::ID) && (id == self.element || self.is_empty()));
 			}
 
-			ctx.x_offset -= 16;
+			ctx.dedent();
 		} else {
 			ctx.skip_line_numbers(self.true_height() - 1);
 		}
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render(&self, builder: &mut VertexBufferBuilder, name: Option<&str>, remaining_scroll: &mut usize, tail: bool, ctx: &mut RenderContext) {
 		let mut y_before = ctx.y_offset;
@@ -415,7 +490,7 @@ impl NbtList {
 		let x_before = ctx.x_offset - 16;
 
 		if self.open {
-			ctx.x_offset += 16;
+			ctx.indent();
 
 			for (idx, element) in self.children().enumerate() {
 				if ctx.y_offset > builder.window_height() {
@@ -433,13 +508,13 @@ impl NbtList {
 				ctx.draw_held_entry_bar(ctx.pos(), builder, |x, y| pos == (x, y), |id| (id != NbtChunk::ID) && (id == self.element || self.is_empty()));
 
 				if *remaining_scroll == 0 {
-					builder.draw_texture(
+					ctx.draw_connection(
 						ctx.pos() - (16, 0),
-						CONNECTION_UV,
 						(
 							16,
 							(idx != self.len() - 1) as usize * 7 + 9,
 						),
+						builder,
 					);
 				}
 				ctx.check_for_key_duplicate(|_, _| false, false);
@@ -458,11 +533,11 @@ impl NbtList {
 			let difference = ctx.y_offset - y_before;
 			if !tail {
 				for i in 0..difference / 16 {
-					builder.draw_texture((x_before, y_before + i * 16), CONNECTION_UV, (8, 16));
+					ctx.draw_connection((x_before, y_before + i * 16), (8, 16), builder);
 				}
 			}
 
-			ctx.x_offset -= 16;
+			ctx.dedent();
 		} else {
 			ctx.skip_line_numbers(self.true_height() - 1);
 		}
@@ -580,6 +655,7 @@ impl NbtList {
 		}
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render_icon(pos: impl Into<(usize, usize)>, z: ZOffset, builder: &mut VertexBufferBuilder) { builder.draw_texture_z(pos, z, LIST_UV, (16, 16)); }
 
@@ -707,3 +783,52 @@ impl<'a> DoubleEndedIterator for ValueMutIterator<'a> {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+
+	use crate::{config, file};
+	use crate::elements::element::NbtElement;
+	use crate::elements::null::NbtNull;
+	use crate::SortAlgorithm;
+
+	use super::{EndListError, NbtList};
+
+	/// A file of the compound `{l: []}` with `l` a list of `TAG_End` written with a length of `len`.
+	fn end_list(len: u32) -> Vec<u8> {
+		[&[0x0A, 0x00, 0x00, NbtList::ID, 0x00, 0x01, b'l', NbtNull::ID][..], &len.to_be_bytes(), &[0x00]].concat()
+	}
+
+	#[test]
+	fn empty_lists_of_end_are_read() {
+		config::set_test_lenient_lists(false);
+		let (nbt, _, end_lists) = NbtElement::from_be_file_counted(&end_list(0), SortAlgorithm::None).expect("empty lists of TAG_End are valid");
+		assert_eq!(end_lists, 0);
+		let list = nbt.as_compound().and_then(|compound| compound.get(0)).and_then(|(_, value)| value.as_list()).expect("the list was read");
+		assert_eq!((list.element, list.len()), (NbtNull::ID, 0));
+	}
+
+	#[test]
+	fn lists_of_end_with_a_length_are_refused_with_why() {
+		config::set_test_lenient_lists(false);
+		let e = NbtElement::from_be_file_counted(&end_list(3), SortAlgorithm::None).expect_err("lists of TAG_End can't have a length");
+		assert_eq!(e.downcast_ref::<EndListError>(), Some(&EndListError { len: 3 }));
+		assert!(e.to_string().contains("`lenient_lists=true`"), "{e}");
+		// opening the file says why too, instead of not finding its type
+		let e = file::from_file(Path::new("test.nbt"), end_list(3), SortAlgorithm::None).expect_err("lists of TAG_End can't have a length");
+		assert_eq!(e.to_string(), "Failed to parse NBT: Found a list of 3 TAG_End elements, set `lenient_lists=true` in the config to read it as an empty list");
+	}
+
+	#[test]
+	fn lenient_lists_of_end_with_a_length_are_read_as_empty() {
+		config::set_test_lenient_lists(true);
+		let (nbt, _, end_lists) = NbtElement::from_be_file_counted(&end_list(3), SortAlgorithm::None).expect("lenient lists are read");
+		config::set_test_lenient_lists(false);
+		assert_eq!(end_lists, 1);
+		let list = nbt.as_compound().and_then(|compound| compound.get(0)).and_then(|(_, value)| value.as_list()).expect("the list was read");
+		assert_eq!(list.len(), 0);
+		// and written back the way vanilla writes them
+		assert_eq!(nbt.to_be_file(), end_list(0));
+	}
+}
@@ -1,10 +1,16 @@
-use std::fmt::{Display, Formatter, Write};
+use std::fmt::{Display, Formatter};
+#[cfg(feature = "gui")]
+use std::fmt::Write;
 
+#[cfg(feature = "gui")]
 use crate::assets::{BASE_Z, JUST_OVERLAPPING_BASE_TEXT_Z, ZOffset};
+#[cfg(feature = "gui")]
 use crate::color::TextColor;
 use crate::encoder::UncheckedBufWriter;
 use crate::formatter::PrettyFormatter;
+#[cfg(feature = "gui")]
 use crate::RenderContext;
+#[cfg(feature = "gui")]
 use crate::vertex_buffer_builder::VertexBufferBuilder;
 
 #[derive(Clone)]
@@ -20,6 +26,7 @@ impl NbtNull {
     #[inline]
     pub fn to_le_bytes(&self, _: &mut UncheckedBufWriter) {}
 
+    #[cfg(feature = "gui")]
     #[inline]
     pub fn render(&self, builder: &mut VertexBufferBuilder, name: Option<&str>, ctx: &mut RenderContext) {
         ctx.line_number();
@@ -39,6 +46,7 @@ impl NbtNull {
         ctx.y_offset += 16;
     }
 
+    #[cfg(feature = "gui")]
     #[inline]
     pub fn render_icon(pos: impl Into<(usize, usize)>, z: ZOffset, builder: &mut VertexBufferBuilder) { builder.draw_texture_z(pos, z, (240, 240), (16, 16)); }
 }
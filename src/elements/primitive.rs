@@ -14,7 +14,7 @@ macro_rules! primitive {
 			pub const ID: u8 = $id;
 
 			#[inline]
-			pub fn to_be_bytes(&self, writer: &mut UncheckedBufWriter) { writer.write(self.value.to_be_bytes().as_ref()); }
+			pub fn to_be_bytes(self, writer: &mut UncheckedBufWriter) { writer.write(self.value.to_be_bytes().as_ref()); }
 
 			#[inline]
 			pub fn from_be_bytes(decoder: &mut BigEndianDecoder) -> Option<Self> {
@@ -27,7 +27,7 @@ macro_rules! primitive {
 			}
 
 			#[inline]
-			pub fn to_le_bytes(&self, writer: &mut UncheckedBufWriter) { writer.write(self.value.to_le_bytes().as_ref()); }
+			pub fn to_le_bytes(self, writer: &mut UncheckedBufWriter) { writer.write(self.value.to_le_bytes().as_ref()); }
 			
 			#[inline]
 			pub fn from_le_bytes(decoder: &mut LittleEndianDecoder) -> Option<Self> {
@@ -39,6 +39,7 @@ macro_rules! primitive {
 				}
 			}
 
+			#[cfg(feature = "gui")]
 			#[inline]
 			pub fn render(&self, builder: &mut VertexBufferBuilder, name: Option<&str>, ctx: &mut RenderContext) {
 				ctx.line_number();
@@ -60,11 +61,24 @@ macro_rules! primitive {
 				ctx.y_offset += 16;
 			}
 
+			#[cfg(feature = "gui")]
 			#[inline]
 			pub fn render_icon(pos: impl Into<(usize, usize)>, z: ZOffset, builder: &mut VertexBufferBuilder) { builder.draw_texture_z(pos, z, $uv, (16, 16)); }
 
 			#[inline]
 			pub fn value(&self) -> CompactString { $compact_format(self.value) }
+
+			/// Applies `operation` to the value (see [`Arithmetic`]), leaving it as is and returning `false` when that's undefined.
+			#[inline]
+			pub fn map_value(&mut self, operation: $crate::elements::primitive::Operation, operand: f64) -> bool {
+				match $crate::elements::primitive::Arithmetic::apply(self.value, operation, operand) {
+					Some(value) => {
+						self.value = value;
+						true
+					}
+					None => false,
+				}
+			}
 		}
 
 		impl Display for $name {
@@ -86,3 +100,88 @@ macro_rules! primitive {
 		}
 	};
 }
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Operation {
+	Add,
+	Subtract,
+	Multiply,
+	Divide,
+}
+
+impl Operation {
+	/// Reads an operator followed by a number, like `+16`, `- 2.5` or `*2`, into the operation and its operand.
+	#[must_use]
+	pub fn parse(str: &str) -> Option<(Self, f64)> {
+		let str = str.trim();
+		let mut chars = str.chars();
+		let operation = match chars.next()? {
+			'+' => Self::Add,
+			'-' => Self::Subtract,
+			'*' | 'x' | '×' => Self::Multiply,
+			'/' | '÷' => Self::Divide,
+			_ => return None,
+		};
+		let operand = chars.as_str().trim().parse::<f64>().ok()?;
+		operand.is_finite().then_some((operation, operand))
+	}
+}
+
+/// Numbers an [`Operation`] can be applied to, the result saturates at the bounds of the type and is `None` when it's undefined, like dividing by zero.
+///
+/// Integers use exact integer arithmetic (division rounds towards zero) unless the operand has a fractional part, in which case the result is rounded to the nearest integer.
+pub trait Arithmetic: Sized {
+	fn apply(self, operation: Operation, operand: f64) -> Option<Self>;
+}
+
+macro_rules! integer_arithmetic {
+	($($t:ty),*) => {
+		$(impl Arithmetic for $t {
+			fn apply(self, operation: Operation, operand: f64) -> Option<Self> {
+				// an i64 times an i64 (or anything smaller) always fits in an i128
+				let result = if operand.fract() == 0.0 && operand.abs() <= i64::MAX as f64 {
+					let (value, operand) = (i128::from(self), operand as i128);
+					match operation {
+						Operation::Add => value + operand,
+						Operation::Subtract => value - operand,
+						Operation::Multiply => value * operand,
+						Operation::Divide => value.checked_div(operand)?,
+					}
+				} else {
+					let (value, operand) = (self as f64, operand);
+					let result = match operation {
+						Operation::Add => value + operand,
+						Operation::Subtract => value - operand,
+						Operation::Multiply => value * operand,
+						Operation::Divide => value / operand,
+					};
+					// `as` casts from floats saturate
+					result.round() as i128
+				};
+				Some(result.clamp(Self::MIN.into(), Self::MAX.into()) as Self)
+			}
+		})*
+	};
+}
+
+macro_rules! float_arithmetic {
+	($($t:ty),*) => {
+		$(impl Arithmetic for $t {
+			fn apply(self, operation: Operation, operand: f64) -> Option<Self> {
+				let value = f64::from(self);
+				let result = match operation {
+					Operation::Add => value + operand,
+					Operation::Subtract => value - operand,
+					Operation::Multiply => value * operand,
+					Operation::Divide if operand == 0.0 => return None,
+					Operation::Divide => value / operand,
+				};
+				if result.is_nan() { return None }
+				Some(result.clamp(Self::MIN.into(), Self::MAX.into()) as Self)
+			}
+		})*
+	};
+}
+
+integer_arithmetic!(i8, i16, i32, i64);
+float_arithmetic!(f32, f64);
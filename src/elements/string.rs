@@ -6,10 +6,14 @@ use std::ptr::NonNull;
 
 use compact_str::CompactString;
 
+#[cfg(feature = "gui")]
 use crate::assets::{BASE_Z, JUST_OVERLAPPING_BASE_TEXT_Z, STRING_UV, ZOffset};
 use crate::be_decoder::BigEndianDecoder;
 use crate::encoder::UncheckedBufWriter;
-use crate::{RenderContext, StrExt, VertexBufferBuilder};
+use crate::StrExt;
+#[cfg(feature = "gui")]
+use crate::{RenderContext, VertexBufferBuilder};
+#[cfg(feature = "gui")]
 use crate::color::TextColor;
 use crate::formatter::PrettyFormatter;
 use crate::le_decoder::LittleEndianDecoder;
@@ -91,6 +95,7 @@ impl NbtString {
 }
 
 impl NbtString {
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render(&self, builder: &mut VertexBufferBuilder, name: Option<&str>, ctx: &mut RenderContext) {
 		use std::fmt::Write;
@@ -112,6 +117,7 @@ impl NbtString {
 		ctx.y_offset += 16;
 	}
 
+	#[cfg(feature = "gui")]
 	#[inline]
 	pub fn render_icon(pos: impl Into<(usize, usize)>, z: ZOffset, builder: &mut VertexBufferBuilder) { builder.draw_texture_z(pos, z, STRING_UV, (16, 16)); }
 }
@@ -185,6 +191,10 @@ impl TwentyThree {
 		}
 	}
 
+	/// The bytes of the string kept on the heap, the ones of up to 23 bytes are stored inline.
+	#[must_use]
+	pub fn heap_size(&self) -> usize { unsafe { if self.heap.variant == 254 { self.heap.len } else { 0 } } }
+
 	#[must_use]
 	pub fn as_str(&self) -> &str {
 		unsafe {
@@ -1,6 +1,11 @@
 use std::alloc::{alloc, dealloc, realloc, Layout};
+use std::cell::Cell;
+use std::io::{BufWriter, Write};
 use std::intrinsics::likely;
 use std::mem::MaybeUninit;
+use std::rc::Rc;
+
+use crate::mutf8;
 
 const WIDTH: usize = 1 << 24;
 
@@ -9,6 +14,43 @@ pub struct UncheckedBufWriter {
 	buf_len: usize,
 	inner: *mut u8,
 	inner_len: usize,
+	/// Where the buffer is flushed to whenever it fills up instead of growing `inner`, along with the first error of writing to it, see [`Self::with_sink`].
+	sink: Option<(Box<dyn Sink>, std::io::Result<()>)>,
+	/// The length of the first string too long for its `u16` length prefix, which fails the write, see [`Self::write_be_str`].
+	too_long: Option<usize>,
+}
+
+/// Somewhere the bytes of an [`UncheckedBufWriter`] can be streamed to, which needs to be told when they're all written so it can write whatever it still holds, like the trailer of a gzip stream.
+pub trait Sink: Write {
+	fn finish(self: Box<Self>) -> std::io::Result<()>;
+}
+
+impl<W: Write> Sink for BufWriter<W> {
+	fn finish(mut self: Box<Self>) -> std::io::Result<()> { self.flush() }
+}
+
+impl<W: Write> Sink for flate2::write::GzEncoder<W> {
+	fn finish(self: Box<Self>) -> std::io::Result<()> { (*self).finish()?.flush() }
+}
+
+impl<W: Write> Sink for flate2::write::ZlibEncoder<W> {
+	fn finish(self: Box<Self>) -> std::io::Result<()> { (*self).finish()?.flush() }
+}
+
+/// A sink which only counts what is written to it, see [`UncheckedBufWriter::count`].
+struct Counter(Rc<Cell<usize>>);
+
+impl Write for Counter {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		self.0.set(self.0.get() + buf.len());
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+impl Sink for Counter {
+	fn finish(self: Box<Self>) -> std::io::Result<()> { Ok(()) }
 }
 
 impl Default for UncheckedBufWriter {
@@ -18,6 +60,8 @@ impl Default for UncheckedBufWriter {
 			buf_len: 0,
 			inner: core::ptr::null_mut(),
 			inner_len: 0,
+			sink: None,
+			too_long: None,
 		}
 	}
 }
@@ -36,6 +80,22 @@ impl Drop for UncheckedBufWriter {
 impl UncheckedBufWriter {
 	pub fn new() -> Self { Self::default() }
 
+	/// A writer streaming everything written to it into `sink` a buffer at a time, so that huge files are never held in memory as a whole, finished with [`Self::finish_into_sink`] rather than [`Self::finish`].
+	pub fn with_sink(sink: impl Sink + 'static) -> Self {
+		let mut writer = Self::default();
+		writer.sink = Some((Box::new(sink), Ok(())));
+		writer
+	}
+
+	/// The amount of bytes `write` writes, counted as the buffer fills up so that they're never all held in memory.
+	pub fn count(write: impl FnOnce(&mut Self)) -> usize {
+		let count = Rc::new(Cell::new(0));
+		let mut writer = Self::with_sink(Counter(Rc::clone(&count)));
+		write(&mut writer);
+		let _ = writer.finish_into_sink();
+		count.get()
+	}
+
 	pub const fn remaining(&self) -> usize { WIDTH - 1 - self.buf_len }
 
 	pub fn write(&mut self, bytes: &[u8]) {
@@ -52,19 +112,44 @@ impl UncheckedBufWriter {
 		}
 	}
 
+	/// Writes `str` as the modified UTF-8 of Java, see [`mutf8::encode`].
+	///
+	/// Strings are prefixed by a `u16` length, so longer ones fail the write (see [`Self::finish_into_sink`]), they're cut down to the whole characters which fit (see [`mutf8::truncate`]) to keep the rest of the bytes readable until then.
 	pub fn write_be_str(&mut self, str: &str) {
-		self.write(&(str.len() as u16).to_be_bytes());
-		self.write(str.as_bytes());
+		let encoded = mutf8::encode(str);
+		let bytes = self.fit_str(&encoded);
+		self.write(&(bytes.len() as u16).to_be_bytes());
+		self.write(bytes);
 	}
 
+	/// [`Self::write_be_str`] for little endian NBT, which is standard UTF-8.
 	pub fn write_le_str(&mut self, str: &str) {
-		self.write(&(str.len() as u16).to_le_bytes());
-		self.write(str.as_bytes());
+		let bytes = self.fit_str(str.as_bytes());
+		self.write(&(bytes.len() as u16).to_le_bytes());
+		self.write(bytes);
+	}
+
+	fn fit_str<'a>(&mut self, bytes: &'a [u8]) -> &'a [u8] {
+		let fit = mutf8::truncate(bytes, u16::MAX as usize);
+		if fit.len() < bytes.len() {
+			self.too_long.get_or_insert(bytes.len());
+		}
+		fit
 	}
 
+	/// The error of a write with a string too long for NBT, see [`Self::write_be_str`].
+	fn too_long_error(len: usize) -> std::io::Error { std::io::Error::new(std::io::ErrorKind::InvalidData, format!("A string of {len} bytes is longer than the {max} bytes NBT can hold", max = u16::MAX)) }
+
 	#[cold]
 	#[inline(never)]
 	unsafe fn write_pushing_cold(&mut self, bytes: &[u8]) {
+		if let Some((sink, result)) = &mut self.sink {
+			if result.is_ok() {
+				*result = sink.write_all(core::slice::from_raw_parts(self.buf.cast::<u8>(), self.buf_len)).and_then(|()| sink.write_all(bytes));
+			}
+			self.buf_len = 0;
+			return
+		}
 		let malloc_size = (self.inner_len + WIDTH - 1) & !(WIDTH - 1);
 		let new_size = (self.inner_len + bytes.len() + self.buf_len + WIDTH - 1) & !(WIDTH - 1);
 		self.inner = if self.inner.is_null() {
@@ -88,6 +173,7 @@ impl UncheckedBufWriter {
 	}
 
 	pub fn finish(mut self) -> Vec<u8> {
+		debug_assert!(self.sink.is_none(), "writers with a sink are finished into it");
 		unsafe {
 			let malloc_size = (self.inner_len + WIDTH - 1) & !(WIDTH - 1);
 			self.inner = if self.inner.is_null() {
@@ -106,4 +192,13 @@ impl UncheckedBufWriter {
 			Vec::from_raw_parts(self.inner, self.inner_len, self.inner_len)
 		}
 	}
+
+	/// Writes what's left of the buffer into the sink of [`Self::with_sink`] and finishes it, returning the first error of writing to it, or of a string which was too long to be written.
+	pub fn finish_into_sink(mut self) -> std::io::Result<()> {
+		let Some((mut sink, result)) = self.sink.take() else { return Ok(()) };
+		result?;
+		if let Some(len) = self.too_long { return Err(Self::too_long_error(len)) }
+		sink.write_all(unsafe { core::slice::from_raw_parts(self.buf.cast::<u8>(), self.buf_len) })?;
+		sink.finish()
+	}
 }
@@ -0,0 +1,517 @@
+use std::ffi::OsStr;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use compact_str::CompactString;
+use flate2::Compression;
+use zune_inflate::DeflateDecoder;
+
+use crate::{config, lz4, SortAlgorithm};
+#[cfg(feature = "gui")]
+use crate::archive;
+#[cfg(feature = "gui")]
+use crate::assets::{GZIP_FILE_TYPE_UV, LITTLE_ENDIAN_HEADER_NBT_FILE_TYPE_UV, LITTLE_ENDIAN_NBT_FILE_TYPE_UV, MCA_FILE_TYPE_UV, NBT_FILE_TYPE_UV, SNBT_FILE_TYPE_UV, ZLIB_FILE_TYPE_UV};
+use crate::elements::chunk::{EncodedExternalChunks, ExternalChunks, NbtRegion};
+#[cfg(feature = "gui")]
+use crate::elements::compound::NbtCompound;
+use crate::elements::element::{NbtElement, PathSegment};
+use crate::elements::list::EndListError;
+#[cfg(feature = "gui")]
+use crate::elements::list::NbtList;
+use crate::encoder::{Sink, UncheckedBufWriter};
+#[cfg(feature = "gui")]
+use crate::vertex_buffer_builder::Vec2u;
+
+/// Detects the format of `buf` read from `path` and parses it, along with the name of its root compound for the formats which keep it.
+///
+/// # Errors
+///
+/// * `buf` isn't any of the formats of [`FileFormat`]
+pub fn from_file(path: &Path, buf: Vec<u8>, sort_algorithm: SortAlgorithm) -> Result<(NbtElement, FileFormat, CompactString)> {
+	let unnamed = |(nbt, format)| (nbt, format, CompactString::new_inline(""));
+	let named = |bytes: &[u8]| NbtElement::from_be_file_counted(bytes, sort_algorithm).map(|(nbt, name, _)| (nbt, name)).map_err(|e| anyhow!("Failed to parse NBT: {e}"));
+	Ok(if let Some("mca" | "mcr") = path.extension().and_then(OsStr::to_str) {
+		unnamed((
+			NbtElement::from_be_mca(buf.as_slice(), sort_algorithm, ExternalChunks::Files(path)).map_err(|e| anyhow!("Failed to parse MCA file: {e}"))?,
+			FileFormat::Mca,
+		))
+	} else if let Some(0x1F8B) = buf.first_chunk::<2>().copied().map(u16::from_be_bytes) {
+		let (nbt, root_name) = named(
+			&DeflateDecoder::new(buf.as_slice())
+				.decode_gzip()
+				.context("Failed to decode gzip compressed NBT")?,
+		)?;
+		(nbt, FileFormat::Gzip, root_name)
+	} else if let Some(0x7801 | 0x789C | 0x78DA) = buf.first_chunk::<2>().copied().map(u16::from_be_bytes) {
+		let (nbt, root_name) = named(
+			&DeflateDecoder::new(buf.as_slice())
+				.decode_zlib()
+				.context("Failed to decode zlib compressed NBT")?,
+		)?;
+		(nbt, FileFormat::Zlib, root_name)
+	} else if lz4::is_lz4(&buf) {
+		let (nbt, root_name) = named(&lz4::decompress(buf.as_slice()).context("Failed to decode LZ4 compressed NBT")?)?;
+		(nbt, FileFormat::Lz4, root_name)
+	} else if let Some((nbt, root_name)) = NbtElement::from_be_file_named(buf.as_slice(), sort_algorithm) {
+		(nbt, FileFormat::Nbt, root_name)
+	} else if let Some((nbt, header)) = NbtElement::from_le_file(buf.as_slice(), sort_algorithm) {
+		unnamed((nbt, if header { FileFormat::LittleEndianHeaderNbt } else { FileFormat::LittleEndianNbt }))
+	} else if let Some((_, nbt)) = core::str::from_utf8(&buf).ok().and_then(|s| NbtElement::from_str(s, sort_algorithm)) {
+		unnamed((nbt, FileFormat::Snbt))
+	} else {
+		// read once more for why, a refused list of `TAG_End` says more than not finding the type of the file
+		let refused = [NbtElement::from_be_file_counted(&buf, sort_algorithm).err(), NbtElement::from_le_file_counted(&buf, sort_algorithm).err()].into_iter().flatten().find_map(|e| e.downcast::<EndListError>().ok());
+		return Err(match refused {
+			Some(e) => anyhow!("Failed to parse NBT: {e}"),
+			None => anyhow!("Failed to find file type for file {}", path.file_name().unwrap_or(OsStr::new("")).to_string_lossy()),
+		})
+	})
+}
+
+/// Parses every NBT entry of a zip archive into a compound keyed by the entry's path, entries which fail to parse are skipped.
+///
+/// # Errors
+///
+/// * `buf` isn't a zip archive or none of its entries are NBT
+#[cfg(feature = "gui")]
+pub fn from_archive(buf: &[u8], sort_algorithm: SortAlgorithm) -> Result<NbtElement> { from_archive_entries(archive::nbt_entries(buf)?, sort_algorithm) }
+
+/// [`from_archive`], of the entries already read by [`archive::nbt_entries`].
+///
+/// # Errors
+///
+/// * None of `entries` parse as NBT
+#[cfg(feature = "gui")]
+pub fn from_archive_entries(entries: Vec<(String, Vec<u8>)>, sort_algorithm: SortAlgorithm) -> Result<NbtElement> {
+	let mut compound = NbtCompound::new();
+	for (name, bytes) in entries {
+		let Ok((nbt, _, _)) = from_file(Path::new(&name), bytes, sort_algorithm) else { continue };
+		if nbt.id() == NbtCompound::ID || nbt.id() == NbtList::ID {
+			compound.insert(compound.len(), CompactString::from(name), nbt);
+		}
+	}
+	if compound.is_empty() { return Err(anyhow!("Archive does not contain any NBT files")) }
+	Ok(NbtElement::Compound(compound))
+}
+
+/// Encodes `value` as `format` along with its external chunks (see [`FileFormat::encode_external`]), when paranoid saving is enabled, the bytes are also parsed back and compared against it.
+///
+/// # Errors
+///
+/// * Paranoid saving is enabled and the bytes don't read back as `value`
+/// * See [`check_strings`]
+pub fn encode(value: &NbtElement, format: FileFormat, root_name: &str) -> Result<(Vec<u8>, EncodedExternalChunks)> {
+	check_strings(value, format, root_name)?;
+	encode_with(value, format, root_name, config::get_paranoid_save(), FileFormat::encode_external)
+}
+
+/// [`encode`] through `encoder` instead of [`FileFormat::encode_external`], checked when `paranoid` is set.
+fn encode_with(value: &NbtElement, format: FileFormat, root_name: &str, paranoid: bool, encoder: impl FnOnce(FileFormat, &NbtElement, &str) -> (Vec<u8>, EncodedExternalChunks)) -> Result<(Vec<u8>, EncodedExternalChunks)> {
+	let (bytes, external) = encoder(format, value, root_name);
+	if paranoid && !format.decode_named(&bytes, SortAlgorithm::None, ExternalChunks::Encoded(&external)).is_some_and(|(nbt, name)| nbt.deep_eq(value) && (name == root_name || !format.has_root_name())) {
+		log::error!("Paranoid save failed, {} bytes of {} did not read back as the same NBT", bytes.len(), format.into_str());
+		return Err(anyhow!("Saved {format} data did not read back as the same NBT, the file was not written", format = format.into_str()))
+	}
+	Ok((bytes, external))
+}
+
+/// Fails if a key or string of `value` (or `root_name`) is too long to be written as `format`, naming its path, see [`NbtElement::too_long_string`].
+///
+/// # Errors
+///
+/// * A key or string is longer than the 65535 bytes NBT can hold
+pub fn check_strings(value: &NbtElement, format: FileFormat, root_name: &str) -> Result<()> {
+	if format == FileFormat::Snbt { return Ok(()) }
+	let little_endian = matches!(format, FileFormat::LittleEndianNbt | FileFormat::LittleEndianHeaderNbt);
+	let max = u16::MAX;
+	if format.has_root_name() && crate::mutf8::encode(root_name).len() > max as usize {
+		return Err(anyhow!("The name of the root is longer than the {max} bytes NBT can hold, the file was not written"))
+	}
+	if let Some((path, len, key)) = value.too_long_string(little_endian) {
+		let what = if key { "key" } else { "string" };
+		return Err(anyhow!("The {what} at {path} is {len} bytes long, longer than the {max} bytes NBT can hold, the file was not written", path = PathSegment::join(&path)))
+	}
+	Ok(())
+}
+
+/// Encodes `value` as `format` and writes it to `path`, along with the `.mcc` files of the chunks too big for a region file, external chunks which are no longer too big are removed.
+///
+/// The file is written to a temporary file next to it which then replaces it, streamed as it's encoded (see [`FileFormat::encode_into`]) unless it has to be checked first, which paranoid saving and region files not named like one (which can't have external chunks) do.
+///
+/// # Errors
+///
+/// * The file couldn't be written
+/// * See [`encode`]
+#[cfg(not(target_arch = "wasm32"))]
+pub fn to_file(path: &Path, value: &NbtElement, format: FileFormat, root_name: &str) -> Result<()> {
+	let region = if format == FileFormat::Mca { NbtRegion::coordinates_of(path) } else { None };
+	let external = if config::get_paranoid_save() || (format == FileFormat::Mca && region.is_none()) {
+		let (bytes, external) = encode(value, format, root_name)?;
+		if region.is_none() && !external.is_empty() {
+			return Err(anyhow!("{n} chunks are too big for the region file and need to be saved next to it, which requires the file to be named like a region file (r.<x>.<z>.mca)", n = external.len()))
+		}
+		write_through_temp(path, |mut file| file.write_all(&bytes))?;
+		external
+	} else {
+		check_strings(value, format, root_name)?;
+		write_through_temp(path, |file| format.encode_into(value, root_name, std::io::BufWriter::new(file)))?
+	};
+	if let Some((x, z, _)) = region {
+		let mut written = Vec::with_capacity(external.len());
+		for (pos, bytes) in &external {
+			let name = ExternalChunks::file_name((x, z), *pos);
+			write_through_temp(&path.with_file_name(&name), |mut file| file.write_all(bytes))?;
+			written.push(name);
+		}
+		// the external files of the region which are left over, instead of trying every slot it has
+		let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+		for entry in std::fs::read_dir(dir)?.flatten() {
+			let name = entry.file_name();
+			let Some(name) = name.to_str() else { continue };
+			if ExternalChunks::region_of(name) == Some((x, z)) && !written.iter().any(|written| written == name) {
+				std::fs::remove_file(entry.path())?;
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Writes `path` through a temporary file next to it which replaces it once `write` succeeds, so that a failed write doesn't leave it half written.
+#[cfg(not(target_arch = "wasm32"))]
+fn write_through_temp<T>(path: &Path, write: impl FnOnce(std::fs::File) -> std::io::Result<T>) -> Result<T> {
+	let name = path.file_name().map_or_else(Default::default, |name| name.to_string_lossy());
+	let temp = path.with_file_name(format!(".{name}.tmp"));
+	match std::fs::File::create(&temp).and_then(write).and_then(|value| std::fs::rename(&temp, path).map(|()| value)) {
+		Ok(value) => Ok(value),
+		Err(e) => {
+			let _ = std::fs::remove_file(&temp);
+			Err(e.into())
+		}
+	}
+}
+
+/// What parsing read past instead of failing, see [`FileFormat::decode_counted`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ParseCounts {
+	/// Entries keyed like an entry before them in their compound, which are kept alongside it.
+	pub duplicate_keys: usize,
+	/// Lists of `TAG_End` with a length, read as empty lists with `lenient_lists`.
+	pub end_lists: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FileFormat {
+	Nbt,
+	Gzip,
+	Zlib,
+	Snbt,
+	LittleEndianNbt,
+	LittleEndianHeaderNbt,
+
+	Lz4,
+
+	Mca,
+}
+
+impl FileFormat {
+	#[must_use]
+	pub const fn cycle(self) -> Self {
+		match self {
+			Self::Nbt => Self::Gzip,
+			Self::Gzip => Self::Zlib,
+			Self::Zlib => Self::LittleEndianNbt,
+			Self::LittleEndianNbt => Self::LittleEndianHeaderNbt,
+			Self::LittleEndianHeaderNbt => Self::Snbt,
+			Self::Snbt => Self::Nbt,
+
+			// has to be separate
+			Self::Mca => Self::Mca,
+			Self::Lz4 => Self::Lz4,
+		}
+	}
+
+	#[must_use]
+	pub const fn rev_cycle(self) -> Self {
+		match self {
+			Self::Nbt => Self::Snbt,
+			Self::Gzip => Self::Nbt,
+			Self::Zlib => Self::Gzip,
+			Self::LittleEndianNbt => Self::Zlib,
+			Self::LittleEndianHeaderNbt => Self::LittleEndianNbt,
+			Self::Snbt => Self::LittleEndianHeaderNbt,
+
+			// has to be separate
+			Self::Mca => Self::Mca,
+			Self::Lz4 => Self::Lz4,
+		}
+	}
+
+	#[must_use]
+	pub fn encode(self, data: &NbtElement) -> Vec<u8> {
+		match self {
+			Self::Nbt | Self::Mca => data.to_be_file(),
+			Self::Gzip | Self::Zlib | Self::Lz4 => self.compress(&data.to_be_file()),
+			Self::Snbt => data.to_string().into_bytes(),
+			format @ (Self::LittleEndianNbt | Self::LittleEndianHeaderNbt) => data.to_le_file(format == Self::LittleEndianHeaderNbt),
+		}
+	}
+
+	/// [`Self::encode`], along with the compressed chunks of a region which are too big for its file by their position, see [`NbtRegion::to_be_bytes_external`].
+	#[must_use]
+	pub fn encode_external(self, data: &NbtElement, root_name: &str) -> (Vec<u8>, EncodedExternalChunks) {
+		if self == Self::Mca && let Some(region) = data.as_region() {
+			let mut writer = UncheckedBufWriter::new();
+			let external = region.to_be_bytes_external(&mut writer);
+			(writer.finish(), external)
+		} else if self.has_root_name() {
+			let nbt = data.to_be_file_named(root_name);
+			(if self == Self::Nbt { nbt } else { self.compress(&nbt) }, vec![])
+		} else {
+			(self.encode(data), vec![])
+		}
+	}
+
+	/// [`Self::encode_external`], streaming the bytes into `sink` as they're encoded instead of holding the whole file in memory, the compressed chunks of regions are still all held until their sector offsets are written.
+	///
+	/// LZ4, SNBT and little endian NBT are encoded at once and written afterwards.
+	pub fn encode_into(self, data: &NbtElement, root_name: &str, sink: impl Sink + 'static) -> std::io::Result<EncodedExternalChunks> {
+		if self == Self::Mca && let Some(region) = data.as_region() {
+			let mut writer = UncheckedBufWriter::with_sink(sink);
+			let external = region.to_be_bytes_external(&mut writer);
+			writer.finish_into_sink()?;
+			return Ok(external)
+		}
+		let (mut writer, name) = match self {
+			Self::Nbt => (UncheckedBufWriter::with_sink(sink), root_name),
+			Self::Mca => (UncheckedBufWriter::with_sink(sink), ""),
+			Self::Gzip => (UncheckedBufWriter::with_sink(flate2::write::GzEncoder::new(sink, Compression::best())), root_name),
+			Self::Zlib => (UncheckedBufWriter::with_sink(flate2::write::ZlibEncoder::new(sink, Compression::best())), root_name),
+			Self::Lz4 | Self::Snbt | Self::LittleEndianNbt | Self::LittleEndianHeaderNbt => {
+				let (bytes, external) = self.encode_external(data, root_name);
+				let mut sink = Box::new(sink);
+				sink.write_all(&bytes)?;
+				sink.finish()?;
+				return Ok(external)
+			}
+		};
+		data.write_be_file_named(name, &mut writer);
+		writer.finish_into_sink()?;
+		Ok(vec![])
+	}
+
+	/// If the format keeps the name of its root compound.
+	#[must_use]
+	pub const fn has_root_name(self) -> bool { matches!(self, Self::Nbt | Self::Gzip | Self::Zlib | Self::Lz4) }
+
+	/// Compresses already encoded big endian NBT, formats without compression return it as is.
+	#[must_use]
+	pub fn compress(self, nbt: &[u8]) -> Vec<u8> {
+		match self {
+			Self::Gzip => {
+				let mut vec = vec![];
+				let _ = flate2::read::GzEncoder::new(nbt, Compression::best()).read_to_end(&mut vec);
+				vec
+			}
+			Self::Zlib => {
+				let mut vec = vec![];
+				let _ = flate2::read::ZlibEncoder::new(nbt, Compression::best()).read_to_end(&mut vec);
+				vec
+			}
+			Self::Lz4 => lz4::compress(nbt),
+			_ => nbt.to_vec(),
+		}
+	}
+
+	/// Parses the bytes written by [`Self::encode`] back into NBT, with the external chunks of regions read from `external`.
+	#[must_use]
+	pub fn decode(self, bytes: &[u8], sort: SortAlgorithm, external: ExternalChunks) -> Option<NbtElement> { self.decode_named(bytes, sort, external).map(|(nbt, _)| nbt) }
+
+	/// [`Self::decode`], along with the name of the root compound for the formats which keep it.
+	#[must_use]
+	pub fn decode_named(self, bytes: &[u8], sort: SortAlgorithm, external: ExternalChunks) -> Option<(NbtElement, CompactString)> {
+		let unnamed = |nbt| (nbt, CompactString::new_inline(""));
+		match self {
+			Self::Nbt => NbtElement::from_be_file_named(bytes, sort),
+			Self::Mca => NbtElement::from_be_mca(bytes, sort, external).ok().map(unnamed),
+			Self::Gzip => NbtElement::from_be_file_named(&DeflateDecoder::new(bytes).decode_gzip().ok()?, sort),
+			Self::Zlib => NbtElement::from_be_file_named(&DeflateDecoder::new(bytes).decode_zlib().ok()?, sort),
+			Self::Lz4 => NbtElement::from_be_file_named(&lz4::decompress(bytes)?, sort),
+			Self::Snbt => NbtElement::from_str(core::str::from_utf8(bytes).ok()?, sort).map(|(_, nbt)| unnamed(nbt)),
+			Self::LittleEndianNbt | Self::LittleEndianHeaderNbt => NbtElement::from_le_file(bytes, sort).map(|(nbt, _)| unnamed(nbt)),
+		}
+	}
+
+	/// [`Self::decode`], along with what was read past instead of failing, see [`ParseCounts`].
+	#[must_use]
+	pub fn decode_counted(self, bytes: &[u8], sort: SortAlgorithm, external: ExternalChunks) -> Option<(NbtElement, ParseCounts)> {
+		let (nbt, end_lists) = match self {
+			Self::Nbt => NbtElement::from_be_file_counted(bytes, sort).ok().map(|(nbt, _, end_lists)| (nbt, end_lists))?,
+			Self::Mca => NbtRegion::from_be_bytes_counted(bytes, sort, external).ok().map(|(region, end_lists)| (NbtElement::Region(region), end_lists))?,
+			Self::Gzip => NbtElement::from_be_file_counted(&DeflateDecoder::new(bytes).decode_gzip().ok()?, sort).ok().map(|(nbt, _, end_lists)| (nbt, end_lists))?,
+			Self::Zlib => NbtElement::from_be_file_counted(&DeflateDecoder::new(bytes).decode_zlib().ok()?, sort).ok().map(|(nbt, _, end_lists)| (nbt, end_lists))?,
+			Self::Lz4 => NbtElement::from_be_file_counted(&lz4::decompress(bytes)?, sort).ok().map(|(nbt, _, end_lists)| (nbt, end_lists))?,
+			// SNBT can't write lists of `TAG_End` with a length
+			Self::Snbt => (NbtElement::from_str(core::str::from_utf8(bytes).ok()?, sort)?.1, 0),
+			Self::LittleEndianNbt | Self::LittleEndianHeaderNbt => NbtElement::from_le_file_counted(bytes, sort).ok().map(|(nbt, _, end_lists)| (nbt, end_lists))?,
+		};
+		let duplicate_keys = nbt.duplicate_keys();
+		Some((nbt, ParseCounts { duplicate_keys, end_lists }))
+	}
+
+	#[must_use]
+	#[cfg(feature = "gui")]
+	pub const fn uv(self) -> Vec2u {
+		match self {
+			Self::Nbt => NBT_FILE_TYPE_UV,
+			Self::Gzip => GZIP_FILE_TYPE_UV,
+			Self::Zlib => ZLIB_FILE_TYPE_UV,
+			Self::Snbt => SNBT_FILE_TYPE_UV,
+			Self::Mca => MCA_FILE_TYPE_UV,
+			Self::LittleEndianNbt => LITTLE_ENDIAN_NBT_FILE_TYPE_UV,
+			Self::LittleEndianHeaderNbt => LITTLE_ENDIAN_HEADER_NBT_FILE_TYPE_UV,
+			Self::Lz4 => Vec2u::new(240, 240),
+		}
+	}
+
+	#[must_use]
+	pub const fn into_str(self) -> &'static str {
+		match self {
+			Self::Nbt => "Uncompressed",
+			Self::Gzip => "GZip",
+			Self::Zlib => "ZLib",
+			Self::Snbt => "SNBT",
+			Self::Mca => "MCA",
+			Self::Lz4 => "LZ4",
+			Self::LittleEndianNbt => "Little Endian NBT",
+			Self::LittleEndianHeaderNbt => "Little Endian NBT (With Header)",
+		}
+	}
+
+	/// The inverse of [`Self::into_str`].
+	#[must_use]
+	pub fn from_str_name(str: &str) -> Option<Self> {
+		[Self::Nbt, Self::Gzip, Self::Zlib, Self::Snbt, Self::Mca, Self::Lz4, Self::LittleEndianNbt, Self::LittleEndianHeaderNbt].into_iter().find(|format| format.into_str() == str)
+	}
+}
+
+impl Display for FileFormat {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { f.write_str(self.into_str()) }
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::elements::chunk::{NbtChunk, NbtRegion};
+	use crate::elements::element::NbtElement;
+
+	use super::{check_strings, encode_with, from_file, to_file, FileFormat};
+
+	#[test]
+	fn paranoid_saving_rejects_a_broken_encoder() {
+		let value = NbtElement::from_snbt("{a: 1, b: \"text\", c: [L; 1L, 2L]}").expect("the fixture is valid SNBT");
+		// changes the last value of `c`, right before the root's end tag
+		let broken = |format: FileFormat, value: &NbtElement, root_name: &str| {
+			let (mut bytes, external) = format.encode_external(value, root_name);
+			let len = bytes.len();
+			bytes[len - 2] ^= 1;
+			(bytes, external)
+		};
+
+		assert!(encode_with(&value, FileFormat::Nbt, "", true, FileFormat::encode_external).is_ok());
+		assert!(encode_with(&value, FileFormat::Nbt, "", true, broken).is_err());
+		// without paranoid saving the bytes are trusted
+		assert!(encode_with(&value, FileFormat::Nbt, "", false, broken).is_ok());
+		// the root name has to read back too
+		assert!(encode_with(&value, FileFormat::Nbt, "Data", true, |format, value, _| format.encode_external(value, "Level")).is_err());
+	}
+
+	#[test]
+	fn paranoid_saving_compares_every_chunk() {
+		let mut region = NbtElement::Region(NbtRegion::new());
+		for (idx, pos) in [(0, 0), (5, 17)].into_iter().enumerate() {
+			let compound = NbtElement::from_snbt(&format!("{{xPos: {x}, zPos: {z}, Status: \"minecraft:full\"}}", x = pos.0, z = pos.1)).and_then(NbtElement::into_compound).expect("the fixture is a compound");
+			region.insert(idx, NbtElement::Chunk(NbtChunk::from_compound(compound, pos, FileFormat::Zlib, 0))).expect("the positions are free");
+		}
+		let without_last_chunk = |format: FileFormat, value: &NbtElement, root_name: &str| {
+			let mut value = value.clone();
+			let _ = value.remove(1);
+			format.encode_external(&value, root_name)
+		};
+
+		assert!(encode_with(&region, FileFormat::Mca, "", true, FileFormat::encode_external).is_ok());
+		assert!(encode_with(&region, FileFormat::Mca, "", true, without_last_chunk).is_err());
+	}
+
+	#[test]
+	#[cfg(not(target_arch = "wasm32"))]
+	fn only_leftover_external_chunks_of_the_region_are_removed() {
+		use compact_str::CompactString;
+
+		use crate::elements::compound::NbtCompound;
+		use crate::elements::element::{NbtByte, NbtByteArray};
+		use crate::SortAlgorithm;
+
+		let dir = std::env::temp_dir().join(format!("nbtworkbench-external-{}", uuid::Uuid::new_v4()));
+		std::fs::create_dir_all(&dir).expect("the temp dir is writable");
+		// of this region, the first is rewritten and the second is no longer needed, and of the region next to it
+		for name in ["c.0.0.mcc", "c.5.5.mcc", "c.40.0.mcc", "notes.txt"] {
+			std::fs::write(dir.join(name), b"stale").expect("the temp dir is writable");
+		}
+		let mut compound = NbtCompound::new();
+		let bytes = NbtByteArray::from_values(vec![NbtElement::Byte(NbtByte { value: 0 }); 1_100_000]).expect("bytes make a byte array");
+		compound.insert(0, CompactString::new("Data"), NbtElement::ByteArray(bytes));
+		let mut region = NbtRegion::new();
+		// uncompressed, too big for the 255 sectors a chunk can take up inside of its region file
+		assert!(region.insert(0, NbtElement::Chunk(NbtChunk::from_compound(compound, (0, 0), FileFormat::Nbt, 0))).is_ok());
+		let region = NbtElement::Region(region);
+
+		let path = dir.join("r.0.0.mca");
+		to_file(&path, &region, FileFormat::Mca, "").expect("the region is written");
+		let mut files = std::fs::read_dir(&dir).expect("the temp dir is readable").flatten().map(|entry| entry.file_name().to_string_lossy().into_owned()).collect::<Vec<_>>();
+		files.sort();
+		assert_eq!(files, ["c.0.0.mcc", "c.40.0.mcc", "notes.txt", "r.0.0.mca"]);
+		assert_ne!(std::fs::read(dir.join("c.0.0.mcc")).expect("the chunk is written"), b"stale");
+		let bytes = std::fs::read(&path).expect("the region is written");
+		assert!(from_file(&path, bytes, SortAlgorithm::None).is_ok_and(|(nbt, _, _)| nbt.deep_eq(&region)));
+		let _ = std::fs::remove_dir_all(dir);
+	}
+
+	#[test]
+	#[cfg(not(target_arch = "wasm32"))]
+	fn checked_saves_are_written_through_a_temporary_file() {
+		let dir = std::env::temp_dir().join(format!("nbtworkbench-checked-{}", uuid::Uuid::new_v4()));
+		std::fs::create_dir_all(&dir).expect("the temp dir is writable");
+		// not named like a region file, so it's encoded in full and checked before being written
+		let path = dir.join("backup.mca");
+		std::fs::write(&path, b"old").expect("the temp dir is writable");
+		to_file(&path, &NbtElement::Region(NbtRegion::new()), FileFormat::Mca, "").expect("the region is written");
+		assert_eq!(std::fs::read(&path).expect("the region is written").len(), 8192);
+		assert_eq!(std::fs::read_dir(&dir).expect("the temp dir is readable").count(), 1, "the temporary file replaced it");
+
+		assert!(to_file(&dir.join("missing/backup.mca"), &NbtElement::Region(NbtRegion::new()), FileFormat::Mca, "").is_err());
+		assert_eq!(std::fs::read_dir(&dir).expect("the temp dir is readable").count(), 1);
+		let _ = std::fs::remove_dir_all(dir);
+	}
+
+	#[test]
+	fn strings_too_long_for_nbt_are_refused() {
+		let long = "a".repeat(u16::MAX as usize + 1);
+		let value = NbtElement::from_snbt(&format!("{{fine: \"{long}\"}}")).expect("the fixture is valid SNBT");
+		let e = check_strings(&value, FileFormat::Nbt, "").expect_err("the string is too long");
+		assert!(e.to_string().starts_with("The string at fine is 65536 bytes long"), "{e}");
+		// nothing limits SNBT
+		assert!(check_strings(&value, FileFormat::Snbt, "").is_ok());
+		// and the encoder fails by itself
+		assert!(FileFormat::Nbt.encode_into(&value, "", std::io::BufWriter::new(vec![])).is_err());
+
+		let value = NbtElement::from_snbt(&format!("{{a: {{{long}: 1b}}}}")).expect("the fixture is valid SNBT");
+		let e = check_strings(&value, FileFormat::LittleEndianNbt, "").expect_err("the key is too long");
+		assert!(e.to_string().starts_with("The key at a."), "{e}");
+		// nulls take two bytes in modified UTF-8
+		let value = NbtElement::from_snbt(&format!("{{a: \"{nulls}\"}}", nulls = "\\u0000".repeat(u16::MAX as usize / 2 + 1))).expect("the fixture is valid SNBT");
+		assert!(check_strings(&value, FileFormat::Nbt, "").is_err());
+		assert!(check_strings(&value, FileFormat::LittleEndianNbt, "").is_ok());
+	}
+}
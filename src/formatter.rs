@@ -1,18 +1,32 @@
+use std::borrow::Cow;
 use std::fmt::Write;
 
 pub struct PrettyFormatter {
     buf: String,
     current_depth: usize,
+    indent: Cow<'static, str>,
+}
+
+impl Default for PrettyFormatter {
+    fn default() -> Self { Self::new() }
 }
 
 impl PrettyFormatter {
-    const INDENT: u8 = b' ';
-    const INDENT_QUANTITY: usize = 4;
+    const INDENT: &'static str = "    ";
 
     pub const fn new() -> Self {
         Self {
             buf: String::new(),
             current_depth: 0,
+            indent: Cow::Borrowed(Self::INDENT),
+        }
+    }
+
+    /// Indents every level with `indent` instead of four spaces.
+    pub fn with_indent(indent: &str) -> Self {
+        Self {
+            indent: Cow::Owned(indent.to_owned()),
+            ..Self::new()
         }
     }
 
@@ -25,12 +39,9 @@ impl PrettyFormatter {
     }
 
     pub fn indent(&mut self) {
-        unsafe {
-            let vec = self.buf.as_mut_vec();
-            let len = vec.len();
-            vec.reserve(self.current_depth * Self::INDENT_QUANTITY);
-            vec.spare_capacity_mut().as_mut_ptr().write_bytes(Self::INDENT, self.current_depth * Self::INDENT_QUANTITY);
-            vec.set_len(len + self.current_depth * Self::INDENT_QUANTITY);
+        self.buf.reserve(self.current_depth * self.indent.len());
+        for _ in 0..self.current_depth {
+            self.buf.push_str(&self.indent);
         }
     }
 
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use anyhow::{anyhow, Context, Result};
+use compact_str::CompactString;
+
+use crate::elements::chunk::{NbtChunk, NbtRegion};
+use crate::elements::compound::NbtCompound;
+use crate::elements::element::{id_to_string_name, NbtByte, NbtByteArray, NbtDouble, NbtElement, NbtFloat, NbtInt, NbtIntArray, NbtLong, NbtLongArray, NbtShort, PathSegment};
+use crate::elements::list::NbtList;
+use crate::elements::string::NbtString;
+
+/// A parsed JSON value, numbers are kept as they were written so that longs aren't rounded through a float.
+pub enum JsonValue {
+	Null,
+	Bool(bool),
+	Number(CompactString),
+	String(CompactString),
+	Array(Vec<JsonValue>),
+	Object(Vec<(CompactString, JsonValue)>),
+}
+
+impl JsonValue {
+	/// # Errors
+	///
+	/// * `str` isn't a single JSON value
+	pub fn parse(str: &str) -> Result<Self> {
+		let mut chars = str.chars().peekable();
+		let value = Self::parse0(&mut chars)?;
+		skip_whitespace(&mut chars);
+		if chars.next().is_some() { return Err(anyhow!("Expected the end of the JSON")) }
+		Ok(value)
+	}
+
+	fn parse0(chars: &mut core::iter::Peekable<core::str::Chars>) -> Result<Self> {
+		skip_whitespace(chars);
+		Ok(match chars.peek().copied().context("Expected a value")? {
+			'"' => Self::String(string(chars)?),
+			'{' => {
+				chars.next();
+				let mut entries = vec![];
+				skip_whitespace(chars);
+				if chars.next_if_eq(&'}').is_none() {
+					loop {
+						skip_whitespace(chars);
+						let key = string(chars)?;
+						skip_whitespace(chars);
+						if chars.next() != Some(':') { return Err(anyhow!("Expected a colon")) }
+						entries.push((key, Self::parse0(chars)?));
+						skip_whitespace(chars);
+						match chars.next() {
+							Some(',') => continue,
+							Some('}') => break,
+							_ => return Err(anyhow!("Expected a comma or closing brace")),
+						}
+					}
+				}
+				Self::Object(entries)
+			}
+			'[' => {
+				chars.next();
+				let mut values = vec![];
+				skip_whitespace(chars);
+				if chars.next_if_eq(&']').is_none() {
+					loop {
+						values.push(Self::parse0(chars)?);
+						skip_whitespace(chars);
+						match chars.next() {
+							Some(',') => continue,
+							Some(']') => break,
+							_ => return Err(anyhow!("Expected a comma or closing bracket")),
+						}
+					}
+				}
+				Self::Array(values)
+			}
+			'-' | '0'..='9' => {
+				let mut number = CompactString::new_inline("");
+				while let Some(char) = chars.next_if(|char| matches!(char, '-' | '+' | '.' | 'e' | 'E' | '0'..='9')) {
+					number.push(char);
+				}
+				if number.parse::<f64>().is_err() { return Err(anyhow!("Invalid number {number}")) }
+				Self::Number(number)
+			}
+			_ => {
+				let mut word = CompactString::new_inline("");
+				while let Some(char) = chars.next_if(char::is_ascii_alphabetic) {
+					word.push(char);
+				}
+				match word.as_str() {
+					"null" => Self::Null,
+					"true" => Self::Bool(true),
+					"false" => Self::Bool(false),
+					_ => return Err(anyhow!("Unexpected {word:?}")),
+				}
+			}
+		})
+	}
+}
+
+fn skip_whitespace(chars: &mut core::iter::Peekable<core::str::Chars>) {
+	while chars.next_if(|char| char.is_whitespace()).is_some() {}
+}
+
+fn string(chars: &mut core::iter::Peekable<core::str::Chars>) -> Result<CompactString> {
+	if chars.next() != Some('"') { return Err(anyhow!("Expected a string")) }
+	let mut out = CompactString::new_inline("");
+	loop {
+		match chars.next().context("Unterminated string")? {
+			'"' => return Ok(out),
+			'\\' => out.push(match chars.next().context("Unterminated string")? {
+				'n' => '\n',
+				'r' => '\r',
+				't' => '\t',
+				'b' => '\x08',
+				'f' => '\x0C',
+				'u' => {
+					let hex = chars.by_ref().take(4).collect::<String>();
+					let code = u32::from_str_radix(&hex, 16).context("Invalid unicode escape")?;
+					char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+				}
+				char => char,
+			}),
+			char => out.push(char),
+		}
+	}
+}
+
+/// Writes `str` to `out` as a quoted JSON string, the inverse of [`JsonValue::String`]'s parsing.
+pub fn write_string(str: &str, out: &mut String) {
+	out.push('"');
+	for char in str.chars() {
+		match char {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			char if (char as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", char as u32); }
+			char => out.push(char),
+		}
+	}
+	out.push('"');
+}
+
+/// The types of the elements JSON can't tell apart by their path, see [`NbtElement::from_json`].
+///
+/// Without a hint integers become ints (or longs when they don't fit), other numbers become doubles and arrays become lists, empty ones of nothing. The type of an empty list is the hint of its first index.
+#[derive(Default)]
+pub struct TypeHints {
+	ids: HashMap<Vec<PathSegment>, u8>,
+}
+
+impl TypeHints {
+	#[must_use]
+	pub fn new() -> Self { Self::default() }
+
+	pub fn insert(&mut self, path: Vec<PathSegment>, id: u8) { self.ids.insert(path, id); }
+
+	#[must_use]
+	pub fn get(&self, path: &[PathSegment]) -> Option<u8> { self.ids.get(path).copied() }
+
+	/// The hints which turn the JSON of `element` (see [`NbtElement::to_json`]) back into it exactly.
+	#[must_use]
+	pub fn of(element: &NbtElement) -> Self {
+		fn of0(element: &NbtElement, path: &mut Vec<PathSegment>, hints: &mut TypeHints) {
+			match element.id() {
+				NbtInt::ID | NbtString::ID => {}
+				NbtList::ID => {
+					if let Some(list) = element.as_list() && list.is_empty() && list.element != 0 {
+						path.push(PathSegment::Index(0));
+						hints.insert(path.clone(), list.element);
+						path.pop();
+					}
+					for (idx, child) in element.as_list().into_iter().flat_map(NbtList::children).enumerate() {
+						path.push(PathSegment::Index(idx));
+						of0(child, path, hints);
+						path.pop();
+					}
+				}
+				NbtCompound::ID | NbtChunk::ID => {
+					if element.id() == NbtChunk::ID {
+						hints.insert(path.clone(), NbtChunk::ID);
+					}
+					let Some(compound) = element.as_compound().or_else(|| element.as_chunk().map(|chunk| &**chunk)) else { return };
+					for (key, child) in compound.children() {
+						path.push(PathSegment::Key(key.into()));
+						of0(child, path, hints);
+						path.pop();
+					}
+				}
+				NbtRegion::ID => {
+					hints.insert(path.clone(), NbtRegion::ID);
+					for chunk in element.as_region().into_iter().flat_map(NbtRegion::children) {
+						let Some(pos) = chunk.as_chunk().map(|chunk| (chunk.x, chunk.z)) else { continue };
+						path.push(PathSegment::Chunk(pos.0, pos.1));
+						of0(chunk, path, hints);
+						path.pop();
+					}
+				}
+				// the elements of arrays follow from the array
+				id => hints.insert(path.clone(), id),
+			}
+		}
+
+		let mut hints = Self::new();
+		of0(element, &mut vec![], &mut hints);
+		hints
+	}
+}
+
+/// The JSON number or string `value` as the number `id`.
+pub(crate) fn number(value: &JsonValue, id: u8) -> Result<NbtElement> {
+	let text = match value {
+		JsonValue::Number(text) | JsonValue::String(text) => text.as_str(),
+		JsonValue::Bool(bool) => if *bool { "1" } else { "0" },
+		_ => return Err(anyhow!("Expected a number")),
+	};
+	let invalid = || anyhow!("{text} is not a valid {}", id_to_string_name(id).0);
+	Ok(match id {
+		NbtByte::ID => NbtElement::Byte(NbtByte { value: text.parse().map_err(|_| invalid())? }),
+		NbtShort::ID => NbtElement::Short(NbtShort { value: text.parse().map_err(|_| invalid())? }),
+		NbtInt::ID => NbtElement::Int(NbtInt { value: text.parse().map_err(|_| invalid())? }),
+		NbtLong::ID => NbtElement::Long(NbtLong { value: text.parse().map_err(|_| invalid())? }),
+		NbtFloat::ID => NbtElement::Float(NbtFloat { value: text.parse().map_err(|_| invalid())? }),
+		NbtDouble::ID => NbtElement::Double(NbtDouble { value: text.parse().map_err(|_| invalid())? }),
+		_ => return Err(invalid()),
+	})
+}
+
+/// The JSON array `values` as the array `id`.
+pub(crate) fn array(values: &[JsonValue], id: u8) -> Result<NbtElement> {
+	let element = match id {
+		NbtByteArray::ID => NbtByte::ID,
+		NbtIntArray::ID => NbtInt::ID,
+		NbtLongArray::ID => NbtLong::ID,
+		_ => return Err(anyhow!("Expected an array")),
+	};
+	let values = values.iter().map(|value| number(value, element)).collect::<Result<Vec<_>>>()?;
+	Ok(match id {
+		NbtByteArray::ID => NbtElement::ByteArray(NbtByteArray::from_values(values).context("Invalid array element")?),
+		NbtIntArray::ID => NbtElement::IntArray(NbtIntArray::from_values(values).context("Invalid array element")?),
+		_ => NbtElement::LongArray(NbtLongArray::from_values(values).context("Invalid array element")?),
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::elements::chunk::{NbtChunk, NbtRegion};
+	use crate::elements::compound::NbtCompound;
+	use crate::elements::element::{NbtElement, NbtShort};
+	use crate::elements::list::NbtList;
+	use crate::file::FileFormat;
+
+	use super::{JsonValue, TypeHints};
+
+	fn round_trip(element: &NbtElement) -> NbtElement {
+		let json = JsonValue::parse(&element.to_json()).expect("the json of an element is valid");
+		NbtElement::from_json(&json, &TypeHints::of(element)).expect("the hints of an element fit its json")
+	}
+
+	#[test]
+	fn every_tag_survives_json() {
+		let nbt = NbtElement::from_snbt(r#"{
+			byte: 1b, short: -2s, int: 3, long: 4L, big_long: 9007199254740993L, float: 0.5f, double: -0.25d, whole_double: 2.0d,
+			string: "a \"quoted\"\nline", numeric_string: "12", bool_string: "true",
+			byte_array: [B; 1b, -2b], int_array: [I; 3, -4], long_array: [L; 5L, 9007199254740993L], empty_array: [I;],
+			bytes: [1b, 2b], shorts: [1s], longs: [1L, 2L], floats: [1.5f], strings: ["a", "1"], lists: [[1s], [2b]], arrays: [[B; 1b], [B;]],
+			compounds: [{a: 1b}, {}], compound: {nested: {deeper: 1s}, "": 0b}, empty: {}
+		}"#).expect("the fixture is valid SNBT");
+		assert_eq!(round_trip(&nbt).to_be_file(), nbt.to_be_file());
+	}
+
+	#[test]
+	fn empty_lists_keep_their_type() {
+		let mut compound = NbtCompound::new();
+		compound.insert(0, "shorts".into(), NbtElement::List(NbtList::new(vec![], NbtShort::ID)));
+		let nested = vec![NbtElement::from_snbt("[1b]").expect("the fixture is valid SNBT"), NbtElement::List(NbtList::new(vec![], NbtCompound::ID))];
+		compound.insert(1, "nested".into(), NbtElement::List(NbtList::new(nested, NbtList::ID)));
+		let nbt = NbtElement::Compound(compound);
+		let json = JsonValue::parse(&nbt.to_json()).expect("the json of an element is valid");
+		assert_eq!(NbtElement::from_json(&json, &TypeHints::of(&nbt)).expect("the hints of an element fit its json").to_be_file(), nbt.to_be_file());
+		// without the hints they are lists of nothing
+		assert_ne!(NbtElement::from_json(&json, &TypeHints::new()).expect("lists can be empty").to_be_file(), nbt.to_be_file());
+	}
+
+	#[test]
+	fn regions_survive_json() {
+		let mut region = NbtRegion::new();
+		for (idx, (x, z)) in [(0, 0), (31, 4)].into_iter().enumerate() {
+			let compound = NbtElement::from_snbt("{DataVersion: 3465, xPos: 0, Status: \"minecraft:full\", Heightmaps: {WORLD_SURFACE: [L; 1L]}}").expect("the fixture is valid SNBT").into_compound().expect("the fixture is a compound");
+			assert!(region.insert(idx, NbtElement::Chunk(NbtChunk::from_compound(compound, (x, z), FileFormat::Zlib, 1_700_000_000))).is_ok());
+		}
+		let region = NbtElement::Region(region);
+		assert!(round_trip(&region).deep_eq(&region));
+	}
+}
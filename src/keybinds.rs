@@ -0,0 +1,239 @@
+use winit::keyboard::KeyCode;
+
+use crate::flags;
+
+/// The actions which can be rebound with `keybind.<action>=<binding>` entries of the config, see [`Keybinds::set`].
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Action {
+	Open,
+	/// Saves as a new file when Shift is held on top of it.
+	Save,
+	Copy,
+	/// Holds the element in the clipboard to be dropped into the tree.
+	Paste,
+	Search,
+	/// Opens or closes the hovered element, or opens everything inside of it when Shift is held on top of it, unbound by default.
+	Toggle,
+	Delete,
+	Undo,
+	Redo,
+	/// Copies the element and deletes it.
+	Cut,
+}
+
+impl Action {
+	const ALL: [Self; 10] = [Self::Open, Self::Save, Self::Copy, Self::Paste, Self::Search, Self::Toggle, Self::Delete, Self::Undo, Self::Redo, Self::Cut];
+
+	/// The name of the action used by its config entry.
+	#[must_use]
+	pub const fn name(self) -> &'static str {
+		match self {
+			Self::Open => "open",
+			Self::Save => "save",
+			Self::Copy => "copy",
+			Self::Paste => "paste",
+			Self::Search => "search",
+			Self::Toggle => "toggle",
+			Self::Delete => "delete",
+			Self::Undo => "undo",
+			Self::Redo => "redo",
+			Self::Cut => "cut",
+		}
+	}
+
+	/// The bindings of the action when the config doesn't set any.
+	#[must_use]
+	const fn defaults(self) -> &'static [Keybind] {
+		match self {
+			Self::Open => &[Keybind { key: KeyCode::KeyO, flags: flags!(Ctrl) }],
+			Self::Save => &[Keybind { key: KeyCode::KeyS, flags: flags!(Ctrl) }],
+			Self::Copy => &[Keybind { key: KeyCode::KeyC, flags: flags!(Ctrl) }],
+			Self::Paste => &[Keybind { key: KeyCode::KeyV, flags: flags!() }],
+			Self::Search => &[Keybind { key: KeyCode::KeyF, flags: flags!(Ctrl) }],
+			Self::Toggle => &[],
+			Self::Delete => &[Keybind { key: KeyCode::Delete, flags: flags!() }, Keybind { key: KeyCode::Backspace, flags: flags!() }],
+			Self::Undo => &[Keybind { key: KeyCode::KeyZ, flags: flags!(Ctrl) }],
+			Self::Redo => &[Keybind { key: KeyCode::KeyY, flags: flags!(Ctrl) }],
+			Self::Cut => &[Keybind { key: KeyCode::KeyX, flags: flags!(Ctrl) }],
+		}
+	}
+}
+
+/// A key along with the modifiers (see [`flags!`]) which have to be held for it.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Keybind {
+	pub key: KeyCode,
+	pub flags: u8,
+}
+
+impl Keybind {
+	#[must_use]
+	pub const fn new(key: KeyCode, flags: u8) -> Self { Self { key, flags } }
+
+	/// Parses a binding like `Ctrl+Shift+S`, the modifiers can be in any order before the key and case doesn't matter.
+	#[must_use]
+	fn from_str(str: &str) -> Option<Self> {
+		let mut parts = str.split('+').map(str::trim).collect::<Vec<_>>();
+		// `Ctrl++` binds the plus key, which is the equals key without Shift
+		if parts.ends_with(&["", ""]) {
+			parts.truncate(parts.len() - 2);
+			parts.push("=");
+		}
+		let (key, modifiers) = parts.split_last()?;
+		let mut flags = flags!();
+		for modifier in modifiers {
+			flags |= match modifier.to_ascii_lowercase().as_str() {
+				"ctrl" | "control" | "cmd" | "super" => flags!(Ctrl),
+				"shift" => flags!(Shift),
+				"alt" | "option" => flags!(Alt),
+				_ => return None,
+			};
+		}
+		Some(Self::new(key_from_str(key)?, flags))
+	}
+}
+
+/// The key named `str`, letters and digits by themselves and other keys by the name on them, like `Delete` or `F3`.
+#[must_use]
+fn key_from_str(str: &str) -> Option<KeyCode> {
+	const LETTERS: [KeyCode; 26] = [KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE, KeyCode::KeyF, KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL, KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS, KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY, KeyCode::KeyZ];
+	const DIGITS: [KeyCode; 10] = [KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9];
+	const FUNCTION_KEYS: [KeyCode; 12] = [KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12];
+
+	let lowercase = str.to_ascii_lowercase();
+	if let [char] = lowercase.as_bytes() {
+		match char {
+			b'a'..=b'z' => return Some(LETTERS[(char - b'a') as usize]),
+			b'0'..=b'9' => return Some(DIGITS[(char - b'0') as usize]),
+			_ => {}
+		}
+	}
+	if let Some(n) = lowercase.strip_prefix('f').and_then(|n| n.parse::<usize>().ok()) {
+		return n.checked_sub(1).and_then(|idx| FUNCTION_KEYS.get(idx)).copied()
+	}
+	Some(match lowercase.as_str() {
+		"delete" | "del" => KeyCode::Delete,
+		"backspace" => KeyCode::Backspace,
+		"enter" | "return" => KeyCode::Enter,
+		"space" => KeyCode::Space,
+		"tab" => KeyCode::Tab,
+		"escape" | "esc" => KeyCode::Escape,
+		"insert" => KeyCode::Insert,
+		"home" => KeyCode::Home,
+		"end" => KeyCode::End,
+		"pageup" => KeyCode::PageUp,
+		"pagedown" => KeyCode::PageDown,
+		"up" => KeyCode::ArrowUp,
+		"down" => KeyCode::ArrowDown,
+		"left" => KeyCode::ArrowLeft,
+		"right" => KeyCode::ArrowRight,
+		"-" | "minus" => KeyCode::Minus,
+		"=" | "equal" | "equals" => KeyCode::Equal,
+		"`" | "backquote" => KeyCode::Backquote,
+		_ => return None,
+	})
+}
+
+/// The bindings of every [`Action`], the defaults for the ones the config doesn't set.
+pub struct Keybinds {
+	/// The bindings set by the config by the index of their action inside of [`Action::ALL`], an empty list leaves the action unbound.
+	overrides: [Option<Vec<Keybind>>; Action::ALL.len()],
+}
+
+impl Keybinds {
+	#[must_use]
+	pub const fn new() -> Self {
+		const UNSET: Option<Vec<Keybind>> = None;
+		Self { overrides: [UNSET; Action::ALL.len()] }
+	}
+
+	/// Sets the bindings of the action named `name` to the comma separated bindings of `value`, `none` unbinds it, returns `false` if the action or any of the bindings aren't known.
+	///
+	/// An empty `value` is ignored, keeping the defaults.
+	pub fn set(&mut self, name: &str, value: &str) -> bool {
+		let Some(idx) = Action::ALL.iter().position(|action| action.name() == name) else { return false };
+		if value.is_empty() { return true }
+		let binds = if value.eq_ignore_ascii_case("none") {
+			vec![]
+		} else {
+			let Some(binds) = value.split(',').map(Keybind::from_str).collect::<Option<Vec<_>>>() else { return false };
+			binds
+		};
+		self.overrides[idx] = Some(binds);
+		true
+	}
+
+	#[must_use]
+	pub fn get(&self, action: Action) -> &[Keybind] {
+		self.overrides[action as usize].as_deref().unwrap_or(action.defaults())
+	}
+
+	/// If pressing `key` while holding `flags` triggers `action`.
+	#[must_use]
+	pub fn matches(&self, action: Action, key: KeyCode, flags: u8) -> bool { self.get(action).iter().any(|bind| bind.key == key && bind.flags == flags) }
+
+	/// [`Self::matches`] for the actions with a variant for holding Shift on top of their binding, `Some(true)` for that variant.
+	#[must_use]
+	pub fn matches_shifted(&self, action: Action, key: KeyCode, flags: u8) -> Option<bool> {
+		self.get(action).iter().filter(|bind| bind.key == key).find_map(|bind| if bind.flags == flags {
+			Some(false)
+		} else if bind.flags & flags!(Shift) == 0 && bind.flags | flags!(Shift) == flags {
+			Some(true)
+		} else {
+			None
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use winit::keyboard::KeyCode;
+
+	use crate::flags;
+
+	use super::{Action, Keybind, Keybinds};
+
+	#[test]
+	fn bindings_are_parsed() {
+		assert!(Keybind::from_str("Ctrl+Shift+S") == Some(Keybind::new(KeyCode::KeyS, flags!(Ctrl + Shift))));
+		assert!(Keybind::from_str("shift + ctrl + s") == Some(Keybind::new(KeyCode::KeyS, flags!(Ctrl + Shift))), "modifiers can be in any order and any case");
+		assert!(Keybind::from_str("Ctrl++") == Some(Keybind::new(KeyCode::Equal, flags!(Ctrl))));
+		assert!(Keybind::from_str("F3") == Some(Keybind::new(KeyCode::F3, flags!())));
+		assert!(Keybind::from_str("Alt+Delete") == Some(Keybind::new(KeyCode::Delete, flags!(Alt))));
+	}
+
+	#[test]
+	fn unknown_bindings_are_rejected() {
+		assert!(Keybind::from_str("Ctrl+Launch").is_none());
+		assert!(Keybind::from_str("Hyper+S").is_none());
+		assert!(Keybind::from_str("F13").is_none());
+		assert!(Keybind::from_str("F0").is_none());
+		assert!(Keybind::from_str("").is_none());
+	}
+
+	#[test]
+	fn actions_are_rebound() {
+		let mut keybinds = Keybinds::new();
+		assert!(keybinds.set("save", "Ctrl+Shift+S, F2"));
+		assert!(keybinds.get(Action::Save) == [Keybind::new(KeyCode::KeyS, flags!(Ctrl + Shift)), Keybind::new(KeyCode::F2, flags!())]);
+		assert!(keybinds.matches(Action::Save, KeyCode::F2, flags!()));
+		assert!(!keybinds.matches(Action::Save, KeyCode::KeyS, flags!(Ctrl)), "the default is replaced");
+	}
+
+	#[test]
+	fn none_unbinds_and_empty_keeps_the_defaults() {
+		let mut keybinds = Keybinds::new();
+		assert!(keybinds.set("undo", "none"));
+		assert!(keybinds.get(Action::Undo).is_empty());
+		assert!(keybinds.set("copy", ""));
+		assert!(keybinds.get(Action::Copy) == Action::Copy.defaults());
+	}
+
+	#[test]
+	fn unknown_actions_and_keys_are_rejected() {
+		let mut keybinds = Keybinds::new();
+		assert!(!keybinds.set("explode", "Ctrl+E"));
+		assert!(!keybinds.set("open", "Ctrl+O, Ctrl+Launch"));
+		assert!(keybinds.get(Action::Open) == Action::Open.defaults(), "a rejected value leaves the binding alone");
+	}
+}
@@ -3,6 +3,7 @@ use std::marker::PhantomData;
 
 use compact_str::CompactString;
 use crate::elements::compound::CompoundMap;
+use crate::elements::list::EndListError;
 use crate::SortAlgorithm;
 
 pub struct LittleEndianDecoder<'a> {
@@ -11,6 +12,10 @@ pub struct LittleEndianDecoder<'a> {
 	sort: SortAlgorithm,
 	_marker: PhantomData<&'a ()>,
 	header: bool,
+	/// The amount of lists of `TAG_End` with a length read so far, they fail to parse unless `lenient_lists` reads them as empty lists.
+	pub end_lists: usize,
+	/// The list of `TAG_End` with a length which failed the parse, see [`crate::elements::list::NbtList::end_list_len`].
+	pub end_list_error: Option<EndListError>,
 }
 
 #[allow(improper_ctypes_definitions)]
@@ -24,6 +29,8 @@ impl<'a> LittleEndianDecoder<'a> {
 			sort,
 			_marker: PhantomData,
 			header: false,
+			end_lists: 0,
+			end_list_error: None,
 		};
 		unsafe {
 			if this.assert_len(8).is_some() && this.data.add(4).cast::<u32>().read_unaligned() as usize == this.remaining_len() - 8 {
@@ -50,8 +57,8 @@ impl<'a> LittleEndianDecoder<'a> {
 	#[optimize(speed)]
 	#[must_use]
 	pub fn assert_len(&self, remaining_len: usize) -> Option<()> {
-		// <= end because it will read *until* that byte
-		if unsafe { likely((self.data.add(remaining_len) as usize) <= self.end as usize) } {
+		// compared as lengths, since a pointer offset by a crafted length can overflow
+		if likely(remaining_len <= self.remaining_len()) {
 			Some(())
 		} else {
 			None
@@ -61,8 +68,7 @@ impl<'a> LittleEndianDecoder<'a> {
 	#[optimize(speed)]
 	#[must_use]
 	pub fn assert_exact_len(&self, remaining_len: usize) -> Option<()> {
-		// <= end because it will read *until* that byte
-		if unsafe { likely((self.data.add(remaining_len) as usize) == self.end as usize) } {
+		if likely(remaining_len == self.remaining_len()) {
 			Some(())
 		} else {
 			None
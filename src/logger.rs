@@ -0,0 +1,44 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+/// Writes [`log`] records to stderr (or the browser console on web).
+///
+/// The level comes from the `RUST_LOG` environment variable (`off`, `error`, `warn`, `info`, `debug` or `trace`) and defaults to `warn`, records from dependencies are never shown past `warn` so that `RUST_LOG=debug` isn't drowned out by wgpu.
+struct Logger;
+
+static LOGGER: Logger = Logger;
+
+impl Log for Logger {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		metadata.level() <= log::max_level() && (metadata.level() <= Level::Warn || metadata.target().starts_with(env!("CARGO_PKG_NAME")))
+	}
+
+	fn log(&self, record: &Record) {
+		if !self.enabled(record.metadata()) { return }
+
+		#[cfg(not(target_arch = "wasm32"))]
+		eprintln!("[{level:<5} {target}] {args}", level = record.level(), target = record.target(), args = record.args());
+		#[cfg(target_arch = "wasm32")] {
+			let message = wasm_bindgen::JsValue::from(&format!("[{target}] {args}", target = record.target(), args = record.args()));
+			match record.level() {
+				Level::Error => web_sys::console::error_1(&message),
+				Level::Warn => web_sys::console::warn_1(&message),
+				Level::Info => web_sys::console::info_1(&message),
+				Level::Debug | Level::Trace => web_sys::console::debug_1(&message),
+			}
+		}
+	}
+
+	fn flush(&self) {}
+}
+
+/// Installs the logger, only the first call has any effect.
+pub fn init() {
+	#[cfg(not(target_arch = "wasm32"))]
+	let level = std::env::var("RUST_LOG").ok().and_then(|level| level.trim().parse::<LevelFilter>().ok()).unwrap_or(LevelFilter::Warn);
+	#[cfg(target_arch = "wasm32")]
+	let level = LevelFilter::Warn;
+
+	if log::set_logger(&LOGGER).is_ok() {
+		log::set_max_level(level);
+	}
+}
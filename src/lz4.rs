@@ -0,0 +1,200 @@
+/// The magic every block of lz4-java's `LZ4BlockOutputStream` starts with, the stream the game writes chunks with compression type `4` as.
+pub const BLOCK_MAGIC: [u8; 8] = *b"LZ4Block";
+/// The magic of a standard LZ4 frame, which is read as well.
+pub const FRAME_MAGIC: u32 = 0x184D_2204;
+/// The method of a block written as it is.
+const RAW: u8 = 0x10;
+/// The method of a block compressed as a single LZ4 block.
+const COMPRESSED: u8 = 0x20;
+/// The block size the game writes, 2^(10 + this) bytes or 64KiB.
+const LEVEL: u8 = 6;
+const BLOCK_SIZE: usize = 1 << (10 + LEVEL as usize);
+/// The magic, the method and level, the compressed and decompressed lengths and the checksum.
+const HEADER_SIZE: usize = 8 + 1 + 4 + 4 + 4;
+/// The seed of the xxHash checksums of blocks.
+const CHECKSUM_SEED: u32 = 0x9747_B28C;
+/// The amount of earlier output a linked frame block can refer back to.
+const WINDOW_SIZE: usize = 64 * 1024;
+
+/// If `bytes` start like LZ4 compressed data, either the blocks of lz4-java or a standard frame.
+#[must_use]
+pub fn is_lz4(bytes: &[u8]) -> bool { bytes.starts_with(&BLOCK_MAGIC) || bytes.first_chunk::<4>().is_some_and(|&magic| u32::from_le_bytes(magic) == FRAME_MAGIC) }
+
+/// Decompresses the blocks of lz4-java (see [`BLOCK_MAGIC`]) or a standard LZ4 frame, checksums are skipped like the ones of zlib chunks.
+#[must_use]
+pub fn decompress(bytes: &[u8]) -> Option<Vec<u8>> {
+	if bytes.starts_with(&BLOCK_MAGIC) { decompress_blocks(bytes) } else { decompress_frame(bytes) }
+}
+
+/// Decompresses the blocks written by lz4-java's `LZ4BlockOutputStream`, each with its own header, up to the empty one which ends the stream (or the end of the bytes).
+#[must_use]
+fn decompress_blocks(mut bytes: &[u8]) -> Option<Vec<u8>> {
+	let mut out = Vec::new();
+	while !bytes.is_empty() {
+		let (header, rest) = bytes.split_first_chunk::<HEADER_SIZE>()?;
+		if !header.starts_with(&BLOCK_MAGIC) { return None }
+		let token = header[8];
+		let max_block_size = 1_usize << (10 + (token & 0x0F));
+		let compressed_len = usize::try_from(i32::from_le_bytes(header[9..13].try_into().ok()?)).ok()?;
+		let decompressed_len = usize::try_from(i32::from_le_bytes(header[13..17].try_into().ok()?)).ok()?;
+		if decompressed_len > max_block_size { return None }
+		let data = rest.get(..compressed_len)?;
+		match token & 0xF0 {
+			RAW if compressed_len == 0 && decompressed_len == 0 => break,
+			RAW if compressed_len == decompressed_len => out.extend_from_slice(data),
+			COMPRESSED => {
+				let start = out.len();
+				out.resize(start + decompressed_len, 0);
+				if lz4_flex::block::decompress_into(data, &mut out[start..]).ok()? != decompressed_len { return None }
+			}
+			_ => return None,
+		}
+		bytes = &rest[compressed_len..];
+	}
+	Some(out)
+}
+
+/// Decompresses a whole standard LZ4 frame (not just a raw block).
+#[must_use]
+fn decompress_frame(bytes: &[u8]) -> Option<Vec<u8>> {
+	let (magic, bytes) = bytes.split_first_chunk::<4>()?;
+	if u32::from_le_bytes(*magic) != FRAME_MAGIC { return None }
+	let (&[flags, block_descriptor], mut bytes) = bytes.split_first_chunk::<2>()?;
+	if flags >> 6 != 0b01 { return None }
+	let independent = flags & 0b0010_0000 > 0;
+	let block_checksums = flags & 0b0001_0000 > 0;
+	let content_size = flags & 0b0000_1000 > 0;
+	let dictionary = flags & 0b0000_0001 > 0;
+	let max_block_size = match (block_descriptor >> 4) & 0b111 {
+		4 => 64 * 1024,
+		5 => 256 * 1024,
+		6 => 1024 * 1024,
+		7 => 4 * 1024 * 1024,
+		_ => return None,
+	};
+	// the content size and dictionary id, then the header checksum
+	bytes = bytes.get(if content_size { 8 } else { 0 } + if dictionary { 4 } else { 0 } + 1..)?;
+
+	let mut out = Vec::new();
+	let mut block = vec![0; max_block_size];
+	loop {
+		let (len, rest) = bytes.split_first_chunk::<4>()?;
+		let len = u32::from_le_bytes(*len);
+		if len == 0 { break }
+		let uncompressed = len & 0x8000_0000 > 0;
+		let len = (len & 0x7FFF_FFFF) as usize;
+		if len > max_block_size { return None }
+		let data = rest.get(..len)?;
+		if uncompressed {
+			out.extend_from_slice(data);
+		} else {
+			let dict = if independent { &[][..] } else { &out[out.len().saturating_sub(WINDOW_SIZE)..] };
+			let n = lz4_flex::block::decompress_into_with_dict(data, &mut block, dict).ok()?;
+			out.extend_from_slice(&block[..n]);
+		}
+		bytes = rest.get(len + if block_checksums { 4 } else { 0 }..)?;
+	}
+	Some(out)
+}
+
+/// Compresses `bytes` into blocks the way lz4-java's `LZ4BlockOutputStream` does, which is how the game reads chunks with compression type `4`, blocks which don't get smaller are written as they are.
+#[must_use]
+pub fn compress(bytes: &[u8]) -> Vec<u8> {
+	fn write_header(out: &mut Vec<u8>, method: u8, compressed_len: usize, decompressed_len: usize, checksum: u32) {
+		out.extend_from_slice(&BLOCK_MAGIC);
+		out.push(method | LEVEL);
+		out.extend_from_slice(&(compressed_len as i32).to_le_bytes());
+		out.extend_from_slice(&(decompressed_len as i32).to_le_bytes());
+		out.extend_from_slice(&checksum.to_le_bytes());
+	}
+
+	let mut out = Vec::with_capacity(bytes.len() / 2 + HEADER_SIZE * 2);
+	for chunk in bytes.chunks(BLOCK_SIZE) {
+		// lz4-java only keeps the lower 28 bits of the hash
+		let checksum = xxh32(chunk, CHECKSUM_SEED) & 0x0FFF_FFFF;
+		let compressed = lz4_flex::block::compress(chunk);
+		if compressed.len() < chunk.len() {
+			write_header(&mut out, COMPRESSED, compressed.len(), chunk.len(), checksum);
+			out.extend_from_slice(&compressed);
+		} else {
+			write_header(&mut out, RAW, chunk.len(), chunk.len(), checksum);
+			out.extend_from_slice(chunk);
+		}
+	}
+	write_header(&mut out, RAW, 0, 0, 0);
+	out
+}
+
+/// 32-bit xxHash, which the frame header checksum is made of.
+#[must_use]
+fn xxh32(bytes: &[u8], seed: u32) -> u32 {
+	const PRIME_1: u32 = 0x9E37_79B1;
+	const PRIME_2: u32 = 0x85EB_CA77;
+	const PRIME_3: u32 = 0xC2B2_AE3D;
+	const PRIME_4: u32 = 0x27D4_EB2F;
+	const PRIME_5: u32 = 0x1656_67B1;
+
+	fn round(acc: u32, lane: u32) -> u32 { acc.wrapping_add(lane.wrapping_mul(PRIME_2)).rotate_left(13).wrapping_mul(PRIME_1) }
+
+	let mut stripes = bytes.array_chunks::<16>();
+	let mut hash = if bytes.len() >= 16 {
+		let mut acc = [seed.wrapping_add(PRIME_1).wrapping_add(PRIME_2), seed.wrapping_add(PRIME_2), seed, seed.wrapping_sub(PRIME_1)];
+		for stripe in stripes.by_ref() {
+			for (acc, lane) in acc.iter_mut().zip(stripe.array_chunks::<4>()) {
+				*acc = round(*acc, u32::from_le_bytes(*lane));
+			}
+		}
+		acc[0].rotate_left(1).wrapping_add(acc[1].rotate_left(7)).wrapping_add(acc[2].rotate_left(12)).wrapping_add(acc[3].rotate_left(18))
+	} else {
+		seed.wrapping_add(PRIME_5)
+	};
+	hash = hash.wrapping_add(bytes.len() as u32);
+
+	let rest = stripes.remainder();
+	let mut words = rest.array_chunks::<4>();
+	for word in words.by_ref() {
+		hash = hash.wrapping_add(u32::from_le_bytes(*word).wrapping_mul(PRIME_3)).rotate_left(17).wrapping_mul(PRIME_4);
+	}
+	for &byte in words.remainder() {
+		hash = hash.wrapping_add(u32::from(byte).wrapping_mul(PRIME_5)).rotate_left(11).wrapping_mul(PRIME_1);
+	}
+
+	hash = (hash ^ (hash >> 15)).wrapping_mul(PRIME_2);
+	hash = (hash ^ (hash >> 13)).wrapping_mul(PRIME_3);
+	hash ^ (hash >> 16)
+}
+
+#[cfg(test)]
+/// The chunk of [`crate::elements::chunk`]'s tests laid out the way lz4-java's `LZ4BlockOutputStream` writes it for the game, a single block stored as it is since it's too small to get smaller.
+pub const RAW_CHUNK: &[u8] = b"\x4C\x5A\x34\x42\x6C\x6F\x63\x6B\x16\x53\x00\x00\x00\x53\x00\x00\x00\xF7\xC9\xAB\x09\x0A\x00\x00\x03\x00\x0B\x44\x61\x74\x61\x56\x65\x72\x73\x69\x6F\x6E\x00\x00\x0D\x89\x03\x00\x04\x78\x50\x6F\x73\x00\x00\x00\x03\x03\x00\x04\x7A\x50\x6F\x73\xFF\xFF\xFF\xFE\x08\x00\x06\x53\x74\x61\x74\x75\x73\x00\x0E\x6D\x69\x6E\x65\x63\x72\x61\x66\x74\x3A\x66\x75\x6C\x6C\x0A\x00\x0A\x48\x65\x69\x67\x68\x74\x6D\x61\x70\x73\x00\x00\x4C\x5A\x34\x42\x6C\x6F\x63\x6B\x16\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+
+#[cfg(test)]
+mod tests {
+	use super::{compress, decompress, RAW_CHUNK};
+	use crate::elements::element::NbtElement;
+
+	/// A chunk with a heightmap of zeroes laid out like [`RAW_CHUNK`], compressed into a block without this crate's compressor.
+	const COMPRESSED_CHUNK: &[u8] = b"\x4C\x5A\x34\x42\x6C\x6F\x63\x6B\x26\x72\x00\x00\x00\x8F\x01\x00\x00\xB2\xEA\x07\x02\xFF\x57\x0A\x00\x00\x03\x00\x0B\x44\x61\x74\x61\x56\x65\x72\x73\x69\x6F\x6E\x00\x00\x0F\x71\x03\x00\x04\x78\x50\x6F\x73\x00\x00\x00\x03\x03\x00\x04\x7A\x50\x6F\x73\xFF\xFF\xFF\xFE\x08\x00\x06\x53\x74\x61\x74\x75\x73\x00\x0E\x6D\x69\x6E\x65\x63\x72\x61\x66\x74\x3A\x66\x75\x6C\x6C\x0A\x00\x0A\x48\x65\x69\x67\x68\x74\x6D\x61\x70\x73\x0C\x00\x0D\x57\x4F\x52\x4C\x44\x5F\x53\x55\x52\x46\x41\x43\x45\x00\x00\x00\x25\x00\x01\x00\xFF\x12\x50\x00\x00\x00\x00\x00\x4C\x5A\x34\x42\x6C\x6F\x63\x6B\x16\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+
+	fn chunk(data_version: i32, heightmaps: &str) -> Vec<u8> { NbtElement::from_snbt(&format!("{{DataVersion: {data_version}, xPos: 3, zPos: -2, Status: \"minecraft:full\", Heightmaps: {{{heightmaps}}}}}")).expect("the fixture is valid SNBT").to_be_file() }
+
+	#[test]
+	fn blocks_of_the_game_are_read() {
+		assert_eq!(decompress(RAW_CHUNK), Some(chunk(3465, "")));
+		assert_eq!(decompress(COMPRESSED_CHUNK), Some(chunk(3953, &format!("WORLD_SURFACE: [L; {zeroes}]", zeroes = ["0L"; 37].join(", ")))));
+		// cut off inside of a block
+		assert_eq!(decompress(&COMPRESSED_CHUNK[..40]), None);
+	}
+
+	#[test]
+	fn blocks_are_written_like_the_game_does() {
+		// down to the checksum of the block
+		assert_eq!(compress(&chunk(3465, "")), RAW_CHUNK);
+		let nbt = chunk(3953, &format!("WORLD_SURFACE: [L; {zeroes}]", zeroes = ["0L"; 37].join(", ")));
+		let compressed = compress(&nbt);
+		assert_eq!(compressed[..8], *b"LZ4Block");
+		assert_eq!(compressed[8], 0x26, "compressed with 64KiB blocks");
+		assert_eq!(compressed[17..21], COMPRESSED_CHUNK[17..21], "the checksum matches");
+		assert_eq!(decompress(&compressed), Some(nbt));
+	}
+}
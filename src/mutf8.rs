@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+
+use compact_str::CompactString;
+
+/// Decodes the modified UTF-8 of Java, where nulls are two bytes and characters past the BMP are a surrogate pair of three bytes each; four byte sequences of standard UTF-8 are accepted too and anything invalid becomes U+FFFD.
+#[must_use]
+pub fn decode(bytes: &[u8]) -> CompactString {
+	// outside of these two lead bytes modified UTF-8 is the same as standard UTF-8
+	if !bytes.iter().any(|&byte| byte == 0xC0 || byte == 0xED) {
+		return CompactString::from_utf8_lossy(bytes);
+	}
+
+	let mut units = Vec::with_capacity(bytes.len());
+	let mut idx = 0;
+	while let Some(&lead) = bytes.get(idx) {
+		let continuation = |offset: usize| bytes.get(idx + offset).copied().filter(|byte| byte & 0xC0 == 0x80).map(|byte| u32::from(byte & 0x3F));
+		let (code, len) = match lead {
+			0x00..=0x7F => (Some(u32::from(lead)), 1),
+			0xC0..=0xDF => (continuation(1).map(|a| (u32::from(lead & 0x1F) << 6) | a), 2),
+			0xE0..=0xEF => (continuation(1).zip(continuation(2)).map(|(a, b)| (u32::from(lead & 0x0F) << 12) | (a << 6) | b), 3),
+			0xF0..=0xF7 => (continuation(1).zip(continuation(2)).zip(continuation(3)).map(|((a, b), c)| (u32::from(lead & 0x07) << 18) | (a << 12) | (b << 6) | c), 4),
+			_ => (None, 1),
+		};
+		match code {
+			Some(code @ 0x1_0000..=0x10_FFFF) => {
+				let code = code - 0x1_0000;
+				units.push(0xD800 | (code >> 10) as u16);
+				units.push(0xDC00 | (code & 0x3FF) as u16);
+			}
+			Some(code @ 0..=0xFFFF) => units.push(code as u16),
+			_ => units.push(0xFFFD),
+		}
+		idx += if code.is_some() { len } else { 1 };
+	}
+	char::decode_utf16(units).map(|char| char.unwrap_or(char::REPLACEMENT_CHARACTER)).collect()
+}
+
+/// The modified UTF-8 of Java for `str`, see [`decode`].
+#[must_use]
+pub fn encode(str: &str) -> Cow<[u8]> {
+	// nulls and four byte sequences are the only ones which differ from standard UTF-8
+	if !str.bytes().any(|byte| byte == 0x00 || byte >= 0xF0) {
+		return Cow::Borrowed(str.as_bytes());
+	}
+
+	let mut bytes = Vec::with_capacity(str.len() + 8);
+	for char in str.chars() {
+		match char {
+			'\0' => bytes.extend_from_slice(&[0xC0, 0x80]),
+			'\u{10000}'.. => {
+				for unit in char.encode_utf16(&mut [0; 2]) {
+					let unit = *unit;
+					bytes.extend_from_slice(&[0xE0 | (unit >> 12) as u8, 0x80 | ((unit >> 6) & 0x3F) as u8, 0x80 | (unit & 0x3F) as u8]);
+				}
+			}
+			_ => bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes()),
+		}
+	}
+	Cow::Owned(bytes)
+}
+
+/// The longest start of the modified UTF-8 `bytes` (see [`encode`]) at most `len` bytes long which doesn't cut a character or surrogate pair in half.
+#[must_use]
+pub fn truncate(bytes: &[u8], len: usize) -> &[u8] {
+	if bytes.len() <= len { return bytes }
+	let mut end = len;
+	while end > 0 && bytes[end] & 0xC0 == 0x80 {
+		end -= 1;
+	}
+	// a high surrogate is only half of its character
+	if end >= 3 && bytes[end - 3] == 0xED && bytes[end - 2] & 0xF0 == 0xA0 {
+		end -= 3;
+	}
+	&bytes[..end]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn nulls_and_supplementary_characters_round_trip() {
+		let str = "a\u{0}b\u{1F600}";
+		let bytes = encode(str);
+		assert_eq!(&*bytes, b"a\xC0\x80b\xED\xA0\xBD\xED\xB8\x80");
+		assert_eq!(decode(&bytes), str);
+	}
+
+	#[test]
+	fn truncation_keeps_whole_characters() {
+		let bytes = encode("ab\u{1F600}");
+		for len in 2..8 {
+			assert_eq!(truncate(&bytes, len), b"ab", "{len}");
+		}
+		assert_eq!(truncate(&bytes, 8), &*bytes);
+	}
+}
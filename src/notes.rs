@@ -0,0 +1,166 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use anyhow::{anyhow, Context};
+
+use crate::elements::element::{NbtElement, PathSegment};
+use crate::json;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::json::JsonValue;
+
+/// A freeform note attached to the element at `path`, see [`NbtElement::path_of`].
+pub struct Note {
+	pub path: Box<[PathSegment]>,
+	pub text: Box<str>,
+}
+
+/// The notes of a file, kept in a sidecar `<file>.notes.json` next to it and never written into the NBT itself.
+///
+/// Notes are stored by the path leading to their element (see [`PathSegment`]), so they stay attached through edits to values and ordering of compounds, paths which no longer lead anywhere are orphaned, but kept in case the structure comes back.
+pub struct Notes {
+	notes: Vec<Note>,
+	/// `None` for tabs which don't belong to a file on disk, notes are then only kept in memory.
+	sidecar: Option<PathBuf>,
+}
+
+impl Default for Notes {
+	fn default() -> Self { Self::new() }
+}
+
+impl Notes {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			notes: vec![],
+			sidecar: None,
+		}
+	}
+
+	#[must_use]
+	#[cfg(not(target_arch = "wasm32"))]
+	fn sidecar_of(path: &Path) -> Option<PathBuf> {
+		let mut name = path.file_name()?.to_os_string();
+		name.push(".notes.json");
+		Some(path.with_file_name(name))
+	}
+
+	/// Reads the notes of the file at `path`, a missing sidecar means there are no notes.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn load(path: &Path) -> Result<Self> {
+		let Some(sidecar) = Self::sidecar_of(path) else { return Ok(Self::new()) };
+		let notes = match std::fs::read_to_string(&sidecar) {
+			Ok(str) => Self::from_json(&str).with_context(|| format!("Invalid notes file {}", sidecar.display()))?,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+			Err(e) => return Err(e.into()),
+		};
+		Ok(Self { notes, sidecar: Some(sidecar) })
+	}
+
+	/// Parses the object of paths to their note's text which [`to_json`] writes.
+	#[cfg(not(target_arch = "wasm32"))]
+	fn from_json(str: &str) -> Result<Vec<Note>> {
+		let JsonValue::Object(entries) = JsonValue::parse(str)? else { return Err(anyhow!("Expected an object")) };
+		entries.into_iter().map(|(path, text)| {
+			let JsonValue::String(text) = text else { return Err(anyhow!("Expected the note of {path:?} to be a string")) };
+			Ok(Note { path: PathSegment::parse(&path).with_context(|| format!("Invalid path {path:?}"))?.into_boxed_slice(), text: text.as_str().into() })
+		}).collect()
+	}
+
+	/// Moves the notes over to the sidecar of `path`, for when the file is saved somewhere else.
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn relocate(&mut self, path: &Path) -> Result<()> {
+		let sidecar = Self::sidecar_of(path);
+		if sidecar == self.sidecar { return Ok(()) }
+		self.sidecar = sidecar;
+		self.write()
+	}
+
+	fn write(&self) -> Result<()> {
+		#[cfg(not(target_arch = "wasm32"))]
+		if let Some(sidecar) = self.sidecar.as_deref() {
+			if self.notes.is_empty() {
+				if sidecar.exists() { std::fs::remove_file(sidecar)? }
+			} else {
+				std::fs::write(sidecar, to_json(self.notes.iter().map(|note| (PathSegment::join(&note.path), &*note.text))))?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Attaches `text` to `path`, replacing any note already there.
+	pub fn set(&mut self, path: &[PathSegment], text: &str) -> Result<()> {
+		if let Some(note) = self.notes.iter_mut().find(|note| &*note.path == path) {
+			note.text = text.into();
+		} else {
+			self.notes.push(Note { path: path.into(), text: text.into() });
+		}
+		self.write()
+	}
+
+	/// Removes the note of `path`, returning if there was one.
+	pub fn remove(&mut self, path: &[PathSegment]) -> Result<bool> {
+		let len = self.notes.len();
+		self.notes.retain(|note| &*note.path != path);
+		if self.notes.len() == len { return Ok(false) }
+		self.write()?;
+		Ok(true)
+	}
+
+	/// Every note with the indices of the element it belongs to, `None` if it is orphaned.
+	pub fn resolve<'a>(&'a self, root: &'a NbtElement) -> impl Iterator<Item = (&'a Note, Option<Vec<usize>>)> + 'a { self.notes.iter().map(move |note| (note, root.indices_of(&note.path))) }
+}
+
+#[must_use]
+fn to_json<'a>(entries: impl Iterator<Item = (String, &'a str)>) -> String {
+	let mut out = String::from("{");
+	for (idx, (path, text)) in entries.enumerate() {
+		out.push_str(if idx == 0 { "\n\t" } else { ",\n\t" });
+		json::write_string(&path, &mut out);
+		out.push_str(": ");
+		json::write_string(text, &mut out);
+	}
+	out.push_str("\n}\n");
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::elements::element::{NbtElement, PathSegment};
+
+	use super::Notes;
+
+	fn parse(snbt: &str) -> NbtElement { NbtElement::from_snbt(snbt).expect("the fixture is valid SNBT") }
+
+	#[test]
+	#[cfg(not(target_arch = "wasm32"))]
+	fn notes_reattach_after_reopening() {
+		let dir = std::env::temp_dir().join(format!("nbtworkbench-notes-{}", uuid::Uuid::new_v4()));
+		std::fs::create_dir_all(&dir).expect("the temp dir is writable");
+		let file = dir.join("level.dat");
+
+		let root = parse(r#"{Data: {Player: {Health: 20.0f, Pos: [1d, 2d, 3d]}, "Time.Of.Day": 6000L}, Version: 1}"#);
+		let mut notes = Notes::load(&file).expect("a missing sidecar means no notes");
+		assert_eq!(notes.resolve(&root).count(), 0);
+		for (indices, text) in [(&[0, 0, 1, 2][..], "the z coordinate"), (&[0, 1], "ticks\nsince \"noon\""), (&[1], "format version")] {
+			notes.set(&root.path_of(indices).expect("the indices are valid"), text).expect("the sidecar is writable");
+		}
+		drop(notes);
+
+		// reordered, with changed values and without `Version`
+		let root = parse(r#"{Data: {"Time.Of.Day": 12000L, Player: {Pos: [4d, 5d, 6d], Health: 1.0f}}}"#);
+		let notes = Notes::load(&file).expect("the sidecar was written");
+		let mut resolved = notes.resolve(&root).map(|(note, indices)| (&*note.text, indices)).collect::<Vec<_>>();
+		resolved.sort_unstable();
+		assert_eq!(resolved, [("format version", None), ("the z coordinate", Some(vec![0, 1, 0, 2])), ("ticks\nsince \"noon\"", Some(vec![0, 0]))]);
+
+		let mut notes = notes;
+		for path in ["Data.Player.Pos[2]", r#"Data."Time.Of.Day""#, "Version"] {
+			assert!(notes.remove(&PathSegment::parse(path).expect("the path is valid")).expect("the sidecar is writable"), "{path}");
+		}
+		assert!(!dir.join("level.dat.notes.json").exists());
+		let _ = std::fs::remove_dir_all(dir);
+	}
+}
@@ -0,0 +1,154 @@
+use std::path::{Path, PathBuf};
+
+use crate::assets::HEADER_SIZE;
+use crate::config;
+use crate::file::FileFormat;
+use crate::vertex_buffer_builder::VertexBufferBuilder;
+
+/// The amount of files remembered, older ones are forgotten.
+pub const MAX_RECENT_FILES: usize = 10;
+
+pub struct RecentFile {
+	pub path: PathBuf,
+	pub format: FileFormat,
+}
+
+/// Files which were recently opened or saved, most recent first, stored as `<format>\t<path>` lines inside `nbtworkbench/recent.txt` of the config directory.
+pub struct RecentFiles {
+	entries: Vec<RecentFile>,
+	/// `None` for lists which are never written back, like the ones of the cli.
+	path: Option<PathBuf>,
+	/// The highlighted entry while the list is shown, along with which entries no longer exist on disk.
+	menu: Option<(usize, Box<[bool]>)>,
+}
+
+impl RecentFiles {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			entries: vec![],
+			path: None,
+			menu: None,
+		}
+	}
+
+	/// Reads the list from the config directory, missing or invalid entries are skipped.
+	#[must_use]
+	pub fn load() -> Self {
+		let Some(path) = config::dir().map(|dir| dir.join("recent.txt")) else { return Self::new() };
+		let entries = std::fs::read_to_string(&path).unwrap_or_default().lines().filter_map(|line| {
+			let (format, file) = line.split_once('\t')?;
+			Some(RecentFile { path: PathBuf::from(file), format: FileFormat::from_str_name(format)? })
+		}).take(MAX_RECENT_FILES).collect();
+		Self {
+			entries,
+			path: Some(path),
+			menu: None,
+		}
+	}
+
+	/// Moves `path` to the top of the list, adding it if it wasn't already present.
+	pub fn push(&mut self, path: &Path, format: FileFormat) {
+		if !path.is_absolute() { return }
+
+		self.entries.retain(|entry| entry.path != path);
+		self.entries.insert(0, RecentFile { path: path.to_path_buf(), format });
+		self.entries.truncate(MAX_RECENT_FILES);
+		self.menu = None;
+		self.write();
+	}
+
+	fn write(&self) {
+		let Some(path) = self.path.as_deref() else { return };
+		let str = self.entries.iter().filter_map(|entry| Some(format!("{}\t{}\n", entry.format.into_str(), entry.path.to_str()?))).collect::<String>();
+		if let Err(e) = path.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|()| std::fs::write(path, str)) {
+			log::warn!("Failed to write recent files to {}: {e}", path.display());
+		}
+	}
+
+	#[must_use]
+	pub const fn is_menu_open(&self) -> bool { self.menu.is_some() }
+
+	/// Shows the list, checking which files have since been deleted or moved.
+	pub fn open_menu(&mut self) {
+		self.menu = Some((0, self.entries.iter().map(|entry| !entry.path.exists()).collect()));
+	}
+
+	pub fn close_menu(&mut self) { self.menu = None; }
+
+	pub fn select_previous(&mut self) {
+		if let Some((selected, _)) = &mut self.menu {
+			*selected = selected.checked_sub(1).unwrap_or(self.entries.len().saturating_sub(1));
+		}
+	}
+
+	pub fn select_next(&mut self) {
+		if let Some((selected, _)) = &mut self.menu {
+			*selected = if *selected + 1 >= self.entries.len() { 0 } else { *selected + 1 };
+		}
+	}
+
+	/// Closes the list, returning the path of the highlighted entry along with the format it was opened as.
+	#[must_use]
+	pub fn take_selected(&mut self) -> Option<(PathBuf, FileFormat)> {
+		let (selected, _) = self.menu.take()?;
+		self.entries.get(selected).map(|entry| (entry.path.clone(), entry.format))
+	}
+
+	pub fn render(&self, builder: &mut VertexBufferBuilder) {
+		let Some((selected, missing)) = &self.menu else { return };
+
+		let mut lines = vec!["Recent files ([Up] / [Down] to select, [Enter] to open, [Esc] to close)".to_owned()];
+		if self.entries.is_empty() {
+			lines.push("  No files opened yet".to_owned());
+		}
+		for (idx, (entry, &missing)) in self.entries.iter().zip(missing.iter()).enumerate() {
+			lines.push(format!("{marker} {path} ({format}){missing}", marker = if idx == *selected { '>' } else { ' ' }, path = entry.path.display(), format = entry.format.into_str(), missing = if missing { " [missing]" } else { "" }));
+		}
+		builder.draw_tooltip(&lines.iter().map(String::as_str).collect::<Vec<_>>(), (16, HEADER_SIZE), true);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::{Path, PathBuf};
+
+	use crate::file::FileFormat;
+
+	use super::{RecentFiles, MAX_RECENT_FILES};
+
+	fn paths(recent: &RecentFiles) -> Vec<&Path> { recent.entries.iter().map(|entry| entry.path.as_path()).collect() }
+
+	#[test]
+	fn reopening_moves_a_file_to_the_top() {
+		// absolute on every platform
+		let saves = std::env::temp_dir();
+		let (a, b) = (&*saves.join("a/level.dat"), &*saves.join("b/level.dat"));
+		let mut recent = RecentFiles::new();
+		recent.push(a, FileFormat::Gzip);
+		recent.push(b, FileFormat::Gzip);
+		recent.push(a, FileFormat::Nbt);
+		assert_eq!(paths(&recent), [a, b]);
+		// reopened as the format it was last opened as
+		recent.open_menu();
+		assert_eq!(recent.take_selected(), Some((a.to_path_buf(), FileFormat::Nbt)));
+		assert!(!recent.is_menu_open());
+
+		recent.open_menu();
+		recent.select_previous();
+		assert_eq!(recent.take_selected(), Some((b.to_path_buf(), FileFormat::Gzip)));
+	}
+
+	#[test]
+	fn only_the_latest_absolute_paths_are_kept() {
+		let mut recent = RecentFiles::new();
+		recent.push(Path::new("level.dat"), FileFormat::Gzip);
+		assert!(recent.entries.is_empty());
+
+		let files = (0..MAX_RECENT_FILES + 2).map(|n| std::env::temp_dir().join(format!("{n}.nbt"))).collect::<Vec<_>>();
+		for file in &files {
+			recent.push(file, FileFormat::Nbt);
+		}
+		assert_eq!(paths(&recent), files.iter().rev().take(MAX_RECENT_FILES).map(PathBuf::as_path).collect::<Vec<_>>());
+	}
+}
@@ -0,0 +1,236 @@
+use crate::elements::compound::NbtCompound;
+use crate::elements::element::{id_to_string_name, NbtByte, NbtDouble, NbtElement, NbtFloat, NbtInt, NbtIntArray, NbtLong, NbtShort, PathSegment};
+use crate::elements::list::NbtList;
+use crate::elements::string::NbtString;
+
+/// The shape Minecraft expects some NBT to have, checked by [`NbtElement::validate`].
+pub enum Schema {
+	/// An element of this type, whatever its value.
+	Id(u8),
+	/// A list of elements which all match the schema, empty lists are always fine.
+	List(&'static Schema),
+	/// A compound with these fields, keys which aren't listed are allowed since mods and newer versions add their own.
+	Compound(&'static [Field]),
+}
+
+pub struct Field {
+	pub key: &'static str,
+	pub schema: Schema,
+	pub required: bool,
+}
+
+impl Field {
+	const fn required(key: &'static str, schema: Schema) -> Self { Self { key, schema, required: true } }
+
+	const fn optional(key: &'static str, schema: Schema) -> Self { Self { key, schema, required: false } }
+}
+
+/// An element which doesn't match its schema.
+pub struct SchemaError {
+	pub indices: Box<[usize]>,
+	/// See [`NbtElement::path_of`].
+	pub path: Box<[PathSegment]>,
+	pub message: String,
+}
+
+const BYTE: Schema = Schema::Id(NbtByte::ID);
+const SHORT: Schema = Schema::Id(NbtShort::ID);
+const INT: Schema = Schema::Id(NbtInt::ID);
+const LONG: Schema = Schema::Id(NbtLong::ID);
+const FLOAT: Schema = Schema::Id(NbtFloat::ID);
+const DOUBLE: Schema = Schema::Id(NbtDouble::ID);
+const STRING: Schema = Schema::Id(NbtString::ID);
+const COMPOUND: Schema = Schema::Id(NbtCompound::ID);
+const INT_ARRAY: Schema = Schema::Id(NbtIntArray::ID);
+
+const ITEM: Schema = Schema::Compound(&[
+	Field::required("id", STRING),
+	Field::optional("Count", BYTE),
+	Field::optional("count", INT),
+	Field::optional("Slot", BYTE),
+	Field::optional("tag", COMPOUND),
+	Field::optional("components", COMPOUND),
+]);
+
+const PLAYER: Schema = Schema::Compound(&[
+	Field::required("Air", SHORT),
+	Field::required("FallDistance", FLOAT),
+	Field::required("Fire", SHORT),
+	Field::required("Health", FLOAT),
+	Field::required("Motion", Schema::List(&DOUBLE)),
+	Field::required("OnGround", BYTE),
+	Field::required("Pos", Schema::List(&DOUBLE)),
+	Field::required("Rotation", Schema::List(&FLOAT)),
+	Field::optional("UUID", INT_ARRAY),
+	Field::optional("Invulnerable", BYTE),
+	Field::optional("PortalCooldown", INT),
+	Field::optional("Dimension", STRING),
+	Field::optional("playerGameType", INT),
+	Field::optional("previousPlayerGameType", INT),
+	Field::optional("Score", INT),
+	Field::optional("SelectedItemSlot", INT),
+	Field::optional("XpLevel", INT),
+	Field::optional("XpP", FLOAT),
+	Field::optional("XpTotal", INT),
+	Field::optional("XpSeed", INT),
+	Field::optional("foodLevel", INT),
+	Field::optional("foodSaturationLevel", FLOAT),
+	Field::optional("foodExhaustionLevel", FLOAT),
+	Field::optional("foodTickTimer", INT),
+	Field::optional("Inventory", Schema::List(&ITEM)),
+	Field::optional("EnderItems", Schema::List(&ITEM)),
+	Field::optional("abilities", COMPOUND),
+	Field::optional("recipeBook", COMPOUND),
+]);
+
+/// The `level.dat` of Java Edition worlds, from beta 1.3 on, fields which were added in later versions are optional.
+pub const LEVEL_DAT: Schema = Schema::Compound(&[Field::required("Data", Schema::Compound(&[
+	Field::required("LevelName", STRING),
+	Field::required("LastPlayed", LONG),
+	Field::required("SpawnX", INT),
+	Field::required("SpawnY", INT),
+	Field::required("SpawnZ", INT),
+	Field::required("Time", LONG),
+	Field::required("version", INT),
+	Field::optional("DataVersion", INT),
+	Field::optional("DayTime", LONG),
+	Field::optional("GameType", INT),
+	Field::optional("Difficulty", BYTE),
+	Field::optional("DifficultyLocked", BYTE),
+	Field::optional("hardcore", BYTE),
+	Field::optional("allowCommands", BYTE),
+	Field::optional("initialized", BYTE),
+	Field::optional("raining", BYTE),
+	Field::optional("rainTime", INT),
+	Field::optional("thundering", BYTE),
+	Field::optional("thunderTime", INT),
+	Field::optional("clearWeatherTime", INT),
+	Field::optional("WanderingTraderSpawnChance", INT),
+	Field::optional("WanderingTraderSpawnDelay", INT),
+	Field::optional("RandomSeed", LONG),
+	Field::optional("SpawnAngle", FLOAT),
+	Field::optional("BorderCenterX", DOUBLE),
+	Field::optional("BorderCenterZ", DOUBLE),
+	Field::optional("BorderSize", DOUBLE),
+	Field::optional("GameRules", COMPOUND),
+	Field::optional("WorldGenSettings", Schema::Compound(&[
+		Field::required("seed", LONG),
+		Field::optional("generate_features", BYTE),
+		Field::optional("bonus_chest", BYTE),
+		Field::optional("dimensions", COMPOUND),
+	])),
+	Field::optional("Version", Schema::Compound(&[
+		Field::optional("Id", INT),
+		Field::optional("Name", STRING),
+		Field::optional("Series", STRING),
+		Field::optional("Snapshot", BYTE),
+	])),
+	Field::optional("DataPacks", Schema::Compound(&[
+		Field::optional("Enabled", Schema::List(&STRING)),
+		Field::optional("Disabled", Schema::List(&STRING)),
+	])),
+	Field::optional("ServerBrands", Schema::List(&STRING)),
+	Field::optional("Player", PLAYER),
+]))]);
+
+impl Schema {
+	/// The built-in schema of files named `name`, if there is one.
+	#[must_use]
+	pub fn for_file_name(name: &str) -> Option<&'static Self> {
+		match name {
+			"level.dat" | "level.dat_old" => Some(&LEVEL_DAT),
+			_ => None,
+		}
+	}
+
+	fn check(&self, element: &NbtElement, indices: &mut Vec<usize>, errors: &mut Vec<(Box<[usize]>, String)>) {
+		let expected = match self {
+			&Self::Id(id) => id,
+			Self::List(_) => NbtList::ID,
+			Self::Compound(_) => NbtCompound::ID,
+		};
+		if element.id() != expected {
+			errors.push((indices.as_slice().into(), format!("Expected {expected}, found {found}", expected = id_to_string_name(expected).0, found = id_to_string_name(element.id()).0)));
+			return
+		}
+		match self {
+			Self::List(schema) => for idx in 0..element.len().unwrap_or(0) {
+				let Some(child) = element.get(idx) else { continue };
+				indices.push(idx);
+				schema.check(child, indices, errors);
+				indices.pop();
+			},
+			Self::Compound(fields) => {
+				let Some(Err(children)) = element.children() else { return };
+				let children = children.collect::<Vec<_>>();
+				for field in *fields {
+					if let Some(idx) = children.iter().position(|&(key, _)| key == field.key) {
+						indices.push(idx);
+						field.schema.check(children[idx].1, indices, errors);
+						indices.pop();
+					} else if field.required {
+						errors.push((indices.as_slice().into(), format!("Missing required key {key:?}", key = field.key)));
+					}
+				}
+			}
+			_ => {}
+		}
+	}
+
+	/// Every element of `root` which doesn't match this schema, in the order they are found.
+	#[must_use]
+	pub fn validate(&self, root: &NbtElement) -> Vec<SchemaError> {
+		let mut errors = vec![];
+		self.check(root, &mut vec![], &mut errors);
+		errors.into_iter().map(|(indices, message)| SchemaError { path: root.path_of(&indices).unwrap_or_default().into_boxed_slice(), indices, message }).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::elements::element::{NbtElement, PathSegment};
+
+	use super::{Field, Schema, BYTE, INT, LEVEL_DAT, STRING};
+
+	const FIXTURE: Schema = Schema::Compound(&[
+		Field::required("name", STRING),
+		Field::optional("count", INT),
+		Field::optional("flags", Schema::List(&BYTE)),
+		Field::optional("inner", Schema::Compound(&[Field::required("id", STRING)])),
+	]);
+
+	/// The joined path and message of every violation of `schema` in `snbt`.
+	fn violations(schema: &Schema, snbt: &str) -> Vec<(String, String)> {
+		let nbt = NbtElement::from_snbt(snbt).expect("fixtures are valid SNBT");
+		schema.validate(&nbt).into_iter().map(|error| (PathSegment::join(&error.path), error.message)).collect()
+	}
+
+	#[test]
+	fn matching_elements_have_no_violations() {
+		assert!(violations(&FIXTURE, r#"{name: "a", count: 1, flags: [0b, 1b], inner: {id: "b"}, extra: 1L}"#).is_empty());
+		assert!(violations(&FIXTURE, r#"{name: "a", flags: []}"#).is_empty());
+	}
+
+	#[test]
+	fn each_kind_of_violation_is_reported() {
+		// the root itself
+		assert_eq!(violations(&FIXTURE, "[1b]"), [(String::new(), "Expected compound, found list".to_owned())]);
+		// a missing required key, reported on its compound
+		assert_eq!(violations(&FIXTURE, "{count: 1}"), [(String::new(), r#"Missing required key "name""#.to_owned())]);
+		assert_eq!(violations(&FIXTURE, "{name: \"a\", inner: {}}"), [("inner".to_owned(), r#"Missing required key "id""#.to_owned())]);
+		// an entry of the wrong type
+		assert_eq!(violations(&FIXTURE, r#"{name: "a", count: 1s}"#), [("count".to_owned(), "Expected int, found short".to_owned())]);
+		// an element of a list
+		assert_eq!(violations(&FIXTURE, r#"{name: "a", flags: [1s]}"#), [("flags[0]".to_owned(), "Expected byte, found short".to_owned())]);
+		// a list which isn't one
+		assert_eq!(violations(&FIXTURE, r#"{name: "a", flags: [B; 1b]}"#), [("flags".to_owned(), "Expected list, found byte array".to_owned())]);
+	}
+
+	#[test]
+	fn level_dat_is_checked_in_order() {
+		let errors = violations(&LEVEL_DAT, r#"{Data: {LevelName: 1b, LastPlayed: 0L, SpawnX: 0, SpawnY: 0, SpawnZ: 0, Time: 0L, Player: {Air: 300s}}}"#);
+		assert_eq!(errors.first(), Some(&("Data.LevelName".to_owned(), "Expected string, found byte".to_owned())));
+		assert!(errors.contains(&("Data".to_owned(), r#"Missing required key "version""#.to_owned())));
+		assert!(errors.contains(&("Data.Player".to_owned(), r#"Missing required key "Health""#.to_owned())));
+	}
+}
@@ -0,0 +1,109 @@
+use anyhow::{anyhow, Result};
+use fxhash::FxHashSet;
+
+use crate::elements::compound::NbtCompound;
+use crate::elements::element::{NbtByte, NbtByteArray, NbtElement, NbtInt, NbtIntArray, PathSegment};
+
+/// The key of the palette the packed indices under `key` point into, `BlockData` and `BiomeData` of Sponge schematics up to version 2, and the `Data` of their `Blocks` and `Biomes` from version 3.
+#[must_use]
+fn palette_key(key: &str) -> Option<&'static str> {
+	match key {
+		"BlockData" | "Data" => Some("Palette"),
+		"BiomeData" => Some("BiomePalette"),
+		_ => None,
+	}
+}
+
+/// If the entry `key` of `parent` holds palette indices of a Sponge schematic, packed as a byte array of varints or unpacked as an int array, see [`unpack`].
+#[must_use]
+pub fn is_palette_data(key: &str, parent: &NbtCompound) -> bool {
+	palette_key(key).and_then(|palette| parent.entries.idx_of(palette)).and_then(|idx| parent.get(idx)).is_some_and(|(_, palette)| palette.id() == NbtCompound::ID)
+}
+
+/// The varints of a packed byte array as an int array of palette indices, `None` if the last one is cut off or one doesn't fit an int.
+#[must_use]
+pub fn unpack(array: &NbtByteArray) -> Option<NbtIntArray> {
+	let mut indices = Vec::with_capacity(array.len());
+	let mut value = 0_u32;
+	let mut shift = 0_u32;
+	for byte in array.children() {
+		let byte = byte.as_byte()?.value as u8;
+		if shift >= 32 { return None }
+		value |= u32::from(byte & 0x7F) << shift;
+		if byte & 0x80 == 0 {
+			indices.push(NbtElement::Int(NbtInt { value: i32::try_from(value).ok()? }));
+			value = 0;
+			shift = 0;
+		} else {
+			shift += 7;
+		}
+	}
+	if shift > 0 { return None }
+	NbtIntArray::from_values(indices)
+}
+
+/// The inverse of [`unpack`], `None` if an index is negative.
+#[must_use]
+pub fn pack(array: &NbtIntArray) -> Option<NbtByteArray> {
+	let mut bytes = Vec::with_capacity(array.len());
+	for index in array.children() {
+		let mut value = u32::try_from(index.as_int()?.value).ok()?;
+		loop {
+			let byte = (value & 0x7F) as u8;
+			value >>= 7;
+			bytes.push(NbtElement::Byte(NbtByte { value: (if value > 0 { byte | 0x80 } else { byte }) as i8 }));
+			if value == 0 { break }
+		}
+	}
+	NbtByteArray::from_values(bytes)
+}
+
+/// Calls `write` with the palette indices at `paths` (the ones unpacked by Alt + B) packed again, since schematics are only read with them packed, and unpacks them once it returns.
+///
+/// They're swapped in place rather than packed into a copy of the tree, the heights of the compounds holding them aren't updated in between so the tree is only meant to be written.
+///
+/// # Errors
+///
+/// * An index is negative
+/// * `write` fails
+pub fn with_packed<T>(root: &mut NbtElement, paths: &FxHashSet<Box<[PathSegment]>>, write: impl FnOnce(&NbtElement) -> Result<T>) -> Result<T> {
+	fn element_at<'a>(root: &'a mut NbtElement, indices: &[usize]) -> Option<&'a mut NbtElement> { indices.iter().try_fold(root, |element, &idx| element.get_mut(idx)) }
+
+	let mut swapped = Vec::new();
+	let mut result = Ok(());
+	for path in paths {
+		let Some(indices) = root.indices_of(path) else { continue };
+		let Some(element) = element_at(root, &indices) else { continue };
+		let Some(array) = element.as_int_array() else { continue };
+		let Some(packed) = pack(array) else {
+			result = Err(anyhow!("The {path} of the schematic has a negative palette index", path = PathSegment::join(path)));
+			break
+		};
+		swapped.push((indices, core::mem::replace(element, NbtElement::ByteArray(packed))));
+	}
+	let result = result.and_then(|()| write(root));
+	for (indices, unpacked) in swapped.into_iter().rev() {
+		if let Some(element) = element_at(root, &indices) {
+			*element = unpacked;
+		}
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use compact_str::CompactString;
+
+	use super::*;
+	use crate::SortAlgorithm;
+
+	#[test]
+	fn only_the_unpacked_indices_are_packed() {
+		let (_, mut root) = NbtElement::from_str("{Palette: {}, BlockData: [I; 1, 300], Data: [I; 5]}", SortAlgorithm::None).expect("valid SNBT");
+		let before = root.to_string();
+		let paths = FxHashSet::from_iter([Box::from([PathSegment::Key(CompactString::new("BlockData"))])]);
+		let written = with_packed(&mut root, &paths, |root| Ok(root.to_string())).expect("packs");
+		assert_eq!(written, "{Palette:{},BlockData:[B;1b,-84b,2b],Data:[I;5]}");
+		assert_eq!(root.to_string(), before);
+	}
+}
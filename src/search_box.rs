@@ -2,6 +2,7 @@ use std::fmt::Display;
 use std::ops::{Deref, DerefMut};
 use std::time::Duration;
 use compact_str::CompactString;
+use fxhash::FxHashMap;
 use regex::Regex;
 use winit::event::MouseButton;
 
@@ -10,9 +11,9 @@ use crate::assets::{ADD_SEARCH_BOOKMARKS_UV, BASE_Z, BOOKMARK_UV, DARK_STRIPE_UV
 
 use crate::color::TextColor;
 use crate::{combined_two_sorted, create_regex, flags, since_epoch, SortAlgorithm, StrExt};
-use crate::elements::element::NbtElement;
-use crate::text::{Cachelike, SearchBoxKeyResult, Text};
-use crate::vertex_buffer_builder::{Vec2u, VertexBufferBuilder};
+use crate::elements::element::{NbtByteArray, NbtElement, NbtIntArray, NbtLongArray};
+use crate::text::{Cachelike, SearchBoxKeyResult, Text, TextInput};
+use crate::vertex_buffer_builder::{char_width, Vec2u, VertexBufferBuilder};
 use crate::bookmark::{Bookmark, Bookmarks};
 
 pub struct SearchPredicate {
@@ -78,6 +79,16 @@ impl SearchMode {
 }
 
 impl SearchPredicate {
+    /// `str` with every match replaced by `with` (a regex's groups can be referred to with `$1` and so on), `None` when nothing matches, SNBT predicates never replace anything since they match whole elements.
+    #[must_use]
+    pub fn replace(&self, str: &str, with: &str) -> Option<String> {
+        match &self.inner {
+            SearchPredicateInner::String(find) => (!find.is_empty() && str.contains(find.as_str())).then(|| str.replace(find.as_str(), with)),
+            SearchPredicateInner::Regex(regex) => regex.is_match(str).then(|| regex.replace_all(str, with).into_owned()),
+            SearchPredicateInner::Snbt(_, _) => None,
+        }
+    }
+
     fn matches(&self, key: Option<&str>, value: &NbtElement) -> bool {
         match &self.inner {
             SearchPredicateInner::String(str) => {
@@ -98,34 +109,39 @@ impl SearchPredicate {
     }
 }
 
+/// A string value or key which [`SearchBox::replacements`] would change.
+pub struct Replacement {
+    pub indices: Box<[usize]>,
+    /// A bookmark of the element's line.
+    pub bookmark: Bookmark,
+    pub key: Option<CompactString>,
+    pub value: Option<CompactString>,
+    /// If the new key stays taken in its compound after the other replacements in it (an earlier one taking it too, or an entry keeping it), the key is left as is since entries are told apart by it.
+    pub duplicate: bool,
+}
+
 #[derive(Clone, Eq)]
 pub struct SearchBoxCache {
-    value: String,
-    cursor: usize,
-    selection: Option<usize>,
+    input: TextInput,
     hits: Option<(usize, Duration)>,
 }
 
 impl PartialEq for SearchBoxCache {
     fn eq(&self, other: &Self) -> bool {
-        self.value == other.value
+        self.input == other.input
     }
 }
 
 impl Cachelike<SearchBoxAdditional> for SearchBoxCache {
     fn new(text: &Text<SearchBoxAdditional, Self>) -> Self where Self: Sized {
         Self {
-            value: text.value.clone(),
-            cursor: text.cursor,
-            selection: text.selection,
+            input: TextInput::of(text),
             hits: text.hits,
         }
     }
 
     fn revert(self, text: &mut Text<SearchBoxAdditional, Self>) where Self: Sized {
-        text.value = self.value;
-        text.cursor = self.cursor;
-        text.selection = self.selection;
+        self.input.restore(text);
         text.hits = self.hits;
     }
 }
@@ -266,7 +282,7 @@ impl SearchBox {
             self.cursor = 'a: {
                 let mut current_x = 0;
                 for (idx, char) in self.value.char_indices() {
-                    let width = if (x as u32) < 56832 { VertexBufferBuilder::CHAR_WIDTH[char as usize] as usize } else { 0 };
+                    let width = char_width(char);
                     if current_x + width / 2 >= x {
                         break 'a idx;
                     }
@@ -343,6 +359,72 @@ impl SearchBox {
         unsafe { Bookmarks::from_raw(new_bookmarks) }
     }
 
+    /// Every string value and key (following the search flags) of `root` which `predicate` would change when replacing its matches with `with`, in the order of the tree.
+    #[must_use]
+    pub fn replacements(root: &NbtElement, predicate: &SearchPredicate, with: &str) -> Vec<Replacement> {
+        fn walk(element: &NbtElement, shown: bool, predicate: &SearchPredicate, with: &str, indices: &mut Vec<usize>, lines: &mut (usize, usize), replacements: &mut Vec<Replacement>) {
+            let open = shown && element.open();
+            // no strings or keys inside of these
+            if matches!(element.id(), NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID) {
+                lines.0 += element.true_height() - 1;
+                if open { lines.1 += element.height() - 1 }
+                return
+            }
+            let children = match element.children() {
+                Some(Ok(iter)) => iter.map(|value| (None, value)).collect::<Vec<_>>(),
+                Some(Err(iter)) => iter.map(|(key, value)| (Some(key), value)).collect::<Vec<_>>(),
+                None => return,
+            };
+            let new_keys = children.iter().map(|(key, _)| key.filter(|_| predicate.search_flags & 0b10 > 0).and_then(|key| predicate.replace(key, with)).map(CompactString::from)).collect::<Vec<_>>();
+            let clashing = clashing(&children.iter().map(|&(key, _)| key.unwrap_or_default()).collect::<Vec<_>>(), &new_keys);
+            for (idx, ((_, value), (new_key, duplicate))) in children.into_iter().zip(new_keys.into_iter().zip(clashing)).enumerate() {
+                // hidden elements are bookmarked on the row of the closed element hiding them
+                let bookmark = if open { Bookmark::new(lines.0, lines.1) } else { Bookmark::with_uv(lines.0, lines.1 - 1, HIDDEN_BOOKMARK_UV) };
+                lines.0 += 1;
+                if open { lines.1 += 1 }
+                indices.push(idx);
+                let new_value = value.as_string().filter(|_| predicate.search_flags & 0b01 > 0).and_then(|str| predicate.replace(str.str.as_str(), with)).map(CompactString::from);
+                if new_key.is_some() || new_value.is_some() {
+                    replacements.push(Replacement { indices: indices.as_slice().into(), bookmark, key: new_key, value: new_value, duplicate });
+                }
+                walk(value, open, predicate, with, indices, lines, replacements);
+                indices.pop();
+            }
+        }
+
+        /// Which of the `renames` of a compound's `keys` can't be made since their new key stays taken, once every rename which frees it (even a later one) is made first and earlier renames to the same key win.
+        fn clashing(keys: &[&str], renames: &[Option<CompactString>]) -> Vec<bool> {
+            let mut current = keys.iter().map(|&key| CompactString::from(key)).collect::<Vec<_>>();
+            let mut taken = FxHashMap::<CompactString, usize>::default();
+            for key in &current {
+                *taken.entry(key.clone()).or_default() += 1;
+            }
+            let mut pending = renames.iter().enumerate().filter_map(|(idx, rename)| rename.as_ref().map(|_| idx)).collect::<Vec<_>>();
+            loop {
+                let len = pending.len();
+                pending.retain(|&idx| {
+                    let Some(new_key) = &renames[idx] else { return false };
+                    let others = taken.get(new_key).copied().unwrap_or(0) - usize::from(current[idx] == *new_key);
+                    if others > 0 { return true }
+                    if let Some(count) = taken.get_mut(&current[idx]) { *count -= 1 }
+                    *taken.entry(new_key.clone()).or_default() += 1;
+                    current[idx] = new_key.clone();
+                    false
+                });
+                if pending.len() == len { break }
+            }
+            let mut clashing = vec![false; renames.len()];
+            for idx in pending {
+                clashing[idx] = true;
+            }
+            clashing
+        }
+
+        let mut replacements = vec![];
+        walk(root, true, predicate, with, &mut vec![], &mut (2, 1), &mut replacements);
+        replacements
+    }
+
     #[inline]
     #[must_use]
     pub fn is_selected(&self) -> bool {
@@ -380,3 +462,24 @@ impl SearchBox {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renamed_keys(snbt: &str, find: &str, with: &str) -> Vec<(CompactString, bool)> {
+        let (_, root) = NbtElement::from_str(snbt, SortAlgorithm::None).expect("valid SNBT");
+        let predicate = SearchMode::String.into_predicate(find.to_owned(), 0b10).expect("string predicate");
+        SearchBox::replacements(&root, &predicate, with).into_iter().map(|replacement| (replacement.key.expect("renamed"), replacement.duplicate)).collect()
+    }
+
+    #[test]
+    fn keys_freed_by_the_same_replacement_dont_clash() {
+        assert_eq!(renamed_keys("{a: 1, ab: 2}", "a", "ab"), [("ab".into(), false), ("abb".into(), false)]);
+    }
+
+    #[test]
+    fn keys_kept_by_other_entries_clash() {
+        assert_eq!(renamed_keys("{a: 1, b: 2, ab: 3}", "b", ""), [("".into(), false), ("a".into(), true)]);
+    }
+}
@@ -3,26 +3,24 @@ use std::ops::{Deref, DerefMut};
 
 use winit::keyboard::KeyCode;
 
-use crate::{flags, OptionExt, StrExt};
+use crate::{flags, indented, OptionExt, StrExt};
 use crate::assets::{BASE_TEXT_Z, HEADER_SIZE, SELECTED_TEXT_SELECTION_Z, SELECTED_TEXT_Z, SELECTION_UV};
 use crate::color::TextColor;
 use crate::selected_text::SelectedTextKeyResult::{Down, ForceClose, ForceOpen, Keyfix, ShiftDown, ShiftUp, Up, Valuefix};
-use crate::text::{Cachelike, SelectedTextKeyResult, Text};
-use crate::vertex_buffer_builder::VertexBufferBuilder;
+use crate::text::{Cachelike, SelectedTextKeyResult, Text, TextInput};
+use crate::vertex_buffer_builder::{char_width, VertexBufferBuilder};
 
 #[derive(Clone, Debug)]
 #[allow(clippy::module_name_repetitions)] // yeah no, it's better like this
 pub struct SelectedTextCache {
 	keyfix: Option<(Box<str>, TextColor)>,
-	value: Box<str>,
+	input: TextInput,
 	valuefix: Option<(Box<str>, TextColor)>,
-	cursor: usize,
-	selection: Option<usize>,
 }
 
 impl PartialEq for SelectedTextCache {
 	fn eq(&self, other: &Self) -> bool {
-		self.keyfix == other.keyfix && self.value == other.value && self.valuefix == other.valuefix
+		self.keyfix == other.keyfix && self.input == other.input && self.valuefix == other.valuefix
 	}
 }
 
@@ -31,19 +29,15 @@ impl Cachelike<SelectedTextAdditional> for SelectedTextCache {
 		Self {
 			keyfix: text.additional.keyfix.clone().map(|(a, b)| (a.into_boxed_str(), b)),
 			valuefix: text.additional.valuefix.clone().map(|(a, b)| (a.into_boxed_str(), b)),
-			value: text.value.clone().into_boxed_str(),
-			cursor: text.cursor,
-			selection: text.selection,
+			input: TextInput::of(text),
 		}
 	}
 
 	fn revert(self, text: &mut Text<SelectedTextAdditional, Self>) where Self: Sized {
-		let Self { keyfix, value, valuefix, cursor, selection } = self;
+		let Self { keyfix, input, valuefix } = self;
 		text.additional.keyfix = keyfix.map(|(a, b)| (a.into_string(), b));
 		text.additional.valuefix = valuefix.map(|(a, b)| (a.into_string(), b));
-		text.value = value.into_string();
-		text.cursor = cursor;
-		text.selection = selection;
+		input.restore(text);
 	}
 }
 
@@ -116,9 +110,7 @@ impl SelectedText {
 						+ key
 							.chars()
 							.last()
-							.and_then(|char| VertexBufferBuilder::CHAR_WIDTH.get(char as usize))
-							.copied()
-							.unwrap_or(0) as usize
+							.map_or(0, char_width)
 					&& mouse_x < target_x + key_width + 7
 				{
 					return Some(
@@ -139,7 +131,7 @@ impl SelectedText {
 				let key_width = key_width as isize;
 
 				for char in key.chars() {
-					let width = VertexBufferBuilder::CHAR_WIDTH[char as usize] as isize;
+					let width = char_width(char) as isize;
 					if x * 2 >= width {
 						// algebra, to understand, divide both sides by two
 						cursor += char.len_utf8();
@@ -199,9 +191,7 @@ impl SelectedText {
 						.as_ref()
 						.chars()
 						.last()
-						.and_then(|char| VertexBufferBuilder::CHAR_WIDTH.get(char as usize))
-						.copied()
-						.unwrap_or(0) as usize
+						.map_or(0, char_width)
 					> value_x + value_width
 					&& mouse_x < value_x + value_width + 5
 				{
@@ -222,7 +212,7 @@ impl SelectedText {
 				let mut cursor = 0;
 
 				for char in value.chars() {
-					let width = VertexBufferBuilder::CHAR_WIDTH[char as usize] as isize;
+					let width = char_width(char) as isize;
 					if x * 2 >= width {
 						// algebra, to understand, divide both sides by two
 						cursor += char.len_utf8();
@@ -306,9 +296,35 @@ impl SelectedText {
 		self.0.post_input()
 	}
 
+	/// Replaces the value with the option before (or after) it inside of `options`, the last (or first) one if it isn't one of them.
+	pub fn select_option(&mut self, options: &[&str], previous: bool) {
+		if options.is_empty() { return }
+		let idx = options.iter().position(|&option| option == self.value);
+		let new = match idx {
+			Some(idx) if previous => idx.checked_sub(1).unwrap_or(options.len() - 1),
+			Some(idx) => (idx + 1) % options.len(),
+			None if previous => options.len() - 1,
+			None => 0,
+		};
+		self.value = options[new].to_owned();
+		self.cursor = self.value.len();
+		self.selection = None;
+		self.post_input();
+	}
+
+	/// Draws the values this text can be set to in a box below it, marking the current one.
+	pub fn render_options(&self, builder: &mut VertexBufferBuilder, left_margin: usize, horizontal_scroll: usize, options: &[&str]) {
+		let x = indented(self.indices.len()) * 16 + 32 + 4 + left_margin + self.prefix.0.width() + self.keyfix.as_ref().map_or(0, |x| x.0.width());
+		let y = if builder.scroll() > self.y { return; } else { self.y - builder.scroll() };
+		if y < HEADER_SIZE { return }
+
+		let lines = options.iter().map(|&option| if option == self.value { format!("> {option}") } else { format!("  {option}") }).collect::<Vec<_>>();
+		builder.draw_tooltip(&lines.iter().map(String::as_str).collect::<Vec<_>>(), (x.saturating_sub(horizontal_scroll), y), true);
+	}
+
 	#[inline]
 	pub fn render(&self, builder: &mut VertexBufferBuilder, left_margin: usize) {
-		let x = self.indices.len() * 16 + 32 + 4 + left_margin;
+		let x = indented(self.indices.len()) * 16 + 32 + 4 + left_margin;
 		let y = if builder.scroll() > self.y { return; } else { self.y - builder.scroll() };
 		if y < HEADER_SIZE { return }
 
@@ -0,0 +1,84 @@
+use std::path::{Path, PathBuf};
+
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+use crate::config;
+
+/// What is restored from the last launch, stored as `key=value` lines inside `nbtworkbench/session.txt` of the config directory.
+///
+/// Unlike [`config`] it's written by the workbench itself, when the window is closed.
+struct Session {
+	size: Option<PhysicalSize<u32>>,
+	position: Option<PhysicalPosition<i32>>,
+	directory: Option<PathBuf>,
+}
+
+static mut SESSION: Session = Session {
+	size: None,
+	position: None,
+	directory: None,
+};
+
+/// Reads the session file, missing or invalid entries are left unset.
+pub fn read() {
+	let Some(str) = config::dir().and_then(|dir| std::fs::read_to_string(dir.join("session.txt")).ok()) else { return };
+	let session = unsafe { &mut *core::ptr::addr_of_mut!(SESSION) };
+	let mut width = None;
+	let mut height = None;
+	let mut x = None;
+	let mut y = None;
+	for (key, value) in str.lines().filter_map(|line| line.split_once('=')) {
+		match key {
+			"width" => width = value.parse::<u32>().ok(),
+			"height" => height = value.parse::<u32>().ok(),
+			"x" => x = value.parse::<i32>().ok(),
+			"y" => y = value.parse::<i32>().ok(),
+			"directory" => session.directory = Some(PathBuf::from(value)).filter(|path| path.is_dir()),
+			_ => {}
+		}
+	}
+	session.size = width.zip(height).map(|(width, height)| PhysicalSize::new(width, height));
+	session.position = x.zip(y).map(|(x, y)| PhysicalPosition::new(x, y));
+}
+
+/// Writes the session file, see [`read`].
+pub fn write() {
+	let Some(path) = config::dir().map(|dir| dir.join("session.txt")) else { return };
+	let session = unsafe { &*core::ptr::addr_of!(SESSION) };
+	let mut str = String::new();
+	if let Some(size) = session.size {
+		str += &format!("width={}\nheight={}\n", size.width, size.height);
+	}
+	if let Some(position) = session.position {
+		str += &format!("x={}\ny={}\n", position.x, position.y);
+	}
+	if let Some(directory) = session.directory.as_deref().and_then(Path::to_str) {
+		str += &format!("directory={directory}\n");
+	}
+	if let Err(e) = path.parent().map_or(Ok(()), std::fs::create_dir_all).and_then(|()| std::fs::write(&path, str)) {
+		log::warn!("Failed to write session to {}: {e}", path.display());
+	}
+}
+
+/// The inner size of the window when it was last closed.
+#[must_use]
+pub fn get_window_size() -> Option<PhysicalSize<u32>> { unsafe { (*core::ptr::addr_of!(SESSION)).size } }
+
+pub fn set_window_size(size: PhysicalSize<u32>) { unsafe { (*core::ptr::addr_of_mut!(SESSION)).size = Some(size) } }
+
+/// The outer position of the window when it was last closed.
+#[must_use]
+pub fn get_window_position() -> Option<PhysicalPosition<i32>> { unsafe { (*core::ptr::addr_of!(SESSION)).position } }
+
+pub fn set_window_position(position: PhysicalPosition<i32>) { unsafe { (*core::ptr::addr_of_mut!(SESSION)).position = Some(position) } }
+
+/// The folder the file dialogs start in, the one of the file last opened or saved through them.
+#[must_use]
+pub fn get_directory() -> Option<&'static Path> { unsafe { (*core::ptr::addr_of!(SESSION)).directory.as_deref() } }
+
+/// Remembers the folder holding `file` for [`get_directory`].
+pub fn set_directory(file: &Path) {
+	if let Some(parent) = file.parent() && parent.is_absolute() {
+		unsafe { (*core::ptr::addr_of_mut!(SESSION)).directory = Some(parent.to_path_buf()) }
+	}
+}
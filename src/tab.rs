@@ -1,32 +1,37 @@
 use std::ffi::OsStr;
-use std::io::Read;
-use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
 use compact_str::{CompactString, ToCompactString};
-use flate2::Compression;
+use fxhash::{FxHashMap, FxHashSet};
 use uuid::Uuid;
-use zune_inflate::DeflateDecoder;
 
-use crate::{LinkedQueue, OptionExt, panic_unchecked, RenderContext, since_epoch, SortAlgorithm, StrExt, WindowProperties};
-use crate::assets::{BASE_Z, BYTE_ARRAY_GHOST_UV, BYTE_ARRAY_UV, BYTE_GRAYSCALE_UV, BYTE_UV, CHUNK_GHOST_UV, CHUNK_UV, COMPOUND_GHOST_UV, COMPOUND_ROOT_UV, COMPOUND_UV, DISABLED_REFRESH_UV, DOUBLE_GRAYSCALE_UV, DOUBLE_UV, ENABLED_FREEHAND_MODE_UV, FLOAT_GRAYSCALE_UV, FLOAT_UV, FREEHAND_MODE_UV, GZIP_FILE_TYPE_UV, HEADER_SIZE, HELD_SCROLLBAR_UV, HOVERED_WIDGET_UV, INT_ARRAY_GHOST_UV, INT_ARRAY_UV, INT_GRAYSCALE_UV, INT_UV, JUST_OVERLAPPING_BASE_Z, LITTLE_ENDIAN_NBT_FILE_TYPE_UV, LINE_NUMBER_SEPARATOR_UV, LIST_GHOST_UV, LIST_UV, LONG_ARRAY_GHOST_UV, LONG_ARRAY_UV, LONG_GRAYSCALE_UV, LONG_UV, MCA_FILE_TYPE_UV, NBT_FILE_TYPE_UV, REDO_UV, REFRESH_UV, REGION_UV, SCROLLBAR_Z, SHORT_GRAYSCALE_UV, SHORT_UV, SNBT_FILE_TYPE_UV, STEAL_ANIMATION_OVERLAY_UV, STRING_GHOST_UV, STRING_UV, UNDO_UV, UNHELD_SCROLLBAR_UV, UNKNOWN_NBT_GHOST_UV, UNKNOWN_NBT_UV, UNSELECTED_WIDGET_UV, ZLIB_FILE_TYPE_UV, ZOffset, LITTLE_ENDIAN_HEADER_NBT_FILE_TYPE_UV};
+use crate::{config, file, schematic, FileUpdateSubscription, LinkedQueue, indented, OptionExt, panic_unchecked, RenderContext, since_epoch, SortAlgorithm, StrExt, WindowProperties};
+use crate::add_menu::AddMenu;
+use crate::assets::{BASE_Z, JUST_OVERLAPPING_BASE_TEXT_Z, BYTE_ARRAY_GHOST_UV, BYTE_ARRAY_UV, BYTE_GRAYSCALE_UV, BYTE_UV, CHUNK_GHOST_UV, CHUNK_UV, COMPOUND_GHOST_UV, COMPOUND_ROOT_UV, COMPOUND_UV, DISABLED_REFRESH_UV, DOUBLE_GRAYSCALE_UV, DOUBLE_UV, ENABLED_FREEHAND_MODE_UV, FLOAT_GRAYSCALE_UV, FLOAT_UV, FREEHAND_MODE_UV, HEADER_SIZE, HELD_SCROLLBAR_UV, STATUS_BAR_SIZE, HOVERED_WIDGET_UV, INT_ARRAY_GHOST_UV, INT_ARRAY_UV, INT_GRAYSCALE_UV, INT_UV, JUST_OVERLAPPING_BASE_Z, LINE_NUMBER_SEPARATOR_UV, LIST_GHOST_UV, LIST_UV, LONG_ARRAY_GHOST_UV, LONG_ARRAY_UV, LONG_GRAYSCALE_UV, LONG_UV, REDO_UV, REFRESH_UV, REGION_UV, SCROLLBAR_Z, SHORT_GRAYSCALE_UV, SHORT_UV, STEAL_ANIMATION_OVERLAY_UV, STRING_GHOST_UV, STRING_UV, UNDO_UV, UNHELD_SCROLLBAR_UV, UNKNOWN_NBT_GHOST_UV, UNKNOWN_NBT_UV, UNSELECTED_WIDGET_UV, ZOffset};
 use crate::color::TextColor;
-use crate::elements::chunk::NbtRegion;
+use crate::elements::array::ArrayView;
+use crate::elements::chunk::{ExternalChunks, NbtRegion};
 use crate::elements::compound::NbtCompound;
-use crate::elements::element::NbtElement;
+use crate::elements::element::{NbtElement, PathSegment};
+use crate::file::FileFormat;
 use crate::selected_text::{SelectedText, SelectedTextAdditional};
 use crate::text::Text;
 use crate::bookmark::Bookmarks;
 use crate::elements::list::NbtList;
+use crate::diff::{DiffEntry, DiffKind};
+use crate::notes::Notes;
+use crate::schema::Schema;
 use crate::tree_travel::Navigate;
-use crate::vertex_buffer_builder::{Vec2u, VertexBufferBuilder};
+use crate::vertex_buffer_builder::VertexBufferBuilder;
 use crate::workbench_action::WorkbenchAction;
 
 pub struct Tab {
 	pub value: Box<NbtElement>,
 	pub name: Box<str>,
+	/// The name of the root compound (or list) as it was read, written back when the tab is saved in a big endian format, empty for almost every file.
+	pub root_name: CompactString,
 	pub path: Option<PathBuf>,
 	pub format: FileFormat,
 	pub undos: LinkedQueue<WorkbenchAction>,
@@ -43,6 +48,23 @@ pub struct Tab {
 	pub last_close_attempt: Duration,
 	pub last_selected_text_interaction: (usize, usize, Duration),
 	pub last_interaction: Duration,
+	/// When the tab was last backed up, see [`Self::backup`].
+	pub last_backup: Duration,
+	pub notes: Notes,
+	/// The paths (see [`NbtElement::path_of`]) of elements toggled to show their raw NBT instead of a specialized view.
+	pub raw_views: FxHashSet<Box<[PathSegment]>>,
+	/// The views of the arrays which aren't written in decimal, by their paths like [`Self::raw_views`].
+	pub array_views: FxHashMap<Box<[PathSegment]>, ArrayView>,
+	/// The paths of the schematic palette indices unpacked by Alt + B, packed again whenever the tab is written, see [`schematic::with_packed`].
+	pub unpacked_palettes: FxHashSet<Box<[PathSegment]>>,
+	/// Siblings picked with Shift + click to be moved together, the indices of their parent and their own sorted indices within it, forgotten on any edit since they can't be trusted past it.
+	pub selection: Option<(Box<[usize]>, Vec<usize>)>,
+	/// The types which can be added with Ctrl + I, closed on any edit like [`Self::selection`] since they're added by the indices of their container.
+	pub add_menu: Option<AddMenu>,
+	/// How this tab differs from the one compared against, forgotten on any edit like [`Self::selection`].
+	pub diff: Vec<DiffEntry>,
+	/// The bytes taken up by the elements shown in the status bar by their indices, since counting them means writing them, forgotten on any edit like [`Self::selection`].
+	pub byte_sizes: FxHashMap<Box<[usize]>, usize>,
 }
 
 impl Tab {
@@ -56,13 +78,16 @@ impl Tab {
 	];
 	pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
 	pub const AUTOSAVE_MAXIMUM_LINES: usize = 1_000_000;
+	pub const UNSAVED_MARKER: &'static str = " •";
 
-	pub fn new(nbt: NbtElement, path: &Path, format: FileFormat, window_height: usize, window_width: usize) -> Result<Self> {
+	pub fn new(mut nbt: NbtElement, path: &Path, format: FileFormat, window_height: usize, window_width: usize) -> Result<Self> {
 		if !(nbt.id() == NbtCompound::ID || nbt.id() == NbtRegion::ID || nbt.id() == NbtList::ID) { return Err(anyhow!("Parsed NBT was not a Compound, Region, or List")) }
+		nbt.expand_to_depth(config::get_expand_depth());
 
 		Ok(Self {
 			value: Box::new(nbt),
 			name: path.file_name().map(OsStr::to_string_lossy).context("Could not obtain path filename")?.into(),
+			root_name: CompactString::new_inline(""),
 			path: Some(path).filter(|path| path.is_absolute()).map(|path| path.to_path_buf()),
 			format,
 			undos: LinkedQueue::new(),
@@ -79,17 +104,41 @@ impl Tab {
 			last_close_attempt: Duration::ZERO,
 			last_selected_text_interaction: (0, 0, Duration::ZERO),
 			last_interaction: since_epoch(),
+			last_backup: since_epoch(),
+			#[cfg(not(target_arch = "wasm32"))]
+			notes: if path.is_absolute() {
+				Notes::load(path).unwrap_or_else(|e| {
+					log::warn!("{e:#}");
+					Notes::new()
+				})
+			} else {
+				Notes::new()
+			},
+			#[cfg(target_arch = "wasm32")]
+			notes: Notes::new(),
+			raw_views: FxHashSet::default(),
+			array_views: FxHashMap::default(),
+			unpacked_palettes: FxHashSet::default(),
+			selection: None,
+			add_menu: None,
+			diff: vec![],
+			byte_sizes: FxHashMap::default(),
 		})
 	}
 
 	#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
 	pub fn save(&mut self, force_dialog: bool) -> Result<()> {
-		if let Some(path) = self.path.as_deref() && path.is_absolute() && !force_dialog {
-			std::fs::write(path, self.format.encode(&self.value))?;
+		if let Some(path) = self.path.clone() && path.is_absolute() && !force_dialog {
+			self.write(&path)?;
+			log::info!("Saved {} as {}", path.display(), self.format.into_str());
+			self.remove_backup();
 			self.unsaved_changes = false;
 			Ok(())
 		} else {
 			let mut builder = native_dialog::FileDialog::new();
+			if let Some(directory) = crate::session::get_directory() {
+				builder = builder.set_location(directory);
+			}
 			let initial_index = match self.format {
 				FileFormat::Nbt => 0,
 				FileFormat::Snbt => 1,
@@ -101,22 +150,195 @@ impl Tab {
 			builder = builder.add_filter(Self::FILE_TYPE_FILTERS[initial_index].0, Self::FILE_TYPE_FILTERS[initial_index].1);
 			builder = Self::FILE_TYPE_FILTERS.iter().enumerate().filter_map(|(idx, value)| if idx == initial_index { None } else { Some(value) }).fold(builder, |builder, filter| builder.add_filter(filter.0, filter.1));
 			let path = builder.show_save_single_file()?.ok_or_else(|| anyhow!("Save cancelled"))?;
+			crate::session::set_directory(&path);
+			self.write(&path)?;
 			self.name = path.file_name().and_then(|x| x.to_str()).expect("Path has a filename").to_string().into_boxed_str();
-			std::fs::write(&path, self.format.encode(&self.value))?;
+			log::info!("Saved {} as {}", path.display(), self.format.into_str());
+			self.remove_backup();
+			self.notes.relocate(&path).context("Saved, but could not move the file's notes")?;
 			self.path = Some(path);
 			self.unsaved_changes = false;
 			Ok(())
 		}
 	}
 
+	/// Writes the file to `path`, see [`file::to_file`], the palette indices unpacked by Alt + B are written packed, see [`schematic::with_packed`].
+	#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+	fn write(&mut self, path: &Path) -> Result<()> {
+		let (format, root_name) = (self.format, &self.root_name);
+		schematic::with_packed(&mut self.value, &self.unpacked_palettes, |value| file::to_file(path, value, format, root_name))
+	}
+
+	/// The path the backups of the file at `path` are written to, its name with `.bak` appended, or for region files the same name inside of a `.bak` directory next to it, since their name holds their coordinates which the `.mcc` files of their external chunks are named after.
+	#[must_use]
+	pub fn backup_path(path: &Path) -> PathBuf {
+		if NbtRegion::coordinates_of(path).is_some() && let Some(name) = path.file_name() {
+			return path.with_file_name(".bak").join(name)
+		}
+		let mut name = path.as_os_str().to_owned();
+		name.push(".bak");
+		PathBuf::from(name)
+	}
+
+	/// Writes the tab to the `.bak` of its file (see [`Self::backup_path`]) in its format, without marking its changes as saved, see [`config::get_backup_interval`].
+	///
+	/// # Errors
+	///
+	/// * The tab fails to encode or write, it isn't retried until the next interval
+	#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+	pub fn backup(&mut self) -> Result<()> {
+		self.last_backup = since_epoch();
+		let Some(path) = self.path.clone() else { return Ok(()) };
+		let backup = Self::backup_path(&path);
+		if let Some(dir) = backup.parent() {
+			std::fs::create_dir_all(dir)?;
+		}
+		self.write(&backup)?;
+		log::info!("Backed up {} to {}", path.display(), backup.display());
+		Ok(())
+	}
+
+	/// Removes the backup of the file once its changes are saved or discarded, a backup left behind is one of changes which were lost.
+	///
+	/// The backup of a region takes the `.mcc` files of its external chunks and the `.bak` directory (once empty) along with it, see [`Self::backup_path`].
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn remove_backup(&self) {
+		let Some(path) = self.path.as_deref() else { return };
+		let backup = Self::backup_path(path);
+		if let Err(e) = std::fs::remove_file(&backup) && e.kind() != std::io::ErrorKind::NotFound {
+			log::warn!("Failed to remove the backup of {}: {e}", path.display());
+		}
+		let Some((x, z, _)) = NbtRegion::coordinates_of(path) else { return };
+		let Some(dir) = backup.parent() else { return };
+		let Ok(entries) = std::fs::read_dir(dir) else { return };
+		for entry in entries.flatten() {
+			let name = entry.file_name();
+			if ExternalChunks::region_of(&name.to_string_lossy()) == Some((x, z)) && let Err(e) = std::fs::remove_file(entry.path()) {
+				log::warn!("Failed to remove the backup of {}: {e}", entry.path().display());
+			}
+		}
+		// only once it's empty, other regions of the same directory keep theirs
+		let _ = std::fs::remove_dir(dir);
+	}
+
 	#[cfg(target_arch = "wasm32")]
 	pub fn save(&mut self, _: bool) -> Result<()> {
-		let bytes = self.format.encode(&self.value);
+		let (format, root_name) = (self.format, &self.root_name);
+		let (bytes, external) = schematic::with_packed(&mut self.value, &self.unpacked_palettes, |value| file::encode(value, format, root_name))?;
+		if !external.is_empty() {
+			return Err(anyhow!("{n} chunks are too big for the region file, which can't be saved in the browser", n = external.len()))
+		}
 		crate::save(self.name.as_ref(), bytes);
+		log::info!("Saved {} as {}", self.name, self.format.into_str());
 		self.unsaved_changes = false;
 		Ok(())
 	}
 
+	/// Draws a `+`, `-` or `~` to the left of the notes of every row with an added, removed or changed element (or the closest visible parent of it), hovering it lists the differences.
+	fn render_diff(&self, builder: &mut VertexBufferBuilder, mouse_x: usize, mouse_y: usize, skip_tooltips: bool) {
+		use std::fmt::Write;
+
+		/// The most differences listed by the tooltip of a single row.
+		const MAX_LISTED: usize = 16;
+
+		if self.diff.is_empty() { return }
+
+		let mut rows = Vec::<(usize, DiffKind, Vec<&DiffEntry>)>::new();
+		for entry in &self.diff {
+			let Some(row) = self.value.row_of_path(&entry.indices) else { continue };
+			let Some(y) = (row * 16 + HEADER_SIZE).checked_sub(builder.scroll()) else { continue };
+			if y < HEADER_SIZE || y >= builder.window_height() { continue }
+			if let Some((_, kind, entries)) = rows.iter_mut().find(|(r, _, _)| *r == row) {
+				// a row hiding more than one kind of difference is a change to it
+				if *kind != entry.kind { *kind = DiffKind::Changed }
+				entries.push(entry);
+			} else {
+				rows.push((row, entry.kind, vec![entry]));
+			}
+		}
+
+		let x = builder.window_width().saturating_sub(40);
+		for (row, kind, entries) in rows {
+			let y = row * 16 + HEADER_SIZE - builder.scroll();
+			let (char, color) = match kind {
+				DiffKind::Added => ('+', TextColor::Green),
+				DiffKind::Removed => ('-', TextColor::Red),
+				DiffKind::Changed => ('~', TextColor::Yellow),
+			};
+			builder.settings((x, y), false, JUST_OVERLAPPING_BASE_TEXT_Z);
+			builder.color = color.to_raw();
+			let _ = write!(builder, "{char}");
+			if !skip_tooltips && (x..x + 16).contains(&mouse_x) && (y..y + 16).contains(&mouse_y) {
+				let mut lines = entries.iter().take(MAX_LISTED).map(|entry| format!("{char} {path}", char = match entry.kind { DiffKind::Added => '+', DiffKind::Removed => '-', DiffKind::Changed => '~' }, path = if entry.path.is_empty() { String::from("(root)") } else { PathSegment::join(&entry.path) })).collect::<Vec<_>>();
+				if entries.len() > MAX_LISTED {
+					lines.push(format!("and {n} more", n = entries.len() - MAX_LISTED));
+				}
+				builder.draw_tooltip(&lines.iter().map(String::as_str).collect::<Vec<_>>(), (mouse_x, mouse_y), false);
+			}
+		}
+		builder.color = TextColor::White.to_raw();
+	}
+
+	/// Draws a marker at the right of every row with a note (or the closest visible parent of it), hovering it shows the note.
+	fn render_notes(&self, builder: &mut VertexBufferBuilder, mouse_x: usize, mouse_y: usize, skip_tooltips: bool) {
+		use std::fmt::Write;
+
+		let x = builder.window_width().saturating_sub(24);
+		for (note, indices) in self.notes.resolve(&self.value) {
+			let Some(row) = indices.and_then(|indices| self.value.row_of_path(&indices)) else { continue };
+			let Some(y) = (row * 16 + HEADER_SIZE).checked_sub(builder.scroll()) else { continue };
+			if y < HEADER_SIZE || y >= builder.window_height() { continue }
+
+			builder.settings((x, y), false, JUST_OVERLAPPING_BASE_TEXT_Z);
+			builder.color = TextColor::Yellow.to_raw();
+			let _ = write!(builder, "*");
+			if !skip_tooltips && (x..x + 16).contains(&mouse_x) && (y..y + 16).contains(&mouse_y) {
+				builder.draw_tooltip(&note.text.lines().collect::<Vec<_>>(), (mouse_x, mouse_y), false);
+			}
+		}
+		builder.color = TextColor::White.to_raw();
+	}
+
+	/// Underlines every shown row with an element which doesn't match the built-in schema of the file (see [`Schema::for_file_name`]) or the closest visible parent of it, hovering it shows what's wrong.
+	fn render_schema_errors(&self, builder: &mut VertexBufferBuilder, ctx: &RenderContext, skip_tooltips: bool) {
+		let Some(schema) = Schema::for_file_name(&self.name) else { return };
+		let errors = self.value.validate(schema);
+		let mut rows = Vec::<(usize, usize, usize, Vec<String>)>::new();
+		for error in &errors {
+			let Some(row) = self.value.row_of_path(&error.indices) else { continue };
+			let message = if error.path.is_empty() { error.message.clone() } else { format!("{path}: {message}", path = PathSegment::join(&error.path), message = error.message) };
+			if let Some((_, _, _, messages)) = rows.iter_mut().find(|(r, _, _, _)| *r == row) {
+				messages.push(message);
+				continue
+			}
+			let mut depth = 0;
+			let mut width = self.name.width();
+			let mut element = &*self.value;
+			for &idx in &*error.indices {
+				if !element.open() { break }
+				if let Some(Err(mut children)) = element.children() && let Some((key, _)) = children.nth(idx) {
+					width = key.width();
+				} else {
+					width = 16;
+				}
+				let Some(child) = element.get(idx) else { break };
+				element = child;
+				depth += 1;
+			}
+			rows.push((row, depth, width, vec![message]));
+		}
+
+		for (row, depth, width, messages) in rows {
+			let Some(y) = (row * 16 + HEADER_SIZE).checked_sub(builder.scroll()) else { continue };
+			if y < HEADER_SIZE || y >= builder.window_height() { continue }
+
+			ctx.draw_error_underline_width(ctx.left_margin + 16 + indented(depth) * 16, y, width, builder);
+			if !skip_tooltips && (y..y + 16).contains(&ctx.mouse_y) && ctx.mouse_x >= ctx.left_margin {
+				builder.draw_tooltip(&messages.iter().map(String::as_str).collect::<Vec<_>>(), (ctx.mouse_x, ctx.mouse_y), false);
+			}
+		}
+	}
+
 	#[allow(clippy::too_many_lines)]
 	pub fn render(&self, builder: &mut VertexBufferBuilder, ctx: &mut RenderContext, held: bool, held_entry: Option<&NbtElement>, skip_tooltips: bool, steal_delta: f32) {
 		let mouse_x = ctx.mouse_x;
@@ -126,21 +348,33 @@ impl Tab {
 			&mut builder.horizontal_scroll,
 			self.horizontal_scroll(held_entry),
 		);
+		if !self.array_views.is_empty() {
+			// the root is the first line
+			ctx.set_array_views(self.array_views.iter().filter_map(|(path, &view)| Some((1 + self.value.line_of_path(&self.value.indices_of(path)?)?, view))).collect());
+		}
 		if let Some(compound) = self.value.as_compound() {
-			compound.render_root(builder, &self.name, ctx);
+			if self.root_name.is_empty() {
+				compound.render_root(builder, &self.name, ctx);
+			} else {
+				compound.render_root(builder, &format!("{name}: {root_name}", name = self.name, root_name = self.root_name), ctx);
+			}
 		} else if let Some(region) = self.value.as_region() {
 			region.render_root(builder, &self.name, ctx);
 		} else if let Some(list) = self.value.as_list() {
 			list.render_root(builder, &self.name, ctx);
 		}
+		ctx.render_clamped_depths(builder);
 		builder.color = TextColor::White.to_raw();
 		ctx.render_line_numbers(builder, &self.bookmarks);
 		ctx.render_key_value_errors(builder);
+		self.render_schema_errors(builder, ctx, skip_tooltips);
 		builder.horizontal_scroll = horizontal_scroll_before;
+		self.render_notes(builder, mouse_x, mouse_y, skip_tooltips);
+		self.render_diff(builder, mouse_x, mouse_y, skip_tooltips);
 
-		if builder.window_height() >= HEADER_SIZE {
+		if builder.window_height() >= HEADER_SIZE + STATUS_BAR_SIZE {
 			let height = self.value.height() * 16;
-			let total = builder.window_height() - HEADER_SIZE;
+			let total = builder.window_height() - HEADER_SIZE - STATUS_BAR_SIZE;
 			if height > total & !15 {
 				let scrollbar_height = (total & !15) * total / height;
 				let offset = total * self.scroll() / height + HEADER_SIZE;
@@ -331,6 +565,16 @@ impl Tab {
 		}
 	}
 
+	/// The text shown on the tab's header, its name followed by a dot while it has unsaved changes.
+	#[must_use]
+	pub fn header(&self) -> CompactString {
+		if self.unsaved_changes {
+			compact_str::format_compact!("{}{}", self.name, Self::UNSAVED_MARKER)
+		} else {
+			self.name.to_compact_string()
+		}
+	}
+
 	pub fn draw_icon(&self, builder: &mut VertexBufferBuilder, pos: impl Into<(usize, usize)>, z: ZOffset) {
 		let id = self.value.id();
 		if id == NbtCompound::ID {
@@ -343,16 +587,83 @@ impl Tab {
 	}
 
 	pub fn append_to_history(&mut self, action: WorkbenchAction) {
+		Self::mark_changed_chunks(&mut self.value, &action);
 		self.undos.push(action);
 		self.redos.clear();
 		self.unsaved_changes = true;
+		self.selection = None;
+		self.add_menu = None;
+		self.diff.clear();
+		self.byte_sizes.clear();
+	}
+
+	/// Undoes the last action, or redoes the last undone one, then scrolls to where it happened.
+	///
+	/// The selected text follows its element through actions which only reorder elements, otherwise it is dropped since its indices (and text) can no longer be trusted.
+	pub fn step_history(&mut self, redo: bool, subscription: &mut Option<FileUpdateSubscription>) -> bool {
+		let Some(action) = (if redo { self.redos.pop() } else { self.undos.pop() }) else { return false };
+		let selected = if action.is_reorder() { self.selected_text.take().and_then(|text| Some((self.value.path_of(&text.indices)?, text))) } else { None };
+		self.selected_text = None;
+		self.selection = None;
+		self.add_menu = None;
+		self.diff.clear();
+		self.byte_sizes.clear();
+
+		let action = action.undo(&mut self.value, &mut self.bookmarks, subscription, &mut self.path, &mut self.name, &mut self.root_name);
+		Self::mark_changed_chunks(&mut self.value, &action);
+
+		if let Some(row) = action.focus().and_then(|indices| self.value.row_of_path(indices)) {
+			self.scroll_to_row(row);
+		}
+		if let Some((path, mut text)) = selected && let Some(indices) = self.value.indices_of(&path) && let Some(row) = self.value.row_of_path(&indices) {
+			text.indices = indices.into_boxed_slice();
+			text.y = row * 16 + HEADER_SIZE;
+			self.selected_text = Some(text);
+		}
+		if redo { self.undos.push(action) } else { self.redos.push(action) }
+		true
+	}
+
+	/// Marks the chunks `action` changed an element of as edited so they're compressed again when saved instead of written as they were read, see [`NbtRegion::mark_dirty`].
+	///
+	/// Every chunk is marked when the action adds, removes or moves chunks along with its other changes, since their indices may then point at a different chunk.
+	fn mark_changed_chunks(value: &mut NbtElement, action: &WorkbenchAction) {
+		let Some(region) = value.as_region_mut() else { return };
+		let mut chunks = vec![];
+		let mut moved = false;
+		action.changed(&mut |indices| match indices.first() {
+			Some(&idx) => chunks.push(idx),
+			None => moved = true,
+		});
+		if moved && !chunks.is_empty() {
+			chunks = (0..region.len()).collect();
+		}
+		for idx in chunks {
+			region.mark_dirty(idx);
+		}
+	}
+
+	/// The height of the tree between the header and the status bar.
+	#[must_use]
+	pub fn visible_height(&self) -> usize { self.window_height.saturating_sub(HEADER_SIZE + STATUS_BAR_SIZE) }
+
+	/// Scrolls the least amount needed to show `row`.
+	pub fn scroll_to_row(&mut self, row: usize) {
+		let y = row * 16;
+		let visible = self.visible_height() & !15;
+		if y < self.scroll {
+			self.scroll = y;
+		} else if y + 16 > self.scroll + visible {
+			self.scroll = (y + 16).saturating_sub(visible);
+		}
+		self.scroll = self.scroll();
 	}
 
 	#[must_use]
 	pub fn scroll(&self) -> usize {
 		let height = self.value.height() * 16 + 32 + 15;
 		let scroll = self.scroll;
-		let max = (height + HEADER_SIZE).saturating_sub(self.window_height);
+		let max = height.saturating_sub(self.visible_height());
 		scroll.min(max) & !15
 	}
 
@@ -360,7 +671,7 @@ impl Tab {
 	pub fn horizontal_scroll(&self, held: Option<&NbtElement>) -> usize {
 		let left_margin = self.left_margin(held);
 		let selected_text_width = if let Some(selected_text) = &self.selected_text {
-			selected_text.indices.len() * 16 + 32 + 4 + selected_text.width()
+			indented(selected_text.indices.len()) * 16 + 32 + 4 + selected_text.width()
 		} else {
 			0
 		};
@@ -380,42 +691,81 @@ impl Tab {
 
 	#[inline]
 	pub fn set_scroll(&mut self, scroll: f32) {
-		#[cfg(target_os = "macos")]
-		const SCROLL_MULTIPLIER: f32 = 4.0;
-		#[cfg(not(target_os = "macos"))]
-		const SCROLL_MULTIPLIER: f32 = 48.0;
-
-		if scroll.is_sign_negative() && self.scroll < (scroll * -SCROLL_MULTIPLIER) as usize {
+		if scroll.is_sign_negative() && self.scroll < -scroll as usize {
 			self.scroll = 0;
 		} else if scroll.is_sign_negative() {
-			self.scroll -= (scroll * -SCROLL_MULTIPLIER) as usize;
+			self.scroll -= -scroll as usize;
 		} else {
-			self.scroll += (scroll * SCROLL_MULTIPLIER) as usize;
+			self.scroll += scroll as usize;
 		}
 		self.scroll = self.scroll();
 	}
 
 	#[inline]
 	pub fn set_horizontal_scroll(&mut self, scroll: f32, held: Option<&NbtElement>) {
-		#[cfg(target_os = "macos")]
-		const SCROLL_MULTIPLIER: f32 = 4.0;
-		#[cfg(not(target_os = "macos"))]
-		const SCROLL_MULTIPLIER: f32 = 48.0;
-
-		if scroll.is_sign_negative() && self.horizontal_scroll < (scroll * -SCROLL_MULTIPLIER) as usize {
+		if scroll.is_sign_negative() && self.horizontal_scroll < -scroll as usize {
 			self.horizontal_scroll = 0;
 		} else if scroll.is_sign_negative() {
-			self.horizontal_scroll -= (scroll * -SCROLL_MULTIPLIER) as usize;
+			self.horizontal_scroll -= -scroll as usize;
 		} else {
-			self.horizontal_scroll += (scroll * SCROLL_MULTIPLIER) as usize;
+			self.horizontal_scroll += scroll as usize;
 		}
 		self.horizontal_scroll = self.horizontal_scroll(held);
 	}
 
+	/// The values the selected text can be set to, if it's editing the value of a chunk's `Status`.
+	#[must_use]
+	pub fn selected_status_options(&self) -> Option<&'static [&'static str]> {
+		let SelectedText(Text { editable: true, additional: SelectedTextAdditional { indices, keyfix, prefix, .. }, .. }) = self.selected_text.as_ref()? else { return None };
+		// keys have neither
+		if keyfix.is_none() && prefix.0.is_empty() { return None }
+		if self.is_raw_view(indices) { return None }
+		let (&chunk, rem) = indices.split_first()?;
+		self.value.as_region()?.get(chunk)?.as_chunk()?.status_options(rem)
+	}
+
+	/// If the element at `indices` was toggled to show its raw NBT, see [`Self::toggle_raw_view`].
+	#[must_use]
+	pub fn is_raw_view(&self, indices: &[usize]) -> bool { !self.raw_views.is_empty() && self.value.path_of(indices).is_some_and(|path| self.raw_views.contains(&*path)) }
+
+	/// Switches the element at `indices` between its specialized view and its raw NBT, the element itself is left untouched, returns if it now shows raw NBT.
+	pub fn toggle_raw_view(&mut self, indices: &[usize]) -> Option<bool> {
+		let path = self.value.path_of(indices)?.into_boxed_slice();
+		Some(if self.raw_views.remove(&path) { false } else { self.raw_views.insert(path) })
+	}
+
+	/// How the array at `indices` writes its values, see [`Self::set_array_view`].
+	#[must_use]
+	pub fn array_view(&self, indices: &[usize]) -> ArrayView {
+		if self.array_views.is_empty() { return ArrayView::Decimal }
+		self.value.path_of(indices).and_then(|path| self.array_views.get(&*path).copied()).unwrap_or(ArrayView::Decimal)
+	}
+
+	/// Sets how the array at `indices` writes its values, the values themselves are left untouched.
+	pub fn set_array_view(&mut self, indices: &[usize], view: ArrayView) -> Option<()> {
+		let path = self.value.path_of(indices)?.into_boxed_slice();
+		if view == ArrayView::Decimal {
+			self.array_views.remove(&path);
+		} else {
+			self.array_views.insert(path, view);
+		}
+		Some(())
+	}
+
+	/// Gives a freshly added element (still keyed `_` and holding the value of [`NbtElement::from_id_configured`]) the value configured for its new key, see [`config::get_default`], returning its previous value.
+	fn apply_key_default(child: &mut NbtElement, old_key: &str, key: &str) -> Option<CompactString> {
+		if old_key != "_" { return None }
+		let default = config::get_default(child.id(), Some(key))?;
+		if child.value().0 != NbtElement::from_id_configured(child.id()).value().0 { return None }
+		let (previous, success) = child.set_value(default)?;
+		success.then_some(previous)
+	}
+
 	#[inline]
 	#[must_use]
 	#[allow(clippy::too_many_lines)]
 	pub fn close_selected_text(&mut self, ignore_invalid_format: bool, window_properties: &mut WindowProperties) -> bool {
+		let status_options = self.selected_status_options();
 		unsafe {
 			if let Some(SelectedText(Text { value, editable: true, additional: SelectedTextAdditional { indices, keyfix, prefix, suffix, valuefix, .. }, .. })) = self.selected_text.clone() {
 				if let Some((&last, rem)) = indices.split_last() {
@@ -434,10 +784,9 @@ impl Tab {
 										ignore_invalid_format
 									};
 								}
-								(
-									Some(compound.update_key(last, value.clone()).unwrap_or(value)),
-									None,
-								)
+								let old_key = compound.update_key(last, value.clone()).unwrap_or_else(|| value.clone());
+								let previous = compound.get_mut(last).and_then(|(_, child)| Self::apply_key_default(child, &old_key, &value));
+								(Some(old_key), previous)
 							} else if let Some(chunk) = element.as_chunk_mut() {
 								let idx = chunk.entries.idx_of(&value);
 								if let Some(idx) = idx {
@@ -448,10 +797,9 @@ impl Tab {
 										ignore_invalid_format
 									};
 								}
-								(
-									Some(chunk.update_key(last, value.clone()).unwrap_or(value)),
-									None,
-								)
+								let old_key = chunk.update_key(last, value.clone()).unwrap_or_else(|| value.clone());
+								let previous = chunk.get_mut(last).and_then(|(_, child)| Self::apply_key_default(child, &old_key, &value));
+								(Some(old_key), previous)
 							} else if let Some(region) = element.as_region_mut() {
 								let (Ok(x @ 0..=31), Ok(z @ 0..=31)) = (
 									value.parse::<u8>(),
@@ -462,33 +810,13 @@ impl Tab {
 								) else {
 									return ignore_invalid_format;
 								};
-								let (old_x, old_z) = {
-									let chunk = region
-										.get_mut(last)
-										.panic_unchecked("Last index was valid")
-										.as_chunk_unchecked_mut();
-									(
-										core::mem::replace(&mut chunk.x, x),
-										core::mem::replace(&mut chunk.z, z),
-									)
+								let Some((old_x, old_z)) = region.relocate_chunk(last, x, z) else {
+									return ignore_invalid_format;
 								};
-								let new_idx = ((x as usize) << 5) | (z as usize);
 								if old_x == x && old_z == z {
 									self.selected_text = None;
 									return true;
 								}
-								if !region.chunks.deref().1[new_idx].is_null() {
-									let chunk = region
-										.get_mut(last)
-										.panic_unchecked("Last index was valid")
-										.as_chunk_unchecked_mut();
-									chunk.x = old_x;
-									chunk.z = old_z;
-									return ignore_invalid_format;
-								}
-								let (map, chunks) = &mut *region.chunks;
-								let from = core::mem::replace(&mut map[last], new_idx as u16) as usize;
-								chunks.swap(from, new_idx);
 								(
 									Some(old_x.to_compact_string()),
 									Some(old_z.to_compact_string()),
@@ -507,38 +835,19 @@ impl Tab {
 								) else {
 									return ignore_invalid_format;
 								};
-								let (old_x, old_z) = {
-									let chunk = region
-										.get_mut(last)
-										.panic_unchecked("Last index was valid")
-										.as_chunk_unchecked_mut();
-									(
-										core::mem::replace(&mut chunk.x, x),
-										core::mem::replace(&mut chunk.z, z),
-									)
+								let Some((old_x, old_z)) = region.relocate_chunk(last, x, z) else {
+									return ignore_invalid_format;
 								};
-								let new_idx = ((x as usize) << 5) | (z as usize);
 								if old_x == x && old_z == z {
 									self.selected_text = None;
 									return true;
 								}
-								if !region.chunks.deref().1[new_idx].is_null() {
-									let chunk = region
-										.get_mut(last)
-										.panic_unchecked("Last index was valid")
-										.as_chunk_unchecked_mut();
-									chunk.x = old_x;
-									chunk.z = old_z;
-									return ignore_invalid_format;
-								};
-								let (map, chunks) = &mut *region.chunks;
-								let from = core::mem::replace(&mut map[last], new_idx as u16) as usize;
-								chunks.swap(from, new_idx);
 								(
 									Some(old_x.to_compact_string()),
 									Some(old_z.to_compact_string()),
 								)
 							} else {
+								if let Some(options) = status_options && !options.contains(&value.as_str()) { return ignore_invalid_format }
 								// no drops dw, well except for the value, but that's a simple thing dw
 								let child = element
 									.get_mut(last)
@@ -562,6 +871,19 @@ impl Tab {
 						value,
 					});
 					self.selected_text = None;
+				} else if keyfix.is_some() {
+					// the name of the root compound, written after the file name
+					if self.root_name == value {
+						self.selected_text = None;
+						return true
+					}
+					let old_root_name = core::mem::replace(&mut self.root_name, CompactString::from(value));
+					self.append_to_history(WorkbenchAction::Rename {
+						indices: Box::new([]),
+						key: Some(old_root_name),
+						value: None,
+					});
+					self.selected_text = None;
 				} else {
 					if self.path.as_ref().map(|path| path.as_os_str().to_string_lossy()).as_deref().unwrap_or(&self.name) == value {
 						return true;
@@ -598,58 +920,9 @@ impl Tab {
 		true
 	}
 
-	#[inline]
-	pub fn parse_raw(path: &Path, buf: Vec<u8>, sort_algorithm: SortAlgorithm) -> Result<(NbtElement, FileFormat)> {
-		Ok(if let Some("mca" | "mcr") = path.extension().and_then(OsStr::to_str) {
-			(
-				NbtElement::from_be_mca(buf.as_slice(), sort_algorithm).context("Failed to parse MCA file")?,
-				FileFormat::Mca,
-			)
-		} else if let Some(0x1F8B) = buf.first_chunk::<2>().copied().map(u16::from_be_bytes) {
-			(
-				NbtElement::from_be_file(
-					&DeflateDecoder::new(buf.as_slice())
-						.decode_gzip()
-						.context("Failed to decode gzip compressed NBT")?,
-					sort_algorithm,
-				)
-					.context("Failed to parse NBT")?,
-				FileFormat::Gzip,
-			)
-		} else if let Some(0x7801 | 0x789C | 0x78DA) = buf.first_chunk::<2>().copied().map(u16::from_be_bytes) {
-			(
-				NbtElement::from_be_file(
-					&DeflateDecoder::new(buf.as_slice())
-						.decode_zlib()
-						.context("Failed to decode zlib compressed NBT")?,
-					sort_algorithm,
-				)
-					.context("Failed to parse NBT")?,
-				FileFormat::Zlib,
-			)
-		} else if let Some(nbt) = NbtElement::from_be_file(buf.as_slice(), sort_algorithm) {
-			(nbt, FileFormat::Nbt)
-		} else if let Some((nbt, header)) = NbtElement::from_le_file(buf.as_slice(), sort_algorithm) {
-			(nbt, if header { FileFormat::LittleEndianHeaderNbt } else { FileFormat::LittleEndianNbt })
-		} else {
-			(
-				core::str::from_utf8(&buf)
-					.ok()
-					.and_then(|s| NbtElement::from_str(s, sort_algorithm))
-					.context(anyhow!(
-							"Failed to find file type for file {}",
-							path.file_name()
-								.unwrap_or(&OsStr::new(""))
-								.to_string_lossy()
-						))?
-					.1,
-				FileFormat::Snbt,
-			)
-		})
-	}
-
+	/// Reads the tab's file again, detecting its format unless `forced` is given, which is then kept for saving.
 	#[cfg(not(target_arch = "wasm32"))]
-	pub fn refresh(&mut self, sort_algorithm: SortAlgorithm) -> Result<()> {
+	pub fn refresh(&mut self, sort_algorithm: SortAlgorithm, forced: Option<FileFormat>) -> Result<()> {
 		let Some(path) = self.path.as_deref() else { return Err(anyhow!("File path was not present in tab")) };
 
 		if self.unsaved_changes && (since_epoch() - core::mem::replace(&mut self.last_close_attempt, since_epoch())) > crate::DOUBLE_CLICK_INTERVAL {
@@ -657,14 +930,26 @@ impl Tab {
 		}
 
 		let bytes = std::fs::read(path)?;
-		let (value, format) = Tab::parse_raw(path, bytes, sort_algorithm)?;
+		let (mut value, format, root_name) = if let Some(format) = forced {
+			let (value, root_name) = format.decode_named(&bytes, sort_algorithm, ExternalChunks::Files(path)).with_context(|| format!("Failed to parse file as {}", format.into_str()))?;
+			(value, format, root_name)
+		} else {
+			file::from_file(path, bytes, sort_algorithm)?
+		};
+		if !(value.id() == NbtCompound::ID || value.id() == NbtRegion::ID || value.id() == NbtList::ID) { return Err(anyhow!("Parsed NBT was not a Compound, Region, or List")) }
+		value.expand_to_depth(config::get_expand_depth());
 
 		self.bookmarks.clear();
 		self.scroll = 0;
 		self.format = format;
+		self.root_name = root_name;
 		self.unsaved_changes = false;
 		self.uuid = Uuid::new_v4();
 		self.selected_text = None;
+		self.selection = None;
+		self.add_menu = None;
+		self.diff.clear();
+		self.byte_sizes.clear();
 		self.last_close_attempt = Duration::ZERO;
 		let old = (core::mem::replace(&mut self.value, Box::new(value)), core::mem::replace(&mut self.undos, LinkedQueue::new()), core::mem::replace(&mut self.redos, LinkedQueue::new()));
 		std::thread::Builder::new().stack_size(50_331_648 /*48MiB*/).spawn(move || drop(old)).expect("Failed to spawn thread");
@@ -673,108 +958,357 @@ impl Tab {
 	}
 
 	#[cfg(target_arch = "wasm32")]
-	pub fn refresh(&mut self, _: SortAlgorithm) -> Result<()> {
+	pub fn refresh(&mut self, _: SortAlgorithm, _: Option<FileFormat>) -> Result<()> {
 		Ok(())
 	}
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-#[repr(u8)]
-pub enum FileFormat {
-	Nbt,
-	Gzip,
-	Zlib,
-	Snbt,
-	LittleEndianNbt,
-	LittleEndianHeaderNbt,
+#[cfg(test)]
+mod tests {
+	use std::io::Write;
+	use std::path::Path;
 
-	Lz4,
+	use flate2::write::ZlibEncoder;
+	use flate2::Compression;
 
-	Mca,
-}
+	use crate::{config, SortAlgorithm, WindowProperties};
+	use crate::assets::HEADER_SIZE;
+	use crate::color::TextColor;
+	use crate::element_action::ElementAction;
+	use crate::elements::chunk::{ExternalChunks, NbtChunk, NbtRegion};
+	use crate::elements::compound::NbtCompound;
+	use crate::elements::element::NbtElement;
+	use crate::file::FileFormat;
+	use crate::selected_text::{SelectedText, SelectedTextAdditional};
+	use crate::text::Text;
 
-impl FileFormat {
-	#[must_use]
-	pub const fn cycle(self) -> Self {
-		match self {
-			Self::Nbt => Self::Gzip,
-			Self::Gzip => Self::Zlib,
-			Self::Zlib => Self::LittleEndianNbt,
-			Self::LittleEndianNbt => Self::LittleEndianHeaderNbt,
-			Self::LittleEndianHeaderNbt => Self::Snbt,
-			Self::Snbt => Self::Nbt,
+	use super::Tab;
 
-			// has to be separate
-			Self::Mca => Self::Mca,
-			Self::Lz4 => Self::Lz4,
-		}
+	#[test]
+	fn edits_close_the_add_menu() {
+		use crate::add_menu::AddMenu;
+		use crate::elements::element::NbtInt;
+		use crate::workbench_action::WorkbenchAction;
+
+		let nbt = NbtElement::from_snbt("{list: []}").expect("the fixture is valid SNBT");
+		let mut tab = Tab::new(nbt, Path::new("test.nbt"), FileFormat::Nbt, 420, 620).expect("compounds can be opened");
+		let menu = |tab: &Tab| AddMenu::new(Box::new([0]), 0, tab.value.get(0).expect("the list is there"));
+		tab.add_menu = menu(&tab);
+		// bytes are offered first
+		let stale = menu(&tab).expect("empty lists can hold anything");
+
+		let action = WorkbenchAction::Remove { element: (None, NbtElement::Int(NbtInt { value: 1 })), indices: Box::new([0, 0]) }.undo(&mut tab.value, &mut tab.bookmarks, &mut None, &mut tab.path, &mut tab.name, &mut tab.root_name);
+		tab.append_to_history(action);
+		assert!(tab.add_menu.is_none());
+		// the list only holds ints now
+		assert!(!stale.still_fits(tab.value.get(0).expect("the list is there")));
+
+		tab.add_menu = menu(&tab);
+		assert!(tab.step_history(false, &mut None));
+		assert!(tab.add_menu.is_none());
 	}
 
-	#[must_use]
-	pub const fn rev_cycle(self) -> Self {
-		match self {
-			Self::Nbt => Self::Snbt,
-			Self::Gzip => Self::Nbt,
-			Self::Zlib => Self::Gzip,
-			Self::LittleEndianNbt => Self::Zlib,
-			Self::LittleEndianHeaderNbt => Self::LittleEndianNbt,
-			Self::Snbt => Self::LittleEndianHeaderNbt,
+	#[test]
+	fn opened_files_are_expanded_to_the_configured_depth() {
+		let nbt = NbtElement::from_snbt("{a: {b: 1}, c: [2, 3]}").expect("the fixture is valid SNBT");
+		let tab = Tab::new(nbt, Path::new("test.nbt"), FileFormat::Nbt, 420, 620).expect("compounds can be opened");
+		let depth = config::get_expand_depth();
+		assert_eq!(tab.value.open(), depth >= 1);
+		assert_eq!(tab.value.get(0).is_some_and(NbtElement::open), depth >= 2);
+		assert_eq!(tab.value.get(1).is_some_and(NbtElement::open), depth >= 2);
+	}
 
-			// has to be separate
-			Self::Mca => Self::Mca,
-			Self::Lz4 => Self::Lz4,
-		}
+	/// The selected value `value` of the entry `key` at `indices`.
+	fn selected_value(key: &str, value: &str, indices: &[usize], y: usize) -> SelectedText {
+		SelectedText(Text::new(value.to_owned(), value.len(), true, SelectedTextAdditional {
+			y,
+			indices: indices.into(),
+			value_color: TextColor::TreeString,
+			keyfix: Some((key.to_owned(), TextColor::TreeKey)),
+			prefix: (": ".to_owned(), TextColor::TreeKey),
+			suffix: (String::new(), TextColor::White),
+			valuefix: None,
+		}))
 	}
 
-	#[must_use]
-	pub fn encode(self, data: &NbtElement) -> Vec<u8> {
-		match self {
-			Self::Nbt | Self::Mca => data.to_be_file(),
-			Self::Gzip => {
-				let mut vec = vec![];
-				let _ = flate2::read::GzEncoder::new(&*data.to_be_file(), Compression::best()).read_to_end(&mut vec);
-				vec
-			}
-			Self::Zlib => {
-				let mut vec = vec![];
-				let _ = flate2::read::ZlibEncoder::new(&*data.to_be_file(), Compression::best()).read_to_end(&mut vec);
-				vec
-			}
-			Self::Lz4 => lz4_flex::compress(&*data.to_be_file()),
-			Self::Snbt => data.to_string().into_bytes(),
-			format @ (Self::LittleEndianNbt | Self::LittleEndianHeaderNbt) => data.to_le_file(format == Self::LittleEndianHeaderNbt),
-		}
+	/// Selects the value of the `Status` of the only chunk of a region with `status`.
+	fn status_tab(status: &str) -> Tab {
+		let compound = NbtElement::from_snbt(&format!("{{DataVersion: 3465, Status: \"{status}\"}}")).and_then(NbtElement::into_compound).expect("the fixture is valid SNBT");
+		let mut region = NbtRegion::new();
+		assert!(region.insert(0, NbtElement::Chunk(NbtChunk::from_compound(compound, (0, 0), FileFormat::Zlib, 0))).is_ok());
+		let mut tab = Tab::new(NbtElement::Region(region), Path::new("r.0.0.mca"), FileFormat::Mca, 420, 620).expect("regions can be opened");
+		tab.selected_text = Some(selected_value("Status", status, &[0, 1], 0));
+		tab
 	}
 
-	#[must_use]
-	pub const fn uv(self) -> Vec2u {
-		match self {
-			Self::Nbt => NBT_FILE_TYPE_UV,
-			Self::Gzip => GZIP_FILE_TYPE_UV,
-			Self::Zlib => ZLIB_FILE_TYPE_UV,
-			Self::Snbt => SNBT_FILE_TYPE_UV,
-			Self::Mca => MCA_FILE_TYPE_UV,
-			Self::LittleEndianNbt => LITTLE_ENDIAN_NBT_FILE_TYPE_UV,
-			Self::LittleEndianHeaderNbt => LITTLE_ENDIAN_HEADER_NBT_FILE_TYPE_UV,
-			Self::Lz4 => Vec2u::new(240, 240),
+	fn status_of(tab: &Tab) -> Option<&str> { tab.value.as_region()?.get(0)?.as_chunk()?.get(1)?.1.as_string().map(|string| string.str.as_str()) }
+
+	#[test]
+	fn picking_a_chunk_status_writes_its_string() {
+		let mut tab = status_tab("minecraft:features");
+		let options = tab.selected_status_options().expect("the status of a 1.20 chunk has options");
+		assert!(options.contains(&"minecraft:initialize_light"));
+
+		let selected_text = tab.selected_text.as_mut().expect("the status is selected");
+		selected_text.select_option(options, false);
+		assert_eq!(selected_text.value, "minecraft:initialize_light");
+		selected_text.select_option(options, true);
+		selected_text.select_option(options, true);
+		assert_eq!(selected_text.value, "minecraft:liquid_carvers");
+		assert!(tab.close_selected_text(false, &mut WindowProperties::Fake));
+		assert!(tab.selected_text.is_none());
+		assert_eq!(status_of(&tab), Some("minecraft:liquid_carvers"));
+
+		// wraps around and starts from either end for statuses which aren't known
+		let mut tab = status_tab("minecraft:unknown");
+		let selected_text = tab.selected_text.as_mut().expect("the status is selected");
+		selected_text.select_option(options, true);
+		assert_eq!(selected_text.value, "minecraft:full");
+		selected_text.select_option(options, false);
+		assert_eq!(selected_text.value, "minecraft:empty");
+	}
+
+	#[test]
+	fn toggling_the_raw_view_leaves_the_data_unchanged() {
+		let mut tab = status_tab("minecraft:features");
+		let before = tab.value.to_be_file();
+		assert!(tab.selected_status_options().is_some());
+
+		assert_eq!(tab.toggle_raw_view(&[0, 1]), Some(true));
+		assert!(tab.is_raw_view(&[0, 1]));
+		assert!(!tab.is_raw_view(&[0]));
+		assert!(tab.selected_status_options().is_none());
+		assert_eq!(tab.value.to_be_file(), before);
+		// without the options any string can be written
+		tab.selected_text.as_mut().expect("the status is selected").value = "minecraft:unknown".to_owned();
+		assert!(tab.close_selected_text(false, &mut WindowProperties::Fake));
+		assert_eq!(status_of(&tab), Some("minecraft:unknown"));
+		tab.selected_text = status_tab("minecraft:features").selected_text;
+		assert!(tab.selected_status_options().is_none());
+		assert!(tab.close_selected_text(false, &mut WindowProperties::Fake));
+
+		assert_eq!(tab.toggle_raw_view(&[0, 1]), Some(false));
+		assert!(!tab.is_raw_view(&[0, 1]));
+		assert_eq!(tab.value.to_be_file(), before);
+		assert!(tab.toggle_raw_view(&[0, 2]).is_none());
+	}
+
+	fn string_at(tab: &Tab, idx: usize) -> Option<&str> { tab.value.get(idx)?.as_string().map(|string| string.str.as_str()) }
+
+	#[test]
+	fn undoing_a_sort_keeps_the_selection_on_its_element() {
+		let nbt = NbtElement::from_snbt(r#"{c: "three", a: "one", b: {z: 1, y: 2}}"#).expect("the fixture is valid SNBT");
+		let mut tab = Tab::new(nbt, Path::new("test.nbt"), FileFormat::Nbt, 420, 620).expect("compounds can be opened");
+		let action = ElementAction::SortCompoundByName.apply(None, Box::new([]), tab.uuid, 1, 0, &mut tab.value, &mut tab.bookmarks, &mut None).expect("compounds can be sorted");
+		tab.append_to_history(action);
+		assert_eq!(string_at(&tab, 2), Some("three"));
+
+		// `c` is the last entry after sorting, and the first one before
+		let row = |tab: &Tab, indices: &[usize]| tab.value.row_of_path(indices).expect("the element is shown");
+		tab.selected_text = Some(selected_value("c", "three", &[2], row(&tab, &[2]) * 16 + HEADER_SIZE));
+		assert!(tab.step_history(false, &mut None));
+		let selected_text = tab.selected_text.as_ref().expect("the selection survives reordering");
+		assert_eq!(&*selected_text.indices, [0]);
+		assert_eq!(selected_text.y, row(&tab, &[0]) * 16 + HEADER_SIZE);
+		assert_eq!(selected_text.value, "three");
+
+		assert!(tab.step_history(true, &mut None));
+		let selected_text = tab.selected_text.as_ref().expect("the selection survives reordering");
+		assert_eq!(&*selected_text.indices, [2]);
+		assert_eq!(selected_text.y, row(&tab, &[2]) * 16 + HEADER_SIZE);
+
+		// closing the selection after undoing writes into the right element
+		assert!(tab.step_history(false, &mut None));
+		tab.selected_text.as_mut().expect("the selection survives reordering").value = "four".to_owned();
+		assert!(tab.close_selected_text(false, &mut WindowProperties::Fake));
+		assert_eq!(string_at(&tab, 0), Some("four"));
+		assert_eq!(string_at(&tab, 1), Some("one"));
+	}
+
+	#[test]
+	fn undoing_an_edit_drops_the_selection() {
+		let nbt = NbtElement::from_snbt(r#"{a: "one", b: "two"}"#).expect("the fixture is valid SNBT");
+		let mut tab = Tab::new(nbt, Path::new("test.nbt"), FileFormat::Nbt, 420, 620).expect("compounds can be opened");
+		tab.selected_text = Some(selected_value("a", "one", &[0], HEADER_SIZE + 16));
+		tab.selected_text.as_mut().expect("the value is selected").value = "uno".to_owned();
+		assert!(tab.close_selected_text(false, &mut WindowProperties::Fake));
+		tab.selected_text = Some(selected_value("b", "two", &[1], HEADER_SIZE + 32));
+		assert!(tab.step_history(false, &mut None));
+		assert!(tab.selected_text.is_none());
+		assert_eq!(string_at(&tab, 0), Some("one"));
+	}
+
+	#[test]
+	fn named_roots_round_trip_and_can_be_renamed() {
+		let nbt = NbtElement::from_snbt(r#"{a: 1, b: "two"}"#).expect("the fixture is valid SNBT");
+		let bytes = nbt.to_be_file_named("Data");
+		let (nbt, format, root_name) = crate::file::from_file(Path::new("test.nbt"), bytes.clone(), SortAlgorithm::None).expect("the file is valid");
+		assert_eq!(format, FileFormat::Nbt);
+		assert_eq!(root_name, "Data");
+		let mut tab = Tab::new(nbt, Path::new("test.nbt"), format, 420, 620).expect("compounds can be opened");
+		tab.root_name = root_name;
+		let encoded = |tab: &Tab| crate::file::encode(&tab.value, tab.format, &tab.root_name).expect("compounds can be encoded").0;
+		assert_eq!(encoded(&tab), bytes);
+
+		// the root row, with the file name as its key and the root name as its value
+		tab.selected_text = Some(selected_value("test.nbt", "Data", &[], HEADER_SIZE));
+		tab.selected_text.as_mut().expect("the root name is selected").value = "Level".to_owned();
+		assert!(tab.close_selected_text(false, &mut WindowProperties::Fake));
+		assert_eq!(tab.root_name, "Level");
+		assert_eq!(tab.name.as_ref(), "test.nbt");
+		assert_eq!(encoded(&tab), tab.value.to_be_file_named("Level"));
+
+		assert!(tab.step_history(false, &mut None));
+		assert_eq!(tab.root_name, "Data");
+		assert_eq!(encoded(&tab), bytes);
+		assert!(tab.step_history(true, &mut None));
+		assert_eq!(tab.root_name, "Level");
+	}
+
+	#[test]
+	#[cfg(not(target_arch = "wasm32"))]
+	fn refreshing_with_a_forced_format_reparses_the_file() {
+		let short = |tab: &Tab| tab.value.get(0).and_then(NbtElement::as_short).map(|short| short.value);
+		// reads as big endian NBT too, as 513s
+		let bytes = NbtElement::from_snbt(r#"{"": 258s}"#).expect("the fixture is valid SNBT").to_le_file(false);
+		let dir = std::env::temp_dir().join(format!("nbtworkbench-refresh-{}", uuid::Uuid::new_v4()));
+		std::fs::create_dir_all(&dir).expect("the temp dir is writable");
+		let path = dir.join("level.dat");
+		std::fs::write(&path, &bytes).expect("the temp dir is writable");
+
+		let (nbt, format, _) = crate::file::from_file(&path, bytes, SortAlgorithm::None).expect("the file is valid");
+		assert_eq!(format, FileFormat::Nbt);
+		let mut tab = Tab::new(nbt, &path, format, 420, 620).expect("compounds can be opened");
+		assert_eq!(short(&tab), Some(513));
+
+		tab.refresh(SortAlgorithm::None, Some(FileFormat::LittleEndianNbt)).expect("the file is valid little endian NBT");
+		assert_eq!(tab.format, FileFormat::LittleEndianNbt);
+		assert_eq!(short(&tab), Some(258));
+
+		assert!(tab.refresh(SortAlgorithm::None, Some(FileFormat::Snbt)).is_err());
+		assert_eq!(tab.format, FileFormat::LittleEndianNbt);
+		assert_eq!(short(&tab), Some(258));
+
+		tab.refresh(SortAlgorithm::None, None).expect("the file is valid");
+		assert_eq!(tab.format, FileFormat::Nbt);
+		assert_eq!(short(&tab), Some(513));
+		let _ = std::fs::remove_dir_all(dir);
+	}
+
+	#[test]
+	fn backups_are_written_next_to_their_file() {
+		let dir = Path::new("/saves/world");
+		assert_eq!(Tab::backup_path(&dir.join("level.dat")), dir.join("level.dat.bak"));
+		// regions keep their name, which their external chunks are named after
+		assert_eq!(Tab::backup_path(&dir.join("region/r.-1.2.mca")), dir.join("region/.bak/r.-1.2.mca"));
+		assert_eq!(Tab::backup_path(&dir.join("r.x.mca")), dir.join("r.x.mca.bak"));
+	}
+
+	#[test]
+	#[cfg(not(target_arch = "wasm32"))]
+	fn region_backups_take_their_external_chunks_along() {
+		use compact_str::CompactString;
+
+		use crate::elements::compound::NbtCompound;
+		use crate::elements::element::{NbtByte, NbtByteArray};
+
+		let dir = std::env::temp_dir().join(format!("nbtworkbench-backup-{}", uuid::Uuid::new_v4()));
+		std::fs::create_dir_all(&dir).expect("the temp dir is writable");
+		let path = dir.join("r.0.0.mca");
+		// too big for the 255 sectors a chunk can take up inside of its region file
+		let mut compound = NbtCompound::new();
+		let bytes = NbtByteArray::from_values(vec![NbtElement::Byte(NbtByte { value: 0 }); 1_100_000]).expect("bytes make a byte array");
+		compound.insert(0, CompactString::new("Data"), NbtElement::ByteArray(bytes));
+		let mut region = NbtRegion::new();
+		assert!(region.insert(0, NbtElement::Chunk(NbtChunk::from_compound(compound, (0, 0), FileFormat::Nbt, 0))).is_ok());
+		let mut tab = Tab::new(NbtElement::Region(region), &path, FileFormat::Mca, 420, 620).expect("regions can be opened");
+
+		tab.backup().expect("the backup is written");
+		let backups = dir.join(".bak");
+		assert!(backups.join("r.0.0.mca").is_file());
+		assert!(backups.join("c.0.0.mcc").is_file());
+		assert!(!path.exists(), "the file itself is left alone");
+
+		tab.remove_backup();
+		assert!(!backups.exists());
+		let _ = std::fs::remove_dir_all(dir);
+	}
+
+	#[test]
+	fn unsaved_tabs_are_marked_on_their_header() {
+		let mut tab = Tab::new(NbtElement::Compound(NbtCompound::new()), Path::new("level.dat"), FileFormat::Gzip, 420, 620).expect("compounds can be opened");
+		assert_eq!(&*tab.header(), "level.dat");
+		tab.unsaved_changes = true;
+		assert_eq!(&*tab.header(), "level.dat •");
+	}
+
+	#[test]
+	fn unknown_chunk_statuses_are_rejected() {
+		let mut tab = status_tab("minecraft:features");
+		tab.selected_text.as_mut().expect("the status is selected").value = "minecraft:unknown".to_owned();
+		assert!(!tab.close_selected_text(false, &mut WindowProperties::Fake));
+		assert_eq!(status_of(&tab), Some("minecraft:features"));
+	}
+
+	#[test]
+	fn moving_a_chunk_onto_another_is_rejected() {
+		let mut region = NbtRegion::new();
+		for (idx, (x, z)) in [(0, 0), (1, 2)].into_iter().enumerate() {
+			assert!(region.insert(idx, NbtElement::Chunk(NbtChunk::from_compound(Default::default(), (x, z), FileFormat::Zlib, 0))).is_ok());
 		}
+		let mut tab = Tab::new(NbtElement::Region(region), Path::new("r.0.0.mca"), FileFormat::Mca, 420, 620).expect("regions can be opened");
+		let before = tab.value.to_be_file();
+		// the z coordinate of the first chunk, with the x coordinate as its key
+		tab.selected_text = Some(selected_value("1", "2", &[0], 0));
+		assert!(!tab.close_selected_text(false, &mut WindowProperties::Fake));
+		assert!(!tab.unsaved_changes);
+		let region = tab.value.as_region().expect("the tab holds a region");
+		let coordinates = (0..region.len()).map(|idx| region.get(idx).and_then(NbtElement::as_chunk).map(|chunk| (chunk.x, chunk.z))).collect::<Vec<_>>();
+		assert_eq!(coordinates, [Some((0, 0)), Some((1, 2))]);
+		assert_eq!(tab.value.to_be_file(), before);
 	}
 
-	#[must_use]
-	pub const fn into_str(self) -> &'static str {
-		match self {
-			Self::Nbt => "Uncompressed",
-			Self::Gzip => "GZip",
-			Self::Zlib => "ZLib",
-			Self::Snbt => "SNBT",
-			Self::Mca => "MCA",
-			Self::Lz4 => "LZ4",
-			Self::LittleEndianNbt => "Little Endian NBT",
-			Self::LittleEndianHeaderNbt => "Little Endian NBT (With Header)",
+	/// A region file with `chunks` in its first slots, compressed with zlib at its best compression unlike the chunks compressed when saving.
+	fn region_file(chunks: &[&str]) -> Vec<u8> {
+		let mut header = vec![0; 8192];
+		let mut sectors = vec![];
+		for (idx, snbt) in chunks.iter().enumerate() {
+			let mut zlib = ZlibEncoder::new(vec![], Compression::best());
+			zlib.write_all(&NbtElement::from_snbt(snbt).expect("the fixture is valid SNBT").to_be_file()).expect("writes into a vec always succeed");
+			let compressed = zlib.finish().expect("writes into a vec always succeed");
+			let start = sectors.len() / 4096 + 2;
+			sectors.extend_from_slice(&(compressed.len() as u32 + 1).to_be_bytes());
+			sectors.push(2);
+			sectors.extend_from_slice(&compressed);
+			sectors.resize(sectors.len().next_multiple_of(4096), 0);
+			header[idx * 4..idx * 4 + 4].copy_from_slice(&((start as u32) << 8 | (sectors.len() / 4096 + 2 - start) as u32).to_be_bytes());
 		}
+		header.extend_from_slice(&sectors);
+		header
+	}
+
+	/// The sectors of the chunk in slot `idx` of the region file `bytes`.
+	fn sectors_of(bytes: &[u8], idx: usize) -> &[u8] {
+		let offset = u32::from_be_bytes(bytes[idx * 4..idx * 4 + 4].try_into().expect("the slice is four bytes"));
+		&bytes[(offset >> 8) as usize * 4096..((offset >> 8) + (offset & 0xFF)) as usize * 4096]
 	}
-}
 
-impl ToString for FileFormat {
-	fn to_string(&self) -> String { self.into_str().to_owned() }
+	#[test]
+	fn saving_keeps_untouched_chunks_as_they_were_read() {
+		let bytes = region_file(&[r#"{zPos: 0, xPos: 0, Status: "minecraft:full"}"#, r#"{zPos: 1, xPos: 0, Status: "minecraft:full"}"#, r#"{zPos: 2, xPos: 0, Status: "minecraft:full"}"#]);
+		let region = NbtRegion::from_be_bytes(&bytes, SortAlgorithm::None, ExternalChunks::None).expect("the region file is valid");
+		let mut tab = Tab::new(NbtElement::Region(region), Path::new("r.0.0.mca"), FileFormat::Mca, 420, 620).expect("regions can be opened");
+		let line_number = tab.value.get(0).expect("the region has three chunks").true_height() + 1;
+		let chunk = tab.value.get_mut(1).expect("the region has three chunks");
+		let action = ElementAction::SortCompoundByName.apply(None, Box::new([1]), tab.uuid, line_number, line_number, chunk, &mut tab.bookmarks, &mut None).expect("chunks can be sorted");
+		tab.append_to_history(action);
+
+		let saved = tab.value.to_be_file();
+		assert_eq!(sectors_of(&saved, 0), sectors_of(&bytes, 0));
+		assert_eq!(sectors_of(&saved, 2), sectors_of(&bytes, 2));
+		assert_ne!(sectors_of(&saved, 1), sectors_of(&bytes, 1));
+		let region = NbtRegion::from_be_bytes(&saved, SortAlgorithm::None, ExternalChunks::None).expect("the saved region file is valid");
+		let chunk = region.get(1).and_then(NbtElement::as_chunk).expect("the sorted chunk was saved");
+		assert_eq!(chunk.children().map(|(key, _)| key).collect::<Vec<_>>(), ["Status", "xPos", "zPos"]);
+	}
 }
@@ -75,6 +75,50 @@ pub trait Cachelike<Additional: Clone>: PartialEq + Clone {
     fn revert(self, text: &mut Text<Additional, Self>) where Self: Sized;
 }
 
+/// The value, cursor and selection of a [`Text`], the part of its state every input keeps in its history.
+///
+/// The caches of [`SelectedText`](crate::selected_text::SelectedText) and [`SearchBox`](crate::search_box::SearchBox) are built on it, a `Text<(), TextInput>` is a plain text box with nothing more to it.
+#[derive(Clone, Debug, Eq)]
+pub struct TextInput {
+    value: String,
+    cursor: usize,
+    selection: Option<usize>,
+}
+
+impl PartialEq for TextInput {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl TextInput {
+    #[must_use]
+    pub fn of<Additional: Clone, Cache: Cachelike<Additional>>(text: &Text<Additional, Cache>) -> Self {
+        Self {
+            value: text.value.clone(),
+            cursor: text.cursor,
+            selection: text.selection,
+        }
+    }
+
+    pub fn restore<Additional: Clone, Cache: Cachelike<Additional>>(self, text: &mut Text<Additional, Cache>) {
+        let Self { value, cursor, selection } = self;
+        text.value = value;
+        text.cursor = cursor;
+        text.selection = selection;
+    }
+}
+
+impl<Additional: Clone> Cachelike<Additional> for TextInput {
+    fn new(text: &Text<Additional, Self>) -> Self where Self: Sized {
+        Self::of(text)
+    }
+
+    fn revert(self, text: &mut Text<Additional, Self>) where Self: Sized {
+        self.restore(text);
+    }
+}
+
 #[derive(Clone)]
 pub struct Text<Additional: Clone, Cache: Cachelike<Additional>> {
     pub value: String,
@@ -130,6 +174,15 @@ impl<Additional: Clone, Cache: Cachelike<Additional>> Text<Additional, Cache> {
         }
     }
 
+    /// How long until the cursor blinks, `None` if it isn't drawn.
+    #[must_use]
+    pub fn next_blink(&self) -> Option<Duration> {
+        if !self.editable { return None }
+        // shown for the first half of every second since the last interaction, see `render`
+        let since = since_epoch() - self.last_interaction;
+        Some(Duration::from_millis(500 - u64::from(since.subsec_millis() % 500)))
+    }
+
     #[inline]
     pub fn interact(&mut self) {
         self.last_interaction = since_epoch();
@@ -617,3 +670,96 @@ impl<Additional: Clone, Cache: Cachelike<Additional>> Text<Additional, Cache> {
         self.last_interaction = since_epoch();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::KeyCode;
+
+    use crate::flags;
+
+    use super::{KeyResult, Text, TextInput};
+
+    fn press(text: &mut Text<(), TextInput>, key: KeyCode, flags: u8) -> KeyResult { text.on_key_press(key, None, flags) }
+
+    fn type_str(text: &mut Text<(), TextInput>, str: &str) {
+        for char in str.chars() {
+            assert!(text.on_key_press(KeyCode::KeyA, Some(char), flags!()) == KeyResult::NothingSpecial);
+        }
+    }
+
+    #[test]
+    fn typing_moves_the_cursor_by_whole_chars() {
+        let mut text = Text::new(String::new(), 0, true, ());
+        type_str(&mut text, "héllo");
+        assert_eq!((text.value.as_str(), text.cursor), ("héllo", 6));
+
+        press(&mut text, KeyCode::ArrowLeft, flags!());
+        press(&mut text, KeyCode::ArrowLeft, flags!());
+        press(&mut text, KeyCode::ArrowLeft, flags!());
+        assert_eq!(text.cursor, 3);
+        press(&mut text, KeyCode::ArrowLeft, flags!());
+        assert_eq!(text.cursor, 1);
+        press(&mut text, KeyCode::ArrowRight, flags!());
+        assert_eq!(text.cursor, 3);
+
+        press(&mut text, KeyCode::Home, flags!());
+        assert_eq!(text.cursor, 0);
+        type_str(&mut text, ">");
+        press(&mut text, KeyCode::End, flags!());
+        assert_eq!((text.value.as_str(), text.cursor), (">héllo", 7));
+    }
+
+    #[test]
+    fn deleting_removes_whole_chars_and_words() {
+        let mut text = Text::new("hello wörld".to_owned(), 12, true, ());
+        press(&mut text, KeyCode::Backspace, flags!());
+        assert_eq!((text.value.as_str(), text.cursor), ("hello wörl", 11));
+        press(&mut text, KeyCode::ArrowLeft, flags!());
+        press(&mut text, KeyCode::ArrowLeft, flags!());
+        press(&mut text, KeyCode::ArrowLeft, flags!());
+        assert_eq!(text.cursor, 7);
+        press(&mut text, KeyCode::Delete, flags!());
+        assert_eq!((text.value.as_str(), text.cursor), ("hello wrl", 7));
+        press(&mut text, KeyCode::End, flags!());
+        press(&mut text, KeyCode::Backspace, flags!(Ctrl));
+        assert_eq!((text.value.as_str(), text.cursor), ("hello ", 6));
+    }
+
+    #[test]
+    fn selections_are_replaced_and_removed() {
+        let mut text = Text::new("abcdef".to_owned(), 6, true, ());
+        press(&mut text, KeyCode::ArrowLeft, flags!(Shift));
+        press(&mut text, KeyCode::ArrowLeft, flags!(Shift));
+        assert_eq!((text.cursor, text.selection), (4, Some(6)));
+        type_str(&mut text, "X");
+        assert_eq!((text.value.as_str(), text.cursor, text.selection), ("abcdX", 5, None));
+
+        press(&mut text, KeyCode::Home, flags!(Shift));
+        assert_eq!((text.cursor, text.selection), (0, Some(5)));
+        // moving without Shift only collapses the selection
+        press(&mut text, KeyCode::ArrowRight, flags!());
+        assert_eq!((text.cursor, text.selection), (5, None));
+
+        press(&mut text, KeyCode::KeyA, flags!(Ctrl));
+        press(&mut text, KeyCode::Backspace, flags!());
+        assert_eq!((text.value.as_str(), text.cursor, text.selection), ("", 0, None));
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_value() {
+        let mut text = Text::new("abc".to_owned(), 3, true, ());
+        type_str(&mut text, "def");
+        assert!(press(&mut text, KeyCode::KeyZ, flags!(Ctrl)) == KeyResult::NothingSpecial);
+        assert_eq!((text.value.as_str(), text.cursor), ("abc", 3));
+        assert!(press(&mut text, KeyCode::KeyY, flags!(Ctrl)) == KeyResult::NothingSpecial);
+        assert_eq!((text.value.as_str(), text.cursor), ("abcdef", 6));
+    }
+
+    #[test]
+    fn read_only_text_ignores_edits() {
+        let mut text = Text::new("abc".to_owned(), 3, false, ());
+        assert!(text.on_key_press(KeyCode::KeyA, Some('a'), flags!()) == KeyResult::Failed);
+        press(&mut text, KeyCode::Backspace, flags!());
+        assert_eq!((text.value.as_str(), text.cursor), ("abc", 3));
+    }
+}
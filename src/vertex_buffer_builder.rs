@@ -1,12 +1,46 @@
 use std::fmt::{Debug, Formatter};
+#[cfg(feature = "gui")]
+use std::hash::Hasher;
+#[cfg(feature = "gui")]
 use std::ops::BitAnd;
 
+#[cfg(feature = "gui")]
+use fxhash::FxHasher;
+
+#[cfg(feature = "gui")]
 use winit::dpi::PhysicalSize;
 
+#[cfg(feature = "gui")]
 use crate::assets::{BASE_TEXT_Z, BASE_Z, TOOLTIP_Z, TOOLTIP_UV, ZOffset};
+#[cfg(feature = "gui")]
 use crate::color::TextColor;
-use crate::StrExt;
 
+/// The width of each character of the font in pixels, by its code point, `0` for the ones it has no glyph for.
+pub const CHAR_WIDTH: &[u8] = include_bytes!("assets/char_widths.hex");
+
+/// The glyph drawn in place of characters the font has none for, a question mark in a box.
+pub const MISSING_GLYPH: u16 = 56829;
+
+/// The glyph of the font `char` is drawn with, [`MISSING_GLYPH`] for the characters past the ones it covers and the ones it has no glyph for.
+#[inline]
+#[must_use]
+pub const fn glyph(char: char) -> u16 {
+	if (char as usize) < CHAR_WIDTH.len() && CHAR_WIDTH[char as usize] > 0 { char as u16 } else { MISSING_GLYPH }
+}
+
+/// The width of `char` in pixels, see [`glyph`].
+#[inline]
+#[must_use]
+pub const fn char_width(char: char) -> usize { CHAR_WIDTH[glyph(char) as usize] as usize }
+
+/// The width of `text` in pixels when written, the sum of its [`char_width`]s.
+///
+/// Every glyph leaves a pixel of space after itself, which is where the drop shadow of text is drawn, so it takes no more room with one.
+#[inline]
+#[must_use]
+pub fn text_width(text: &str) -> usize { text.chars().map(char_width).sum() }
+
+#[cfg(feature = "gui")]
 pub struct VertexBufferBuilder {
 	vertices: Vec<f32>,
 	indices: Vec<u16>,
@@ -30,31 +64,23 @@ pub struct VertexBufferBuilder {
 	scale: f32,
 }
 
+#[cfg(feature = "gui")]
 impl core::fmt::Write for VertexBufferBuilder {
 	fn write_str(&mut self, text: &str) -> std::fmt::Result {
 		let (mut x, y) = self.text_coords;
-		x += text.chars().fold(0, |offset, char| {
-			let char = if (char as u32) < 56832 {
-				char as u16
-			} else {
-				56829
-			};
-			offset + self.draw_char(char, x + offset, y, self.text_z)
-		});
+		x += text.chars().fold(0, |offset, char| offset + self.draw_char(glyph(char), x + offset, y, self.text_z));
 		self.text_coords = (x, y);
 		Ok(())
 	}
 
 	fn write_char(&mut self, c: char) -> std::fmt::Result {
-		let c = if (c as u32) < 56832 { c as u16 } else { 56829 };
-		self.text_coords.0 += self.draw_char(c, self.text_coords.0, self.text_coords.1, self.text_z);
+		self.text_coords.0 += self.draw_char(glyph(c), self.text_coords.0, self.text_coords.1, self.text_z);
 		Ok(())
 	}
 }
 
+#[cfg(feature = "gui")]
 impl VertexBufferBuilder {
-	pub const CHAR_WIDTH: &'static [u8] = include_bytes!("assets/char_widths.hex");
-
 	pub fn new(size: PhysicalSize<u32>, texture_width: usize, texture_height: usize, scroll: usize, scale: usize) -> Self {
 		Self {
 			vertices: Vec::with_capacity(98304),
@@ -86,6 +112,11 @@ impl VertexBufferBuilder {
 	#[inline]
 	pub const fn drew_tooltip(&self) -> bool { self.owned_tooltip.is_some() }
 
+	/// The width of `text` in pixels when written by this builder, see [`text_width`].
+	#[inline]
+	#[must_use]
+	pub fn text_width(&self, text: &str) -> usize { text_width(text) }
+
 	#[inline]
 	pub fn settings(&mut self, pos: impl Into<(usize, usize)>, dropshadow: bool, z: ZOffset) {
 		self.text_coords = pos.into();
@@ -119,7 +150,7 @@ impl VertexBufferBuilder {
 			});
 		}
 		self.draw_unicode_z_color(x, y, z, c, self.color & 0xFFFFFF);
-		Self::CHAR_WIDTH[c as usize] as usize
+		CHAR_WIDTH[c as usize] as usize
 	}
 
 	#[inline]
@@ -144,21 +175,19 @@ impl VertexBufferBuilder {
 
 		let (mut x, y) = pos.into();
 		let y = y + 16;
-		let text_width = text.iter().map(|x| x.width()).max().unwrap_or(0);
+		let text_width = text.iter().map(|line| self.text_width(line)).max().unwrap_or(0);
 		if x >= self.window_width() / 2 && !force_draw_right {
 			x = x.saturating_sub(text_width + 3);
 		}
 		self.text_z = TOOLTIP_Z;
 		self.text_coords = (x + 3, y + 3);
 		self.draw_texture_z((x, y), TOOLTIP_Z, TOOLTIP_UV, (3, 3));
-		let mut max = x + 3;
 		for line in text.iter() {
 			let _ = write!(self, "{line}");
-			max = max.max(self.text_coords.0);
 			self.text_coords.0 = x + 3;
 			self.text_coords.1 += 16;
 		}
-		let width = max - 3 - x;
+		let width = text_width;
 		let height = self.text_coords.1 - 3 - y;
 		self.draw_texture_region_z(
 			(x + 3, y),
@@ -308,6 +337,26 @@ impl VertexBufferBuilder {
 	#[inline]
 	pub fn window_width(&self) -> usize { (self.window_width / self.scale) as usize }
 
+	/// A hash of everything drawn so far, including the tooltip, frames with the same one look the same.
+	#[must_use]
+	pub fn fingerprint(&self) -> u64 {
+		let mut hasher = FxHasher::default();
+		hasher.write(self.vertices());
+		hasher.write(unsafe { core::slice::from_raw_parts(self.indices.as_ptr().cast::<u8>(), self.indices.len() * 2) });
+		hasher.write(self.text_vertices());
+		hasher.write(self.text_indices());
+		if let Some((text, pos, force_draw_right)) = &self.owned_tooltip {
+			for line in text.iter() {
+				hasher.write(line.as_bytes());
+				hasher.write_u8(0xFF);
+			}
+			hasher.write_usize(pos.x);
+			hasher.write_usize(pos.y);
+			hasher.write_u8(u8::from(*force_draw_right));
+		}
+		hasher.finish()
+	}
+
 	#[inline]
 	pub fn vertices(&self) -> &[u8] { unsafe { core::slice::from_raw_parts(self.vertices.as_ptr().cast::<u8>(), self.vertices.len() * 4) } }
 
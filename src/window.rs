@@ -8,10 +8,10 @@ use wasm_bindgen::JsValue;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 #[allow(clippy::wildcard_imports)]
 use wgpu::*;
-use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Size};
 #[allow(clippy::wildcard_imports)]
 use winit::event::*;
-use winit::event_loop::EventLoop;
+use winit::event_loop::{ControlFlow, EventLoop};
 #[cfg(target_os = "windows")]
 use winit::platform::windows::WindowBuilderExtWindows;
 #[cfg(target_arch = "wasm32")]
@@ -23,8 +23,10 @@ use crate::alert::Alert;
 use crate::assets::HEADER_SIZE;
 use crate::color::TextColor;
 use crate::vertex_buffer_builder::VertexBufferBuilder;
-use crate::workbench::Workbench;
-use crate::{assets, WORKBENCH, WINDOW_PROPERTIES, error, OptionExt, since_epoch, WindowProperties};
+use crate::workbench::{Workbench, TICK_INTERVAL};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::session;
+use crate::{assets, config, WORKBENCH, WINDOW_PROPERTIES, OptionExt, since_epoch, WindowProperties};
 
 pub const WINDOW_HEIGHT: usize = 420;
 pub const WINDOW_WIDTH: usize = 720;
@@ -33,21 +35,38 @@ pub const MIN_WINDOW_WIDTH: usize = 720;
 
 pub async fn run() -> ! {
 	let event_loop = EventLoop::new().expect("Event loop was unconstructable");
+	#[cfg(not(target_arch = "wasm32"))]
+	session::read();
+	// logical sizes are scaled by the monitor the window opens on, so that it has room for the zoom of `Workbench::set_scale_factor`
+	#[cfg(not(target_arch = "wasm32"))]
+	let initial_size: Size = session::get_window_size().map_or_else(|| LogicalSize::new(WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32).into(), Into::into);
+	#[cfg(target_arch = "wasm32")]
+	let initial_size: Size = PhysicalSize::new(WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32).into();
 	let builder = WindowBuilder::new()
 		.with_title("NBT Workbench")
-		.with_inner_size(PhysicalSize::new(WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32))
-		.with_min_inner_size(PhysicalSize::new(
+		.with_inner_size(initial_size)
+		.with_min_inner_size(LogicalSize::new(
 			MIN_WINDOW_WIDTH as u32,
 			MIN_WINDOW_HEIGHT as u32,
 		))
 		.with_window_icon(Some(
 			Icon::from_rgba(
-				assets::icon(),
+				// it's a good random only because its used once
+				assets::icon(config::get_icon().unwrap_or_else(|| (since_epoch().as_millis() & 7) as usize)),
 				assets::ICON_WIDTH as u32,
 				assets::ICON_HEIGHT as u32,
 			)
 			.expect("valid format"),
 		));
+	// a monitor may have been unplugged since, so the window is only put back where it was if that's still on one
+	#[cfg(not(target_arch = "wasm32"))]
+	let builder = match session::get_window_position() {
+		Some(position) if event_loop.available_monitors().any(|monitor| {
+			let (origin, size) = (monitor.position(), monitor.size());
+			(origin.x..origin.x + size.width as i32).contains(&position.x) && (origin.y..origin.y + size.height as i32).contains(&position.y)
+		}) => builder.with_position(position),
+		_ => builder,
+	};
 	let window = Rc::new('a: {
 		#[cfg(target_os = "windows")] {
 			break 'a builder.with_drag_and_drop(true)
@@ -72,28 +91,48 @@ pub async fn run() -> ! {
 		}).expect("Couldn't append canvas to document body")
 	};
 	#[cfg(not(target_arch = "wasm32"))]
-	let window_size = PhysicalSize::new(WINDOW_WIDTH as u32, WINDOW_HEIGHT as u32);
+	let window_size = window.inner_size();
 	let mut state = State::new(&window, window_size).await;
 	unsafe { std::ptr::write(std::ptr::addr_of_mut!(WINDOW_PROPERTIES), UnsafeCell::new(WindowProperties::new(Rc::clone(&window)))); }
 	let window_properties = unsafe { WINDOW_PROPERTIES.get_mut() };
 	unsafe { std::ptr::write(std::ptr::addr_of_mut!(WORKBENCH), UnsafeCell::new(Workbench::new(window_properties))); }
 	let workbench = unsafe { WORKBENCH.get_mut() };
-	event_loop.run(|event, _| match event {
+	workbench.window_dimensions(window_size.width as usize, window_size.height as usize);
+	workbench.set_scale_factor(window.scale_factor());
+	// if the next redraw was asked for by us rather than the OS
+	let mut requested = false;
+	event_loop.run(|event, target| match event {
 		Event::WindowEvent { event, window_id } if window_id == window.id() => {
 			#[cfg(target_arch = "wasm32")]
 			crate::on_input();
 			if !State::input(&event, workbench, window_properties) {
 				match event {
 					WindowEvent::RedrawRequested => {
-						match state.render(workbench, window.as_ref()) {
-							Ok(()) => {}
-							Err(SurfaceError::Lost | SurfaceError::Outdated) => state.surface.configure(&state.device, &state.config),
-							Err(SurfaceError::OutOfMemory) => std::process::exit(1),
-							Err(SurfaceError::Timeout) => { error!("Frame took too long to process") },
+						// the OS asks for redraws when the window lost what it showed, so those are always drawn
+						let force = !std::mem::replace(&mut requested, false);
+						match state.render(workbench, window.as_ref(), force) {
+							Ok(_) => {}
+							Err(e @ (SurfaceError::Lost | SurfaceError::Outdated)) => {
+								log::info!("Reconfiguring surface after it was {}", if e == SurfaceError::Lost { "lost" } else { "outdated" });
+								state.surface.configure(&state.device, &state.config);
+								state.frame = None;
+								workbench.dirty = true;
+							}
+							Err(SurfaceError::OutOfMemory) => {
+								log::error!("Ran out of memory while rendering");
+								std::process::exit(1)
+							}
+							Err(SurfaceError::Timeout) => log::warn!("Frame took too long to process"),
 						}
 					}
-					WindowEvent::CloseRequested => if workbench.close() == 0 { std::process::exit(0) },
-					WindowEvent::Resized(new_size) => state.resize(workbench, new_size),
+					WindowEvent::CloseRequested => if workbench.close() == 0 { workbench.exit() },
+					WindowEvent::Resized(new_size) => {
+						#[cfg(not(target_arch = "wasm32"))]
+						if new_size.width > 0 && new_size.height > 0 && !window.is_maximized() { session::set_window_size(new_size) }
+						state.resize(workbench, new_size)
+					}
+					#[cfg(not(target_arch = "wasm32"))]
+					WindowEvent::Moved(position) => if !window.is_maximized() { session::set_window_position(position) },
 					_ => {}
 				}
 			}
@@ -108,8 +147,19 @@ pub async fn run() -> ! {
 					state.resize(workbench, new_size);
 				}
 			}
-			window.request_redraw();
+			// input only asks for a frame when it changed something, timed changes wake the loop when they're due
+			match workbench.next_change() {
+				Some(Duration::ZERO) => workbench.dirty = true,
+				Some(duration) => target.set_control_flow(ControlFlow::wait_duration(duration)),
+				None => target.set_control_flow(ControlFlow::Wait),
+			}
+			if workbench.dirty {
+				requested = true;
+				window.request_redraw();
+				target.set_control_flow(ControlFlow::Wait);
+			}
 		}
+		Event::NewEvents(StartCause::ResumeTimeReached { .. }) => workbench.dirty = true,
 		_ => {}
 	}).expect("Event loop failed");
 	loop {}
@@ -130,6 +180,47 @@ pub struct State<'window> {
 	sampler: Sampler,
 	last_tick: Duration,
 	copy_render_pipeline: RenderPipeline,
+	present_modes: Vec<PresentMode>,
+	/// The present mode last asked for by the workbench, which [`Self::config`] may have fallen back from.
+	requested_present_mode: PresentMode,
+	/// The [`VertexBufferBuilder::fingerprint`] of the frame last drawn, `None` once the surface is reconfigured, frames with the same one aren't drawn again.
+	frame: Option<u64>,
+	vertex_buffer: ReusedBuffer,
+	index_buffer: ReusedBuffer,
+	text_vertex_buffer: ReusedBuffer,
+	text_index_buffer: ReusedBuffer,
+}
+
+/// A buffer kept across frames, rewritten with the contents of each one and only recreated when they outgrow it.
+struct ReusedBuffer {
+	label: &'static str,
+	usage: BufferUsages,
+	buffer: Option<Buffer>,
+}
+
+impl ReusedBuffer {
+	const fn new(label: &'static str, usage: BufferUsages) -> Self {
+		Self { label, usage, buffer: None }
+	}
+
+	fn write(&mut self, device: &Device, queue: &Queue, contents: &[u8]) -> &Buffer {
+		debug_assert_eq!(contents.len() % COPY_BUFFER_ALIGNMENT as usize, 0, "vertices and indices are written in whole words");
+		let len = (contents.len() as u64).max(COPY_BUFFER_ALIGNMENT);
+		if self.buffer.as_ref().map_or(true, |buffer| buffer.size() < len) {
+			// grown by powers of two so that growing frames don't recreate it every time
+			self.buffer = Some(device.create_buffer(&BufferDescriptor {
+				label: Some(self.label),
+				size: len.next_power_of_two(),
+				usage: self.usage | BufferUsages::COPY_DST,
+				mapped_at_creation: false,
+			}));
+		}
+		let buffer = unsafe { self.buffer.as_ref().panic_unchecked("Buffer was just created") };
+		if !contents.is_empty() {
+			queue.write_buffer(buffer, 0, contents);
+		}
+		buffer
+	}
 }
 
 impl<'window> State<'window> {
@@ -165,18 +256,20 @@ impl<'window> State<'window> {
 			)
 			.await
 			.expect("Could obtain device");
-		let format = surface
-			.get_capabilities(&adapter)
+		let capabilities = surface.get_capabilities(&adapter);
+		let format = capabilities
 			.formats
 			.into_iter()
 			.find(|format| !format.is_srgb())
 			.expect("An SRGB format exists");
+		let present_modes = capabilities.present_modes;
+		let requested_present_mode = config::get_present_mode();
 		let config = SurfaceConfiguration {
 			usage: TextureUsages::RENDER_ATTACHMENT,
 			format,
 			width: size.width,
 			height: size.height,
-			present_mode: PresentMode::AutoVsync,
+			present_mode: Self::supported_present_mode(&present_modes, requested_present_mode),
 			desired_maximum_frame_latency: 0,
 			alpha_mode: CompositeAlphaMode::Auto,
 			view_formats: vec![],
@@ -563,6 +656,35 @@ impl<'window> State<'window> {
 			copy_render_pipeline,
 			sampler,
 			last_tick: Duration::ZERO,
+			present_modes,
+			requested_present_mode,
+			frame: None,
+			vertex_buffer: ReusedBuffer::new("Vertex Buffer", BufferUsages::VERTEX),
+			index_buffer: ReusedBuffer::new("Index Buffer", BufferUsages::INDEX),
+			text_vertex_buffer: ReusedBuffer::new("Text Vertex Buffer", BufferUsages::VERTEX),
+			text_index_buffer: ReusedBuffer::new("Text Index Buffer", BufferUsages::INDEX),
+		}
+	}
+
+	/// `mode` if the adapter supports it, otherwise [`PresentMode::Fifo`], which every adapter does.
+	#[must_use]
+	fn supported_present_mode(present_modes: &[PresentMode], mode: PresentMode) -> PresentMode {
+		if present_modes.contains(&mode) { mode } else { PresentMode::Fifo }
+	}
+
+	fn set_present_mode(&mut self, workbench: &mut Workbench) {
+		let mode = workbench.present_mode;
+		self.requested_present_mode = mode;
+		let supported = Self::supported_present_mode(&self.present_modes, mode);
+		if supported != self.config.present_mode {
+			self.config.present_mode = supported;
+			self.surface.configure(&self.device, &self.config);
+			self.frame = None;
+		}
+		if supported == mode {
+			workbench.alert(Alert::new("Present Mode", TextColor::White, format!("Presenting with {}", config::present_mode_into_str(mode))));
+		} else {
+			workbench.alert(Alert::new("Warning!", TextColor::Yellow, format!("{} is not supported, presenting with {}", config::present_mode_into_str(mode), config::present_mode_into_str(supported))));
 		}
 	}
 
@@ -572,6 +694,7 @@ impl<'window> State<'window> {
 			self.config.width = new_size.width;
 			self.config.height = new_size.height;
 			self.surface.configure(&self.device, &self.config);
+			self.frame = None;
 			workbench.window_dimensions(new_size.width as usize, new_size.height as usize);
 			for tab in &mut workbench.tabs {
 				tab.scroll = tab.scroll();
@@ -583,14 +706,8 @@ impl<'window> State<'window> {
 	#[allow(clippy::match_same_arms)] // it's cool to be reminded more often how many things I can have events for to make nbt workbench more reactive
 	fn input(event: &WindowEvent, workbench: &mut Workbench, window_properties: &mut WindowProperties) -> bool {
 		match event {
-			WindowEvent::Resized(size) => {
-				workbench.window_height = size.height as usize;
-				for entry in &mut workbench.tabs {
-					entry.window_height = workbench.window_height;
-					entry.scroll = entry.scroll();
-				}
-				false
-			}
+			// handled by `State::resize` once it isn't consumed, which keeps the heights scaled by the zoom
+			WindowEvent::Resized(_) => false,
 			WindowEvent::Moved(_) => false,
 			WindowEvent::CloseRequested => false,
 			WindowEvent::Destroyed => false,
@@ -623,7 +740,10 @@ impl<'window> State<'window> {
 					workbench.on_mouse_input(ElementState::Released, MouseButton::Left, window_properties)
 				}
 			},
-			WindowEvent::ScaleFactorChanged { .. } => false,
+			WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+				workbench.set_scale_factor(*scale_factor);
+				true
+			}
 			WindowEvent::ThemeChanged(_) => false,
 			WindowEvent::Ime(_) => false,
 			WindowEvent::Occluded(_) => false,
@@ -635,8 +755,9 @@ impl<'window> State<'window> {
 		}
 	}
 
-	fn render(&mut self, workbench: &mut Workbench, window: &Window) -> Result<(), SurfaceError> {
-		if (since_epoch() - self.last_tick).as_millis() >= 25
+	/// Builds the frame if the workbench changed or it's `force`d, then draws it unless it looks the same as the last one and isn't `force`d, returns if it was drawn.
+	fn render(&mut self, workbench: &mut Workbench, window: &Window, force: bool) -> Result<bool, SurfaceError> {
+		if since_epoch() - self.last_tick >= TICK_INTERVAL
 		{
 			workbench.tick();
 			self.last_tick = since_epoch();
@@ -644,6 +765,33 @@ impl<'window> State<'window> {
 		if let Err(e) = workbench.try_subscription() {
 			workbench.alert(Alert::new("Error!", TextColor::Red, e.to_string()))
 		}
+		if workbench.present_mode != self.requested_present_mode {
+			self.set_present_mode(workbench);
+		}
+
+		if !force && !workbench.dirty { return Ok(false) }
+		workbench.dirty = false;
+
+		let mut builder = VertexBufferBuilder::new(
+			self.size,
+			assets::ATLAS_WIDTH,
+			assets::ATLAS_HEIGHT,
+			workbench.scroll(),
+			workbench.scale,
+		);
+		workbench.render(&mut builder);
+
+		let show_cursor = true;
+		if show_cursor != workbench.cursor_visible {
+			window.set_cursor_visible(show_cursor);
+			workbench.cursor_visible = show_cursor;
+		}
+
+		let fingerprint = builder.fingerprint();
+		if !force && self.frame == Some(fingerprint) {
+			return Ok(false);
+		}
+
 		let surface_texture = self.surface.get_current_texture()?;
 		let size = Extent3d {
 			width: surface_texture.texture.width(),
@@ -679,32 +827,14 @@ impl<'window> State<'window> {
 		});
 		let depth_texture_view = depth_texture.create_view(&TextureViewDescriptor::default());
 
-		let mut builder = VertexBufferBuilder::new(
-			self.size,
-			assets::ATLAS_WIDTH,
-			assets::ATLAS_HEIGHT,
-			workbench.scroll(),
-			workbench.scale,
-		);
-
 		{
-			let vertex_buffer;
-			let index_buffer;
-			let text_vertex_buffer;
-			let text_index_buffer;
-
 			let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
 				label: Some("Render Pass"),
 				color_attachments: &[Some(RenderPassColorAttachment {
 					view: &view,
 					resolve_target: None,
 					ops: Operations {
-						load: LoadOp::Clear(Color {
-							r: 0.11774103726,
-							g: 0.11774103726,
-							b: 0.11774103726,
-							a: 1.0,
-						}),
+						load: LoadOp::Clear(config::get_theme().background()),
 						store: StoreOp::Store,
 					},
 				})],
@@ -719,29 +849,13 @@ impl<'window> State<'window> {
 				timestamp_writes: None,
 				occlusion_query_set: None,
 			});
-			workbench.render(&mut builder);
-
-			let show_cursor = true;
-			if show_cursor != workbench.cursor_visible {
-				window.set_cursor_visible(show_cursor);
-				workbench.cursor_visible = show_cursor;
-			}
 
 			{
 				render_pass.set_pipeline(&self.text_render_pipeline);
 				render_pass.set_bind_group(0, &self.unicode_bind_group, &[]);
 
-				text_vertex_buffer = self.device.create_buffer_init(&BufferInitDescriptor {
-					label: Some("Text Vertex Buffer"),
-					contents: builder.text_vertices(),
-					usage: BufferUsages::VERTEX,
-				});
-
-				text_index_buffer = self.device.create_buffer_init(&BufferInitDescriptor {
-					label: Some("Text Index Buffer"),
-					contents: builder.text_indices(),
-					usage: BufferUsages::INDEX,
-				});
+				let text_vertex_buffer = self.text_vertex_buffer.write(&self.device, &self.queue, builder.text_vertices());
+				let text_index_buffer = self.text_index_buffer.write(&self.device, &self.queue, builder.text_indices());
 
 				render_pass.set_vertex_buffer(0, text_vertex_buffer.slice(..));
 				render_pass.set_index_buffer(text_index_buffer.slice(..), IndexFormat::Uint32);
@@ -753,17 +867,8 @@ impl<'window> State<'window> {
 				render_pass.set_pipeline(&self.render_pipeline);
 				render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
 
-				vertex_buffer = self.device.create_buffer_init(&BufferInitDescriptor {
-					label: Some("Vertex Buffer"),
-					contents: builder.vertices(),
-					usage: BufferUsages::VERTEX,
-				});
-
-				index_buffer = self.device.create_buffer_init(&BufferInitDescriptor {
-					label: Some("Index Buffer"),
-					contents: builder.indices(),
-					usage: BufferUsages::INDEX,
-				});
+				let vertex_buffer = self.vertex_buffer.write(&self.device, &self.queue, builder.vertices());
+				let index_buffer = self.index_buffer.write(&self.device, &self.queue, builder.indices());
 
 				render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
 				render_pass.set_index_buffer(index_buffer.slice(..), IndexFormat::Uint16);
@@ -1038,7 +1143,8 @@ impl<'window> State<'window> {
 		self.queue.submit(Some(encoder.finish()));
 
 		surface_texture.present();
+		self.frame = Some(fingerprint);
 
-		Ok(())
+		Ok(true)
 	}
 }
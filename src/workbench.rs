@@ -6,28 +6,43 @@ use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
 use compact_str::{CompactString, format_compact, ToCompactString};
-use fxhash::{FxBuildHasher, FxHashSet};
+use fxhash::{FxBuildHasher, FxHashMap, FxHashSet};
 use uuid::Uuid;
+use wgpu::PresentMode;
 use winit::dpi::PhysicalPosition;
 use winit::event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta};
 use winit::keyboard::{KeyCode, PhysicalKey};
 
-use crate::{Bookmark, DOUBLE_CLICK_INTERVAL, DropFn, encompasses, encompasses_or_equal, FileUpdateSubscription, FileUpdateSubscriptionType, flags, get_clipboard, HeldEntry, LinkedQueue, OptionExt, panic_unchecked, Position, recache_along_indices, RenderContext, set_clipboard, since_epoch, SortAlgorithm, StrExt, sum_indices, tab, tab_mut, WindowProperties};
+use crate::{Bookmark, combined_two_sorted, DOUBLE_CLICK_INTERVAL, DropFn, encompasses, encompasses_or_equal, shift_for_insertion, shift_for_removal, FileUpdateSubscription, FileUpdateSubscriptionType, flags, get_clipboard, HeldEntry, LinkedQueue, OptionExt, panic_unchecked, Position, recache_along_indices, indented, unindented, RenderContext, set_clipboard, since_epoch, SortAlgorithm, StrExt, tab, tab_mut, WindowProperties};
+use crate::add_menu::AddMenu;
+use crate::command_menu::CommandMenu;
 use crate::alert::Alert;
-use crate::assets::{ACTION_WHEEL_Z, BACKDROP_UV, BASE_TEXT_Z, BASE_Z, BOOKMARK_UV, CLOSED_WIDGET_UV, DARK_STRIPE_UV, SAVE_UV, HEADER_SIZE, HELD_ENTRY_Z, HIDDEN_BOOKMARK_UV, HORIZONTAL_SEPARATOR_UV, HOVERED_STRIPE_UV, HOVERED_WIDGET_UV, JUST_OVERLAPPING_BASE_TEXT_Z, LIGHT_STRIPE_UV, LINE_NUMBER_SEPARATOR_UV, NEW_FILE_UV, OPEN_FOLDER_UV, SELECTED_ACTION_WHEEL, SELECTED_WIDGET_UV, SELECTION_UV, TRAY_UV, JUST_UNDERLAPPING_BASE_Z, SAVE_GRAYSCALE_UV, UNSELECTED_ACTION_WHEEL, UNSELECTED_WIDGET_UV};
+use crate::archive::{self, EntryPicker};
+use crate::config;
+use crate::assets::{ACTION_WHEEL_Z, BACKDROP_UV, BASE_TEXT_Z, BASE_Z, BOOKMARK_UV, CLOSED_WIDGET_UV, DARK_STRIPE_UV, SAVE_UV, HEADER_SIZE, STATUS_BAR_SIZE, HELD_ENTRY_Z, HIDDEN_BOOKMARK_UV, HORIZONTAL_SEPARATOR_UV, HOVERED_STRIPE_UV, HOVERED_WIDGET_UV, JUST_OVERLAPPING_BASE_TEXT_Z, LIGHT_STRIPE_UV, LINE_NUMBER_SEPARATOR_UV, NEW_FILE_UV, OPEN_FOLDER_UV, SELECTED_ACTION_WHEEL, SELECTED_WIDGET_UV, SELECTION_UV, STATUS_BAR_TEXT_Z, STATUS_BAR_Z, TRAY_UV, JUST_UNDERLAPPING_BASE_Z, SAVE_GRAYSCALE_UV, UNSELECTED_ACTION_WHEEL, UNSELECTED_WIDGET_UV};
 use crate::bookmark::Bookmarks;
 use crate::color::TextColor;
 use crate::be_decoder::BigEndianDecoder;
+use crate::elements::array::ArrayView;
 use crate::elements::chunk::{NbtChunk, NbtRegion};
-use crate::elements::compound::NbtCompound;
+use crate::elements::compound::{MergeStrategy, NbtCompound};
 use crate::elements::element::{NbtByte, NbtByteArray, NbtDouble, NbtFloat, NbtInt, NbtIntArray, NbtLong, NbtLongArray, NbtShort};
-use crate::elements::element::NbtElement;
+use crate::elements::element::{NbtElement, PathSegment};
 use crate::elements::list::{NbtList, ValueIterator};
+use crate::elements::primitive::Operation;
+use crate::json::{JsonValue, TypeHints};
+use crate::keybinds::Action;
+use crate::schematic;
 use crate::elements::string::NbtString;
 use crate::encoder::UncheckedBufWriter;
-use crate::search_box::SearchBox;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::recent::RecentFiles;
+use crate::diff::{self, DiffKind};
+use crate::notes::Notes;
+use crate::search_box::{Replacement, SearchBox, SearchMode};
 use crate::selected_text::{SelectedText, SelectedTextAdditional};
-use crate::tab::{FileFormat, Tab};
+use crate::file::{self, FileFormat};
+use crate::tab::Tab;
 use crate::text::{SearchBoxKeyResult, SelectedTextKeyResult, Text};
 use crate::tree_travel::{Navigate, Traverse, TraverseParents};
 use crate::vertex_buffer_builder::Vec2u;
@@ -35,6 +50,18 @@ use crate::vertex_buffer_builder::VertexBufferBuilder;
 use crate::window::{MIN_WINDOW_HEIGHT, MIN_WINDOW_WIDTH, WINDOW_HEIGHT, WINDOW_WIDTH};
 use crate::workbench_action::WorkbenchAction;
 
+/// How long the mouse has to rest over an element before its full value is shown.
+pub const HOVER_TOOLTIP_DELAY: Duration = Duration::from_millis(500);
+
+/// How often [`Workbench::tick`] runs while something is going on, like scrolling toward a held entry or stealing an element.
+pub const TICK_INTERVAL: Duration = Duration::from_millis(25);
+
+/// How often the file of a subscription is checked for changes written to it by another program.
+const SUBSCRIPTION_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The most characters shown on a line of the hovered value's tooltip, longer values are wrapped.
+const HOVER_TOOLTIP_WIDTH: usize = 96;
+
 pub struct Workbench {
 	pub tabs: Vec<Tab>,
 	pub tab: usize,
@@ -55,11 +82,26 @@ pub struct Workbench {
 	action_wheel: Option<(usize, usize)>,
 	subscription: Option<FileUpdateSubscription>,
 	pub cursor_visible: bool,
+	/// If what's drawn may have changed since the last frame was built, set by input which scrolls, edits or changes what's hovered, the timed changes are left to [`Self::next_change`].
+	pub dirty: bool,
 	alerts: Vec<Alert>,
 	pub scale: usize,
+	/// The zoom last asked for, [`Self::scale`] is smaller while it doesn't fit the window.
+	requested_scale: usize,
+	/// The scale factor of the monitor the window is on, rounded since the pixel art only stays crisp at whole multiples, see [`Self::set_scale_factor`].
+	dpi_scale: usize,
+	/// The present mode asked for, the window falls back to [`PresentMode::Fifo`] when the adapter doesn't support it.
+	pub present_mode: PresentMode,
 	steal_animation_data: Option<(Duration, Vec2u)>,
+	/// When the mouse last moved or the tree under it scrolled, resting for [`HOVER_TOOLTIP_DELAY`] shows the full value of the hovered element.
+	last_mouse_move: Duration,
 	sort_algorithm: SortAlgorithm,
 	search_box: SearchBox,
+	#[cfg(not(target_arch = "wasm32"))]
+	recent_files: RecentFiles,
+	command_menu: Option<CommandMenu>,
+	/// The archive being opened while one of its entries is picked.
+	entry_picker: Option<EntryPicker>,
 }
 
 impl Workbench {
@@ -86,11 +128,20 @@ impl Workbench {
 			action_wheel: None,
 			subscription: None,
 			cursor_visible: false,
+			dirty: true,
 			alerts: vec![],
 			scale: 0,
+			requested_scale: 0,
+			dpi_scale: 1,
+			present_mode: PresentMode::Fifo,
 			steal_animation_data: None,
+			last_mouse_move: Duration::ZERO,
 			sort_algorithm: SortAlgorithm::None,
 			search_box: SearchBox::uninit(),
+			#[cfg(not(target_arch = "wasm32"))]
+			recent_files: RecentFiles::new(),
+			command_menu: None,
+			entry_picker: None,
 		}
 	}
 
@@ -117,23 +168,34 @@ impl Workbench {
 			action_wheel: None,
 			subscription: None,
 			cursor_visible: true,
+			dirty: true,
 			alerts: vec![],
 			scale: 1,
+			requested_scale: 1,
+			dpi_scale: 1,
+			present_mode: config::get_present_mode(),
 			steal_animation_data: None,
+			last_mouse_move: since_epoch(),
 			sort_algorithm: SortAlgorithm::Type,
 			search_box: SearchBox::new(),
+			#[cfg(not(target_arch = "wasm32"))]
+			recent_files: if let WindowProperties::Real(_) = window_properties { RecentFiles::load() } else { RecentFiles::new() },
+			command_menu: None,
+			entry_picker: None,
 		};
 		'create_tab: {
-			if let Some(path) = &std::env::args()
-				.nth(1)
-				.and_then(|x| PathBuf::from_str(&x).ok())
-			{
-				if let Err(e) = workbench.on_open_file(path, std::fs::read(path).unwrap_or(vec![]), window_properties) {
+			// every argument is a file to open as its own tab, like when launched through a file association
+			let cwd = std::env::current_dir().unwrap_or_default();
+			let mut opened = false;
+			for path in std::env::args().skip(1).filter(|arg| arg != "--open").filter_map(|arg| PathBuf::from_str(&arg).ok()) {
+				let path = cwd.join(path);
+				if let Err(e) = workbench.on_open_file(&path, std::fs::read(&path).unwrap_or_default(), window_properties) {
 					workbench.alert(Alert::new("Error!", TextColor::Red, e.to_string()))
 				} else {
-					break 'create_tab;
+					opened = true;
 				}
 			}
+			if opened { break 'create_tab }
 			workbench.new_custom_tab(window_properties, Tab {
 				#[cfg(debug_assertions)]
 				value: Box::new(NbtElement::from_be_file(include_bytes!("assets/test.nbt"), SortAlgorithm::None).expect("Included debug nbt contains valid data")),
@@ -143,6 +205,7 @@ impl Workbench {
 				value: Box::new(NbtElement::Compound(NbtCompound::new())),
 				#[cfg(not(debug_assertions))]
 				name: "new.nbt".into(),
+				root_name: CompactString::new_inline(""),
 				path: None,
 				format: FileFormat::Nbt,
 				undos: LinkedQueue::new(),
@@ -159,31 +222,108 @@ impl Workbench {
 				last_close_attempt: Duration::ZERO,
 				last_selected_text_interaction: (0, 0, Duration::ZERO),
 				last_interaction: since_epoch(),
+				last_backup: since_epoch(),
+			notes: Notes::new(),
+			raw_views: FxHashSet::default(),
+			array_views: FxHashMap::default(),
+			unpacked_palettes: FxHashSet::default(),
+			selection: None,
+			add_menu: None,
+			diff: vec![],
+			byte_sizes: FxHashMap::default(),
 			});
 		}
 		workbench
 	}
 
 	#[inline]
-	pub fn alert(&mut self, alert: Alert) { self.alerts.insert(0, alert); }
+	pub fn alert(&mut self, alert: Alert) {
+		log::info!("{alert}");
+		self.dirty = true;
+		self.alerts.insert(0, alert);
+	}
 
 	#[inline]
 	#[allow(clippy::equatable_if_let)]
 	pub fn on_open_file(&mut self, path: &Path, buf: Vec<u8>, window_properties: &mut WindowProperties) -> Result<()> {
-		let (nbt, format) = Tab::parse_raw(path, buf, self.sort_algorithm)?;
+		self.dirty = true;
+		if archive::is_zip(&buf) {
+			let entries = archive::nbt_entries(&buf)?;
+			if entries.is_empty() { return Err(anyhow!("Archive does not contain any NBT files")) }
+			let single = entries.len() == 1;
+			let mut picker = EntryPicker::new(Path::new(path.file_name().context("Could not obtain path filename")?), entries);
+			if single {
+				picker.select_next();
+				return self.open_archive_entry(picker, window_properties);
+			}
+			self.entry_picker = Some(picker);
+			return Ok(())
+		}
+		// read as unreadable chunks, this says why
+		let truncation = matches!(path.extension().and_then(|extension| extension.to_str()), Some("mca" | "mcr")).then(|| NbtRegion::truncation(&buf)).flatten();
+		let (nbt, format, root_name) = file::from_file(path, buf, self.sort_algorithm)?;
+		self.open_tab(nbt, path, format, root_name, window_properties)?;
+		if let Some(truncation) = truncation {
+			self.alert(Alert::new("Warning!", TextColor::Yellow, truncation));
+		}
+		Ok(())
+	}
+
+	/// Opens the entry highlighted by `picker`, archives are read-only, their entries are opened as relative paths which make saving ask for a new file instead of writing over the archive.
+	fn open_archive_entry(&mut self, picker: EntryPicker, window_properties: &mut WindowProperties) -> Result<()> {
+		let (nbt, format, root_name, path) = picker.open(self.sort_algorithm)?;
+		self.open_tab(nbt, &path, format, root_name, window_properties)
+	}
+
+	/// Opens `path` as the `format` it was last opened as instead of detecting it, see [`RecentFiles`].
+	#[cfg(not(target_arch = "wasm32"))]
+	fn reopen_file(&mut self, path: &Path, buf: Vec<u8>, format: FileFormat, window_properties: &mut WindowProperties) -> Result<()> {
+		self.dirty = true;
+		let (nbt, root_name) = format.decode_named(&buf, self.sort_algorithm, crate::elements::chunk::ExternalChunks::Files(path)).with_context(|| format!("Failed to parse {} as {}", path.display(), format.into_str()))?;
+		self.open_tab(nbt, path, format, root_name, window_properties)?;
+		if format == FileFormat::Mca && let Some(truncation) = NbtRegion::truncation(&buf) {
+			self.alert(Alert::new("Warning!", TextColor::Yellow, truncation));
+		}
+		Ok(())
+	}
+
+	fn open_tab(&mut self, nbt: NbtElement, path: &Path, format: FileFormat, root_name: CompactString, window_properties: &mut WindowProperties) -> Result<()> {
 		let mut tab = Tab::new(nbt, path, format, self.window_height, self.window_width)?;
+		log::info!("Opened {} as {}", path.display(), format.into_str());
+		// only once it's opened, files which fail to open aren't remembered
+		#[cfg(not(target_arch = "wasm32"))]
+		self.recent_files.push(path, format);
+		tab.root_name = root_name;
 		if !tab.close_selected_text(false, window_properties) {
 			tab.selected_text = None;
 		};
+		if let Some(region) = tab.value.as_region() {
+			let unreadable = region.unreadable().map(|(x, z)| format!("{x}|{z}")).collect::<Vec<_>>();
+			if !unreadable.is_empty() {
+				self.alert(Alert::new("Warning!", TextColor::Yellow, format!("{n} chunk{s} could not be read and will be saved as they were, Alt + H shows their bytes: {chunks}", n = unreadable.len(), s = if unreadable.len() == 1 { "" } else { "s" }, chunks = unreadable.join(", "))));
+			}
+		}
+		#[cfg(not(target_arch = "wasm32"))]
+		if path.is_absolute() && let backup = Tab::backup_path(path) && backup.is_file() {
+			self.alert(Alert::new("Warning!", TextColor::Yellow, format!("Unsaved changes to this file were backed up to {} by a session which never saved them, rename it over the file to recover them", backup.display())));
+		}
+		let orphaned = tab.notes.resolve(&tab.value).filter(|(_, indices)| indices.is_none()).count();
+		if orphaned > 0 {
+			self.alert(Alert::new("Warning!", TextColor::Yellow, format!("{orphaned} note{s} no longer lead to an element", s = if orphaned == 1 { "" } else { "s" })));
+		}
 		self.new_custom_tab(window_properties, tab);
 		Ok(())
 	}
 
 	#[inline]
 	pub fn on_scroll(&mut self, scroll: MouseScrollDelta) -> bool {
+		self.last_mouse_move = since_epoch();
+		self.dirty = true;
+		// in pixels, wheels report notches and trackpads report pixels
 		let (h, v) = match scroll {
 			MouseScrollDelta::LineDelta(h, v) => {
-				(h, v)
+				let lines = config::get_scroll_lines() * 16.0;
+				(h * lines, v * lines)
 			},
 			MouseScrollDelta::PixelDelta(pos) => {
 				(pos.x as f32, pos.y as f32)
@@ -194,28 +334,23 @@ impl Workbench {
 			self.set_scale(self.scale.wrapping_add(v.signum() as isize as usize));
 			return true;
 		}
-		let shift = self.shift();
+		let (h, v) = if config::get_invert_scroll() { (-h, -v) } else { (h, v) };
+		// some platforms already turn shift + wheel into horizontal scrolling
+		let (h, v) = if self.shift() && h == 0.0 { (v, h) } else { (h, v) };
 		if self.mouse_y < 21 {
-			let scroll = if shift { -v } else { -h };
-			self.tab_scroll = ((self.tab_scroll as isize + (scroll * 48.0) as isize).max(0) as usize).min(
+			self.tab_scroll = ((self.tab_scroll as isize - h as isize).max(0) as usize).min(
 				{
 					let mut tabs_width = 3_usize;
 					for tab in &self.tabs {
-						tabs_width += tab.name.width() + 32 + 6 + 6;
+						tabs_width += tab.header().width() + 32 + 6 + 6;
 					}
 					tabs_width
 				}.saturating_sub(self.window_width),
 			);
 		} else {
 			let tab = tab_mut!(self);
-			let held = self.held_entry.element();
-			if shift {
-				tab.set_horizontal_scroll(-v, held);
-				tab.set_scroll(-h);
-			} else {
-				tab.set_horizontal_scroll(-h, held);
-				tab.set_scroll(-v);
-			}
+			tab.set_horizontal_scroll(-h, self.held_entry.element());
+			tab.set_scroll(-v);
 		}
 		true
 	}
@@ -224,6 +359,7 @@ impl Workbench {
 	#[allow(clippy::collapsible_if)]
 	pub fn on_mouse_input(&mut self, state: ElementState, button: MouseButton, window_properties: &mut WindowProperties) -> bool {
 		tab_mut!(self).last_interaction = since_epoch();
+		self.dirty = true;
 		let left_margin = self.left_margin();
 		let horizontal_scroll = self.horizontal_scroll();
 		let shift = self.shift();
@@ -252,7 +388,7 @@ impl Workbench {
 								if button == MouseButton::Right {
 									let tab = tab_mut!(self);
 									let depth = Traverse::new(tab.scroll() / 16 + (y - HEADER_SIZE) / 16, &mut tab.value).enumerate().last().0;
-									self.action_wheel = Some((left_margin + depth * 16 + 16 + 6, ((y - HEADER_SIZE) & !15) + HEADER_SIZE + 7));
+									self.action_wheel = Some((left_margin + indented(depth) * 16 + 16 + 6, ((y - HEADER_SIZE) & !15) + HEADER_SIZE + 7));
 									break 'a;
 								}
 							}
@@ -271,7 +407,7 @@ impl Workbench {
 						if ((self.window_width - 7)..self.window_width).contains(&x) {
 							let tab = tab_mut!(self);
 							let height = tab.value.height() * 16 + 48;
-							let total = self.window_height - HEADER_SIZE;
+							let total = tab.visible_height();
 							if height - 48 > total {
 								let start = total * self.scroll() / height + HEADER_SIZE;
 								let end = start + total * total / height;
@@ -337,7 +473,7 @@ impl Workbench {
 				} else if y < 42 && y > 26 && x < 32 {
 					self.new_tab(window_properties, shift);
 				} else if y < 42 && y > 26 && x >= 296 && x < 312 {
-					if let Err(e) = tab_mut!(self).refresh(self.sort_algorithm) {
+					if let Err(e) = tab_mut!(self).refresh(self.sort_algorithm, None) {
 						self.alert(Alert::new("Error!", TextColor::Red, e.to_string()))
 					}
 				} else if y >= HEADER_SIZE {
@@ -359,6 +495,20 @@ impl Workbench {
 								self.drop(x, Some(indices), left_margin);
 								break 'a;
 							}
+							HeldEntry::FromKnownGroup(x, indices, siblings) => {
+								self.drop_group(x, indices, &siblings, left_margin);
+								break 'a;
+							}
+						}
+
+						if button == MouseButton::Left {
+							if shift {
+								if self.extend_selection() {
+									break 'a;
+								}
+							} else {
+								tab_mut!(self).selection = None;
+							}
 						}
 
 						if (y - HEADER_SIZE) < 16 && x > 32 + left_margin {
@@ -423,7 +573,7 @@ impl Workbench {
 							let Some(action) = element.actions().get(highlight_idx).copied() else {
 								break 'a;
 							};
-							let min_x = depth * 16 + left_margin;
+							let min_x = indented(depth) * 16 + left_margin;
 							let max_x = min_x + 32;
 							if !(min_x..max_x).contains(&cx) {
 								break 'a;
@@ -472,25 +622,8 @@ impl Workbench {
 							let Ok(z @ 0..=31) = z.trim_start().parse() else {
 								return Err(anyhow!("Invalid Y coordinate for chunk"));
 							};
-							let pos = ((x as u16) << 5) | z as u16;
-							let (_, chunks) = &*region.chunks;
-							if !chunks[pos as usize].is_null() { return Err(anyhow!("Replacement chunk is already filled")) }
-							let mut element = region.remove(last);
-							let old_key = format_compact!("{}|{}", unsafe { element.as_chunk_unchecked().x }, unsafe {
-								element.as_chunk_unchecked().z
-							});
-							// all values inside a region are chunks
-							unsafe {
-								element.as_chunk_unchecked_mut().x = x;
-							}
-							unsafe {
-								element.as_chunk_unchecked_mut().z = z;
-							}
-							// chunk is asserted above
-							unsafe {
-								region.insert_unchecked(pos as usize, last, element);
-							}
-							old_key
+							let Some((old_x, old_z)) = region.relocate_chunk(last, x, z) else { return Err(anyhow!("Replacement chunk is already filled")) };
+							format_compact!("{old_x}|{old_z}")
 						});
 					}
 				}
@@ -608,51 +741,54 @@ impl Workbench {
 					return Err(e);
 				};
 				match subscription.rx.try_recv() {
-					Ok(data) => match subscription.subscription_type {
-						FileUpdateSubscriptionType::Snbt => write_snbt(subscription, &data, tab)?,
-						kind @ (FileUpdateSubscriptionType::ByteArray | FileUpdateSubscriptionType::IntArray | FileUpdateSubscriptionType::LongArray | FileUpdateSubscriptionType::ByteList | FileUpdateSubscriptionType::ShortList | FileUpdateSubscriptionType::IntList | FileUpdateSubscriptionType::LongList) => write_array(subscription, tab, {
-							let mut buf = UncheckedBufWriter::new();
-							let id = match kind {
-								FileUpdateSubscriptionType::ByteArray if data.len() % 1 == 0 => {
-									buf.write(&(data.len() as u32).to_be_bytes());
-									NbtByteArray::ID
-								},
-								FileUpdateSubscriptionType::IntArray if data.len() % 4 == 0 => {
-									buf.write(&(data.len() as u32 / 4).to_be_bytes());
-									NbtIntArray::ID
-								},
-								FileUpdateSubscriptionType::LongArray if data.len() % 8 == 0 => {
-									buf.write(&(data.len() as u32 / 8).to_be_bytes());
-									NbtLongArray::ID
-								},
-								FileUpdateSubscriptionType::ByteList if data.len() % 1 == 0 => {
-									buf.write(&[NbtByte::ID]);
-									buf.write(&(data.len() as u32).to_be_bytes());
-									NbtList::ID
-								},
-								FileUpdateSubscriptionType::ShortList if data.len() % 2 == 0 => {
-									buf.write(&[NbtShort::ID]);
-									buf.write(&(data.len() as u32 / 2).to_be_bytes());
-									NbtList::ID
-								},
-								FileUpdateSubscriptionType::IntList if data.len() % 4 == 0 => {
-									buf.write(&[NbtInt::ID]);
-									buf.write(&(data.len() as u32 / 4).to_be_bytes());
-									NbtList::ID
-								},
-								FileUpdateSubscriptionType::LongList if data.len() % 8 == 0 => {
-									buf.write(&[NbtLong::ID]);
-									buf.write(&(data.len() as u32 / 8).to_be_bytes());
-									NbtList::ID
-								},
-								_ => return Err(anyhow!("Invalid width for designated type of array")),
-							};
-							buf.write(&data);
-							let buf = buf.finish();
-							let mut decoder = BigEndianDecoder::new(&buf, SortAlgorithm::None);
-							NbtElement::from_be_bytes(id, &mut decoder).context("Could not read bytes for array")?
-						})?,
-					},
+					Ok(data) => {
+						self.dirty = true;
+						match subscription.subscription_type {
+							FileUpdateSubscriptionType::Snbt => write_snbt(subscription, &data, tab)?,
+							kind @ (FileUpdateSubscriptionType::ByteArray | FileUpdateSubscriptionType::IntArray | FileUpdateSubscriptionType::LongArray | FileUpdateSubscriptionType::ByteList | FileUpdateSubscriptionType::ShortList | FileUpdateSubscriptionType::IntList | FileUpdateSubscriptionType::LongList) => write_array(subscription, tab, {
+								let mut buf = UncheckedBufWriter::new();
+								let id = match kind {
+									FileUpdateSubscriptionType::ByteArray if data.len() % 1 == 0 => {
+										buf.write(&(data.len() as u32).to_be_bytes());
+										NbtByteArray::ID
+									},
+									FileUpdateSubscriptionType::IntArray if data.len() % 4 == 0 => {
+										buf.write(&(data.len() as u32 / 4).to_be_bytes());
+										NbtIntArray::ID
+									},
+									FileUpdateSubscriptionType::LongArray if data.len() % 8 == 0 => {
+										buf.write(&(data.len() as u32 / 8).to_be_bytes());
+										NbtLongArray::ID
+									},
+									FileUpdateSubscriptionType::ByteList if data.len() % 1 == 0 => {
+										buf.write(&[NbtByte::ID]);
+										buf.write(&(data.len() as u32).to_be_bytes());
+										NbtList::ID
+									},
+									FileUpdateSubscriptionType::ShortList if data.len() % 2 == 0 => {
+										buf.write(&[NbtShort::ID]);
+										buf.write(&(data.len() as u32 / 2).to_be_bytes());
+										NbtList::ID
+									},
+									FileUpdateSubscriptionType::IntList if data.len() % 4 == 0 => {
+										buf.write(&[NbtInt::ID]);
+										buf.write(&(data.len() as u32 / 4).to_be_bytes());
+										NbtList::ID
+									},
+									FileUpdateSubscriptionType::LongList if data.len() % 8 == 0 => {
+										buf.write(&[NbtLong::ID]);
+										buf.write(&(data.len() as u32 / 8).to_be_bytes());
+										NbtList::ID
+									},
+									_ => return Err(anyhow!("Invalid width for designated type of array")),
+								};
+								buf.write(&data);
+								let buf = buf.finish();
+								let mut decoder = BigEndianDecoder::new(&buf, SortAlgorithm::None);
+								NbtElement::from_be_bytes(id, &mut decoder).context("Could not read bytes for array")?
+							})?,
+						}
+					}
 					Err(TryRecvError::Disconnected) => {
 						self.subscription = None;
 						return Err(anyhow!("Could not update; file subscription disconnected."));
@@ -681,8 +817,8 @@ impl Workbench {
 
 		let y = (self.mouse_y - HEADER_SIZE) / 16 + scroll / 16;
 		if y < tab.value.height() && y > 0 {
-			let target_depth = (self.mouse_x + horizontal_scroll - left_margin - 16) / 16;
 			let (depth, (_, _, _, _)) = Traverse::new(y, &mut tab.value).enumerate().last();
+			let target_depth = unindented((self.mouse_x + horizontal_scroll - left_margin - 16) / 16, depth);
 			if initialize {
 				self.steal_animation_data.get_or_insert((since_epoch(), (target_depth, y).into()));
 			}
@@ -698,14 +834,14 @@ impl Workbench {
 		let left_margin = self.left_margin();
 		let horizontal_scroll = self.horizontal_scroll();
 		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
-		let target_depth = (self.mouse_x + horizontal_scroll - left_margin - 16) / 16;
+		let column = (self.mouse_x + horizontal_scroll - left_margin - 16) / 16;
 		let tab = tab_mut!(self);
 		if y < tab.value.height() && y > 0 {
 			let (depth, height, true_height, line_number) = {
 				let (depth, (_, _, element, line_number)) = Traverse::new(y, &mut tab.value).enumerate().last();
 				(depth, element.height(), element.true_height(), line_number)
 			};
-			if depth != target_depth { return false }
+			if depth != unindented(column, depth) { return false }
 
 			let mut indices = vec![];
 			let mut iter = TraverseParents::new(y, &mut tab.value);
@@ -728,11 +864,17 @@ impl Workbench {
 			};
 
 			recache_along_indices(&indices[..indices.len() - 1], &mut tab.value);
+			// it could be added to by its indices, which no longer match
+			tab.add_menu = None;
 			value.1.shut();
 			let _ = tab.bookmarks.remove(line_number..line_number + true_height);
 			tab.bookmarks[line_number..].decrement(height, true_height);
 			// no need for encompass_or_equal since `drop` handles that
-			self.held_entry = HeldEntry::FromKnown(value, indices.into_boxed_slice());
+			self.held_entry = if let Some((parent, selected)) = tab.selection.take() && selected.len() > 1 && let Some((&idx, rest)) = indices.split_last() && *rest == *parent && selected.contains(&idx) {
+				HeldEntry::FromKnownGroup(value, indices.into_boxed_slice(), selected.into_iter().filter(|&n| n != idx).collect())
+			} else {
+				HeldEntry::FromKnown(value, indices.into_boxed_slice())
+			};
 			true
 		} else {
 			false
@@ -753,12 +895,14 @@ impl Workbench {
 			.as_ref()
 			.and_then(|x| x.to_str())
 			.map_or_else(|| tab.name.clone(), Into::into);
+		// the name of the root compound is edited as its value, see `Tab::root_name`
+		let root_name = (tab.format.has_root_name() && tab.value.id() == NbtCompound::ID).then(|| (tab.root_name.as_str().into(), TextColor::TreeString, true));
 		tab.selected_text = SelectedText::new(
 			36 + left_margin,
 			offset + path_minus_name_width,
 			HEADER_SIZE,
 			Some((name, TextColor::TreeKey, true)),
-			None,
+			root_name,
 			false,
 			vec![],
 		);
@@ -772,12 +916,14 @@ impl Workbench {
 		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
 		let tab = tab_mut!(self);
 		if y < tab.value.height() && y > 0 {
-			let (height, true_height, line_number) = unsafe {
+			let (height, true_height, line_number, id) = unsafe {
 				Traverse::new(y, &mut tab.value)
 					.last()
-					.map(|(_, _, element, line_number)| (element.height(), element.true_height(), line_number))
+					.map(|(_, _, element, line_number)| (element.height(), element.true_height(), line_number, element.id()))
 					.panic_unchecked("we've asserted that y > 0")
 			};
+			// the coordinates of a chunk are always taken by itself, so the copy would have nowhere to go after its parents were already grown
+			if id == NbtChunk::ID { return false }
 
 			let mut indices = vec![];
 			let mut iter = TraverseParents::new(y, &mut tab.value);
@@ -795,12 +941,10 @@ impl Workbench {
 								.panic_unchecked("it exists mate, let's stop playing around")
 						}
 						.clone();
-						if let Some(compound) = element.as_compound_mut() {
-							compound.insert(
-								idx + 1,
-								unsafe { key.panic_unchecked("it's a compound, it **has** a key for every value") },
-								duplicate,
-							);
+						let compound = if element.id() == NbtChunk::ID { element.as_chunk_mut().map(|chunk| &mut **chunk) } else { element.as_compound_mut() };
+						if let Some(compound) = compound {
+							let key = compound.copy_key(&unsafe { key.panic_unchecked("it's a compound, it **has** a key for every value") });
+							compound.insert(idx + 1, key, duplicate);
 						} else {
 							if element.insert(idx + 1, duplicate).is_err() { return false }
 						}
@@ -877,6 +1021,616 @@ impl Workbench {
 		}
 	}
 
+	/// Copies the hovered element as JSON, see [`NbtElement::to_json`].
+	fn copy_as_json(&mut self) -> bool {
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+		let (_, _, element, _) = unsafe {
+			Traverse::new(y, &mut tab.value)
+				.last()
+				.panic_unchecked("There is always at least one element - Master Oogway")
+		};
+		set_clipboard(element.to_json())
+	}
+
+	/// Shows the commands the hovered compound can be copied as, see [`CommandMenu`].
+	fn open_command_menu(&mut self) -> bool {
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+		let (_, _, element, _) = unsafe {
+			Traverse::new(y, &mut tab.value)
+				.last()
+				.panic_unchecked("There is always at least one element - Master Oogway")
+		};
+		let Some(compound) = element.as_compound() else { return false };
+		self.command_menu = Some(CommandMenu::new(compound));
+		true
+	}
+
+	/// Replaces the hovered element with the JSON in the clipboard, taking the types JSON doesn't keep from the element it replaces, see [`NbtElement::from_json`].
+	fn paste_json_over_hovered(&mut self) -> bool {
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let Some(clipboard) = get_clipboard() else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Failed to get clipboard"));
+			return true
+		};
+		let json = match JsonValue::parse(&clipboard) {
+			Ok(json) => json,
+			Err(e) => {
+				self.alert(Alert::new("Error!", TextColor::Red, format!("Could not parse clipboard as JSON: {e}")));
+				return true
+			}
+		};
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let key = indices.split_last().and_then(|(&last, rest)| {
+			let (_, _, parent, _) = Navigate::new(rest.iter().copied(), &mut tab.value).last();
+			parent.as_compound().map(|compound| compound.get(last).map(|(key, _)| key.to_compact_string())).or_else(|| parent.as_chunk().map(|chunk| chunk.get(last).map(|(key, _)| key.to_compact_string()))).flatten()
+		});
+		let (_, _, element, _) = Navigate::new(indices.iter().copied(), &mut tab.value).last();
+		let value = match NbtElement::from_json(&json, &TypeHints::of(element)) {
+			Ok(value) => value,
+			Err(e) => {
+				self.alert(Alert::new("Error!", TextColor::Red, format!("{e:#}")));
+				return true
+			}
+		};
+		if let Some(chunk) = element.as_chunk() {
+			let Some(new) = value.as_chunk() else {
+				self.alert(Alert::new("Error!", TextColor::Red, "The chunks of a region can only be replaced by chunks"));
+				return true
+			};
+			let (old, new) = ((chunk.x, chunk.z), (new.x, new.z));
+			let (_, _, region, _) = Navigate::new(indices.iter().copied().take(indices.len() - 1), &mut tab.value).last();
+			if old != new && region.as_region().is_some_and(|region| region.children().any(|chunk| chunk.as_chunk().is_some_and(|chunk| (chunk.x, chunk.z) == new))) {
+				self.alert(Alert::new("Error!", TextColor::Red, format!("There is already a chunk at {}, {}", new.0, new.1)));
+				return true
+			}
+		}
+		tab.selected_text = None;
+		let action = WorkbenchAction::Replace { indices: indices.into_boxed_slice(), value: (key, value) }.undo(&mut tab.value, &mut tab.bookmarks, &mut self.subscription, &mut tab.path, &mut tab.name, &mut tab.root_name);
+		tab.append_to_history(action);
+		true
+	}
+
+	/// Merges the SNBT compound in the clipboard into the hovered compound (or chunk), see [`NbtCompound::merge`].
+	fn merge_clipboard_into_hovered(&mut self, strategy: MergeStrategy) -> bool {
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let (_, _, element, _) = Navigate::new(indices.iter().copied(), &mut tab.value).last();
+		if !matches!(element.id(), NbtCompound::ID | NbtChunk::ID) { return false }
+		let Some(clipboard) = get_clipboard() else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Failed to get clipboard"));
+			return true
+		};
+		let Some((_, mut other)) = NbtElement::from_str(&clipboard, self.sort_algorithm) else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Could not parse clipboard as SNBT"));
+			return true
+		};
+		let Some(other) = other.as_compound_mut().map(core::mem::take) else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Only compounds can be merged into the hovered compound"));
+			return true
+		};
+		let mut value = element.clone();
+		if let Some(compound) = value.as_compound_mut() {
+			compound.merge(other, strategy);
+		} else if let Some(chunk) = value.as_chunk_mut() {
+			chunk.merge(other, strategy);
+		}
+		let key = indices.split_last().and_then(|(&last, rest)| {
+			let (_, _, parent, _) = Navigate::new(rest.iter().copied(), &mut tab.value).last();
+			parent.as_compound().map(|compound| compound.get(last).map(|(key, _)| key.to_compact_string())).or_else(|| parent.as_chunk().map(|chunk| chunk.get(last).map(|(key, _)| key.to_compact_string()))).flatten()
+		});
+		tab.selected_text = None;
+		let action = WorkbenchAction::Replace { indices: indices.into_boxed_slice(), value: (key, value) }.undo(&mut tab.value, &mut tab.bookmarks, &mut self.subscription, &mut tab.path, &mut tab.name, &mut tab.root_name);
+		tab.append_to_history(action);
+		true
+	}
+
+	/// Unpacks the hovered varint palette indices of a Sponge schematic into an int array, or packs them again, see [`schematic::unpack`].
+	///
+	/// They're written packed either way, see [`schematic::with_packed`].
+	fn toggle_palette_data(&mut self) -> bool {
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let Some((&last, rest)) = indices.split_last() else { return false };
+		let (_, _, parent, _) = Navigate::new(rest.iter().copied(), &mut tab.value).last();
+		let Some(compound) = parent.as_compound() else { return false };
+		let Some((key, element)) = compound.get(last) else { return false };
+		if !schematic::is_palette_data(key, compound) { return false }
+		let value = if let Some(array) = element.as_byte_array() {
+			schematic::unpack(array).map(NbtElement::IntArray).ok_or("The palette indices are cut off or too big to unpack")
+		} else if let Some(array) = element.as_int_array() {
+			schematic::pack(array).map(NbtElement::ByteArray).ok_or("Negative palette indices can't be packed")
+		} else {
+			return false
+		};
+		let key = key.to_compact_string();
+		let value = match value {
+			Ok(value) => value,
+			Err(e) => {
+				self.alert(Alert::new("Error!", TextColor::Red, e));
+				return true
+			}
+		};
+		if let Some(path) = tab.value.path_of(&indices) {
+			let path = path.into_boxed_slice();
+			if value.id() == NbtIntArray::ID {
+				tab.unpacked_palettes.insert(path);
+			} else {
+				tab.unpacked_palettes.remove(&path);
+			}
+		}
+		tab.selected_text = None;
+		let action = WorkbenchAction::Replace { indices: indices.into_boxed_slice(), value: (Some(key), value) }.undo(&mut tab.value, &mut tab.bookmarks, &mut self.subscription, &mut tab.path, &mut tab.name, &mut tab.root_name);
+		tab.append_to_history(action);
+		true
+	}
+
+	/// Sorts the keys of the hovered compound (or chunk) by name, along with the ones of every compound inside of it when `recursive`.
+	#[inline]
+	fn sort_hovered(&mut self, recursive: bool) -> bool {
+		fn sortable_paths(element: &NbtElement, indices: &mut Vec<usize>, recursive: bool, paths: &mut Vec<Box<[usize]>>) {
+			let id = element.id();
+			if recursive && matches!(id, NbtCompound::ID | NbtChunk::ID | NbtList::ID | NbtRegion::ID) {
+				for idx in 0..element.len().unwrap_or(0) {
+					let Some(child) = element.get(idx) else { continue };
+					indices.push(idx);
+					sortable_paths(child, indices, recursive, paths);
+					indices.pop();
+				}
+			}
+			// after its children, so that the indices of each reordering are still valid once the ones after it are undone
+			if id == NbtCompound::ID || id == NbtChunk::ID {
+				paths.push(indices.clone().into_boxed_slice());
+			}
+		}
+
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let mut paths = vec![];
+		let mut element = &*tab.value;
+		for &idx in &indices {
+			let Some(child) = element.get(idx) else { return false };
+			element = child;
+		}
+		sortable_paths(element, &mut indices, recursive, &mut paths);
+
+		let mut actions = Vec::with_capacity(paths.len());
+		for path in paths {
+			let Some(line_number) = tab.value.row_of_path(&path) else { continue };
+			let shown = tab.value.is_shown(&path);
+			let (_, _, element, true_line_number) = Navigate::new(path.iter().copied(), &mut tab.value).last();
+			let open = element.open() && shown;
+			let true_height = element.true_height();
+			let bookmarks = &mut tab.bookmarks[true_line_number..true_line_number + true_height];
+			let reordering_indices = if let Some(compound) = element.as_compound_mut() {
+				compound.entries.sort_by(SortAlgorithm::by_name, line_number, true_line_number, true_height, open, bookmarks)
+			} else if let Some(chunk) = element.as_chunk_mut() {
+				chunk.entries.sort_by(SortAlgorithm::by_name, line_number, true_line_number, true_height, open, bookmarks)
+			} else {
+				continue
+			};
+			actions.push(WorkbenchAction::ReorderCompound { indices: path, reordering_indices });
+		}
+		let action = match actions.len() {
+			0 => return false,
+			1 => unsafe { actions.pop().panic_unchecked("Length is one") },
+			_ => WorkbenchAction::Bulk { actions: actions.into_boxed_slice() },
+		};
+		tab.selected_text = None;
+		tab.append_to_history(action);
+		true
+	}
+
+	/// Converts the hovered element to the next (or previous) type it keeps its value as, see [`NbtElement::convert_to`], numbers cycle through each other and arrays go back and forth with lists.
+	#[inline]
+	fn convert_hovered(&mut self, backwards: bool) -> bool {
+		const NUMBERS: [u8; 6] = [NbtByte::ID, NbtShort::ID, NbtInt::ID, NbtLong::ID, NbtFloat::ID, NbtDouble::ID];
+
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() || y == 0 { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let Some((&last, rest)) = indices.split_last() else { return false };
+		let (_, _, parent, _) = Navigate::new(rest.iter().copied(), &mut tab.value).last();
+		// the elements of a list share their type, and the ones of an array are fixed
+		if matches!(parent.id(), NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID) || (parent.id() == NbtList::ID && parent.len() != Some(1)) { return false }
+		let key = parent.as_compound().map(|compound| compound.get(last).map(|(key, _)| key.to_compact_string())).or_else(|| parent.as_chunk().map(|chunk| chunk.get(last).map(|(key, _)| key.to_compact_string()))).flatten();
+		let Some(element) = parent.get_mut(last) else { return false };
+
+		let id = element.id();
+		let target = if let Some(idx) = NUMBERS.iter().position(|&number| number == id) {
+			NUMBERS[if backwards { idx.checked_sub(1).unwrap_or(NUMBERS.len() - 1) } else { (idx + 1) % NUMBERS.len() }]
+		} else if let Some(list) = element.as_list() {
+			match list.element {
+				NbtInt::ID => NbtIntArray::ID,
+				NbtLong::ID => NbtLongArray::ID,
+				_ => NbtByteArray::ID,
+			}
+		} else {
+			NbtList::ID
+		};
+		let Some(old_value) = element.convert_to(target) else { return false };
+		if element.open() != old_value.open() {
+			let _ = element.toggle();
+		}
+		let (diff, true_diff) = (element.height().wrapping_sub(old_value.height()), element.true_height().wrapping_sub(old_value.true_height()));
+		if let Some(list) = parent.as_list_mut() {
+			list.element = target;
+		}
+
+		let mut iter = Navigate::new(rest.iter().copied(), &mut tab.value);
+		while let Some((_, _, _, parent, _)) = iter.next() {
+			parent.increment(diff, true_diff);
+		}
+		recache_along_indices(rest, &mut tab.value);
+		let (_, _, _, true_line_number) = Navigate::new(indices.iter().copied(), &mut tab.value).last();
+		tab.bookmarks[true_line_number + old_value.true_height()..].increment(diff, true_diff);
+		if let Some(subscription) = &self.subscription && encompasses_or_equal(&indices, &subscription.indices) {
+			self.subscription = None;
+		}
+		tab.selected_text = None;
+		tab.append_to_history(WorkbenchAction::Replace {
+			indices: indices.into_boxed_slice(),
+			value: (key, old_value),
+		});
+		true
+	}
+
+	/// Applies the arithmetic typed into the search box (like `+16`, `- 2.5` or `*2`) to the hovered number, or to every number of the hovered list or array, see [`NbtElement::map_number`].
+	fn apply_operation_to_hovered(&mut self) -> bool {
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let Some((operation, operand)) = Operation::parse(&self.search_box.value) else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Type an operation like +16, -2.5, *2 or /4 into the search box"));
+			return true
+		};
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let key = indices.split_last().and_then(|(&last, rest)| {
+			let (_, _, parent, _) = Navigate::new(rest.iter().copied(), &mut tab.value).last();
+			parent.as_compound().map(|compound| compound.get(last).map(|(key, _)| key.to_compact_string())).or_else(|| parent.as_chunk().map(|chunk| chunk.get(last).map(|(key, _)| key.to_compact_string()))).flatten()
+		});
+		let (_, _, element, _) = Navigate::new(indices.iter().copied(), &mut tab.value).last();
+		// applied to a copy, so that nothing changes when it fails partway through
+		let mut value = element.clone();
+		match value.map_number(operation, operand) {
+			None => return false,
+			Some(false) => {
+				self.alert(Alert::new("Error!", TextColor::Red, "The operation is undefined for some of the numbers, like when dividing by zero"));
+				return true
+			}
+			Some(true) => {}
+		}
+		let old_value = core::mem::replace(element, value);
+		recache_along_indices(&indices, &mut tab.value);
+		if let Some(subscription) = &self.subscription && encompasses_or_equal(&indices, &subscription.indices) {
+			self.subscription = None;
+		}
+		tab.selected_text = None;
+		tab.append_to_history(WorkbenchAction::Replace {
+			indices: indices.into_boxed_slice(),
+			value: (key, old_value),
+		});
+		true
+	}
+
+	/// Shows the chunks of the region which failed to parse as hex dumps of their bytes as a single undoable action, see [`NbtRegion::view_unreadable`].
+	fn view_unreadable_chunks(&mut self) -> bool {
+		let tab = tab_mut!(self);
+		let (height, true_height) = (tab.value.height(), tab.value.true_height());
+		let Some(region) = tab.value.as_region_mut() else { return false };
+		let (viewed, failed) = region.view_unreadable();
+		let any = !viewed.is_empty();
+		if any {
+			recache_along_indices(&[], &mut tab.value);
+			tab.bookmarks[true_height + 1..].increment(tab.value.height() - height, tab.value.true_height() - true_height);
+			let mut actions = viewed.into_iter().map(|idx| WorkbenchAction::Add { indices: Box::new([idx]) }).collect::<Vec<_>>();
+			let action = if actions.len() == 1 { actions.remove(0) } else { WorkbenchAction::Bulk { actions: actions.into_boxed_slice() } };
+			tab.append_to_history(action);
+			tab.selected_text = None;
+		}
+		if failed > 0 {
+			self.alert(Alert::new("Warning!", TextColor::Yellow, format!("{failed} chunk{s} could not be decompressed to show their bytes", s = if failed == 1 { "" } else { "s" })));
+		}
+		any || failed > 0
+	}
+
+	/// Replaces the matches of the search box in string values and keys (following its flags) with the contents of the clipboard as a single undoable action, or with `preview` only counts them, see [`SearchBox::replacements`].
+	///
+	/// Keys which would clash with another one of their compound are left as they are and bookmarked.
+	fn replace_search_hits(&mut self, preview: bool) -> bool {
+		if self.search_box.value.is_empty() { return false }
+		if let SearchMode::Snbt = self.search_box.mode {
+			self.alert(Alert::new("Error!", TextColor::Red, "SNBT mode matches whole elements, switch to String or Regex mode to replace"));
+			return true
+		}
+		let Some(predicate) = self.search_box.mode.into_predicate(self.search_box.value.clone(), self.search_box.flags) else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Invalid regex"));
+			return true
+		};
+		let Some(with) = get_clipboard() else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Failed to get clipboard"));
+			return true
+		};
+		let tab = tab_mut!(self);
+		let replacements = SearchBox::replacements(&tab.value, &predicate, &with);
+		let count = replacements.iter().filter(|replacement| replacement.value.is_some() || (replacement.key.is_some() && !replacement.duplicate)).count();
+		let duplicates = replacements.iter().filter(|replacement| replacement.duplicate).map(|replacement| replacement.bookmark).collect::<Vec<_>>();
+		let duplicate_count = duplicates.len();
+		let s = if duplicate_count == 1 { "" } else { "s" };
+		if preview {
+			let clashes = if duplicate_count > 0 { format!(", {duplicate_count} key{s} would clash with another one") } else { String::new() };
+			self.alert(Alert::new("Replace", TextColor::White, format!("{count} string{s} and key{s} would be replaced with {with:?}{clashes} (Ctrl + Shift + H to replace)", s = if count == 1 { "" } else { "s" })));
+			return true
+		}
+		if duplicate_count > 0 {
+			let old_bookmarks = core::mem::replace(&mut tab.bookmarks, Bookmarks::new());
+			tab.bookmarks = unsafe { Bookmarks::from_raw(combined_two_sorted(duplicates.into_boxed_slice(), old_bookmarks.into_raw())) };
+		}
+		let clashes = if duplicate_count > 0 { format!(", {duplicate_count} key{s} would clash with another one and {are} bookmarked", are = if duplicate_count == 1 { "is" } else { "are" }) } else { String::new() };
+
+		let mut actions = Vec::with_capacity(count);
+		let mut parents = Vec::<Box<[usize]>>::new();
+		let mut pending = replacements;
+		// a key can be freed by a later replacement, so those waiting on one are retried until a pass makes no progress, then they give up on their key
+		let mut stuck = false;
+		while !pending.is_empty() {
+			let len = pending.len();
+			pending.retain_mut(|Replacement { indices, key, value, duplicate, .. }| {
+				let Some((&last, rest)) = indices.split_last() else { return false };
+				let key = match key.as_ref().filter(|_| !*duplicate && !stuck) {
+					Some(new_key) => {
+						let (_, _, parent, _) = Navigate::new(rest.iter().copied(), &mut tab.value).last();
+						let old_key = if let Some(compound) = parent.as_compound_mut() {
+							compound.update_key(last, new_key.clone())
+						} else if let Some(chunk) = parent.as_chunk_mut() {
+							chunk.update_key(last, new_key.clone())
+						} else {
+							None
+						};
+						if old_key.is_none() { return true }
+						old_key
+					}
+					None => None,
+				};
+				let value = value.take().and_then(|value| Navigate::new(indices.iter().copied(), &mut tab.value).last().2.set_value(value).map(|(before, _)| before));
+				if key.is_none() && value.is_none() { return false }
+				if let Some(subscription) = &self.subscription && encompasses_or_equal(indices, &subscription.indices) {
+					self.subscription = None;
+				}
+				parents.push(rest.into());
+				actions.push(WorkbenchAction::Rename { indices: core::mem::take(indices), key, value });
+				false
+			});
+			stuck = pending.len() == len;
+		}
+		parents.sort_unstable();
+		parents.dedup();
+		for parent in &parents {
+			recache_along_indices(parent, &mut tab.value);
+		}
+		let message = format!("Replaced {n} string{s} and key{s} with {with:?}{clashes}", n = actions.len(), s = if actions.len() == 1 { "" } else { "s" });
+		let action = match actions.len() {
+			0 => None,
+			1 => actions.pop(),
+			_ => Some(WorkbenchAction::Bulk { actions: actions.into_boxed_slice() }),
+		};
+		if let Some(action) = action {
+			tab.selected_text = None;
+			tab.append_to_history(action);
+		}
+		self.alert(Alert::new("Replace", TextColor::White, message));
+		true
+	}
+
+	/// Toggles the selected element (or the hovered one without a selection) between its specialized view and raw NBT.
+	#[inline]
+	fn toggle_raw_view(&mut self) -> bool {
+		let y = self.mouse_y.saturating_sub(HEADER_SIZE) / 16 + self.scroll() / 16;
+		let hovered = self.mouse_y >= HEADER_SIZE;
+		let tab = tab_mut!(self);
+		let indices = if let Some(selected_text) = &tab.selected_text {
+			selected_text.indices.to_vec()
+		} else if hovered && y < tab.value.height() {
+			let mut indices = vec![];
+			let mut iter = TraverseParents::new(y, &mut tab.value);
+			while let Some((_, idx, _, _, _)) = iter.next() {
+				indices.push(idx);
+			}
+			indices
+		} else {
+			return false
+		};
+		tab.toggle_raw_view(&indices).is_some()
+	}
+
+	/// Shows the types which can be added to the hovered container, or next to the hovered element when it isn't one, see [`AddMenu`].
+	#[inline]
+	fn open_add_menu(&mut self) -> bool {
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let Some(hovered) = indices.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)) else { return false };
+		let (parent, idx) = if hovered.len().is_some() {
+			(indices, 0)
+		} else {
+			let Some(idx) = indices.pop() else { return false };
+			(indices, idx + 1)
+		};
+		let Some(container) = parent.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)) else { return false };
+		tab.add_menu = AddMenu::new(parent.into_boxed_slice(), idx, container);
+		tab.add_menu.is_some()
+	}
+
+	/// Adds an element of the type picked in `add_menu`, opening its container and selecting its key (or value) to be typed over.
+	fn add_from_menu(&mut self, add_menu: &AddMenu) -> bool {
+		let tab = tab_mut!(self);
+		let Some(container) = add_menu.parent.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)) else { return false };
+		// a list given a type since the menu opened can only hold that one
+		if !add_menu.still_fits(container) { return false }
+		let id = add_menu.selected();
+		let mut element = NbtElement::from_id_configured(id);
+		if let Some(chunk) = element.as_chunk_mut() {
+			// the coordinates of a chunk are its place inside the region, so it's given the first free one
+			let Some(region) = container.as_region() else { return false };
+			let Some(pos) = region.chunks.1.iter().position(NbtElement::is_null) else { return false };
+			chunk.x = (pos >> 5) as u8;
+			chunk.z = (pos & 31) as u8;
+		}
+		let key = matches!(container.id(), NbtCompound::ID | NbtChunk::ID).then(|| CompactString::new_inline("_"));
+		if add_menu.idx > container.len().unwrap_or(0) { return false }
+
+		let mut indices = add_menu.parent.to_vec();
+		indices.push(add_menu.idx);
+		let height = element.height();
+		let action = WorkbenchAction::Remove { element: (key, element), indices: indices.clone().into_boxed_slice() }.undo(&mut tab.value, &mut tab.bookmarks, &mut self.subscription, &mut tab.path, &mut tab.name, &mut tab.root_name);
+		tab.append_to_history(action);
+
+		let (_, _, container, _) = Navigate::new(add_menu.parent.iter().copied(), &mut tab.value).last();
+		if !container.open() {
+			let before = container.height();
+			let _ = container.toggle();
+			// the parents of the container were already grown by the element as if it was open
+			let increment = container.height().wrapping_sub(before).wrapping_sub(height);
+			let mut iter = Navigate::new(add_menu.parent.iter().copied(), &mut tab.value);
+			while let Some((position, _, _, element, _)) = iter.next() {
+				if let Position::First | Position::Middle = position {
+					element.increment(increment, 0);
+				}
+			}
+			recache_along_indices(&add_menu.parent, &mut tab.value);
+		}
+
+		let Some(row) = tab.value.row_of_path(&indices) else { return true };
+		if self.select_text_at(row, 0, true) && let Some(selected_text) = tab_mut!(self).selected_text.as_mut() {
+			selected_text.cursor = selected_text.value.len();
+			selected_text.selection = Some(0);
+		}
+		true
+	}
+
+	/// Cycles the hovered array (or the array of the hovered value) between writing its values in decimal, hex and binary, or with `packed` unpacks the hovered long array into values of the bit width in the clipboard, and back.
+	#[inline]
+	fn cycle_array_view(&mut self, packed: bool) -> bool {
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let is_array = |indices: &[usize]| indices.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)).map(NbtElement::id).filter(|&id| matches!(id, NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID));
+		let id = match is_array(&indices) {
+			Some(id) => id,
+			None => {
+				indices.pop();
+				let Some(id) = is_array(&indices) else { return false };
+				id
+			}
+		};
+		let view = tab.array_view(&indices);
+		let view = if !packed {
+			view.next()
+		} else if id != NbtLongArray::ID {
+			return false
+		} else if let ArrayView::Packed(_) = view {
+			ArrayView::Decimal
+		} else if let Some(bits) = get_clipboard().and_then(|clipboard| clipboard.trim().parse::<u8>().ok()).filter(|bits| (1..=32).contains(bits)) {
+			ArrayView::Packed(bits)
+		} else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Copy the bit width of the packed values (1 to 32) to unpack the array with"));
+			return true
+		};
+		tab_mut!(self).set_array_view(&indices, view).is_some()
+	}
+
+	/// Attaches the clipboard as a note to the hovered element, or removes its note.
+	#[inline]
+	fn note(&mut self, remove: bool) -> bool {
+		if self.mouse_y < HEADER_SIZE { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y >= tab.value.height() { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let Some(path) = tab.value.path_of(&indices) else { return false };
+		let result = if remove {
+			tab.notes.remove(&path).map(|_| ())
+		} else if let Some(text) = get_clipboard() && !text.trim().is_empty() {
+			tab.notes.set(&path, text.trim_end())
+		} else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Copy the text of the note first"));
+			return false
+		};
+		if let Err(e) = result {
+			self.alert(Alert::new("Error!", TextColor::Red, format!("Could not save notes: {e}")));
+		}
+		true
+	}
+
 	#[inline]
 	fn delete(&mut self, clipboard: bool) -> bool {
 		if self.mouse_y < HEADER_SIZE { return false };
@@ -954,6 +1708,7 @@ impl Workbench {
 		let y = self.mouse_y - HEADER_SIZE + self.scroll();
 		let x = (self.mouse_x + horizontal_scroll - left_margin) / 16;
 		let tab = tab_mut!(self);
+		let x = if y / 16 < tab.value.height() { unindented(x, Traverse::new(y / 16, &mut tab.value).enumerate().last().0) } else { x };
 
 		if element.id() == NbtChunk::ID && tab.value.id() != NbtRegion::ID { return false }
 		let mut indices = vec![];
@@ -1019,6 +1774,104 @@ impl Workbench {
 		}
 	}
 
+	/// Drops an element taken from a selection, then moves the rest of the selected `siblings` (by their indices before it was taken) next to it in their order.
+	///
+	/// If the element can't be dropped there, or the place it lands can't hold every sibling, everything is put back where it was.
+	fn drop_group(&mut self, pair: (Option<CompactString>, NbtElement), from: Box<[usize]>, siblings: &[usize], left_margin: usize) -> bool {
+		let (key, element) = pair;
+		let horizontal_scroll = self.horizontal_scroll();
+		let y = self.mouse_y.saturating_sub(HEADER_SIZE) + self.scroll();
+		let x = (self.mouse_x + horizontal_scroll).saturating_sub(left_margin) / 16;
+		let missed = self.mouse_y <= HEADER_SIZE || self.mouse_x + horizontal_scroll < left_margin;
+		let tab = tab_mut!(self);
+		let x = if y / 16 < tab.value.height() { unindented(x, Traverse::new(y / 16, &mut tab.value).enumerate().last().0) } else { x };
+
+		let mut to = vec![];
+		let dropped = if missed { DropFn::Missed(key.clone(), element) } else { NbtElement::drop(tab.value.as_mut(), key.clone(), element, &mut y.clone(), 2, x, 1, &mut to) };
+		let (height, true_height, line_number) = match dropped {
+			DropFn::Dropped(height, true_height, _, line_number) => (height, true_height, line_number),
+			DropFn::InvalidType(key, element) | DropFn::Missed(key, element) => {
+				let _ = WorkbenchAction::Remove { element: (key, element), indices: from }.undo(&mut tab.value, &mut tab.bookmarks, &mut self.subscription, &mut tab.path, &mut tab.name, &mut tab.root_name);
+				return false;
+			}
+		};
+		recache_along_indices(&to[..to.len() - 1], &mut tab.value);
+		tab.bookmarks[line_number..].increment(height, true_height);
+
+		let (&from_idx, parent) = unsafe { from.split_last().panic_unchecked("stolen elements have a parent") };
+		// where the siblings are now that the element has moved
+		let mut pending = siblings.iter().map(|&idx| {
+			let mut indices = parent.to_vec();
+			indices.push(idx);
+			shift_for_removal(&mut indices, &from);
+			shift_for_insertion(&mut indices, &to);
+			indices
+		}).collect::<Vec<_>>();
+		let target = &to[..to.len() - 1];
+		let accepts = target.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)).is_some_and(|target_parent| pending.iter().all(|indices| !encompasses_or_equal(indices, target) && indices.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)).is_some_and(|sibling| target_parent.can_insert(sibling))));
+		if !accepts {
+			let _ = WorkbenchAction::Move { from, to: to.into_boxed_slice(), original_key: key }.undo(&mut tab.value, &mut tab.bookmarks, &mut self.subscription, &mut tab.path, &mut tab.name, &mut tab.root_name);
+			self.alert(Alert::new("Error!", TextColor::Red, "The selected elements can't all be moved there"));
+			return false;
+		}
+
+		let mut actions = vec![WorkbenchAction::Move { from: from.clone(), to: to.clone().into_boxed_slice(), original_key: key }];
+		// the siblings before it go in front of it closest first, the ones after go behind it in order
+		let before = siblings.partition_point(|&idx| idx < from_idx);
+		let (mut front, mut back) = (to.clone(), to);
+		for n in (0..before).rev().chain(before..siblings.len()) {
+			let current = pending[n].clone();
+			let key = current.split_last().and_then(|(&idx, parent)| match parent.iter().try_fold(&*tab.value, |element, &idx| element.get(idx))?.children()? {
+				Ok(_) => None,
+				Err(mut iter) => iter.nth(idx).map(|(key, _)| key.to_compact_string()),
+			}).unwrap_or(CompactString::new_inline("_"));
+			for indices in pending.iter_mut().chain([&mut front, &mut back]) {
+				shift_for_removal(indices, &current);
+			}
+			let mut target = if n < before { front.clone() } else { back.clone() };
+			if n >= before {
+				if let Some(last) = target.last_mut() { *last += 1 }
+			}
+			for indices in pending.iter_mut().chain([&mut front, &mut back]) {
+				shift_for_insertion(indices, &target);
+			}
+			if n < before { front.clone_from(&target) } else { back.clone_from(&target) }
+			// a move is undone by moving it back, so undoing the opposite move performs it
+			actions.push(WorkbenchAction::Move { from: target.into_boxed_slice(), to: current.into_boxed_slice(), original_key: Some(key) }.undo(&mut tab.value, &mut tab.bookmarks, &mut self.subscription, &mut tab.path, &mut tab.name, &mut tab.root_name));
+		}
+		tab.append_to_history(WorkbenchAction::Bulk { actions: actions.into_boxed_slice() });
+		self.subscription = None;
+		true
+	}
+
+	/// Adds the hovered element to the selection, or removes it if it was selected, starting over when it isn't a sibling of the selected ones.
+	fn extend_selection(&mut self) -> bool {
+		let left_margin = self.left_margin();
+		let horizontal_scroll = self.horizontal_scroll();
+		if self.mouse_y < HEADER_SIZE || self.mouse_x + horizontal_scroll < left_margin { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y == 0 || y >= tab.value.height() { return false }
+
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		while let Some((_, idx, _, _, _)) = iter.next() {
+			indices.push(idx);
+		}
+		let Some((&idx, parent)) = indices.split_last() else { return false };
+		match &mut tab.selection {
+			Some((selected_parent, selected)) if **selected_parent == *parent => match selected.binary_search(&idx) {
+				Ok(n) => {
+					selected.remove(n);
+					if selected.is_empty() { tab.selection = None }
+				}
+				Err(n) => selected.insert(n, idx),
+			},
+			_ => tab.selection = Some((parent.into(), vec![idx])),
+		}
+		true
+	}
+
 	#[inline]
 	fn hold_entry(&mut self, button: MouseButton) -> Result<bool> {
 		if button == MouseButton::Left && self.mouse_x >= 16 + 16 + 4 {
@@ -1036,7 +1889,7 @@ impl Workbench {
 					None => return Err(anyhow!("Could not parse clipboard as SNBT")),
 				}
 			} else {
-				self.held_entry = HeldEntry::FromAether((None, NbtElement::from_id(match x / 16 {
+				self.held_entry = HeldEntry::FromAether((None, NbtElement::from_id_configured(match x / 16 {
 					0 => NbtByte::ID,
 					1 => NbtShort::ID,
 					2 => NbtInt::ID,
@@ -1066,7 +1919,7 @@ impl Workbench {
 		let mut x = mouse_x - 2;
 		'a: {
 			for (idx, tab) in self.tabs.iter_mut().enumerate() {
-				let width = tab.name.width() + 48 + 5;
+				let width = tab.header().width() + 48 + 5;
 
 				if x <= width {
 					if button == MouseButton::Middle {
@@ -1080,6 +1933,9 @@ impl Workbench {
 					} else if idx == self.tab && x + 1 >= width - 32 && x < width - 16 {
 						if let Err(e) = tab.save(self.held_keys.contains(&KeyCode::ShiftLeft) || self.held_keys.contains(&KeyCode::ShiftRight)) {
 							self.alert(Alert::new("Error!", TextColor::Red, e.to_string()));
+						} else {
+							#[cfg(not(target_arch = "wasm32"))]
+							if let Some(path) = tab.path.as_deref() { self.recent_files.push(path, tab.format) }
 						}
 					} else if button == MouseButton::Left {
 						self.set_tab(idx, window_properties);
@@ -1105,11 +1961,40 @@ impl Workbench {
 
 	#[inline]
 	pub fn new_tab(&mut self, window_properties: &mut WindowProperties, region: bool) {
+		self.new_unsaved_tab(window_properties, if region { NbtElement::Region(NbtRegion::new()) } else { NbtElement::Compound(NbtCompound::new()) }, "new.nbt", FileFormat::Nbt);
+	}
+
+	/// Opens the SNBT of the clipboard in a new tab, see [`NbtElement::from_snbt`].
+	#[inline]
+	fn new_tab_from_clipboard(&mut self, window_properties: &mut WindowProperties) -> bool {
+		let Some(clipboard) = get_clipboard() else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Failed to get clipboard"));
+			return false
+		};
+		match NbtElement::from_snbt(&clipboard) {
+			Some(value) if matches!(value.id(), NbtCompound::ID | NbtList::ID) => {
+				self.new_unsaved_tab(window_properties, value, "clipboard.snbt", FileFormat::Snbt);
+				true
+			}
+			Some(_) => {
+				self.alert(Alert::new("Error!", TextColor::Red, "Clipboard must be a compound or a list"));
+				false
+			}
+			None => {
+				self.alert(Alert::new("Error!", TextColor::Red, "Could not parse clipboard as SNBT"));
+				false
+			}
+		}
+	}
+
+	#[inline]
+	fn new_unsaved_tab(&mut self, window_properties: &mut WindowProperties, value: NbtElement, name: &str, format: FileFormat) {
 		self.new_custom_tab(window_properties, Tab {
-			value: Box::new(if region { NbtElement::Region(NbtRegion::new()) } else { NbtElement::Compound(NbtCompound::new()) }),
-			name: "new.nbt".into(),
+			value: Box::new(value),
+			name: name.into(),
+			root_name: CompactString::new_inline(""),
 			path: None,
-			format: FileFormat::Nbt,
+			format,
 			undos: LinkedQueue::new(),
 			redos: LinkedQueue::new(),
 			unsaved_changes: false,
@@ -1124,6 +2009,15 @@ impl Workbench {
 			last_close_attempt: Duration::ZERO,
 			last_selected_text_interaction: (0, 0, Duration::ZERO),
 			last_interaction: since_epoch(),
+			last_backup: since_epoch(),
+			notes: Notes::new(),
+			raw_views: FxHashSet::default(),
+			array_views: FxHashMap::default(),
+			unpacked_palettes: FxHashSet::default(),
+			selection: None,
+			add_menu: None,
+			diff: vec![],
+			byte_sizes: FxHashMap::default(),
 		});
 	}
 
@@ -1137,20 +2031,25 @@ impl Workbench {
 	pub fn remove_tab(&mut self, idx: usize, window_properties: &mut WindowProperties) -> bool {
 		let tab = unsafe { self.tabs.get_unchecked_mut(idx) };
 		if tab.unsaved_changes && (since_epoch() - core::mem::replace(&mut tab.last_close_attempt, since_epoch())) > DOUBLE_CLICK_INTERVAL {
+			let message = format!("{} has unsaved changes, close it again right away to discard them.", tab.name);
+			self.alert(Alert::new("Are you sure you want to close this tab?", TextColor::Yellow, message));
 			return false;
 		}
 
 		let tab = self.tabs.remove(idx);
+		#[cfg(not(target_arch = "wasm32"))]
+		tab.remove_backup();
 		if self.tabs.is_empty() {
 			#[cfg(target_arch = "wasm32")]
 			if let Some(window) = web_sys::window() {
 				let _ = window.close();
 			}
-			std::process::exit(0);
+			self.exit();
 		}
 		if idx <= self.tab {
 			self.set_tab(self.tab.saturating_sub(1), window_properties);
 		}
+		log::debug!("Closed tab {}", tab.name);
 		#[cfg(not(target_arch = "wasm32"))]
 		std::thread::Builder::new().stack_size(50_331_648 /*48MiB*/).spawn(move || drop(tab)).expect("Failed to spawn thread");
 		#[cfg(target_arch = "wasm32")]
@@ -1161,14 +2060,17 @@ impl Workbench {
 	#[inline]
 	#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
 	fn open_file(&mut self, window_properties: &mut WindowProperties) {
-		match Tab::FILE_TYPE_FILTERS.iter().fold(native_dialog::FileDialog::new().set_location("~/Downloads"), |builder, filter| builder.add_filter(filter.0, filter.1)).show_open_single_file() {
+		match Tab::FILE_TYPE_FILTERS.iter().fold(native_dialog::FileDialog::new().set_location(crate::session::get_directory().unwrap_or(Path::new("~/Downloads"))), |builder, filter| builder.add_filter(filter.0, filter.1)).add_filter("Archive", &["jar", "zip"]).show_open_single_file() {
 			Err(e) => self.alert(Alert::new("Error!", TextColor::Red, e.to_string())),
 			Ok(None) => {},
-			Ok(Some(path)) => match std::fs::read(&path) {
-				Ok(bytes) => if let Err(e) = self.on_open_file(&path, bytes, window_properties) {
-					self.alert(Alert::new("Error!", TextColor::Red, e.to_string()))
-				},
-				Err(e) => self.alert(Alert::new("Error!", TextColor::Red, e.to_string())),
+			Ok(Some(path)) => {
+				crate::session::set_directory(&path);
+				match std::fs::read(&path) {
+					Ok(bytes) => if let Err(e) = self.on_open_file(&path, bytes, window_properties) {
+						self.alert(Alert::new("Error!", TextColor::Red, e.to_string()))
+					},
+					Err(e) => self.alert(Alert::new("Error!", TextColor::Red, e.to_string())),
+				}
 			}
 		}
 	}
@@ -1211,7 +2113,7 @@ impl Workbench {
 		let (depth, (_, _, element, line_number)) = Traverse::new(y, &mut tab.value).enumerate().last();
 		let true_height = element.true_height();
 
-		if x > depth && !ignore_depth { return false }
+		if x > indented(depth) && !ignore_depth { return false }
 		let before = element.height();
 		if expand {
 			#[cfg(not(target_arch = "wasm32"))]
@@ -1277,14 +2179,34 @@ impl Workbench {
 	}
 
 	#[inline]
-	fn try_select_text(&mut self, snap_to_ends: bool) -> bool {
+	fn try_select_text(&mut self, snap_to_ends: bool) -> bool {
+		let left_margin = self.left_margin();
+		let horizontal_scroll = self.horizontal_scroll();
+		if self.mouse_x + horizontal_scroll < left_margin { return false }
+		if self.mouse_y < HEADER_SIZE + 16 { return false }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		if !self.select_text_at(y, self.mouse_x + horizontal_scroll, snap_to_ends) { return false }
+
+		let tab = tab_mut!(self);
+		if let Some(selected_text) = tab.selected_text.as_mut() {
+			let now = since_epoch();
+			let (old_y, old_cursor, timestamp) = core::mem::replace(&mut tab.last_selected_text_interaction, (y, selected_text.cursor, now));
+			if now - timestamp < DOUBLE_CLICK_INTERVAL && old_y == y && old_cursor == selected_text.cursor && !selected_text.value.is_empty() {
+				tab.last_selected_text_interaction = (0, 0, Duration::ZERO);
+				selected_text.cursor = selected_text.value.len();
+				selected_text.selection = Some(0);
+			}
+		}
+		true
+	}
+
+	/// Selects the text of row `y` with the cursor at `x` (clamped to the text with `snap_to_ends`).
+	#[inline]
+	fn select_text_at(&mut self, y: usize, x: usize, snap_to_ends: bool) -> bool {
 		let left_margin = self.left_margin();
-		let horizontal_scroll = self.horizontal_scroll();
-		if self.mouse_x + horizontal_scroll < left_margin { return false }
-		if self.mouse_y < HEADER_SIZE + 16 { return false }
-		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
 		let tab = tab_mut!(self);
-		if y >= tab.value.height() { return false }
+		// the root has no text to select
+		if y == 0 || y >= tab.value.height() { return false }
 
 		let mut indices = vec![];
 		let mut iter = TraverseParents::new(y, &mut tab.value);
@@ -1292,39 +2214,178 @@ impl Workbench {
 			indices.push(idx);
 			if let Position::Last | Position::Only = position {
 				let child = unsafe { value.get(idx).panic_unchecked("Child didn't exist somehow") };
-				let target_x = indices.len() * 16 + 32 + 4 + left_margin;
+				let target_x = indented(indices.len()) * 16 + 32 + 4 + left_margin;
 				let k = key.map_or_else(|| child.as_chunk().map(|chunk| (chunk.x.to_string().into_boxed_str(), TextColor::TreePrimitive, true)), |x| Some((x.into_string().into_boxed_str(), TextColor::TreeKey, true)));
 				let v = Some(child.value()).map(|(a, c)| (a.into_string().into_boxed_str(), c, c != TextColor::TreeKey));
 				tab.selected_text = SelectedText::new(
 					target_x,
-					(self.mouse_x + horizontal_scroll).clamp(if snap_to_ends { target_x } else { 0 }, if snap_to_ends { k.as_ref().map_or(0, |(k, _, b)| (*b as usize) * (k.width() + ": ".width() * v.is_some() as usize)) + v.as_ref().map_or(0, |(v, _, b)| (*b as usize) * v.width()) + target_x } else { usize::MAX }),
+					x.clamp(if snap_to_ends { target_x } else { 0 }, if snap_to_ends { k.as_ref().map_or(0, |(k, _, b)| (*b as usize) * (k.width() + ": ".width() * v.is_some() as usize)) + v.as_ref().map_or(0, |(v, _, b)| (*b as usize) * v.width()) + target_x } else { usize::MAX }),
 					y * 16 + HEADER_SIZE,
 					k,
 					v,
 					child.id() == NbtChunk::ID,
 					indices,
 				);
-				if let Some(selected_text) = tab.selected_text.as_mut() {
-					let now = since_epoch();
-					let (old_y, old_cursor, timestamp) = core::mem::replace(&mut tab.last_selected_text_interaction, (y, selected_text.cursor, now));
-					if now - timestamp < DOUBLE_CLICK_INTERVAL && old_y == y && old_cursor == selected_text.cursor && !selected_text.value.is_empty() {
-						tab.last_selected_text_interaction = (0, 0, Duration::ZERO);
-						selected_text.cursor = selected_text.value.len();
-						selected_text.selection = Some(0);
-					}
-				}
 				return tab.selected_text.is_some();
 			}
 		}
 		false
 	}
 
+	/// Scrolls to the next bookmarked row after the selected one (or the top of the view), or the previous one with `previous`, wrapping around, and selects it, for stepping through search hits.
+	fn jump_to_bookmark(&mut self, previous: bool) -> bool {
+		let tab = tab_mut!(self);
+		let current = tab.selected_text.as_ref().map(|text| (text.y - HEADER_SIZE) / 16);
+		let top = tab.scroll() / 16;
+		// hits inside of closed elements share the row of their closest open parent
+		let mut rows = tab.bookmarks.iter().map(|bookmark| bookmark.line_number()).collect::<Vec<_>>();
+		rows.dedup();
+		let row = if previous {
+			let before = current.unwrap_or(top);
+			rows.iter().rev().find(|&&row| row < before).or_else(|| rows.last())
+		} else {
+			rows.iter().find(|&&row| current.map_or(row >= top, |current| row > current)).or_else(|| rows.first())
+		};
+		let Some(&row) = row else { return false };
+		tab.selected_text = None;
+		tab.scroll_to_row(row);
+		self.select_text_at(row, 0, true);
+		true
+	}
+
+	/// Jumps to the line number typed into the search box, see [`NbtElement::row_of`], or with two numbers, to a chunk, see [`Self::go_to_chunk`].
+	fn go_to(&mut self, blocks: bool) -> bool {
+		let value = self.search_box.value.trim();
+		if !value.contains([',', '|', ' ']) && let Ok(line) = value.parse::<usize>() {
+			let tab = tab_mut!(self);
+			let row = tab.value.row_of(line);
+			tab.selected_text = None;
+			// as the top row, unless it's too close to the end to scroll that far
+			tab.scroll_to_row(tab.value.height() - 1);
+			tab.scroll_to_row(row);
+			self.select_text_at(row, 0, true);
+			return true
+		}
+		self.go_to_chunk(blocks)
+	}
+
+	/// Jumps to the chunk of the region at the coordinates typed into the search box (like `3, 17` or `3|17`), world coordinates are wrapped into the region, `blocks` picks the chunk containing those block coordinates instead.
+	///
+	/// The region is opened when it is closed, since its chunks can't be selected otherwise.
+	fn go_to_chunk(&mut self, blocks: bool) -> bool {
+		let tab = tab_mut!(self);
+		let Some(region) = tab.value.as_region_mut() else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Only region files contain chunks"));
+			return true;
+		};
+		let mut coordinates = self.search_box.value.split([',', '|', ' ']).filter(|str| !str.is_empty()).map(str::parse::<i32>);
+		let (Some(Ok(x)), Some(Ok(z)), None) = (coordinates.next(), coordinates.next(), coordinates.next()) else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Type the x and z coordinates of a chunk into the search box"));
+			return true;
+		};
+		let (x, z) = if blocks { (x >> 4, z >> 4) } else { (x, z) };
+		let pos = (((x & 31) << 5) | (z & 31)) as u16;
+		let Some(idx) = (*region.chunks).0.iter().position(|&p| p == pos) else {
+			self.alert(Alert::new("Error!", TextColor::Red, format!("There is no chunk {x}|{z} in this region", x = x & 31, z = z & 31)));
+			return true;
+		};
+		if !region.open() {
+			let _ = region.toggle();
+			// every chunk was shut along with the region, so each takes up a single row
+			let starts = region.children().scan(2, |line_number, chunk| {
+				let start = *line_number;
+				*line_number += chunk.true_height();
+				Some(start)
+			}).collect::<Vec<_>>();
+			for bookmark in tab.bookmarks.iter_mut() {
+				let line_number = bookmark.true_line_number();
+				*bookmark = if line_number <= 1 {
+					Bookmark::new(line_number, 0)
+				} else {
+					let row = starts.partition_point(|&start| start <= line_number).saturating_sub(1);
+					Bookmark::with_uv(line_number, row + 1, if starts.get(row) == Some(&line_number) { BOOKMARK_UV } else { HIDDEN_BOOKMARK_UV })
+				};
+			}
+		}
+		let row = 1 + region.children().take(idx).map(NbtElement::height).sum::<usize>();
+		tab.selected_text = None;
+		tab.scroll_to_row(row);
+		self.select_text_at(row, 0, true);
+		true
+	}
+
+	/// Compares the tab with the one before it, showing what was added, removed or changed to get from that one to this one, or hides the comparison when it is shown.
+	fn diff_with_previous_tab(&mut self) -> bool {
+		if !tab!(self).diff.is_empty() {
+			tab_mut!(self).diff.clear();
+			return true;
+		}
+		let Some(previous) = self.tab.checked_sub(1).and_then(|idx| self.tabs.get(idx)) else {
+			self.alert(Alert::new("Error!", TextColor::Red, "Open the file to compare against in the tab before this one"));
+			return true;
+		};
+		let entries = diff::diff(&previous.value, &tab!(self).value);
+		let count = |kind: DiffKind| entries.iter().filter(|entry| entry.kind == kind).count();
+		let message = if entries.is_empty() {
+			format!("No differences from {name}", name = previous.name)
+		} else {
+			format!("From {name}, {added} added, {removed} removed and {changed} changed", name = previous.name, added = count(DiffKind::Added), removed = count(DiffKind::Removed), changed = count(DiffKind::Changed))
+		};
+		self.alert(Alert::new("Diff", TextColor::White, message));
+		tab_mut!(self).diff = entries;
+		true
+	}
+
+	/// Opens every element of the tree, or shuts all of them but the root so its children stay listed.
+	fn toggle_all(&mut self, expand: bool) -> bool {
+		let held = self.held_entry.element();
+		let tab = tab_mut!(self);
+		let root = &mut tab.value;
+		if expand {
+			#[cfg(not(target_arch = "wasm32"))]
+			std::thread::scope(|scope| root.expand(scope));
+			#[cfg(target_arch = "wasm32")]
+			root.expand();
+			for bookmark in tab.bookmarks.iter_mut() {
+				*bookmark = Bookmark::new(bookmark.true_line_number(), bookmark.true_line_number().saturating_sub(1));
+			}
+		} else {
+			root.shut();
+			if root.toggle().is_none() { return false }
+			// every child was shut along with the root, so each takes up a single row
+			let starts = (0..root.len().unwrap_or(0)).filter_map(|idx| root.get(idx)).scan(2, |line_number, child| {
+				let start = *line_number;
+				*line_number += child.true_height();
+				Some(start)
+			}).collect::<Vec<_>>();
+			for bookmark in tab.bookmarks.iter_mut() {
+				let line_number = bookmark.true_line_number();
+				*bookmark = if line_number <= 1 {
+					Bookmark::new(line_number, 0)
+				} else {
+					let row = starts.partition_point(|&start| start <= line_number).saturating_sub(1);
+					Bookmark::with_uv(line_number, row + 1, if starts.get(row) == Some(&line_number) { BOOKMARK_UV } else { HIDDEN_BOOKMARK_UV })
+				};
+			}
+		}
+		tab.selected_text = None;
+		tab.scroll = tab.scroll();
+		tab.horizontal_scroll = tab.horizontal_scroll(held);
+		true
+	}
+
 	#[inline]
 	fn bookmark_line(&mut self) -> bool {
 		let left_margin = self.left_margin();
 		let horizontal_scroll = self.horizontal_scroll();
-		let scroll = self.scroll();
 		if self.mouse_x + horizontal_scroll > left_margin { return false }
+		self.bookmark_hovered_line()
+	}
+
+	/// Toggles the bookmark of the hovered line, wherever the mouse is along it.
+	#[inline]
+	fn bookmark_hovered_line(&mut self) -> bool {
+		let scroll = self.scroll();
 		if self.mouse_y < HEADER_SIZE { return false }
 		let tab = tab_mut!(self);
 		if self.mouse_y + scroll > HEADER_SIZE + tab.value.height() * 16 { return false }
@@ -1444,6 +2505,7 @@ impl Workbench {
 	#[inline]
 	pub fn shift_selected_text_down(&mut self) {
 		let tab = tab_mut!(self);
+		let visible_height = tab.visible_height();
 		if let Some(SelectedText(Text { additional: SelectedTextAdditional { y, indices, .. }, .. })) = &mut tab.selected_text {
 			// well, it could be empty
 			if indices.is_empty() { return }
@@ -1485,8 +2547,8 @@ impl Workbench {
 				}
 			}
 
-			if *y + 48 > tab.scroll + tab.window_height - HEADER_SIZE {
-				tab.scroll = *y + 48 - (tab.window_height - HEADER_SIZE);
+			if *y + 48 > tab.scroll + visible_height {
+				tab.scroll = *y + 48 - visible_height;
 				tab.scroll = tab.scroll();
 			}
 
@@ -1512,7 +2574,7 @@ impl Workbench {
 			if !tab.close_selected_text(false, window_properties) { return }
 			let cache_cursor_x = self.cache_cursor_x;
 			let original_indices_len = indices.len();
-			let mouse_x = cache_cursor_x.unwrap_or(original_indices_len * 16 + 32 + 4 + left_margin + keyfix.as_ref().map_or(0, |x| x.0.width()) + prefix.0.width() + str_value.split_at(cursor).0.width());
+			let mouse_x = cache_cursor_x.unwrap_or(indented(original_indices_len) * 16 + 32 + 4 + left_margin + keyfix.as_ref().map_or(0, |x| x.0.width()) + prefix.0.width() + str_value.split_at(cursor).0.width());
 
 			if y == HEADER_SIZE + 16 {
 				let width = tab.name.width();
@@ -1566,10 +2628,10 @@ impl Workbench {
 						}
 					},
 				);
-				let new_y = sum_indices(indices.iter().copied(), &tab.value) * 16;
+				let new_y = tab.value.row_of_path(&indices).panic_unchecked("indices are valid") * 16;
 				(k, v, indices, new_y)
 			} else {
-				let total = sum_indices(indices.iter().copied(), &tab.value) - 1;
+				let total = tab.value.row_of_path(&indices).panic_unchecked("indices are valid") - 1;
 				let mut indices = vec![];
 				// SAFETY: total is -1'd means that it's original range of 1..=root.height() is now ..root.height(), which is in range
  				let (k, v) = 'w: {
@@ -1595,7 +2657,7 @@ impl Workbench {
 			};
 			tab.scroll = tab.scroll.min(new_y.saturating_sub(16));
 			tab.scroll = tab.scroll();
-			let low = indices.len() * 16 + 32 + 4 + left_margin;
+			let low = indented(indices.len()) * 16 + 32 + 4 + left_margin;
 			let high = low
 				+ k.as_ref()
 					.map_or(0, |(x, _)| x.width())
@@ -1624,7 +2686,7 @@ impl Workbench {
 
 		let tab = tab_mut!(self);
 		let total = if let Some(SelectedText(Text { additional: SelectedTextAdditional { indices, .. }, .. })) = tab.selected_text.as_ref() {
-			let mut total = sum_indices(indices.iter().copied(), &tab.value);
+			let mut total = tab.value.row_of_path(indices).panic_unchecked("indices are valid");
 			total += 1; // move down
 			// needs a check that it doesn't surpass the end
 			if total >= tab.value.height() { return }
@@ -1637,7 +2699,7 @@ impl Workbench {
 			if !tab.close_selected_text(false, window_properties) { return }
 			let cache_cursor_x = self.cache_cursor_x;
 			let original_indices_len = indices.len();
-			let mouse_x = cache_cursor_x.unwrap_or(original_indices_len * 16 + 32 + 4 + left_margin + keyfix.as_ref().map_or(0, |x| x.0.width()) + prefix.0.width() + str_value.split_at(cursor).0.width());
+			let mouse_x = cache_cursor_x.unwrap_or(indented(original_indices_len) * 16 + 32 + 4 + left_margin + keyfix.as_ref().map_or(0, |x| x.0.width()) + prefix.0.width() + str_value.split_at(cursor).0.width());
 			let (k, v, end_idx) = if ctrl && indices.len() > 0 {
 				Navigate::new(
 					indices.iter().copied().take(indices.len() - 1),
@@ -1689,7 +2751,7 @@ impl Workbench {
 				*indices
 					.last_mut()
 					.panic_unchecked("it literally just can't be empty") = end_idx;
-				let new_y = sum_indices(indices.iter().copied(), &tab.value) * 16;
+				let new_y = tab.value.row_of_path(&indices).panic_unchecked("indices are valid") * 16;
 				(k, v, indices, new_y)
 			} else {
 				'w: {
@@ -1720,15 +2782,15 @@ impl Workbench {
 					panic_unchecked("Iterator was empty, somehow")
 				}
 			};
-			let low = indices.len() * 16 + 32 + 4 + left_margin;
+			let low = indented(indices.len()) * 16 + 32 + 4 + left_margin;
 			let high = low
 				+ k.as_ref()
 					.map_or(0, |(x, _)| x.width())
 				+ (k.is_some() && v.as_ref().is_some_and(|(_, color)| *color != TextColor::TreeKey)) as usize * ": ".width()
 				+ v.as_ref()
 					.map_or(0, |(x, color)| ((*color != TextColor::TreeKey) as usize) * x.width());
-			if new_y + 48 > tab.scroll + tab.window_height - HEADER_SIZE {
-				tab.scroll = new_y + 48 - (tab.window_height - HEADER_SIZE);
+			if new_y + 48 > tab.scroll + tab.visible_height() {
+				tab.scroll = new_y + 48 - tab.visible_height();
 				tab.scroll = tab.scroll();
 			}
 			self.cache_cursor_x = self.cache_cursor_x.or(Some(mouse_x));
@@ -1744,6 +2806,39 @@ impl Workbench {
 		}
 	}
 
+	/// Selects the parent of the selected element when it has nothing left to shut, or with `descend`, its first child when it is already open, returning if the selection moved.
+	fn step_through_tree(&mut self, descend: bool, window_properties: &mut WindowProperties) -> bool {
+		let tab = tab_mut!(self);
+		let Some(selected_text) = tab.selected_text.as_ref() else { return false };
+		let indices = selected_text.indices.clone();
+		let Some(element) = indices.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)) else { return false };
+		let row = if descend {
+			if !element.open() || element.len().unwrap_or(0) == 0 { return false }
+			tab.value.row_of_path(&indices).map(|row| row + 1)
+		} else {
+			if element.open() { return false }
+			// the root has no text to select
+			let Some((_, parent)) = indices.split_last() else { return false };
+			if parent.is_empty() { return true }
+			tab.value.row_of_path(parent)
+		};
+		let Some(row) = row else { return false };
+		if !tab.close_selected_text(false, window_properties) { return true }
+		tab.scroll_to_row(row);
+		self.select_text_at(row, 0, true);
+		true
+	}
+
+	/// Selects the first line in view, or with `last`, the last one, so the tree can be moved through with the keyboard from nothing being selected.
+	fn select_line_in_view(&mut self, last: bool) -> bool {
+		let tab = tab!(self);
+		let first = (tab.scroll() / 16).max(1);
+		let rows = tab.visible_height() / 16;
+		let row = if last { (first + rows.saturating_sub(1)).min(tab.value.height().saturating_sub(1)) } else { first };
+		tab_mut!(self).scroll_to_row(row);
+		self.select_text_at(row, 0, true)
+	}
+
 	#[inline]
 	pub fn force_close(&mut self) {
 		let tab = tab_mut!(self);
@@ -1841,7 +2936,7 @@ impl Workbench {
 		if let Some(selected_text) = tab.selected_text.as_ref() {
 			let left_margin = tab.left_margin(held_element);
 			let horizontal_scroll = tab.horizontal_scroll(held_element);
-			let pos = left_margin + selected_text.indices.len() * 16 + 32 + 4 + selected_text.prefix.0.width() + selected_text.keyfix.as_ref().map_or(0, |x| x.0.width()) + selected_text.value.split_at(selected_text.cursor).0.width();
+			let pos = left_margin + indented(selected_text.indices.len()) * 16 + 32 + 4 + selected_text.prefix.0.width() + selected_text.keyfix.as_ref().map_or(0, |x| x.0.width()) + selected_text.value.split_at(selected_text.cursor).0.width();
 			if pos + free_space < self.window_width {
 				tab.horizontal_scroll = 0;
 				tab.horizontal_scroll = tab.horizontal_scroll(held_element);
@@ -1863,12 +2958,75 @@ impl Workbench {
 	)]
 	pub fn on_key_input(&mut self, key: &KeyEvent, window_properties: &mut WindowProperties) -> bool {
 		tab_mut!(self).last_interaction = since_epoch();
+		// held modifiers change what's drawn too
+		self.dirty = true;
 		if key.state == ElementState::Pressed {
+			let text = key.text.as_deref();
 			if let PhysicalKey::Code(key) = key.physical_key {
 				self.held_keys.insert(key);
-				let char = self.char_from_key(key);
+				let char = self.char_from_key(key, text);
 				let flags = (self.held_keys.contains(&KeyCode::ControlLeft) as u8 | self.held_keys.contains(&KeyCode::ControlRight) as u8 | self.held_keys.contains(&KeyCode::SuperLeft) as u8 | self.held_keys.contains(&KeyCode::SuperRight) as u8) | ((self.held_keys.contains(&KeyCode::ShiftLeft) as u8 | self.held_keys.contains(&KeyCode::ShiftRight) as u8) << 1) | ((self.held_keys.contains(&KeyCode::AltLeft) as u8 | self.held_keys.contains(&KeyCode::AltRight) as u8) << 2);
+				let keybinds = config::get_keybinds();
 				let left_margin = self.left_margin();
+				#[cfg(not(target_arch = "wasm32"))]
+				if self.recent_files.is_menu_open() && flags == flags!() {
+					match key {
+						KeyCode::ArrowUp => self.recent_files.select_previous(),
+						KeyCode::ArrowDown => self.recent_files.select_next(),
+						KeyCode::Escape => self.recent_files.close_menu(),
+						KeyCode::Enter | KeyCode::NumpadEnter => if let Some((path, format)) = self.recent_files.take_selected() {
+							match std::fs::read(&path) {
+								Ok(bytes) => if let Err(e) = self.reopen_file(&path, bytes, format, window_properties) {
+									self.alert(Alert::new("Error!", TextColor::Red, e.to_string()));
+								},
+								Err(e) => self.alert(Alert::new("Error!", TextColor::Red, format!("Could not read {}: {e}", path.display()))),
+							}
+						},
+						_ => {}
+					}
+					return true;
+				}
+				if let Some(entry_picker) = &mut self.entry_picker && flags == flags!() {
+					match key {
+						KeyCode::ArrowUp => entry_picker.select_previous(),
+						KeyCode::ArrowDown => entry_picker.select_next(),
+						KeyCode::Escape => self.entry_picker = None,
+						KeyCode::Enter | KeyCode::NumpadEnter => if let Some(entry_picker) = self.entry_picker.take() && let Err(e) = self.open_archive_entry(entry_picker, window_properties) {
+							self.alert(Alert::new("Error!", TextColor::Red, e.to_string()));
+						},
+						_ => {}
+					}
+					return true;
+				}
+				if let Some(command_menu) = &mut self.command_menu && flags == flags!() {
+					match key {
+						KeyCode::ArrowUp => command_menu.select_previous(),
+						KeyCode::ArrowDown => command_menu.select_next(),
+						KeyCode::Escape => self.command_menu = None,
+						KeyCode::Enter | KeyCode::NumpadEnter => if let Some(command_menu) = self.command_menu.take() && !set_clipboard(command_menu.selected().to_owned()) {
+							self.alert(Alert::new("Error!", TextColor::Red, "Failed to set clipboard"));
+						},
+						_ => {}
+					}
+					return true;
+				}
+				if let Some(add_menu) = &mut tab_mut!(self).add_menu && flags == flags!() {
+					match key {
+						KeyCode::ArrowUp => add_menu.select_previous(),
+						KeyCode::ArrowDown => add_menu.select_next(),
+						KeyCode::Escape => tab_mut!(self).add_menu = None,
+						KeyCode::Enter | KeyCode::NumpadEnter => if let Some(add_menu) = tab_mut!(self).add_menu.take() {
+							self.add_from_menu(&add_menu);
+						},
+						_ => {}
+					}
+					return true;
+				}
+				#[cfg(not(target_arch = "wasm32"))]
+				if key == KeyCode::KeyO && flags == flags!(Ctrl + Shift) {
+					if self.recent_files.is_menu_open() { self.recent_files.close_menu() } else { self.recent_files.open_menu() }
+					return true;
+				}
 				let tab = tab_mut!(self);
 				if self.search_box.is_selected() {
 					match self.search_box.on_key_press(key, char, flags) {
@@ -1894,6 +3052,17 @@ impl Workbench {
 						}
 					}
 				}
+				if let Some(options) = tab.selected_status_options()
+					&& let KeyCode::ArrowUp | KeyCode::ArrowDown = key
+					&& flags == flags!()
+					&& let Some(selected_text) = &mut tab.selected_text
+				{
+					// cycles through the dropdown instead of moving lines
+					selected_text.select_option(options, key == KeyCode::ArrowUp);
+					self.cache_cursor_x = None;
+					self.refresh_selected_text_horizontal_scroll();
+					return true;
+				}
 				if let Some(selected_text) = &mut tab.selected_text {
 					match selected_text.on_key_press(key, char, flags) {
 						SelectedTextKeyResult::NothingSpecial => {
@@ -1941,12 +3110,16 @@ impl Workbench {
 						}
 						SelectedTextKeyResult::ForceClose => {
 							selected_text.post_input();
-							self.force_close();
+							if !self.step_through_tree(false, window_properties) {
+								self.force_close();
+							}
 							return true;
 						}
 						SelectedTextKeyResult::ForceOpen => {
 							selected_text.post_input();
-							self.force_open();
+							if flags & flags!(Shift) > 0 || !self.step_through_tree(true, window_properties) {
+								self.force_open();
+							}
 							return true;
 						}
 						SelectedTextKeyResult::ShiftUp => {
@@ -1962,7 +3135,10 @@ impl Workbench {
 						SelectedTextKeyResult::Failed => {} // next thing pls
 					}
 				}
-				if key == KeyCode::KeyF && flags == flags!(Ctrl) {
+				if (key == KeyCode::ArrowUp || key == KeyCode::ArrowDown) && flags == flags!() && tab.selected_text.is_none() && self.held_entry.is_empty() && self.action_wheel.is_none() {
+					return self.select_line_in_view(key == KeyCode::ArrowUp);
+				}
+				if keybinds.matches(Action::Search, key, flags) {
 					self.search_box.select(0, MouseButton::Left);
 					return true;
 				}
@@ -1987,7 +3163,8 @@ impl Workbench {
 					return match self.held_entry.take() {
 						HeldEntry::Empty => { self.try_select_text(true); true },
 						HeldEntry::FromAether(pair) => self.drop(pair, None, left_margin),
-						HeldEntry::FromKnown(pair, indices) => return self.drop(pair, Some(indices), left_margin)
+						HeldEntry::FromKnown(pair, indices) => return self.drop(pair, Some(indices), left_margin),
+						HeldEntry::FromKnownGroup(pair, indices, siblings) => return self.drop_group(pair, indices, &siblings, left_margin),
 					}
 				}
 				if flags == flags!(Ctrl) {
@@ -2028,8 +3205,9 @@ impl Workbench {
 						return true;
 					}
 				}
-				if key == KeyCode::KeyR && flags == flags!(Ctrl) {
-					if let Err(e) = tab.refresh(self.sort_algorithm) {
+				if key == KeyCode::KeyR && (flags == flags!(Ctrl) || flags == flags!(Alt)) {
+					// alt skips format detection and reads the file as the format picked for the tab
+					if let Err(e) = tab.refresh(self.sort_algorithm, (flags == flags!(Alt)).then_some(tab.format)) {
 						self.alert(Alert::new("Error!", TextColor::Red, e.to_string()))
 					}
 					return true;
@@ -2038,22 +3216,63 @@ impl Workbench {
 					tab.freehand_mode = !tab.freehand_mode;
 					return true;
 				}
+				if key == KeyCode::KeyV && flags == flags!(Alt) {
+					self.present_mode = match self.present_mode {
+						PresentMode::Immediate => PresentMode::Fifo,
+						PresentMode::Fifo => PresentMode::Mailbox,
+						_ => PresentMode::Immediate,
+					};
+					return true;
+				}
 				if key == KeyCode::KeyN && flags & (!flags!(Shift)) == flags!(Ctrl) {
 					tab.selected_text = None;
 					self.new_tab(window_properties, (flags & flags!(Shift)) > 0);
 					return true;
 				}
-				if key == KeyCode::KeyO && flags == flags!(Ctrl) {
+				if key == KeyCode::KeyV && flags == flags!(Ctrl + Shift) {
+					tab.selected_text = None;
+					return self.new_tab_from_clipboard(window_properties);
+				}
+				if keybinds.matches(Action::Open, key, flags) {
 					tab.selected_text = None;
 					self.open_file(window_properties);
 					return true;
 				}
-				if key == KeyCode::KeyS && flags & (!flags!(Shift)) == flags!(Ctrl) {
-					return if let Err(e) = tab.save((flags & flags!(Shift)) > 0) {
+				if key == KeyCode::F3 && flags & (!flags!(Shift)) == flags!() {
+					return self.jump_to_bookmark((flags & flags!(Shift)) > 0);
+				}
+				if key == KeyCode::KeyG && flags & (!flags!(Shift)) == flags!(Ctrl) {
+					return self.go_to((flags & flags!(Shift)) > 0);
+				}
+				if key == KeyCode::KeyS && flags & (!flags!(Shift)) == flags!(Alt) {
+					return self.sort_hovered((flags & flags!(Shift)) > 0);
+				}
+				if key == KeyCode::KeyH && flags & (!flags!(Shift)) == flags!(Ctrl) {
+					return self.replace_search_hits((flags & flags!(Shift)) == 0);
+				}
+				if key == KeyCode::KeyA && flags == flags!(Alt) {
+					return self.apply_operation_to_hovered();
+				}
+				if key == KeyCode::KeyH && flags == flags!(Alt) {
+					return self.view_unreadable_chunks();
+				}
+				if (key == KeyCode::ArrowRight || key == KeyCode::ArrowLeft) && flags == flags!(Ctrl) {
+					return self.toggle_all(key == KeyCode::ArrowRight);
+				}
+				if key == KeyCode::KeyB && flags == flags!(Ctrl) {
+					return self.bookmark_hovered_line();
+				}
+				if key == KeyCode::KeyB && flags == flags!(Ctrl + Shift) {
+					return self.jump_to_bookmark(false);
+				}
+				if let Some(save_as) = keybinds.matches_shifted(Action::Save, key, flags) {
+					return if let Err(e) = tab.save(save_as) {
 						self.alert(Alert::new("Error!", TextColor::Red, e.to_string()));
 						false
 					} else {
 						tab.selected_text = None;
+						#[cfg(not(target_arch = "wasm32"))]
+						if let Some(path) = tab.path.as_deref() { self.recent_files.push(path, tab.format) }
 						true
 					}
 				}
@@ -2061,45 +3280,40 @@ impl Workbench {
 					self.remove_tab(self.tab, window_properties);
 					return true;
 				}
-				if key == KeyCode::KeyZ && flags == flags!(Ctrl) {
-					if let Some(action) = tab.undos.pop() {
-						tab.redos.push(action.undo(
-							&mut tab.value,
-							&mut tab.bookmarks,
-							&mut self.subscription,
-							&mut tab.path,
-							&mut tab.name,
-						));
-						tab.selected_text = None;
+				let redo = keybinds.matches(Action::Redo, key, flags);
+				if redo || keybinds.matches(Action::Undo, key, flags) {
+					// a held element remembers the indices it was taken from, which the undone action could invalidate
+					if !self.held_entry.is_empty() { return false }
+					if tab.step_history(redo, &mut self.subscription) {
+						self.action_wheel = None;
+						if self.search_box.hits.is_some() {
+							self.search_box.search(&mut tab.bookmarks, &tab.value, true);
+						}
 						return true;
 					}
 				}
-				if key == KeyCode::KeyY && flags == flags!(Ctrl) {
-					if let Some(action) = tab.redos.pop() {
-						tab.undos.push(action.undo(
-							&mut tab.value,
-							&mut tab.bookmarks,
-							&mut self.subscription,
-							&mut tab.path,
-							&mut tab.name,
-						));
-						tab.selected_text = None;
+				let cut = keybinds.matches(Action::Cut, key, flags);
+				if cut || keybinds.matches(Action::Delete, key, flags) {
+					if self.delete(cut) {
+						tab_mut!(self).selected_text = None;
 						return true;
 					}
 				}
-				if ((key == KeyCode::Backspace || key == KeyCode::Delete) && flags == flags!()) || (key == KeyCode::KeyX && flags == flags!(Ctrl)) {
-					if self.delete(flags & flags!(Ctrl) > 0) {
-						tab_mut!(self).selected_text = None;
+				if key == KeyCode::KeyT && flags & (!flags!(Shift)) == flags!(Ctrl) {
+					if self.convert_hovered(flags == flags!(Ctrl + Shift)) {
 						return true;
 					}
 				}
+				if key == KeyCode::KeyD && flags == flags!(Alt) {
+					return self.diff_with_previous_tab();
+				}
 				if key == KeyCode::KeyD && flags == flags!(Ctrl) {
 					if self.duplicate() {
 						tab_mut!(self).selected_text = None;
 						return true;
 					}
 				}
-				if key == KeyCode::KeyC && flags == flags!(Ctrl) {
+				if keybinds.matches(Action::Copy, key, flags) {
 					if self.copy(false) {
 						tab_mut!(self).selected_text = None;
 						return true;
@@ -2111,49 +3325,108 @@ impl Workbench {
 						return true;
 					}
 				}
-				if flags == flags!() {
+				if key == KeyCode::KeyC && flags == flags!(Alt) {
+					if self.copy_as_json() {
+						tab_mut!(self).selected_text = None;
+						return true;
+					}
+				}
+				if key == KeyCode::KeyC && flags == flags!(Ctrl + Alt) {
+					if self.open_command_menu() {
+						return true;
+					}
+				}
+				if key == KeyCode::KeyV && flags == flags!(Ctrl + Alt) {
+					return self.paste_json_over_hovered();
+				}
+				if key == KeyCode::KeyB && flags == flags!(Alt) {
+					return self.toggle_palette_data();
+				}
+				if key == KeyCode::KeyM && let Some(strategy) = match flags {
+					flags!(Alt) => Some(MergeStrategy::RecurseCompounds),
+					flags!(Shift + Alt) => Some(MergeStrategy::KeepSelf),
+					flags!(Ctrl + Alt) => Some(MergeStrategy::KeepOther),
+					_ => None,
+				} {
+					if self.merge_clipboard_into_hovered(strategy) {
+						return true;
+					}
+				}
+				if key == KeyCode::KeyI && flags == flags!(Ctrl) {
+					if self.open_add_menu() {
+						return true;
+					}
+				}
+				if key == KeyCode::KeyX && flags == flags!(Alt) {
+					if self.cycle_array_view(false) {
+						return true;
+					}
+				}
+				if key == KeyCode::KeyU && flags == flags!(Alt) {
+					if self.cycle_array_view(true) {
+						return true;
+					}
+				}
+				if key == KeyCode::KeyE && flags == flags!(Ctrl) {
+					if self.toggle_raw_view() {
+						return true;
+					}
+				}
+				if key == KeyCode::KeyM && flags & (!flags!(Shift)) == flags!(Ctrl) {
+					if self.note((flags & flags!(Shift)) > 0) {
+						tab_mut!(self).selected_text = None;
+						return true;
+					}
+				}
+				if let Some(expand) = keybinds.matches_shifted(Action::Toggle, key, flags) {
+					if self.toggle(expand, true) {
+						return true;
+					}
+				}
+				let paste = keybinds.matches(Action::Paste, key, flags);
+				if paste || flags == flags!() {
 					let tab = tab_mut!(self);
-					let x = if key == KeyCode::Digit1 {
-						(None, NbtElement::from_id(NbtByte::ID))
+					let x = if paste {
+						let Some(clipboard) = get_clipboard() else {
+							self.alert(Alert::new("Error!", TextColor::Red, "Failed to get clipboard"));
+							return true;
+						};
+						if let Some((key, value)) = NbtElement::from_str(&clipboard, self.sort_algorithm) {
+							(key, value)
+						} else {
+							self.alert(Alert::new("Error!", TextColor::Red, "Could not parse clipboard as SNBT"));
+							return true;
+						}
+					} else if key == KeyCode::Digit1 {
+						(None, NbtElement::from_id_configured(NbtByte::ID))
 					} else if key == KeyCode::Digit2 {
-						(None, NbtElement::from_id(NbtShort::ID))
+						(None, NbtElement::from_id_configured(NbtShort::ID))
 					} else if key == KeyCode::Digit3 {
-						(None, NbtElement::from_id(NbtInt::ID))
+						(None, NbtElement::from_id_configured(NbtInt::ID))
 					} else if key == KeyCode::Digit4 {
-						(None, NbtElement::from_id(NbtLong::ID))
+						(None, NbtElement::from_id_configured(NbtLong::ID))
 					} else if key == KeyCode::Digit5 {
-						(None, NbtElement::from_id(NbtFloat::ID))
+						(None, NbtElement::from_id_configured(NbtFloat::ID))
 					} else if key == KeyCode::Digit6 {
-						(None, NbtElement::from_id(NbtDouble::ID))
+						(None, NbtElement::from_id_configured(NbtDouble::ID))
 					} else if key == KeyCode::Digit7 {
-						(None, NbtElement::from_id(NbtByteArray::ID))
+						(None, NbtElement::from_id_configured(NbtByteArray::ID))
 					} else if key == KeyCode::Digit8 {
-						(None, NbtElement::from_id(NbtIntArray::ID))
+						(None, NbtElement::from_id_configured(NbtIntArray::ID))
 					} else if key == KeyCode::Digit9 {
-						(None, NbtElement::from_id(NbtLongArray::ID))
+						(None, NbtElement::from_id_configured(NbtLongArray::ID))
 					} else if key == KeyCode::Digit0 {
-						(None, NbtElement::from_id(NbtString::ID))
+						(None, NbtElement::from_id_configured(NbtString::ID))
 					} else if key == KeyCode::Minus {
-						(None, NbtElement::from_id(NbtList::ID))
+						(None, NbtElement::from_id_configured(NbtList::ID))
 					} else if key == KeyCode::Equal {
-						(None, NbtElement::from_id(NbtCompound::ID))
+						(None, NbtElement::from_id_configured(NbtCompound::ID))
 					} else if key == KeyCode::Backquote && tab.value.id() == NbtRegion::ID {
-						(None, NbtElement::from_id(NbtChunk::ID))
-					} else if key == KeyCode::KeyV {
-						let Some(clipboard) = get_clipboard() else {
-							self.alert(Alert::new("Error!", TextColor::Red, "Failed to get clipboard"));
-							return true;
-						};
-						if let Some((key, value)) = NbtElement::from_str(&clipboard, self.sort_algorithm) {
-							(key, value)
-						} else {
-							self.alert(Alert::new("Error!", TextColor::Red, "Could not parse clipboard as SNBT"));
-							return true;
-						}
+						(None, NbtElement::from_id_configured(NbtChunk::ID))
 					} else {
 						return true;
 					};
-					if let HeldEntry::FromKnown(element, indices) = core::mem::replace(&mut self.held_entry, HeldEntry::FromAether(x)) {
+					if let HeldEntry::FromKnown(element, indices) | HeldEntry::FromKnownGroup(element, indices, _) = core::mem::replace(&mut self.held_entry, HeldEntry::FromAether(x)) {
 						tab.append_to_history(WorkbenchAction::Remove {
 							indices,
 							element,
@@ -2173,16 +3446,22 @@ impl Workbench {
 
 	#[inline]
 	pub fn on_mouse_move(&mut self, pos: PhysicalPosition<f64>) -> bool {
+		let hovered = self.hovered_cell();
+		let tooltip = since_epoch() - self.last_mouse_move >= HOVER_TOOLTIP_DELAY;
 		self.raw_mouse_x = pos.x;
 		self.raw_mouse_y = pos.y;
 		self.mouse_x = (self.raw_mouse_x / self.scale as f64) as usize;
 		self.mouse_y = (self.raw_mouse_y / self.scale as f64) as usize;
+		self.last_mouse_move = since_epoch();
+		if tooltip || hovered.is_none() || hovered != self.hovered_cell() || self.follows_mouse() {
+			self.dirty = true;
+		}
 		let mouse_y = self.mouse_y;
 		let tab = tab_mut!(self);
 		if let Some(scrollbar_offset) = self.scrollbar_offset && mouse_y >= HEADER_SIZE {
 			let mouse_y = mouse_y - HEADER_SIZE;
 			let height = tab.value.height() * 16 + 32 + 15;
-			let total = tab.window_height - HEADER_SIZE;
+			let total = tab.visible_height();
 			let start = total * tab.scroll() / height;
 			let scrollbar_point = start + scrollbar_offset;
 			let dy = mouse_y as isize - scrollbar_point as isize;
@@ -2193,6 +3472,50 @@ impl Workbench {
 		true
 	}
 
+	/// The row and column of the tree the mouse is over, which is all that the tree's hover effects depend on, `None` outside of it where they're laid out by the pixel.
+	#[must_use]
+	fn hovered_cell(&self) -> Option<(usize, usize)> {
+		let left_margin = self.left_margin();
+		let x = (self.mouse_x + self.horizontal_scroll()).checked_sub(left_margin)?;
+		let y = self.mouse_y.checked_sub(HEADER_SIZE)?;
+		Some((x / 16, y / 16))
+	}
+
+	/// If anything is drawn by the mouse or dragged along by it, so that every move of it changes the frame.
+	#[must_use]
+	fn follows_mouse(&self) -> bool {
+		!self.held_entry.is_empty() || !self.held_mouse_keys.is_empty() || self.scrollbar_offset.is_some() || self.action_wheel.is_some() || tab!(self).add_menu.is_some() || self.command_menu.is_some() || tab!(self).freehand_mode || tab!(self).selected_text.is_some()
+	}
+
+	/// How long until something timed changes what's drawn (the text cursor blinking, a tooltip showing, alerts, autosaves and the like), `None` if nothing will until the next input.
+	#[must_use]
+	pub fn next_change(&self) -> Option<Duration> {
+		let now = since_epoch();
+		let mut next = None::<Duration>;
+		let mut at = |duration: Duration| next = Some(next.map_or(duration, |next| next.min(duration)));
+		if self.steal_animation_data.is_some() || !self.held_entry.is_empty() || tab!(self).freehand_mode { at(TICK_INTERVAL) }
+		if self.subscription.is_some() { at(SUBSCRIPTION_POLL_INTERVAL) }
+		if let Some(blink) = tab!(self).selected_text.as_ref().and_then(|text| text.next_blink()) { at(blink) }
+		if self.search_box.is_selected() && let Some(blink) = self.search_box.next_blink() { at(blink) }
+		if now - self.last_mouse_move < HOVER_TOOLTIP_DELAY && self.hovered_cell().is_some() { at(HOVER_TOOLTIP_DELAY - (now - self.last_mouse_move)) }
+		for alert in &self.alerts {
+			at(alert.next_change());
+		}
+		for tab in &self.tabs {
+			if now - tab.last_close_attempt <= DOUBLE_CLICK_INTERVAL { at(DOUBLE_CLICK_INTERVAL - (now - tab.last_close_attempt)) }
+			#[cfg(not(target_arch = "wasm32"))]
+			if tab.unsaved_changes && tab.path.as_deref().is_some_and(Path::is_absolute) {
+				// only ahead of time, a failed autosave or backup waits for the next input rather than being retried every frame
+				let autosave = (tab.value.true_height() <= Tab::AUTOSAVE_MAXIMUM_LINES).then_some(tab.last_interaction + Tab::AUTOSAVE_INTERVAL);
+				let backup = config::get_backup_interval().map(|interval| tab.last_backup + interval);
+				for deadline in autosave.into_iter().chain(backup) {
+					if deadline > now { at(deadline - now) }
+				}
+			}
+		}
+		next
+	}
+
 	#[inline]
 	pub fn window_dimensions(&mut self, window_width: usize, window_height: usize) {
 		let width_scaling = window_width as f64 / self.raw_window_width as f64;
@@ -2201,11 +3524,25 @@ impl Workbench {
 		self.raw_window_height = window_height;
 		self.raw_mouse_x = self.raw_mouse_x * width_scaling;
 		self.raw_mouse_y = self.raw_mouse_y * height_scaling;
-		self.set_scale(self.scale);
+		self.set_scale(self.requested_scale);
+	}
+
+	/// Zooms along with the scale factor of the monitor the window moved to, keeping the zoom asked for on top of it.
+	///
+	/// The window is resized by the OS afterward, which lets the new zoom fit into it.
+	#[inline]
+	pub fn set_scale_factor(&mut self, scale_factor: f64) {
+		let dpi_scale = (scale_factor.round() as usize).max(1);
+		if dpi_scale == self.dpi_scale { return }
+		let scale = (self.requested_scale * dpi_scale / self.dpi_scale).max(1);
+		self.dpi_scale = dpi_scale;
+		self.set_scale(scale);
 	}
 
 	#[inline]
 	fn set_scale(&mut self, scale: usize) {
+		self.dirty = true;
+		self.requested_scale = scale.max(1);
 		let scale = scale.min(usize::min(self.raw_window_width / MIN_WINDOW_WIDTH, self.raw_window_height / MIN_WINDOW_HEIGHT)).max(1);
 
 		self.scale = scale;
@@ -2231,6 +3568,7 @@ impl Workbench {
 
 	#[inline]
 	fn set_tab(&mut self, idx: usize, window_properties: &mut WindowProperties) {
+		tab_mut!(self).add_menu = None;
 		self.tab = idx.min(self.tabs.len() - 1);
 		// on any tab switch this should be discarded.
 		self.steal_animation_data = None;
@@ -2243,6 +3581,19 @@ impl Workbench {
 
 		let shift = self.held_keys.contains(&KeyCode::ShiftLeft) || self.held_keys.contains(&KeyCode::ShiftRight);
 
+		// drawn first so that no hover tooltip takes its place
+		#[cfg(not(target_arch = "wasm32"))]
+		self.recent_files.render(builder);
+		if let Some(add_menu) = &tab!(self).add_menu {
+			add_menu.render(builder);
+		}
+		if let Some(entry_picker) = &self.entry_picker {
+			entry_picker.render(builder);
+		}
+		if let Some(command_menu) = &self.command_menu {
+			command_menu.render(builder);
+		}
+
 		{
 			builder.draw_texture_region_z(
 				(313, 22),
@@ -2294,6 +3645,7 @@ impl Workbench {
 					x.id(),
 					((self.mouse_x + horizontal_scroll - left_margin) & !15) + left_margin,
 					((self.mouse_y - HEADER_SIZE) & !0b0111) + HEADER_SIZE,
+					x.height(),
 				)
 			})
 		} else {
@@ -2327,6 +3679,23 @@ impl Workbench {
 				(14, 14),
 			);
 		}
+		if let Some((parent, selected)) = &tab.selection && parent.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)).is_some_and(|parent| parent.open()) {
+			let mut indices = parent.to_vec();
+			indices.push(0);
+			for &idx in selected {
+				if let Some(last) = indices.last_mut() { *last = idx }
+				let Some(row) = tab.value.row_of_path(&indices) else { continue };
+				let Some(y) = (row * 16 + HEADER_SIZE).checked_sub(builder.scroll()) else { continue };
+				if y < HEADER_SIZE { continue }
+				builder.draw_texture_region_z(
+					(0, y),
+					BASE_Z,
+					HOVERED_STRIPE_UV,
+					(builder.window_width(), 16),
+					(14, 14),
+				);
+			}
+		}
 		{
 			builder.draw_texture((0, 26), OPEN_FOLDER_UV, (16, 16));
 			builder.draw_texture((16, 26), NEW_FILE_UV, (16, 16));
@@ -2363,10 +3732,100 @@ impl Workbench {
 			builder.horizontal_scroll = horizontal_scroll;
 			selected_text.render(builder, left_margin);
 			builder.horizontal_scroll = 0;
+			if let Some(options) = tab.selected_status_options() {
+				selected_text.render_options(builder, left_margin, horizontal_scroll, options);
+			}
 		}
+		self.render_status_bar(builder);
 		self.render_action_wheel(builder);
 		self.render_held_entry(builder);
 		self.render_alerts(builder);
+		// drawn last so that every other tooltip takes its place
+		self.render_hovered_value(builder);
+	}
+
+	/// Writes the type, path, amount of children and size in the file of the selected element (or the hovered one without a selection, or the root without either) along the bottom of the window.
+	fn render_status_bar(&mut self, builder: &mut VertexBufferBuilder) {
+		let y = (self.mouse_y >= HEADER_SIZE).then(|| (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16);
+		let tab = tab_mut!(self);
+		let indices: Box<[usize]> = if let Some(selected_text) = &tab.selected_text {
+			selected_text.indices.clone()
+		} else if let Some(y) = y && y < tab.value.height() {
+			let mut indices = vec![];
+			let mut iter = TraverseParents::new(y, &mut tab.value);
+			while let Some((_, idx, _, _, _)) = iter.next() {
+				indices.push(idx);
+			}
+			indices.into_boxed_slice()
+		} else {
+			Box::new([])
+		};
+		let Some(element) = indices.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)) else { return };
+		// whole regions and chunks are too large to be written on every frame, and they're compressed on save anyway
+		let size = if matches!(element.id(), NbtRegion::ID | NbtChunk::ID) {
+			None
+		} else if let Some(&size) = tab.byte_sizes.get(&indices) {
+			Some(size)
+		} else {
+			let little_endian = matches!(tab.format, FileFormat::LittleEndianNbt | FileFormat::LittleEndianHeaderNbt);
+			let mut size = UncheckedBufWriter::count(|writer| if little_endian { element.to_le_bytes(writer) } else { element.to_be_bytes(writer) });
+			// entries of compounds are written after their type and key
+			if let Some((&idx, parent)) = indices.split_last() && let Some(Err(mut entries)) = parent.iter().try_fold(&*tab.value, |element, &idx| element.get(idx)).and_then(NbtElement::children) && let Some((key, _)) = entries.nth(idx) {
+				size += 1 + 2 + if little_endian { key.len() } else { crate::mutf8::encode(key).len() };
+			}
+			tab.byte_sizes.insert(indices.clone(), size);
+			Some(size)
+		};
+		let path = tab.value.path_of(&indices).map_or_else(String::new, |path| PathSegment::join(&path));
+		let mut text = format!("{name} | {path}", name = element.display_name(), path = if path.is_empty() { "(root)" } else { &path });
+		if let Some(len) = element.len() {
+			let _ = write!(text, " | {len} {children}", children = if len == 1 { "child" } else { "children" });
+		}
+		if let Some(size) = size {
+			let _ = write!(text, " | {size}", size = crate::byte_size(size));
+		}
+
+		let y = builder.window_height() - STATUS_BAR_SIZE;
+		builder.draw_texture_region_z(
+			(0, y),
+			STATUS_BAR_Z,
+			BACKDROP_UV,
+			(builder.window_width(), STATUS_BAR_SIZE),
+			(16, 16),
+		);
+		builder.settings((4, y), false, STATUS_BAR_TEXT_Z);
+		builder.color = TextColor::Gray.to_raw();
+		let _ = write!(builder, "{text}");
+	}
+
+	/// Shows the full value and type of the primitive or string the mouse rests over, or the length and range of an array.
+	fn render_hovered_value(&mut self, builder: &mut VertexBufferBuilder) {
+		if since_epoch() - self.last_mouse_move < HOVER_TOOLTIP_DELAY || !self.held_entry.is_empty() || self.action_wheel.is_some() || self.scrollbar_offset.is_some() { return }
+		let left_margin = self.left_margin();
+		let horizontal_scroll = self.horizontal_scroll();
+		if self.mouse_y < HEADER_SIZE || self.mouse_x + horizontal_scroll < left_margin { return }
+		let y = (self.mouse_y - HEADER_SIZE) / 16 + self.scroll() / 16;
+		let tab = tab_mut!(self);
+		if y == 0 || y >= tab.value.height() || tab.selected_text.as_ref().is_some_and(|text| text.y == y * 16 + HEADER_SIZE) { return }
+
+		let (_, _, element, _) = unsafe { Traverse::new(y, &mut tab.value).last().panic_unchecked("Traverse always has something") };
+		let mut lines = vec![element.display_name().to_owned()];
+		if let Some(Ok(values)) = element.children() && matches!(element.id(), NbtByteArray::ID | NbtIntArray::ID | NbtLongArray::ID) {
+			let values = values.filter_map(|value| value.as_byte().map(|byte| i64::from(byte.value)).or_else(|| value.as_int().map(|int| i64::from(int.value))).or_else(|| value.as_long().map(|long| long.value))).collect::<Vec<_>>();
+			lines.push(format!("{len} entr{suffix}", len = values.len(), suffix = if values.len() == 1 { "y" } else { "ies" }));
+			if let (Some(min), Some(max)) = (values.iter().min(), values.iter().max()) {
+				lines.push(format!("Min: {min}, Max: {max}"));
+			}
+		} else if matches!(element.id(), NbtByte::ID | NbtShort::ID | NbtInt::ID | NbtLong::ID | NbtFloat::ID | NbtDouble::ID | NbtString::ID) {
+			let (value, _) = element.value();
+			for line in value.lines() {
+				let chars = line.chars().collect::<Vec<_>>();
+				lines.extend(chars.chunks(HOVER_TOOLTIP_WIDTH).map(|chunk| chunk.iter().collect::<String>()));
+			}
+		} else {
+			return;
+		}
+		builder.draw_tooltip(&lines.iter().map(String::as_str).collect::<Vec<_>>(), (self.mouse_x, self.mouse_y), false);
 	}
 
 	#[inline]
@@ -2378,6 +3837,12 @@ impl Workbench {
 					if let Err(e) = tab.save(false) {
 						alerts.push(Alert::new("Error!", TextColor::Red, e.context(format!("Failed to autosave {nth} tab", nth = crate::nth(idx + 1))).to_string()));
 					}
+					self.dirty = true;
+				}
+				if let Some(interval) = config::get_backup_interval() && tab.path.as_deref().is_some_and(Path::is_absolute) && tab.unsaved_changes && since_epoch() - tab.last_backup >= interval {
+					if let Err(e) = tab.backup() {
+						alerts.push(Alert::new("Error!", TextColor::Red, e.context(format!("Failed to back up {nth} tab", nth = crate::nth(idx + 1))).to_string()));
+					}
 				}
 			}
 			for alert in alerts {
@@ -2386,16 +3851,57 @@ impl Workbench {
 		}
 		if (!self.held_entry.is_empty() || tab!(self).freehand_mode) && self.action_wheel.is_none() && self.scrollbar_offset.is_none() {
 			self.try_mouse_scroll();
+			self.dirty = true;
 		}
+		self.try_show_more();
 		if self.try_steal(false) {
 			if self.steal_animation_data.as_ref().is_some_and(|x| (since_epoch() - x.0) >= Duration::from_millis(500)) {
 				self.steal();
 			}
+			self.dirty = true;
 		} else {
 			self.steal_animation_data = None;
 		}
 	}
 
+	/// Lays out the next chunk of a partially shown array once its last shown element reaches the bottom of the screen.
+	#[inline]
+	fn try_show_more(&mut self) {
+		let tab = tab_mut!(self);
+		let y = usize::min(tab.scroll() / 16 + tab.visible_height() / 16, tab.value.height() - 1);
+		let mut indices = vec![];
+		let mut iter = TraverseParents::new(y, &mut tab.value);
+		let (idx, increment, line_number, true_height) = 'a: {
+			while let Some((position, idx, _, element, line_number)) = iter.next() {
+				if let Position::Last | Position::Only = position {
+					break 'a (idx, element.show_more(idx), line_number, element.true_height());
+				}
+				indices.push(idx);
+			}
+			return;
+		};
+		if increment == 0 { return }
+
+		let mut iter = Navigate::new(indices.iter().copied(), &mut tab.value);
+		while let Some((position, _, _, element, _)) = iter.next() {
+			if let Position::First | Position::Middle = position {
+				element.increment(increment, 0);
+			}
+		}
+		recache_along_indices(&indices, &mut tab.value);
+		let row = y - idx - 1;
+		for bookmark in tab.bookmarks[line_number + idx + 2..].iter_mut() {
+			let offset = bookmark.true_line_number() - line_number;
+			*bookmark = if offset < idx + 2 + increment {
+				bookmark.open(row + offset)
+			} else if offset < true_height {
+				*bookmark
+			} else {
+				bookmark.offset(increment, 0)
+			};
+		}
+	}
+
 	#[inline]
 	#[must_use]
 	pub fn close(&mut self) -> usize {
@@ -2408,11 +3914,23 @@ impl Workbench {
 		}
 
 		if failed_tabs > 0 {
-			self.alert(Alert::new("Are you sure you want to exit?", TextColor::Yellow, format!("You have {failed_tabs} unsaved tabs.")));
+			self.alert(Alert::new("Are you sure you want to exit?", TextColor::Yellow, format!("You have {failed_tabs} unsaved tab{s}, exit again right away to discard {them}.", s = if failed_tabs == 1 { "" } else { "s" }, them = if failed_tabs == 1 { "its changes" } else { "their changes" })));
 		}
 		failed_tabs
 	}
 
+	/// Exits once [`Self::close`] lets it (or the last tab is closed), writing the session, the changes of the tabs which are still open are discarded so their backups are removed along with them, see [`Tab::remove_backup`].
+	pub fn exit(&self) -> ! {
+		#[cfg(not(target_arch = "wasm32"))]
+		{
+			crate::session::write();
+			for tab in &self.tabs {
+				tab.remove_backup();
+			}
+		}
+		std::process::exit(0)
+	}
+
 	#[inline]
 	fn render_held_entry(&self, builder: &mut VertexBufferBuilder) {
 		if let Some(element) = self.held_entry.element() {
@@ -2446,7 +3964,8 @@ impl Workbench {
 		let mut offset = 3;
 		builder.horizontal_scroll = self.tab_scroll;
 		for (idx, tab) in self.tabs.iter().enumerate() {
-			let remaining_width = tab.name.width() + 48 + 3;
+			let header = tab.header();
+			let remaining_width = header.width() + 48 + 3;
 			let uv = if (since_epoch() - tab.last_close_attempt) <= DOUBLE_CLICK_INTERVAL {
 				CLOSED_WIDGET_UV
 			} else if idx == self.tab {
@@ -2458,7 +3977,7 @@ impl Workbench {
 			};
 			builder.draw_texture((offset, 3), uv, (3, 16));
 			if (offset..offset + 16).contains(&self.mouse_x) && (3..19).contains(&self.mouse_y) {
-				builder.draw_tooltip(&[tab.value.display_name()], (self.mouse_x, self.mouse_y), false);
+				builder.draw_tooltip(&[tab.value.display_name(), &format!("{} in memory", crate::byte_size(core::mem::size_of::<NbtElement>() + tab.value.memory_size()))], (self.mouse_x, self.mouse_y), false);
 			}
 			offset += 2;
 			tab.draw_icon(builder, (offset, 2), JUST_OVERLAPPING_BASE_TEXT_Z);
@@ -2471,7 +3990,7 @@ impl Workbench {
 				(10, 16),
 			);
 			builder.settings((offset + 16, 3), false, BASE_TEXT_Z);
-			let _ = write!(builder, "{}", tab.name);
+			let _ = write!(builder, "{header}");
 			offset += remaining_width;
 			builder.draw_texture((offset, 3), uv + (13, 0), (3, 16));
 			builder.draw_texture(
@@ -2526,7 +4045,7 @@ impl Workbench {
 			let (depth, (_, key, element, _)) = Traverse::new((cy - HEADER_SIZE) / 16 + scroll / 16, &mut tab.value)
 				.enumerate()
 				.last();
-			let min_x = depth * 16 + left_margin;
+			let min_x = indented(depth) * 16 + left_margin;
 			let max_x = min_x + 32 + element.value().0.width() + key.map(|key| key.width() + ": ".width()).unwrap_or(0);
 			if !(min_x..max_x).contains(&cx) { return };
 			builder.draw_texture_z((cx - 31, cy - 31), ACTION_WHEEL_Z, TRAY_UV, (64, 64));
@@ -2611,8 +4130,15 @@ impl Workbench {
 		clippy::match_same_arms,
 		clippy::too_many_lines
 	)]
-	fn char_from_key(&self, key: KeyCode) -> Option<char> {
+	fn char_from_key(&self, key: KeyCode, text: Option<&str>) -> Option<char> {
 		if self.ctrl() { return None }
+		// the text winit resolved through the keyboard layout, only falling back to a US layout when it's missing or a control character
+		if let Some(char) = text.and_then(|text| {
+			let mut chars = text.chars();
+			chars.next().filter(|char| !char.is_control() && chars.next().is_none())
+		}) {
+			return Some(char);
+		}
 		let shift = self.held_keys.contains(&KeyCode::ShiftLeft) || self.held_keys.contains(&KeyCode::ShiftRight);
 		Some(match key {
 			KeyCode::Digit1 => if shift { '!' } else { '1' },
@@ -6,7 +6,7 @@ use crate::assets::{ADD_TAIL_UV, ADD_UV, BULK_TAIL_UV, BULK_UV, MOVE_TAIL_UV, MO
 use crate::elements::element::NbtElement;
 use crate::vertex_buffer_builder::VertexBufferBuilder;
 use crate::{encompasses, encompasses_or_equal, FileUpdateSubscription};
-use crate::{panic_unchecked, Position, sum_indices};
+use crate::{panic_unchecked, Position};
 use crate::{Navigate, OptionExt};
 use crate::elements::compound::{CompoundMap, Entry};
 use crate::bookmark::{Bookmark, Bookmarks};
@@ -44,9 +44,9 @@ pub enum WorkbenchAction {
 
 impl WorkbenchAction {
 	#[cfg_attr(debug_assertions, inline(never))]
-	pub fn undo(self, root: &mut NbtElement, bookmarks: &mut Bookmarks, subscription: &mut Option<FileUpdateSubscription>, path: &mut Option<PathBuf>, name: &mut Box<str>) -> Self {
+	pub fn undo(self, root: &mut NbtElement, bookmarks: &mut Bookmarks, subscription: &mut Option<FileUpdateSubscription>, path: &mut Option<PathBuf>, name: &mut Box<str>, root_name: &mut CompactString) -> Self {
 		unsafe {
-			self.undo0(root, bookmarks, subscription, path, name)
+			self.undo0(root, bookmarks, subscription, path, name, root_name)
 				.panic_unchecked("Failed to undo action")
 		}
 	}
@@ -58,7 +58,7 @@ impl WorkbenchAction {
 		clippy::too_many_lines,
 		clippy::cognitive_complexity
 	)]
-	unsafe fn undo0(self, root: &mut NbtElement, bookmarks: &mut Bookmarks, subscription: &mut Option<FileUpdateSubscription>, path: &mut Option<PathBuf>, name: &mut Box<str>) -> Option<Self> {
+	unsafe fn undo0(self, root: &mut NbtElement, bookmarks: &mut Bookmarks, subscription: &mut Option<FileUpdateSubscription>, path: &mut Option<PathBuf>, name: &mut Box<str>, root_name: &mut CompactString) -> Option<Self> {
 		Some(match self {
 			Self::Remove {
 				element: (key, value),
@@ -145,12 +145,9 @@ impl WorkbenchAction {
 					let key = if let Some(key) = key {
 						let parent = Navigate::new(rem.iter().copied(), root).last().2;
 						Some(if let Some(region) = parent.as_region_mut() {
-							let (map, chunks) = &mut *region.chunks;
-							let from = map.get(last).copied()? as usize;
-							let to = ((key.parse::<u8>().ok()? as usize) << 5) | (value.as_ref()?.parse::<u8>().ok()? as usize);
-							chunks.swap(from, to);
-							override_value = Some((from & 31).to_compact_string());
-							(from >> 5).to_compact_string()
+							let (old_x, old_z) = region.relocate_chunk(last, key.parse().ok()?, value.as_ref()?.parse().ok()?)?;
+							override_value = Some(old_z.to_compact_string());
+							old_x.to_compact_string()
 						} else if let Some(compound) = parent.as_compound_mut() {
 							compound.update_key(last, key)?
 						} else if let Some(chunk) = parent.as_chunk_mut() {
@@ -178,6 +175,13 @@ impl WorkbenchAction {
 						key,
 						value,
 					}
+				} else if let Some(key) = key {
+					// the name of the root compound, see `Tab::root_name`
+					Self::Rename {
+						indices,
+						key: Some(core::mem::replace(root_name, key)),
+						value,
+					}
 				} else {
 					let old = PathBuf::from(value?.into_string());
 					let old_name = old
@@ -342,9 +346,11 @@ impl WorkbenchAction {
 				}
 			}
 			Self::ReorderCompound { indices: traversal_indices, reordering_indices } => {
-				let line_number = sum_indices(traversal_indices.iter().copied(), root);
+				let line_number = root.row_of_path(&traversal_indices).panic_unchecked("indices are valid");
+				let shown = root.is_shown(&traversal_indices);
 				let (_, _, element, true_line_number) = Navigate::new(traversal_indices.iter().copied(), root).last();
-				let open = element.open();
+				// the rows of hidden elements don't move, only their true line numbers
+				let open = element.open() && shown;
 				let true_height = element.true_height();
 				let CompoundMap { indices, entries } = if let Some(compound) = element.as_compound_mut() {
 					 &mut *compound.entries
@@ -400,7 +406,7 @@ impl WorkbenchAction {
 				let mut array = Box::new_uninit_slice(actions.len());
 
 				for (idx, action) in actions.into_vec().into_iter().rev().enumerate() {
-					array[idx].write(action.undo(root, bookmarks, subscription, path, name));
+					array[idx].write(action.undo(root, bookmarks, subscription, path, name, root_name));
 				}
 
 				return Some(Self::Bulk {
@@ -410,6 +416,39 @@ impl WorkbenchAction {
 		})
 	}
 
+	/// The indices of the element this action was applied to, or of its parent when it no longer exists.
+	#[must_use]
+	pub fn focus(&self) -> Option<&[usize]> {
+		match self {
+			Self::Remove { indices, .. } => indices.split_last().map(|(_, parent)| parent),
+			Self::Add { indices } | Self::Rename { indices, .. } | Self::Replace { indices, .. } | Self::ReorderCompound { indices, .. } => Some(indices),
+			Self::Move { to, .. } => Some(to),
+			Self::Bulk { actions } => actions.first().and_then(Self::focus),
+		}
+	}
+
+	/// If this action only changes the order of elements, leaving every element itself as it was.
+	#[must_use]
+	pub fn is_reorder(&self) -> bool {
+		match self {
+			Self::ReorderCompound { .. } => true,
+			Self::Bulk { actions } => actions.iter().all(Self::is_reorder),
+			_ => false,
+		}
+	}
+
+	/// Calls `f` with the indices of every element whose contents this action changes, which are the parents of the elements it adds, removes or moves.
+	pub fn changed(&self, f: &mut impl FnMut(&[usize])) {
+		match self {
+			Self::Remove { indices, .. } | Self::Add { indices } => if let Some((_, parent)) = indices.split_last() { f(parent) },
+			Self::Rename { indices, .. } | Self::Replace { indices, .. } | Self::ReorderCompound { indices, .. } => f(indices),
+			Self::Move { from, to, .. } => for indices in [from, to] {
+				if let Some((_, parent)) = indices.split_last() { f(parent) }
+			},
+			Self::Bulk { actions } => for action in actions.iter() { action.changed(f) },
+		}
+	}
+
 	#[inline]
 	pub fn render(&self, pos: impl Into<(usize, usize)>, builder: &mut VertexBufferBuilder, tail: bool) {
 		match self {